@@ -0,0 +1,167 @@
+//    This file is part of org-rs.
+//
+//    org-rs is free software: you can redistribute it and/or modify
+//    it under the terms of the GNU General Public License as published by
+//    the Free Software Foundation, either version 3 of the License, or
+//    (at your option) any later version.
+//
+//    org-rs is distributed in the hope that it will be useful,
+//    but WITHOUT ANY WARRANTY; without even the implied warranty of
+//    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//    GNU General Public License for more details.
+//
+//    You should have received a copy of the GNU General Public License
+//    along with org-rs.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Parses a large generated Org file with `Parser::parse_buffer`,
+//! then traverses the tree and detaches it from the input with
+//! `into_owned`.
+//!
+//! Building and traversal of the arena based `SyntaxTree` are compared
+//! with the previous `Rc<SyntaxNode>` + `RefCell<Vec<_>>`
+//! representation, both built from the nodes of the parsed tree.
+
+#[macro_use]
+extern crate criterion;
+extern crate org_element;
+
+use criterion::{black_box, Criterion};
+use org_element::data::{Interval, NodeId, StringOrObject, Syntax, SyntaxNode, SyntaxTree};
+use org_element::options::ParserOptions;
+use org_element::parser::{ParseGranularity, Parser};
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+/// Tree node as it was stored before the arena.
+struct RcNode<'a> {
+    parent: RefCell<Option<Weak<RcNode<'a>>>>,
+    children: RefCell<Vec<Rc<RcNode<'a>>>>,
+    data: Syntax<'a>,
+    location: Interval,
+}
+
+impl<'a> RcNode<'a> {
+    fn new(data: Syntax<'a>, location: Interval) -> Rc<RcNode<'a>> {
+        Rc::new(RcNode {
+            parent: RefCell::new(None),
+            children: RefCell::new(vec![]),
+            data,
+            location,
+        })
+    }
+
+    fn append(parent: &Rc<RcNode<'a>>, child: Rc<RcNode<'a>>) -> Rc<RcNode<'a>> {
+        *child.parent.borrow_mut() = Some(Rc::downgrade(parent));
+        parent.children.borrow_mut().push(child.clone());
+        child
+    }
+}
+
+/// A few megabytes of headlines, sections and paragraphs with markup
+fn large_org_file() -> String {
+    let mut text = String::new();
+    for i in 0..2_000 {
+        text.push_str(&format!("* TODO Chapter {} :work:\n", i));
+        text.push_str(
+            "Some *bold* text, some /italic/ text and a [[https://orgmode.org][link]].\n\n",
+        );
+        for j in 0..5 {
+            text.push_str(&format!("** Section {}.{}\n", i, j));
+            for _ in 0..3 {
+                text.push_str("A paragraph of ~code~ and =verbatim= text with <<target>>\n");
+                text.push_str("spanning a couple of lines, see [[target]].\n\n");
+            }
+        }
+    }
+    text
+}
+
+fn parse(text: &str) -> SyntaxTree<'_> {
+    Parser::new(text, ParseGranularity::Object, ParserOptions::default()).parse_buffer()
+}
+
+/// Data of node `id`. Parsed titles refer to objects of `tree`,
+/// the copy gets the raw ones.
+fn data<'a>(tree: &SyntaxTree<'a>, id: NodeId) -> Syntax<'a> {
+    let mut data = tree[id].data.clone();
+    if let Syntax::Headline(h) = &mut data {
+        h.title = StringOrObject::Raw(h.raw_value.clone());
+    }
+    data
+}
+
+/// Copy of `tree` as an `Rc` tree
+fn to_rc<'a>(tree: &SyntaxTree<'a>, id: NodeId) -> Rc<RcNode<'a>> {
+    let node = RcNode::new(data(tree, id), tree[id].location);
+    for child in tree.children(id) {
+        RcNode::append(&node, to_rc(tree, child));
+    }
+    node
+}
+
+/// Copy of the children of `id` in `tree`, appended to `parent` of `arena`
+fn to_arena<'a>(tree: &SyntaxTree<'a>, id: NodeId, arena: &mut SyntaxTree<'a>, parent: NodeId) {
+    for child in tree.children(id) {
+        let node = SyntaxNode::new(data(tree, child), tree[child].location, None, 0);
+        let node = arena.append(parent, node);
+        to_arena(tree, child, arena, node);
+    }
+}
+
+fn text_length_arena(tree: &SyntaxTree<'_>, id: NodeId) -> usize {
+    tree.descendants(id)
+        .map(|node| match &tree[node].data {
+            Syntax::PlainText(_) => tree[node].location.end - tree[node].location.start,
+            _ => 0,
+        })
+        .sum()
+}
+
+fn text_length_rc(node: &Rc<RcNode<'_>>) -> usize {
+    let own = match &node.data {
+        Syntax::PlainText(_) => node.location.end - node.location.start,
+        _ => 0,
+    };
+    own + node
+        .children
+        .borrow()
+        .iter()
+        .map(text_length_rc)
+        .sum::<usize>()
+}
+
+fn tree_benchmark(c: &mut Criterion) {
+    let text = large_org_file();
+
+    c.bench_function("parse_buffer", |b| b.iter(|| parse(black_box(&text))));
+
+    let tree = parse(&text);
+    c.bench_function("build arena", |b| {
+        b.iter(|| {
+            let mut arena = SyntaxTree::new();
+            let root = arena.root();
+            to_arena(black_box(&tree), tree.root(), &mut arena, root);
+            arena
+        })
+    });
+    c.bench_function("build rc", |b| {
+        b.iter(|| to_rc(black_box(&tree), tree.root()))
+    });
+
+    let rc = to_rc(&tree, tree.root());
+    c.bench_function("traverse arena", |b| {
+        b.iter(|| text_length_arena(black_box(&tree), tree.root()))
+    });
+    c.bench_function("traverse rc", |b| b.iter(|| text_length_rc(black_box(&rc))));
+
+    c.bench_function("into_owned", |b| {
+        b.iter_batched(
+            || tree.clone(),
+            |tree| tree.into_owned(),
+            criterion::BatchSize::LargeInput,
+        )
+    });
+}
+
+criterion_group!(benches, tree_benchmark);
+criterion_main!(benches);
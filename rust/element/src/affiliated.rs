@@ -43,110 +43,132 @@
 //! or if its pattern is “#+ATTR_BACKEND: VALUE”.
 //!
 //! “CAPTION” keyword can contain objects in both VALUE and OPTIONAL fileds.
+//!
+//! These lists are the defaults of `ParserOptions`, which can
+//! add new keywords or change their capabilities.
 
 use crate::cursor::REGEX_EMPTY_LINE;
-use crate::data::StringOrObject;
-use crate::data::SyntaxT;
-use crate::parser::Parser;
-use regex::{Match, Regex};
+use crate::data::{Interval, StringOrObject, SyntaxT};
+use crate::parser::{ParseGranularity, Parser};
+use crate::source::{self, TextSource};
+use regex::Regex;
 use std::borrow::Cow;
 use std::collections::HashMap;
-use std::str::FromStr;
 
 lazy_static! {
-
-   /// Regexp matching any affiliated keyword
-   ///
-   /// This is different from original implementation for several reasons:
-   ///
-   /// ERE regexes support explicit numbering for capture groups,
-   /// which also can be repetetive. Rust's regex is PCRE, and does
-   /// not have such feature.
-   ///
-   /// Secondly, it uses different capture group arrangement to
-   /// simplify parsing and serialization of the keywords
-   ///
-   /// Thirdly, translation from old keywords already built-in
-   ///
-   /// Capture groups are named after each Keyword respectively.
-   ///
-   /// If you want to use group numbers:
-   /// CAPTION keyword is captured to group 1
-   /// RESULTS keyword is captured to group 2
-   ///
-   /// Secondary value of CAPTION or RESULTS is captured to group 3
-   ///
-   /// HEADER keyword is captured to group 4
-   /// PLOT keyword is captured to group 5
-   /// NAME keyword is captured to group 6
-   /// ATTR_ exported attribute is captured to group 7
-   ///
-   /// Warning! If you add more keywords then you must update this regex!
-   /// Original elisp implementation dynamically creates this regex based on
-   /// definitions lists of dual,regular and attribute keywords.
-   /// While this is possible to do in rust, and maybe it will be required in
-   /// the future, for now due laziness and lack of time static regex will be used.
-   ///
-   /// elisp: `org-element--affiliated-re`
-   pub static ref REGEX_AFFILIATED: Regex = Regex::new(
-           &format!(
-              r"(?i)^[ \t]*{}|{}|{}|{}|{}[ \t]*",
-              r"#\+(?:(?:(?P<CAPTION>CAPTION)|(?P<RESULTS>RESULTS?))(?:\[(?P<SECONDARY>.*)\])?",   // DUAL
-              r"(?P<HEADER>HEADERS?)",
-              r"(?P<PLOT>PLOT)",
-              r"(?P<NAME>(?:DATA|LABEL|NAME|RESNAME|(?:S(?:OURC|RCNAM)|TBLNAM)E))",
-              r"(?P<ATTR>ATTR_[-_A-Za-z0-9]+)):")
-       ).unwrap();
+    /// Attribute name in the value of `#+ATTR_BACKEND` keywords
+    static ref REGEX_ATTRIBUTE_KEY: Regex =
+        Regex::new(r"(?:^|[ \t]+)(:[-a-zA-Z0-9_]+)(?:[ \t]+|$)").unwrap();
 }
 
 /// Since CAPTION is both DUAL and PARSED DualVal has to be able to store Strings or StringOrObject
-#[derive(Default, Debug, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct DualVal<T> {
     pub value: T,
     pub secondary: Option<T>,
 }
 
-/// List of affiliated keywords as strings.
-/// elisp: `defconst org-element-affiliated-keywords`
-/// Capabilities:
-/// DUAL - can have optional secontary value
+/// Affiliated keywords of an element.
+///
+/// Keywords are stored by their upper case name, after translation of
+/// old names (`#+TBLNAME` is stored as `NAME`). Capabilities of
+/// a keyword come from `ParserOptions`:
+/// DUAL - can have optional secondary value
 /// PARSED - value can be either string or an Object
-/// MULTI: can occur more than once in an element.
-
-#[derive(Debug, PartialEq)]
+/// MULTI - can occur more than once in an element, all values are kept
+/// in order of appearance. Only the last value is kept otherwise.
+///
+/// elisp: `defconst org-element-affiliated-keywords`
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct AffiliatedData<'a> {
-    /// DUAL, PARSED, MULTI
-    pub caption: Vec<DualVal<StringOrObject<'a>>>,
-    /// MULTI
-    pub header: Vec<Cow<'a, str>>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(flatten, serialize_with = "crate::serialize::ordered_map")
+    )]
+    pub keywords: HashMap<String, Vec<DualVal<StringOrObject<'a>>>>,
+}
 
-    /// No special capabilities
-    pub name: Option<Cow<'a, str>>,
+impl<'a> AffiliatedData<'a> {
+    pub fn into_owned(self) -> AffiliatedData<'static> {
+        AffiliatedData {
+            keywords: self
+                .keywords
+                .into_iter()
+                .map(|(k, v)| {
+                    let v = v
+                        .into_iter()
+                        .map(|d| DualVal {
+                            value: d.value.into_owned(),
+                            secondary: d.secondary.map(StringOrObject::into_owned),
+                        })
+                        .collect();
+                    (k, v)
+                })
+                .collect(),
+        }
+    }
 
-    /// No special capabilities
-    pub plot: Option<Cow<'a, str>>,
+    pub fn is_empty(&self) -> bool {
+        self.keywords.is_empty()
+    }
 
-    /// DUAL
-    pub results: Option<DualVal<Cow<'a, str>>>,
+    /// Last value of `keyword`, e.g. `get("NAME")`
+    pub fn get(&self, keyword: &str) -> Option<&DualVal<StringOrObject<'a>>> {
+        self.get_all(keyword).last()
+    }
 
-    /// MULTI
-    pub attr: HashMap<String, Vec<Cow<'a, str>>>,
-}
+    /// All values of `keyword` in order of appearance
+    pub fn get_all(&self, keyword: &str) -> &[DualVal<StringOrObject<'a>>] {
+        self.keywords
+            .get(&keyword.to_ascii_uppercase())
+            .map_or(&[], Vec::as_slice)
+    }
 
-impl<'a> Default for AffiliatedData<'a> {
-    fn default() -> AffiliatedData<'a> {
-        AffiliatedData {
-            caption: vec![],
-            header: vec![],
-            name: None,
-            plot: None,
-            results: None,
-            attr: HashMap::new(),
+    /// Adds a value of `keyword`. Values of keywords that can't occur
+    /// more than once replace the previous one.
+    pub fn insert(&mut self, keyword: String, value: DualVal<StringOrObject<'a>>, multiple: bool) {
+        let values = self.keywords.entry(keyword).or_insert_with(Vec::new);
+        if !multiple {
+            values.clear();
+        }
+        values.push(value);
+    }
+
+    /// Raw value of `#+NAME` keyword
+    pub fn name(&self) -> Option<&str> {
+        self.get("NAME")?.value.as_raw()
+    }
+
+    /// Attributes of `#+ATTR_BACKEND` keywords of `backend`, as
+    /// (name, value) pairs in order of appearance. Names lose their
+    /// colon: `#+ATTR_HTML: :alt A cat :width 50%` gives
+    /// `[("alt", "A cat"), ("width", "50%")]`.
+    ///
+    /// (defun org-export-read-attribute (attribute element &optional property)
+    pub fn attr(&self, backend: &str) -> Vec<(String, String)> {
+        let value = self
+            .get_all(&format!("ATTR_{}", backend))
+            .iter()
+            .filter_map(|v| v.value.as_raw())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mut attributes = vec![];
+        let mut keys = REGEX_ATTRIBUTE_KEY.captures_iter(&value).peekable();
+        while let Some(cap) = keys.next() {
+            let key = cap.get(1).unwrap();
+            let end = keys
+                .peek()
+                .map_or(value.len(), |next| next.get(0).unwrap().start());
+            let val = value[cap.get(0).unwrap().end()..end].trim();
+            attributes.push((key.as_str()[1..].to_string(), val.to_string()));
         }
+        attributes
     }
 }
 
-impl<'a> Parser<'a> {
+impl<'a, S: TextSource<'a>> Parser<'a, S> {
     /// Collect affiliated keywords from point down to LIMIT.
     ///
     /// Most elements can have affiliated keywords.  When looking for an
@@ -166,87 +188,66 @@ impl<'a> Parser<'a> {
     ///   position of the cursor and second is None.
     ///   elisp `defun org-element--collect-affiliated-keywords (limit)`
     ///
-    /// NB: it looks like this function parses objects ignoring parser granularity settings
-    ///
-    /// "FIXME: currently CAPTION's values are not parsed into objects and can only contain
-    /// raw strings for now for the following reasons:
-    ///
-    /// - Original algorithm does not take into account granularity, and it is probably a bug.
-    ///
-    /// - It is unclear what should be the type of the field that stores the
-    /// object since it can contain many objects.
-    ///
-    /// - It is unclear who should be the "parent" of these objects.  parse-objects function says
-    /// that: "Eventually, if both ACC and PARENT are nil, the common parent is the list of
-    /// objects itself." - It is hard to encode this into a type system, since in all other
-    /// cases, apart from affiliated keywords, objects parents are nodes of syntax trees
-    /// (ACC or PARENT)
-    pub fn collect_affiliated_keywords(&self, limit: usize) -> (usize, Option<AffiliatedData>) {
+    /// Values of keywords from `ParserOptions::parsed_keywords` (e.g. CAPTION) are parsed
+    /// into objects when granularity is `Object`, and kept as raw strings otherwise.
+    /// Original algorithm parses them regardless of granularity.
+    /// Parsed objects are not attached to any node: the element holding them becomes
+    /// their parent once it is added to the tree, see `SyntaxTree::alloc`.
+    pub fn collect_affiliated_keywords(&self, limit: usize) -> (usize, Option<AffiliatedData<'a>>) {
         if !self.cursor.borrow().is_bol() {
             return (self.cursor.borrow().pos(), None);
         }
         let origin = self.cursor.borrow().pos();
-        let _restrict = |that| SyntaxT::Keyword.can_contain(that);
+        let restrict = |that| SyntaxT::Keyword.can_contain(that);
+        let parsed = self.granularity == ParseGranularity::Object;
 
         let mut output: AffiliatedData = Default::default();
 
         loop {
-            let maybe_affiliated = capturing_at!(REGEX_AFFILIATED, self);
+            let maybe_affiliated = self.cursor.borrow().capturing_at(&self.re.affiliated);
             let current_pos = self.cursor.borrow().pos();
             if current_pos >= limit || maybe_affiliated.is_none() {
                 break;
             }
             let captures = maybe_affiliated.expect("Captures are expected here");
 
-            let matched: (&str, Match) = REGEX_AFFILIATED
-                .capture_names()
-                .flatten()
-                .filter(|n| n != &"SECONDARY")
-                .filter_map(|n| Some((n, captures.name(n)?)))
+            let matched = ["DUAL", "REGULAR", "ATTR"]
+                .iter()
+                .filter_map(|n| captures.name(n))
                 .next()
-                .unwrap();
-
-            let value_begin = match captures.name("SECONDARY") {
-                None => self.cursor.borrow().pos() + matched.1.end() + 1,
-                Some(sec) => self.cursor.borrow().pos() + sec.end() + 2,
-            };
+                .expect("One of keyword groups matches");
+            let keyword = self.options.translate_keyword(matched.as_str());
 
+            let value_begin = captures.end();
             let value_end = self.cursor.borrow_mut().line_end_position(None);
-
-            let value = Cow::from(self.input[value_begin..value_end].trim());
-
-            let secondary_value = match captures.name("SECONDARY") {
-                None => None,
-                Some(sec) => Some(Cow::from(sec.as_str().trim())),
+            let secondary = captures.name("SECONDARY").map(|sec| Interval {
+                start: sec.start(),
+                end: sec.end(),
+            });
+            let secondary = secondary.filter(|_| self.options.is_dual_keyword(&keyword));
+
+            let (value, secondary) = if parsed && self.options.is_parsed_keyword(&keyword) {
+                let parse = |start, end| {
+                    StringOrObject::Parsed(self.parse_object_list(start, end, &restrict))
+                };
+                (
+                    parse(value_begin, value_end),
+                    secondary.map(|sec| parse(sec.start, sec.end)),
+                )
+            } else {
+                let raw = |start, end| {
+                    let value = self.input.slice(start, end);
+                    let from = value.len() - value.trim_start().len();
+                    StringOrObject::Raw(source::sub(&value, from, from + value.trim().len()))
+                };
+                (
+                    raw(value_begin, value_end),
+                    secondary.map(|sec| raw(sec.start, sec.end)),
+                )
             };
 
-            match matched.0 {
-                "CAPTION" => output.caption.push(DualVal {
-                    value: StringOrObject::Raw(value),
-                    secondary: secondary_value.map(StringOrObject::Raw),
-                }),
-
-                "RESULTS" => {
-                    output.results = Some(DualVal {
-                        value: value,
-                        secondary: secondary_value,
-                    })
-                }
-
-                "NAME" => output.name = Some(value),
-                "PLOT" => output.plot = Some(value),
-                "HEADER" => output.header.push(value),
-                "ATTR" => {
-                    let backend = captures.name("ATTR").unwrap().as_str().to_ascii_uppercase();
-                    if let Some(vec) = output.attr.get_mut(backend.as_str()) {
-                        vec.push(value);
-                    } else {
-                        output.attr.insert(backend, vec![value]);
-                    }
-                }
-
-                _ => unreachable!(),
-            }
+            let multiple = self.options.is_multiple_keyword(&keyword);
+            output.insert(keyword, DualVal { value, secondary }, multiple);
 
             self.cursor.borrow_mut().goto_next_line();
         }
@@ -263,81 +264,81 @@ impl<'a> Parser<'a> {
 }
 
 mod test {
-    use super::REGEX_AFFILIATED;
     use crate::affiliated::DualVal;
     use crate::cursor::{is_multiline_regex, Cursor};
-    use crate::data::RepeaterType::CatchUp;
-    use crate::data::StringOrObject;
+    use crate::data::{NodeId, StringOrObject, SyntaxT};
+    use crate::options::{ParserOptions, Regexes};
     use crate::parser::ParseGranularity;
     use crate::parser::Parser;
-    use regex::Match;
     use std::borrow::Cow;
-    use std::collections::HashMap;
+
+    fn raw(s: &str) -> StringOrObject {
+        StringOrObject::Raw(Cow::from(s))
+    }
 
     #[test]
     fn test_re() {
-        let expected = r"(?i)^[ \t]*#\+(?:(?:(?P<CAPTION>CAPTION)|(?P<RESULTS>RESULTS?))(?:\[(?P<SECONDARY>.*)\])?|(?P<HEADER>HEADERS?)|(?P<PLOT>PLOT)|(?P<NAME>(?:DATA|LABEL|NAME|RESNAME|(?:S(?:OURC|RCNAM)|TBLNAM)E))|(?P<ATTR>ATTR_[-_A-Za-z0-9]+)):[ \t]*";
-        assert_eq!(expected, REGEX_AFFILIATED.as_str());
+        let expected = r"(?i)^[ \t]*#\+(?:(?P<DUAL>CAPTION|RESULTS)(?:\[(?P<SECONDARY>.*)\])?|(?P<REGULAR>DATA|HEADER|HEADERS|LABEL|NAME|PLOT|RESNAME|RESULT|SOURCE|SRCNAME|TBLNAME)|(?P<ATTR>ATTR_[-_A-Za-z0-9]+)):[ \t]*";
+        assert_eq!(expected, ParserOptions::default().affiliated_re());
     }
 
     #[test]
     fn affiliated_re() {
-        assert!(!is_multiline_regex(REGEX_AFFILIATED.as_str()));
-        let mut maybe_cap = REGEX_AFFILIATED.captures(r"  \n#+caPtion[GIT]: org-rs");
+        let re = Regexes::new(&ParserOptions::default()).affiliated;
+        assert!(!is_multiline_regex(re.as_str()));
+        let mut maybe_cap = re.captures(r"  \n#+caPtion[GIT]: org-rs");
         assert!(maybe_cap.is_none());
 
-        maybe_cap = REGEX_AFFILIATED.captures(r"   #+caPtion[GIT]: org-rs");
+        maybe_cap = re.captures(r"   #+caPtion[GIT]: org-rs");
         let mut cap = maybe_cap.unwrap();
-        assert_eq!("caPtion", cap.get(1).unwrap().as_str());
-        assert_eq!("GIT", cap.get(3).unwrap().as_str());
-        assert_eq!(None, cap.get(2));
-        assert_eq!(None, cap.get(4));
-        assert_eq!(None, cap.get(5));
-        assert_eq!(None, cap.get(6));
-        assert_eq!(None, cap.get(7));
+        assert_eq!("caPtion", cap.name("DUAL").unwrap().as_str());
+        assert_eq!("GIT", cap.name("SECONDARY").unwrap().as_str());
+        assert_eq!(None, cap.name("REGULAR"));
+        assert_eq!(None, cap.name("ATTR"));
 
         let dual_part = r"#+CAPTION: Orgmode";
-        cap = REGEX_AFFILIATED.captures(dual_part).unwrap();
-        assert_eq!("CAPTION", cap.get(1).unwrap().as_str());
-        assert_eq!(None, cap.get(2));
-        assert_eq!(None, cap.get(3));
-        assert_eq!(None, cap.get(4));
+        cap = re.captures(dual_part).unwrap();
+        assert_eq!("CAPTION", cap.name("DUAL").unwrap().as_str());
+        assert_eq!(None, cap.name("SECONDARY"));
 
         let single = r"#+RESNAME: someresult";
-        cap = REGEX_AFFILIATED.captures(single).unwrap();
-        assert_eq!("RESNAME", cap.get(6).unwrap().as_str());
-        assert_eq!(None, cap.get(1));
-        assert_eq!(None, cap.get(2));
-        assert_eq!(None, cap.get(4));
+        cap = re.captures(single).unwrap();
+        assert_eq!("RESNAME", cap.name("REGULAR").unwrap().as_str());
+        assert_eq!(None, cap.name("DUAL"));
+        assert_eq!(None, cap.name("ATTR"));
 
         let attr = r"#+attr_html: :file filename.ext";
-        cap = REGEX_AFFILIATED.captures(attr).unwrap();
-        assert_eq!("attr_html", cap.get(7).unwrap().as_str());
-        assert_eq!(None, cap.get(1));
-        assert_eq!(None, cap.get(2));
-        assert_eq!(None, cap.get(3));
+        cap = re.captures(attr).unwrap();
+        assert_eq!("attr_html", cap.name("ATTR").unwrap().as_str());
+        assert_eq!(None, cap.name("DUAL"));
+        assert_eq!(None, cap.name("REGULAR"));
+
+        assert!(re.captures(r"#+PLOT[x]: y").is_none());
+        assert!(re.captures(r"#+AUTHOR: me").is_none());
     }
 
     #[test]
     fn looking_at_affiliated_re() {
+        let re = Regexes::new(&ParserOptions::default()).affiliated;
         let caption_txt = " \n #+caPtion[GIT]: org-rs";
         let mut cursor = Cursor::new(caption_txt, 0);
 
-        assert!(cursor.looking_at(&*REGEX_AFFILIATED).is_none());
+        assert!(cursor.looking_at(&re).is_none());
         cursor.goto_next_line();
         assert_eq!(2, cursor.pos());
-        assert!(cursor.looking_at(&*REGEX_AFFILIATED).is_some());
+        assert!(cursor.looking_at(&re).is_some());
     }
 
     #[test]
     fn capturing_at_affiliated_re() {
+        let re = Regexes::new(&ParserOptions::default()).affiliated;
         let mut text = String::new();
         text.push_str(r"#+attr_html: :file filename.ext");
         text.push_str("\n");
         text.push_str(r"#+caPtion[GIT]: org-rs");
 
         let mut cursor = Cursor::new(text.as_str(), 0);
-        let maybe_affiliated = cursor.capturing_at(&*REGEX_AFFILIATED);
+        let maybe_affiliated = cursor.capturing_at(&re);
 
         assert!(maybe_affiliated.is_some());
     }
@@ -350,7 +351,11 @@ mod test {
         text.push_str(r"#+attr_html: :file filename.ext");
         text.push_str("\n\n");
         {
-            let p = Parser::new(text.as_str(), ParseGranularity::Object);
+            let p = Parser::new(
+                text.as_str(),
+                ParseGranularity::Object,
+                ParserOptions::default(),
+            );
             let maybe_collected = p.collect_affiliated_keywords(text.len());
             assert_eq!(0, maybe_collected.0);
             assert!(maybe_collected.1.is_none());
@@ -358,24 +363,151 @@ mod test {
         text.pop();
         text.push_str("#+BEGIN_SRC");
 
-        let p = Parser::new(text.as_str(), ParseGranularity::Object);
+        let p = Parser::new(
+            text.as_str(),
+            ParseGranularity::Element,
+            ParserOptions::default(),
+        );
         let maybe_collected = p.collect_affiliated_keywords(text.len());
         assert_eq!(0, maybe_collected.0);
         assert!(maybe_collected.1.is_some());
         let collected = maybe_collected.1.unwrap();
-        let mut test_attrs: HashMap<String, Vec<Cow<str>>> = HashMap::new();
-        test_attrs.insert(
-            "ATTR_HTML".to_string(),
-            vec![Cow::from(":file filename.ext")],
+        let test_attrs = vec![DualVal {
+            value: raw(":file filename.ext"),
+            secondary: None,
+        }];
+        assert_eq!(test_attrs, collected.get_all("ATTR_HTML"));
+
+        let test_caption = vec![DualVal {
+            value: raw("org-rs"),
+            secondary: Some(raw("GIT")),
+        }];
+        assert_eq!(test_caption, collected.get_all("caption"));
+    }
+
+    #[test]
+    fn attributes() {
+        let text = "#+ATTR_HTML: :alt A  cat :width 50%\n#+attr_html: :class big\n\
+                    #+ATTR_LATEX: :float t\nParagraph\n";
+        let p = Parser::new(text, ParseGranularity::Object, ParserOptions::default());
+        let collected = p.collect_affiliated_keywords(text.len()).1.unwrap();
+        let attr = |k: &str, v: &str| (k.to_string(), v.to_string());
+        assert_eq!(
+            vec![
+                attr("alt", "A  cat"),
+                attr("width", "50%"),
+                attr("class", "big")
+            ],
+            collected.attr("html")
+        );
+        assert_eq!(vec![attr("float", "t")], collected.attr("LATEX"));
+        assert!(collected.attr("md").is_empty());
+    }
+
+    #[test]
+    fn collect_affiliated_semantics() {
+        let text = "#+TBLNAME: old\n#+NAME: new\n#+HEADER: :a 1\n#+HEADERS: :b 2\n\
+                    #+RESULTS[abc]: out\n#+ATTR_LATEX: :width 1\n#+attr_latex: :float t\n| table |\n";
+        let p = Parser::new(text, ParseGranularity::Object, ParserOptions::default());
+        let collected = p.collect_affiliated_keywords(text.len()).1.unwrap();
+        assert_eq!(Some("new"), collected.name());
+        assert_eq!(1, collected.get_all("NAME").len());
+        let headers: Vec<_> = collected
+            .get_all("HEADER")
+            .iter()
+            .map(|v| &v.value)
+            .collect();
+        assert_eq!(vec![&raw(":a 1"), &raw(":b 2")], headers);
+        assert_eq!(
+            Some(&DualVal {
+                value: raw("out"),
+                secondary: Some(raw("abc")),
+            }),
+            collected.get("RESULTS")
         );
-        assert_eq!(test_attrs, collected.attr);
+        assert_eq!(2, collected.get_all("ATTR_LATEX").len());
+        assert_eq!(4, collected.keywords.len());
+    }
 
-        let mut test_caption: Vec<DualVal<StringOrObject>> = vec![];
-        test_caption.push(DualVal {
-            value: StringOrObject::Raw(Cow::from("org-rs")),
-            secondary: Some(StringOrObject::Raw(Cow::from("GIT"))),
-        });
+    #[test]
+    fn collect_affiliated_custom() {
+        let mut options = ParserOptions::default();
+        options.affiliated_keywords.push("OURTOOL".to_string());
+        options.dual_keywords.push("SOURCE_INFO".to_string());
+        options.multiple_keywords.push("SOURCE_INFO".to_string());
+        options
+            .keyword_translations
+            .push(("OT".to_string(), "OURTOOL".to_string()));
+        options.affiliated_keywords.push("OT".to_string());
+
+        let text = "#+OURTOOL: first\n#+ot: second\n#+source_info[v1]: a\n\
+                    #+SOURCE_INFO: b\n#+ATTR_OURTOOL: :x y\nParagraph\n";
+        let p = Parser::new(text, ParseGranularity::Object, options);
+        let collected = p.collect_affiliated_keywords(text.len()).1.unwrap();
+        assert_eq!(
+            &[DualVal {
+                value: raw("second"),
+                secondary: None,
+            }],
+            collected.get_all("OURTOOL")
+        );
+        assert_eq!(
+            &[
+                DualVal {
+                    value: raw("a"),
+                    secondary: Some(raw("v1")),
+                },
+                DualVal {
+                    value: raw("b"),
+                    secondary: None,
+                },
+            ],
+            collected.get_all("SOURCE_INFO")
+        );
+        assert_eq!(raw(":x y"), collected.get("ATTR_OURTOOL").unwrap().value);
+        assert_eq!(text.find("Paragraph").unwrap(), p.cursor.borrow().pos());
+    }
 
-        assert_eq!(test_caption, collected.caption);
+    #[test]
+    fn parsed_caption() {
+        let text =
+            "#+CAPTION[Short *one*]: See [[https://orgmode.org][Org]] and *this*\nParagraph\n";
+        let p = Parser::new(text, ParseGranularity::Object, ParserOptions::default());
+        let tree = p.parse_buffer();
+        let section = tree.children(tree.root()).next().unwrap();
+        let paragraph = tree.children(section).next().unwrap();
+        let caption = tree[paragraph]
+            .affiliated
+            .as_ref()
+            .unwrap()
+            .get("CAPTION")
+            .unwrap();
+
+        let names = |objects: &[NodeId]| -> Vec<&str> {
+            objects
+                .iter()
+                .map(|&id| SyntaxT::from(&tree[id].data).org_name())
+                .collect()
+        };
+        let value = caption.value.objects();
+        assert_eq!(
+            vec!["plain-text", "link", "plain-text", "bold"],
+            names(value)
+        );
+        assert_eq!("[[https://orgmode.org][Org]] ", tree.text_of(value[1]));
+        let secondary = caption.secondary.as_ref().unwrap().objects();
+        assert_eq!(vec!["plain-text", "bold"], names(secondary));
+        for &id in value.iter().chain(secondary) {
+            assert_eq!(Some(paragraph), tree[id].parent());
+        }
+        assert_eq!(
+            value.len() + secondary.len(),
+            tree[paragraph].secondary_objects().len()
+        );
+        // Caption objects are not children of the paragraph
+        assert!(tree.children(paragraph).all(|id| !value.contains(&id)));
+        // Objects inside of the link description
+        let description: Vec<_> = tree.children(value[1]).collect();
+        assert_eq!(vec!["plain-text"], names(&description));
     }
 }
@@ -0,0 +1,493 @@
+//    This file is part of org-rs.
+//
+//    org-rs is free software: you can redistribute it and/or modify
+//    it under the terms of the GNU General Public License as published by
+//    the Free Software Foundation, either version 3 of the License, or
+//    (at your option) any later version.
+//
+//    org-rs is distributed in the hope that it will be useful,
+//    but WITHOUT ANY WARRANTY; without even the implied warranty of
+//    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//    GNU General Public License for more details.
+//
+//    You should have received a copy of the GNU General Public License
+//    along with org-rs.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Element and object at a given position
+//!
+//! Interactive commands need to know what is under the cursor, and
+//! parsing the whole buffer for that is wasteful. `element_at_point`
+//! only parses the headlines enclosing the position, then walks the
+//! elements of their section, descending into those containing it.
+//! Text before the section of the enclosing headline, and after the
+//! found element, is never looked at, except for finding where the
+//! enclosing headlines end.
+
+use crate::data::{Interval, NodeId, Syntax, SyntaxNode, SyntaxT, SyntaxTree};
+use crate::headline::headline_level;
+use crate::parser::{ParseGranularity, Parser, ParserMode};
+use crate::source::TextSource;
+
+/// Node found at a position of the input, along with its ancestors
+pub struct AtPoint<'a> {
+    /// Tree holding the found node and its ancestors only, each of them
+    /// being the only child of the previous one. Root of the tree is
+    /// the document.
+    pub tree: SyntaxTree<'a>,
+
+    /// The found node
+    pub node: NodeId,
+}
+
+impl<'a> AtPoint<'a> {
+    /// The found node
+    pub fn get(&self) -> &SyntaxNode<'a> {
+        &self.tree[self.node]
+    }
+
+    /// Ancestors of the found node, starting from the closest one
+    /// and ending with the document
+    pub fn ancestors(&self) -> impl Iterator<Item = &SyntaxNode<'a>> {
+        self.tree.ancestors(self.node).map(move |id| &self.tree[id])
+    }
+}
+
+impl<'a, S: TextSource<'a>> Parser<'a, S> {
+    /// Determine closest element around point.
+    /// https://code.orgmode.org/bzg/org-mode/src/master/lisp/org-element.el#L5698
+    ///
+    /// Return value is a list like (TYPE PROPS) where TYPE is the type
+    /// of the element and PROPS a plist of properties associated to the
+    /// element.
+    ///
+    /// Possible types are defined in `org-element-all-elements'.
+    /// Properties depend on element or object type, but always include
+    /// `:begin', `:end', `:parent' and `:post-blank' properties.
+    ///
+    /// As a special case, if point is at the very beginning of the first
+    /// item in a list or sub-list, returned element will be that list
+    /// instead of the item.  Likewise, if point is at the beginning of
+    /// the first row of a table, returned element will be the table
+    /// instead of the first row.
+    ///
+    /// When point is at the end of the buffer, return the innermost
+    /// element ending there."
+    /// (defun org-element-at-point ())
+    ///
+    /// Returns `None` within blank lines at the beginning of the input.
+    /// Within blank lines at the end of a section, returns the section.
+    pub fn element_at_point(&self, pos: usize) -> Option<AtPoint<'a>> {
+        let input = self.input;
+        let origin = pos.min(input.len());
+        let saved = self.cursor.borrow().pos();
+
+        let line_end = input
+            .find_byte(b'\n', origin, input.len())
+            .unwrap_or_else(|| input.len());
+        {
+            let mut c = self.cursor.borrow_mut();
+            c.set(line_end);
+            c.skip_chars_backward(" \r\t\n", None);
+            if c.pos() == 0 {
+                c.set(saved);
+                return None;
+            }
+        }
+        let on_headline = self.on_headline();
+        let mut enclosing = vec![];
+        let mut current = if on_headline {
+            Some(self.cursor.borrow_mut().line_beginning_position(None))
+        } else {
+            self.prev_headline()
+        };
+        // Enclosing headline and its ancestors, innermost first
+        while let Some(start) = current {
+            let level = headline_level(input, start);
+            enclosing.push(start);
+            current = None;
+            while let Some(prev) = self.prev_headline() {
+                if headline_level(input, prev) < level {
+                    current = Some(prev);
+                    break;
+                }
+            }
+        }
+
+        let raw_secondary_p = self.granularity != ParseGranularity::Object;
+        // Build the tree in `self.tree`, where secondary strings are parsed.
+        self.tree.replace(SyntaxTree::from_source(input));
+        let mut parent = self.tree.borrow().root();
+        for &start in enclosing.iter().rev() {
+            self.cursor.borrow_mut().set(start);
+            let headline = self.headline_parser(input.len(), raw_secondary_p);
+            parent = self.tree.borrow_mut().append(parent, headline);
+        }
+
+        // Within blank lines right after a headline, return that headline.
+        if on_headline {
+            self.cursor.borrow_mut().set(saved);
+            return Some(AtPoint {
+                tree: self.tree.replace(SyntaxTree::new()),
+                node: parent,
+            });
+        }
+
+        // Otherwise move at the beginning of the section containing point.
+        let headline_contents = self.tree.borrow()[parent].content_location;
+        let section_start = match headline_contents {
+            Some(contents) if !enclosing.is_empty() => contents.start,
+            _ => {
+                let mut c = self.cursor.borrow_mut();
+                c.set(0);
+                c.skip_chars_forward(" \r\t\n", None);
+                c.goto_line_begin()
+            }
+        };
+        self.cursor.borrow_mut().set(section_start);
+        let section = self.section_parser(input.len());
+        let Interval {
+            start: mut beg,
+            mut end,
+        } = section.content_location.unwrap();
+        parent = self.tree.borrow_mut().append(parent, section);
+
+        // Parse successively each element, skipping those ending
+        // before original position.
+        let mut mode = ParserMode::Planning;
+        let mut structure = None;
+        let node = loop {
+            if beg >= end {
+                break parent;
+            }
+            self.cursor.borrow_mut().set(beg);
+            let element = self.current_element(end, mode, structure.clone());
+            let syntax = SyntaxT::from(&element.data);
+
+            // Skip any element ending before point. Also skip element
+            // ending at point (unless it is also the end of buffer)
+            // since we're sure that another element begins after it.
+            if element.location.end <= origin && element.location.end != input.len() {
+                beg = element.location.end;
+                mode = Self::next_mode(syntax, false).unwrap_or(mode);
+                continue;
+            }
+
+            let contents = element.content_location;
+            let list_structure = match &element.data {
+                Syntax::PlainList(d) => Some(d.structure.clone()),
+                _ => None,
+            };
+            let id = self.tree.borrow_mut().append(parent, element);
+
+            // Return element at point if it's not a greater element
+            // or point is not within its contents.
+            let contents = match contents {
+                Some(c) if syntax.is_greater_element() && c.start <= origin && origin <= c.end => c,
+                _ => break id,
+            };
+
+            // Create an anchor for tables and plain lists: when point
+            // is at the very beginning of these elements, ignoring
+            // affiliated keywords, target them instead of their
+            // contents.
+            let anchor = contents.start == origin
+                && (syntax == SyntaxT::PlainList || syntax == SyntaxT::Table);
+
+            // When point is at contents end, do not move into elements
+            // with an explicit ending, but return that element instead.
+            let explicit_end = contents.end == origin
+                && match syntax {
+                    SyntaxT::CenterBlock
+                    | SyntaxT::Drawer
+                    | SyntaxT::DynamicBlock
+                    | SyntaxT::InlineTask
+                    | SyntaxT::PropertyDrawer
+                    | SyntaxT::QuoteBlock
+                    | SyntaxT::SpecialBlock => true,
+                    // Corner case: if a list ends at the end of a
+                    // buffer without a final new line, return last
+                    // element in last item instead.
+                    SyntaxT::Item | SyntaxT::PlainList => {
+                        contents.end != input.len() || input.byte_at(input.len() - 1) == b'\n'
+                    }
+                    _ => false,
+                };
+            if anchor || explicit_end {
+                break id;
+            }
+
+            parent = id;
+            // Elements with no special mode of their own are parsed in
+            // planning mode, which only differs from no mode at all
+            // right after a headline.
+            mode = Self::next_mode(syntax, true).unwrap_or(ParserMode::Planning);
+            match syntax {
+                SyntaxT::PlainList => structure = list_structure,
+                SyntaxT::Item => (),
+                _ => structure = None,
+            }
+            beg = contents.start;
+            end = contents.end;
+        };
+
+        self.cursor.borrow_mut().set(saved);
+        Some(AtPoint {
+            tree: self.tree.replace(SyntaxTree::new()),
+            node,
+        })
+    }
+
+    /// Return smallest element or object around point.
+    /// https://code.orgmode.org/bzg/org-mode/src/master/lisp/org-element.el#L5792
+    ///
+    /// Return value is a list like (TYPE PROPS) where TYPE is the type
+    /// of the element or object and PROPS a plist of properties
+    /// associated to it.
+    ///
+    /// Possible types are defined in `org-element-all-elements' and
+    /// `org-element-all-objects'.  Properties depend on element or
+    /// object type, but always include `:begin', `:end', `:parent' and
+    /// `:post-blank'.
+    ///
+    /// As a special case, if point is right after an object and not at
+    /// the beginning of any other object, return that object.
+    ///
+    /// Optional argument ELEMENT, when non-nil, is the closest element
+    /// containing point, as returned by `org-element-at-point'.
+    /// Providing it allows for quicker computation."
+    /// (defun org-element-context (&optional element))
+    ///
+    /// Returns the element at point when there is no object at `pos`.
+    pub fn context_at_point(&self, pos: usize) -> Option<AtPoint<'a>> {
+        let mut at = self.element_at_point(pos)?;
+        let origin = pos.min(self.input.len());
+        let element = at.get();
+        let syntax = SyntaxT::from(&element.data);
+
+        // If point is inside an element containing objects, narrow to
+        // the container and proceed with parsing. Otherwise, return
+        // the element.
+        // TODO objects in item tags, planning lines and affiliated keywords
+        let container = match (syntax, element.content_location) {
+            (SyntaxT::Headline, _) | (SyntaxT::InlineTask, _) => {
+                let title = self.heading_line(element.location.start).title;
+                if title.start <= origin && origin <= title.end {
+                    title
+                } else {
+                    return Some(at);
+                }
+            }
+            (SyntaxT::Paragraph, Some(c))
+            | (SyntaxT::TableRow, Some(c))
+            | (SyntaxT::VerseBlock, Some(c))
+                if c.start <= origin
+                    && (origin < c.end || (origin == c.end && c.end == self.input.len())) =>
+            {
+                c
+            }
+            _ => return Some(at),
+        };
+
+        let saved = self.cursor.borrow().pos();
+        self.cursor.borrow_mut().set(container.start);
+        let mut limit = container.end;
+        let mut restriction = syntax;
+        let mut parent = at.node;
+        let mut last = None;
+        while let Some(object) = self.object_lex(limit, |that| restriction.can_contain(that)) {
+            // Object ends before point: skip it.
+            if object.location.end <= origin {
+                let end = object.location.end;
+                self.cursor.borrow_mut().set(end);
+                // For convenience, when object ends at point, without any
+                // space, keep it as we will return it if no object starts
+                // here.
+                if end == origin && !matches!(self.input.byte_at(end - 1), b' ' | b'\t') {
+                    last = Some((parent, object));
+                }
+                continue;
+            }
+            // Object starts after point: return parent.
+            if object.location.start > origin {
+                break;
+            }
+            last = None;
+            let object_syntax = SyntaxT::from(&object.data);
+            let contents = object.content_location;
+            parent = at.tree.append(parent, object);
+            match contents {
+                // If point is within object's contents, descend into it.
+                Some(c)
+                    if c.start <= origin
+                        && (origin < c.end || (origin == c.end && c.end == self.input.len())) =>
+                {
+                    self.cursor.borrow_mut().set(c.start);
+                    limit = c.end;
+                    restriction = object_syntax;
+                }
+                _ => break,
+            }
+        }
+        self.cursor.borrow_mut().set(saved);
+
+        if let Some((last_parent, object)) = last {
+            parent = at.tree.append(last_parent, object);
+        }
+        at.node = parent;
+        Some(at)
+    }
+}
+
+mod test {
+    use crate::data::{Syntax, SyntaxT};
+    use crate::options::ParserOptions;
+    use crate::parser::{ParseGranularity, Parser};
+
+    const TEXT: &str = "\
+Intro paragraph.
+
+* TODO First :work:
+Some text
+spanning lines.
+
+Another paragraph.
+** Child
+Child text.
+* Second
+Last.
+";
+
+    fn types(at: &super::AtPoint) -> Vec<SyntaxT> {
+        at.ancestors()
+            .map(|node| SyntaxT::from(&node.data))
+            .collect()
+    }
+
+    fn title(node: &crate::data::SyntaxNode) -> String {
+        match &node.data {
+            Syntax::Headline(h) => h.raw_value.to_string(),
+            _ => panic!("not a headline"),
+        }
+    }
+
+    #[test]
+    fn before_first_headline() {
+        let parser = Parser::new(TEXT, ParseGranularity::Element, ParserOptions::default());
+        let at = parser.element_at_point(3).unwrap();
+        assert_eq!(SyntaxT::Paragraph, SyntaxT::from(&at.get().data));
+        assert_eq!(0, at.get().location.start);
+        assert_eq!(17, at.get().location.end);
+        assert_eq!(vec![SyntaxT::Section, SyntaxT::OrgData], types(&at));
+
+        let blank = Parser::new(
+            "\n  \n* A\n",
+            ParseGranularity::Element,
+            ParserOptions::default(),
+        );
+        assert!(blank.element_at_point(1).is_none());
+    }
+
+    #[test]
+    fn on_headline() {
+        let parser = Parser::new(TEXT, ParseGranularity::Element, ParserOptions::default());
+        let at = parser
+            .element_at_point(TEXT.find("First").unwrap())
+            .unwrap();
+        assert_eq!(vec![SyntaxT::OrgData], types(&at));
+        match &at.get().data {
+            Syntax::Headline(h) => {
+                assert_eq!(1, h.level);
+                assert_eq!("First", h.raw_value);
+                assert_eq!(1, h.tags.len());
+                assert_eq!("work", h.tags[0].0);
+                assert!(h.todo_keyword.is_some());
+            }
+            _ => panic!("expected headline"),
+        }
+        assert_eq!(18, at.get().location.start);
+        assert_eq!(TEXT.find("* Second").unwrap(), at.get().location.end);
+    }
+
+    #[test]
+    fn inside_section() {
+        let parser = Parser::new(TEXT, ParseGranularity::Element, ParserOptions::default());
+
+        let at = parser
+            .element_at_point(TEXT.find("spanning").unwrap())
+            .unwrap();
+        assert_eq!(SyntaxT::Paragraph, SyntaxT::from(&at.get().data));
+        assert_eq!(TEXT.find("Some").unwrap(), at.get().location.start);
+        assert_eq!(TEXT.find("Another").unwrap(), at.get().location.end);
+        assert_eq!(1, at.get().post_blank);
+        assert_eq!(
+            vec![SyntaxT::Section, SyntaxT::Headline, SyntaxT::OrgData],
+            types(&at)
+        );
+
+        // Blank line belongs to the paragraph before it
+        let blank = parser.element_at_point(TEXT.find("\n\nAnother").unwrap() + 1);
+        assert_eq!(at.get().location, blank.unwrap().get().location);
+
+        let at = parser
+            .element_at_point(TEXT.find("text.").unwrap())
+            .unwrap();
+        assert_eq!(TEXT.find("Child text").unwrap(), at.get().location.start);
+        let headlines: Vec<String> = at
+            .ancestors()
+            .filter(|node| SyntaxT::from(&node.data) == SyntaxT::Headline)
+            .map(title)
+            .collect();
+        assert_eq!(vec!["Child", "First"], headlines);
+    }
+
+    #[test]
+    fn parses_from_enclosing_headline() {
+        // Drawers are not parsed yet, so reaching the one before
+        // the headline would panic.
+        let text = ":DRAWER:\nnot parsed\n:END:\n* Headline\nText\n";
+        let parser = Parser::new(text, ParseGranularity::Element, ParserOptions::default());
+        let at = parser.element_at_point(text.len() - 2).unwrap();
+        assert_eq!(SyntaxT::Paragraph, SyntaxT::from(&at.get().data));
+        assert_eq!(text.find("Text").unwrap(), at.get().location.start);
+    }
+
+    #[test]
+    fn context() {
+        let parser = Parser::new(TEXT, ParseGranularity::Object, ParserOptions::default());
+        let at = parser.context_at_point(TEXT.find("Last").unwrap()).unwrap();
+        assert_eq!(SyntaxT::Paragraph, SyntaxT::from(&at.get().data));
+        assert_eq!(
+            vec![SyntaxT::Section, SyntaxT::Headline, SyntaxT::OrgData],
+            types(&at)
+        );
+
+        let at = parser
+            .context_at_point(TEXT.find("Second").unwrap())
+            .unwrap();
+        assert_eq!(SyntaxT::Headline, SyntaxT::from(&at.get().data));
+
+        // Objects in headline titles
+        let text = "* See [[https://orgmode.org][the *best* site]] :tag:\n";
+        let parser = Parser::new(text, ParseGranularity::Object, ParserOptions::default());
+        let at = parser.context_at_point(text.find("best").unwrap()).unwrap();
+        assert_eq!(SyntaxT::Bold, SyntaxT::from(&at.get().data));
+        assert_eq!(
+            vec![SyntaxT::Link, SyntaxT::Headline, SyntaxT::OrgData],
+            types(&at)
+        );
+        let at = parser.context_at_point(text.find("tag").unwrap()).unwrap();
+        assert_eq!(SyntaxT::Headline, SyntaxT::from(&at.get().data));
+        // Right after an object, without any space
+        let text = "x *bold*. *end* z\n";
+        let parser = Parser::new(text, ParseGranularity::Object, ParserOptions::default());
+        let at = parser.context_at_point(text.find('.').unwrap()).unwrap();
+        assert_eq!(SyntaxT::Bold, SyntaxT::from(&at.get().data));
+        assert_eq!(text.find("*bold*").unwrap(), at.get().location.start);
+        assert_eq!(
+            vec![SyntaxT::Paragraph, SyntaxT::Section, SyntaxT::OrgData],
+            types(&at)
+        );
+        let at = parser.context_at_point(text.find('z').unwrap()).unwrap();
+        assert_eq!(SyntaxT::Paragraph, SyntaxT::from(&at.get().data));
+    }
+}
@@ -14,33 +14,48 @@
 //    along with org-rs.  If not, see <https://www.gnu.org/licenses/>.
 
 use crate::affiliated::AffiliatedData;
-use crate::data::SyntaxNode;
+use crate::data::{owned, SyntaxNode};
 use crate::parser::Parser;
+use crate::source::TextSource;
 use regex::Regex;
+use std::borrow::Cow;
 
 lazy_static! {
     pub static ref REGEX_BABEL_CALL: Regex = Regex::new(r"\+CALL:").unwrap();
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct BabelCallData<'a> {
     /// Name of code block being called (string).
-    pub call: &'a str,
+    pub call: Cow<'a, str>,
 
     /// Header arguments applied to the named code block (string or nil).
-    pub inside_header: Option<&'a str>,
+    pub inside_header: Option<Cow<'a, str>>,
 
     /// Arguments passed to the code block (string or nil).
-    pub arguments: Option<&'a str>,
+    pub arguments: Option<Cow<'a, str>>,
 
     /// Header arguments applied to the calling instance (string or nil).
-    pub end_header: Option<&'a str>,
+    pub end_header: Option<Cow<'a, str>>,
 
     /// Raw call, as Org syntax (string).
-    pub value: &'a str,
+    pub value: Cow<'a, str>,
 }
 
-impl<'a> Parser<'a> {
+impl<'a> BabelCallData<'a> {
+    pub fn into_owned(self) -> BabelCallData<'static> {
+        BabelCallData {
+            call: owned(self.call),
+            inside_header: self.inside_header.map(owned),
+            arguments: self.arguments.map(owned),
+            end_header: self.end_header.map(owned),
+            value: owned(self.value),
+        }
+    }
+}
+
+impl<'a, S: TextSource<'a>> Parser<'a, S> {
     // TODO implement babel_call_parser
     pub fn babel_call_parser(
         &self,
@@ -15,9 +15,11 @@
 
 use crate::affiliated::AffiliatedData;
 use crate::data::LineNumberingMode;
-use crate::data::SyntaxNode;
+use crate::data::{owned, Interval, Syntax, SyntaxNode};
 use crate::parser::Parser;
+use crate::source::TextSource;
 use regex::Regex;
+use std::borrow::Cow;
 
 lazy_static! {
     /// Used to identify the  Inline Comments, Blocks, Babel Calls, Dynamic Blocks and Keywords.
@@ -32,119 +34,216 @@ lazy_static! {
 
     pub static ref REGEX_DYNAMIC_BLOCK: Regex = Regex::new(r"\+BEGIN:? ").unwrap();
 
+    static ref REGEX_SRC_BLOCK_END: Regex = Regex::new(r"(?i)^[ \t]*#\+END_SRC[ \t]*$").unwrap();
+
+    /// Language in group 1, switches in group 2 and parameters in group 3
+    static ref REGEX_SRC_BLOCK_BEGIN: Regex = Regex::new(
+        r#"(?i)^[ \t]*#\+BEGIN_SRC(?: +(\S+))?((?: +(?:-(?:l ".+"|[ikr])|[-+]n(?: *[0-9]+)?))+)?(.*)[ \t]*$"#
+    )
+    .unwrap();
+
+    /// Sign of the line numbering switch in group 1
+    static ref REGEX_SWITCH_NUMBER_LINES: Regex = Regex::new(r"([-+])n(?: *([0-9]+))?\b").unwrap();
+    static ref REGEX_SWITCH_PRESERVE_INDENT: Regex = Regex::new(r"-i\b").unwrap();
+    static ref REGEX_SWITCH_REMOVE_LABELS: Regex = Regex::new(r"-r\b").unwrap();
+    static ref REGEX_SWITCH_KEEP_LABELS: Regex = Regex::new(r"-k\b").unwrap();
+    /// Label format in group 1
+    static ref REGEX_SWITCH_LABEL_FORMAT: Regex = Regex::new(r#"-l +"([^"\n]+)""#).unwrap();
+
+    /// Comma protecting lines starting with a star or `#+` in a block.
+    /// elisp: `org-unescape-code-in-string`
+    static ref REGEX_ESCAPED_CODE: Regex = Regex::new(r"(?m)^([ \t]*,*),(\*|#\+)").unwrap();
+
 }
 
 /// Greater element
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct DynamicBlockData<'a> {
     /// Block's parameters (string).
-    arguments: &'a str,
+    pub arguments: Cow<'a, str>,
 
     /// Block's name (string).
-    block_name: &'a str,
+    pub block_name: Cow<'a, str>,
 
     /// Drawer's name (string).
-    drawer_name: &'a str,
+    pub drawer_name: Cow<'a, str>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct CommentBlockData<'a> {
     /// Comments, without block's boundaries (string).
-    value: &'a str,
+    pub value: Cow<'a, str>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct ExampleBlockData<'a> {
     /// Format string used to write labels in current block,
     /// if different from org_coderef_label_format (string or nil).
-    label_fmt: Option<&'a str>,
+    pub label_fmt: Option<Cow<'a, str>>,
 
     ///Language of the code in the block, if specified (string or nil).
-    language: Option<&'a str>,
+    pub language: Option<Cow<'a, str>>,
 
     /// Non_nil if code lines should be numbered.
     /// A `new` value starts numbering from 1 wheareas
     /// `continued` resume numbering from previous numbered block
     /// (symbol new, continued or nil).
-    number_lines: Option<LineNumberingMode>,
+    pub number_lines: Option<LineNumberingMode>,
 
     /// Block's options located on the block's opening line (string)
-    options: &'a str,
+    pub options: Cow<'a, str>,
 
     /// Optional header arguments (string or nil)
-    parameters: Option<&'a str>,
+    pub parameters: Option<Cow<'a, str>>,
 
     /// Non_nil when indentation within the block mustn't be modified
     /// upon export (boolean).
-    preserve_indent: bool,
+    pub preserve_indent: bool,
 
     /// Non_nil if labels should be kept visible upon export (boolean).
-    retain_labels: bool,
+    pub retain_labels: bool,
 
     /// Optional switches for code block export (string or nil).
-    switches: Option<&'a str>,
+    pub switches: Option<Cow<'a, str>>,
 
     /// Non_nil if links to labels contained in the block should
     /// display the label instead of the line number (boolean).
-    use_labels: bool,
+    pub use_labels: bool,
 
     /// Contents (string).
-    value: &'a str,
+    pub value: Cow<'a, str>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct ExportBlockData<'a> {
     ///Related back_end's name (string).
-    type_s: &'a str,
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub type_s: Cow<'a, str>,
 
     ///Contents (string)
-    value: &'a str,
+    pub value: Cow<'a, str>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct SpecialBlockData<'a> {
     /// Block's name (string).
-    type_s: &'a str,
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub type_s: Cow<'a, str>,
     /// Raw contents in block (string).
-    raw_value: &'a str,
+    pub raw_value: Cow<'a, str>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct SrcBlockData<'a> {
     /// Format string used to write labels in current block,
     /// if different from org_coderef_label_format (string or nil).
-    label_fmt: Option<&'a str>,
+    pub label_fmt: Option<Cow<'a, str>>,
 
     /// Language of the code in the block, if specified (string or nil).
-    language: Option<&'a str>,
+    pub language: Option<Cow<'a, str>>,
 
     /// Non_nil if code lines should be numbered.
     /// A `new` value starts numbering from 1 wheareas
     /// `continued` resume numbering from previous
     /// numbered block (symbol new, continued or nil).
-    number_lines: Option<LineNumberingMode>,
+    pub number_lines: Option<LineNumberingMode>,
 
     /// Optional header arguments (string or nil).
-    parameters: Option<&'a str>,
+    pub parameters: Option<Cow<'a, str>>,
 
     /// Non_nil when indentation within the block
     /// mustn't be modified upon export (boolean).
-    preserve_indent: bool,
+    pub preserve_indent: bool,
     ///Non_nil if labels should be kept visible upon export (boolean).
-    retain_labels: bool,
+    pub retain_labels: bool,
 
     /// Optional switches for code block export (string or nil).
-    switches: Option<&'a str>,
+    pub switches: Option<Cow<'a, str>>,
 
     /// Non_nil if links to labels contained in the block
     /// should display the label instead of the line number (boolean).
-    use_labels: bool,
+    pub use_labels: bool,
 
     ///Source code (string).
-    value: &'a str,
+    pub value: Cow<'a, str>,
+}
+
+impl<'a> DynamicBlockData<'a> {
+    pub fn into_owned(self) -> DynamicBlockData<'static> {
+        DynamicBlockData {
+            arguments: owned(self.arguments),
+            block_name: owned(self.block_name),
+            drawer_name: owned(self.drawer_name),
+        }
+    }
+}
+
+impl<'a> CommentBlockData<'a> {
+    pub fn into_owned(self) -> CommentBlockData<'static> {
+        CommentBlockData {
+            value: owned(self.value),
+        }
+    }
+}
+
+impl<'a> ExampleBlockData<'a> {
+    pub fn into_owned(self) -> ExampleBlockData<'static> {
+        ExampleBlockData {
+            label_fmt: self.label_fmt.map(owned),
+            language: self.language.map(owned),
+            number_lines: self.number_lines,
+            options: owned(self.options),
+            parameters: self.parameters.map(owned),
+            preserve_indent: self.preserve_indent,
+            retain_labels: self.retain_labels,
+            switches: self.switches.map(owned),
+            use_labels: self.use_labels,
+            value: owned(self.value),
+        }
+    }
+}
+
+impl<'a> ExportBlockData<'a> {
+    pub fn into_owned(self) -> ExportBlockData<'static> {
+        ExportBlockData {
+            type_s: owned(self.type_s),
+            value: owned(self.value),
+        }
+    }
+}
+
+impl<'a> SpecialBlockData<'a> {
+    pub fn into_owned(self) -> SpecialBlockData<'static> {
+        SpecialBlockData {
+            type_s: owned(self.type_s),
+            raw_value: owned(self.raw_value),
+        }
+    }
+}
+
+impl<'a> SrcBlockData<'a> {
+    pub fn into_owned(self) -> SrcBlockData<'static> {
+        SrcBlockData {
+            label_fmt: self.label_fmt.map(owned),
+            language: self.language.map(owned),
+            number_lines: self.number_lines,
+            parameters: self.parameters.map(owned),
+            preserve_indent: self.preserve_indent,
+            retain_labels: self.retain_labels,
+            switches: self.switches.map(owned),
+            use_labels: self.use_labels,
+            value: owned(self.value),
+        }
+    }
 }
 
-impl<'a> Parser<'a> {
+impl<'a, S: TextSource<'a>> Parser<'a, S> {
     // TODO implement center_block_parser
     pub fn center_block_parser(
         &self,
@@ -195,14 +294,106 @@ impl<'a> Parser<'a> {
         unimplemented!()
     }
 
-    // TODO implement src_block_parser
+    /// Parse a source block.
+    ///
+    /// LIMIT bounds the search.  AFFILIATED is a list of which CAR is
+    /// the buffer position at the beginning of the first affiliated
+    /// keyword and CDR is a plist of affiliated keywords along with
+    /// their value.
+    ///
+    /// Return a list whose CAR is `src-block' and CDR is a plist
+    /// containing `:language', `:switches', `:parameters', `:begin',
+    /// `:end', `:number-lines', `:retain-labels', `:use-labels',
+    /// `:label-fmt', `:preserve-indent', `:value', `:post-blank' and
+    /// `:post-affiliated' keywords.
+    ///
+    /// Assume point is at the beginning of the block.
+    /// (defun org-element-src-block-parser (limit affiliated)
     pub fn src_block_parser(
         &self,
         limit: usize,
         start: usize,
-        affiliated: Option<AffiliatedData>,
+        maybe_aff: Option<AffiliatedData<'a>>,
     ) -> SyntaxNode<'a> {
-        unimplemented!()
+        let post_affiliated = self.cursor.borrow().pos();
+        let contents_end = match self.block_end(&REGEX_SRC_BLOCK_END, limit) {
+            Some(contents_end) => contents_end,
+            // Incomplete block: parse it as a paragraph.
+            None => return self.paragraph_parser(limit, start, maybe_aff),
+        };
+
+        let mut c = self.cursor.borrow_mut();
+        let cap = c
+            .capturing_at(&*REGEX_SRC_BLOCK_BEGIN)
+            .expect("src block parser called outside of a block");
+        // Get language as a string.
+        let language = cap.get(1).map(|m| m.text());
+        let non_blank = |m: Option<Cow<'a, str>>| match m {
+            Some(Cow::Borrowed(s)) if !s.trim().is_empty() => Some(Cow::Borrowed(s.trim())),
+            Some(Cow::Owned(s)) if !s.trim().is_empty() => Some(Cow::Owned(s.trim().to_string())),
+            _ => None,
+        };
+        let switches = non_blank(cap.get(2).map(|m| m.text()));
+        let parameters = non_blank(cap.get(3).map(|m| m.text()));
+
+        // Switches analysis.
+        let switch = |re: &Regex| switches.as_ref().map_or(false, |s| re.is_match(s));
+        let number_lines = switches
+            .as_ref()
+            .and_then(|s| REGEX_SWITCH_NUMBER_LINES.captures(s))
+            .map(|cap| match &cap[1] {
+                "-" => LineNumberingMode::New,
+                _ => LineNumberingMode::Continued,
+            });
+        let preserve_indent = switch(&REGEX_SWITCH_PRESERVE_INDENT);
+        let label_fmt = switches
+            .as_ref()
+            .and_then(|s| REGEX_SWITCH_LABEL_FORMAT.captures(s))
+            .map(|cap| Cow::from(cap[1].to_string()));
+        // Should labels be retained in (or stripped from) source blocks?
+        let retain_labels = switches.is_none()
+            || !switch(&REGEX_SWITCH_REMOVE_LABELS)
+            || (number_lines.is_some() && switch(&REGEX_SWITCH_KEEP_LABELS));
+        // What should code-references use - labels or line-numbers?
+        let use_labels =
+            switches.is_none() || (retain_labels && !switch(&REGEX_SWITCH_KEEP_LABELS));
+
+        // Retrieve code.
+        let value_begin = c.line_beginning_position(Some(2)).min(contents_end);
+        let value = unescape_code(self.input.slice(value_begin, contents_end));
+
+        c.set(contents_end);
+        let pos_before_blank = c.goto_next_line();
+        c.skip_chars_forward(" \r\t\n", Some(limit));
+        let end = if c.pos() >= self.input.len() {
+            self.input.len()
+        } else {
+            c.line_beginning_position(None)
+        }
+        .min(limit);
+        let post_blank = c.count_lines(pos_before_blank, end);
+        c.set(post_affiliated);
+
+        let mut node = SyntaxNode::new(
+            Syntax::SrcBlock(Box::new(SrcBlockData {
+                label_fmt,
+                language,
+                number_lines,
+                parameters,
+                preserve_indent,
+                retain_labels,
+                switches,
+                use_labels,
+                value,
+            })),
+            Interval { start, end },
+            None,
+            post_blank,
+        );
+        node.affiliated = maybe_aff
+            .filter(|aff| *aff != AffiliatedData::default())
+            .map(Box::new);
+        node
     }
 
     // TODO implement verse_block_parser
@@ -234,4 +425,74 @@ impl<'a> Parser<'a> {
     ) -> SyntaxNode<'a> {
         unimplemented!()
     }
+
+    /// Beginning of the first line after the current one and before
+    /// `limit` matching `end_re`, i.e. the end of the contents of the
+    /// block at point.
+    fn block_end(&self, end_re: &Regex, limit: usize) -> Option<usize> {
+        let from = self.cursor.borrow_mut().line_beginning_position(Some(2));
+        self.lines(from, limit)
+            .find(|&(line, end)| end_re.is_match(&self.input.slice(line, end)))
+            .map(|(line, _)| line)
+    }
+}
+
+/// Regexp matching the line closing a block of `kind`, i.e. what
+/// follows `#+BEGIN` on its opening line, like `_SRC` or `:`.
+/// To be compiled with `Parser::regex`.
+pub(crate) fn block_end_re(kind: &str) -> String {
+    format!(r"(?mi)^[ \t]*#\+END{}[ \t]*$", regex::escape(kind))
+}
+
+/// Removes the comma protecting lines starting with a star or `#+`
+/// in `code`.
+///
+/// elisp: `org-unescape-code-in-string`
+fn unescape_code(code: Cow<str>) -> Cow<str> {
+    if REGEX_ESCAPED_CODE.is_match(&code) {
+        Cow::from(REGEX_ESCAPED_CODE.replace_all(&code, "$1$2").into_owned())
+    } else {
+        code
+    }
+}
+
+mod test {
+    use crate::data::{LineNumberingMode, Syntax};
+    use crate::options::ParserOptions;
+    use crate::parser::{ParseGranularity, Parser};
+
+    #[test]
+    fn src_blocks() {
+        let text = "#+BEGIN_SRC emacs-lisp -n 5 -r -l \"(ref:%s)\" :tangle yes\n\
+                    ,* not a headline\n  ,#+END_SRC\n#+end_src\n\n\
+                    #+BEGIN_SRC\nno end\n";
+        let parser = Parser::new(text, ParseGranularity::Element, ParserOptions::default());
+        let tree = parser.parse_buffer();
+        let section = tree.children(tree.root()).next().unwrap();
+        let mut elements = tree.children(section);
+
+        let block = elements.next().unwrap();
+        let data = match &tree[block].data {
+            Syntax::SrcBlock(data) => data,
+            data => panic!("not a source block: {:?}", data),
+        };
+        assert_eq!(Some("emacs-lisp"), data.language.as_deref());
+        assert_eq!(Some("-n 5 -r -l \"(ref:%s)\""), data.switches.as_deref());
+        assert_eq!(Some(":tangle yes"), data.parameters.as_deref());
+        assert_eq!(Some(LineNumberingMode::New), data.number_lines);
+        assert_eq!(Some("(ref:%s)"), data.label_fmt.as_deref());
+        assert_eq!(
+            (false, false, false),
+            (data.preserve_indent, data.retain_labels, data.use_labels)
+        );
+        assert_eq!("* not a headline\n  #+END_SRC\n", data.value);
+        let second = text.find("#+BEGIN_SRC\n").unwrap();
+        assert_eq!(second, tree[block].location.end);
+        assert_eq!(1, tree[block].post_blank);
+
+        // Incomplete block: parsed as a paragraph
+        let paragraph = elements.next().unwrap();
+        assert_eq!(Syntax::Paragraph, tree[paragraph].data);
+        assert_eq!(second, tree[paragraph].location.start);
+    }
 }
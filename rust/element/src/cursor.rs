@@ -16,45 +16,53 @@
 // Parts of the cursor code are shamelessly copied from xi-rope
 // https://github.com/xi-editor/xi-editor/tree/master/rust/rope
 
-use memchr::{memchr, memrchr};
-use regex::{Captures, Match, Regex};
+use regex::{CaptureLocations, Regex};
+use std::borrow::Cow;
+use std::marker::PhantomData;
+use unicode_segmentation::{GraphemeCursor, GraphemeIncomplete};
+use unicode_width::UnicodeWidthChar;
 
-use crate::headline::{REGEX_HEADLINE_MULTILINE, REGEX_HEADLINE_SHORT};
+use crate::source::{self, TextSource};
 
 lazy_static! {
     pub static ref REGEX_EMPTY_LINE: Regex = Regex::new(r"^[ \t]*$").unwrap();
 }
 
 pub trait Metric {
-    fn is_boundary(s: &str, offset: usize) -> bool;
-    fn prev(s: &str, offset: usize) -> Option<usize>;
-    fn next(s: &str, offset: usize) -> Option<usize>;
+    fn is_boundary<'a, S: TextSource<'a>>(s: S, offset: usize) -> bool;
+    fn prev<'a, S: TextSource<'a>>(s: S, offset: usize) -> Option<usize>;
+    fn next<'a, S: TextSource<'a>>(s: S, offset: usize) -> Option<usize>;
 }
 
 pub struct BaseMetric(());
 
 impl Metric for BaseMetric {
-    fn is_boundary(s: &str, offset: usize) -> bool {
-        s.is_char_boundary(offset)
+    fn is_boundary<'a, S: TextSource<'a>>(s: S, offset: usize) -> bool {
+        let (chunk, start) = s.chunk_at(offset);
+        chunk.is_char_boundary(offset - start)
     }
 
-    fn prev(s: &str, offset: usize) -> Option<usize> {
+    fn prev<'a, S: TextSource<'a>>(s: S, offset: usize) -> Option<usize> {
         if offset == 0 {
             None
         } else {
+            // Chunks hold whole characters, so the previous one is
+            // in the chunk of the byte before `offset`.
+            let (chunk, start) = s.chunk_at(offset - 1);
+            let offset = offset - start;
             let mut len = 1;
-            while !s.is_char_boundary(offset - len) {
+            while !chunk.is_char_boundary(offset - len) {
                 len += 1;
             }
-            Some(offset - len)
+            Some(start + offset - len)
         }
     }
 
-    fn next(s: &str, offset: usize) -> Option<usize> {
+    fn next<'a, S: TextSource<'a>>(s: S, offset: usize) -> Option<usize> {
         if offset == s.len() {
             None
         } else {
-            let b = s.as_bytes()[offset];
+            let b = s.byte_at(offset);
             Some(offset + len_utf8_from_first_byte(b))
         }
     }
@@ -62,32 +70,128 @@ impl Metric for BaseMetric {
 
 pub struct LinesMetric(());
 impl Metric for LinesMetric {
-    fn is_boundary(s: &str, offset: usize) -> bool {
+    fn is_boundary<'a, S: TextSource<'a>>(s: S, offset: usize) -> bool {
         if offset == 0 {
             false
         } else {
-            s.as_bytes()[offset - 1] == b'\n'
+            s.byte_at(offset - 1) == b'\n'
         }
     }
 
-    fn prev(s: &str, offset: usize) -> Option<usize> {
+    fn prev<'a, S: TextSource<'a>>(s: S, offset: usize) -> Option<usize> {
         debug_assert!(offset > 0, "caller is responsible for validating input");
-        memrchr(b'\n', &s.as_bytes()[..offset - 1]).map(|pos| pos + 1)
+        s.rfind_byte(b'\n', 0, offset - 1).map(|pos| pos + 1)
     }
 
-    fn next(s: &str, offset: usize) -> Option<usize> {
-        memchr(b'\n', &s.as_bytes()[offset..]).map(|pos| offset + pos + 1)
+    fn next<'a, S: TextSource<'a>>(s: S, offset: usize) -> Option<usize> {
+        s.find_byte(b'\n', offset, s.len()).map(|pos| pos + 1)
     }
 }
 
-pub struct Cursor<'a> {
-    data: &'a str,
+/// Extended grapheme clusters, i.e. what users perceive as
+/// characters: a letter with its combining accents, an emoji
+/// sequence, or "\r\n".
+pub struct GraphemeMetric(());
+
+impl GraphemeMetric {
+    /// Runs `step` on a `GraphemeCursor` at `offset`, starting with
+    /// the chunk holding the byte at `first`, then feeding it the
+    /// chunks it asks for.
+    fn walk<'a, S: TextSource<'a>, T>(
+        s: S,
+        offset: usize,
+        first: usize,
+        step: impl Fn(&mut GraphemeCursor, &str, usize) -> Result<T, GraphemeIncomplete>,
+    ) -> Option<T> {
+        let mut cursor = GraphemeCursor::new(offset, s.len(), true);
+        let (mut chunk, mut start) = s.chunk_at(first);
+        loop {
+            match step(&mut cursor, chunk, start) {
+                Ok(result) => return Some(result),
+                Err(GraphemeIncomplete::NextChunk) => {
+                    let next = s.chunk_at(start + chunk.len());
+                    chunk = next.0;
+                    start = next.1;
+                }
+                Err(GraphemeIncomplete::PrevChunk) => {
+                    let prev = s.chunk_at(start - 1);
+                    chunk = prev.0;
+                    start = prev.1;
+                }
+                Err(GraphemeIncomplete::PreContext(end)) => {
+                    let (context, context_start) = s.chunk_at(end - 1);
+                    cursor.provide_context(&context[..end - context_start], context_start);
+                }
+                Err(GraphemeIncomplete::InvalidOffset) => return None,
+            }
+        }
+    }
+}
+
+impl Metric for GraphemeMetric {
+    fn is_boundary<'a, S: TextSource<'a>>(s: S, offset: usize) -> bool {
+        offset == 0
+            || offset == s.len()
+            || BaseMetric::is_boundary(s, offset)
+                && GraphemeMetric::walk(s, offset, offset, |c, chunk, start| {
+                    c.is_boundary(chunk, start)
+                })
+                .unwrap_or(false)
+    }
+
+    fn prev<'a, S: TextSource<'a>>(s: S, offset: usize) -> Option<usize> {
+        if offset == 0 {
+            return None;
+        }
+        GraphemeMetric::walk(s, offset, offset - 1, |c, chunk, start| {
+            c.prev_boundary(chunk, start)
+        })?
+    }
+
+    fn next<'a, S: TextSource<'a>>(s: S, offset: usize) -> Option<usize> {
+        if offset == s.len() {
+            return None;
+        }
+        GraphemeMetric::walk(s, offset, offset, |c, chunk, start| {
+            c.next_boundary(chunk, start)
+        })?
+    }
+}
+
+/// Column reached after displaying `c` from `column`.
+///
+/// Tabs move to the next tab stop, every `tab_width` columns. Wide
+/// characters, e.g. CJK, take two columns and combining characters
+/// none. Like in Emacs, control characters other than tabs take two
+/// columns, as they are displayed as `^C`.
+pub fn next_column(c: char, column: usize, tab_width: usize) -> usize {
+    match c {
+        '\t' if tab_width > 0 => (column / tab_width + 1) * tab_width,
+        '\t' => column,
+        c => column + c.width().unwrap_or(2),
+    }
+}
+
+/// Cursor over a `TextSource`, a `&str` unless specified otherwise
+pub struct Cursor<'a, S: TextSource<'a> = &'a str> {
+    data: S,
     pos: usize,
+    _text: PhantomData<&'a str>,
 }
 
 impl<'a> Cursor<'a> {
     pub fn new(data: &'a str, pos: usize) -> Cursor<'a> {
-        Cursor { data, pos }
+        Cursor::from_source(data, pos)
+    }
+}
+
+impl<'a, S: TextSource<'a>> Cursor<'a, S> {
+    pub fn from_source(data: S, pos: usize) -> Cursor<'a, S> {
+        Cursor {
+            data,
+            pos,
+            _text: PhantomData,
+        }
     }
 
     pub fn set(&mut self, pos: usize) {
@@ -98,12 +202,18 @@ impl<'a> Cursor<'a> {
         self.pos
     }
 
+    /// Character starting at `offset`
+    fn char_at(&self, offset: usize) -> Option<char> {
+        let (chunk, start) = self.data.chunk_at(offset);
+        chunk[offset - start..].chars().next()
+    }
+
     /// Get next codepoint after cursor position, and advance cursor.
     pub fn get_next_char(&mut self) -> Option<char> {
         let pos = self.pos;
         if let Some(offset) = self.next::<BaseMetric>() {
             self.pos = offset;
-            self.data[pos..].chars().next()
+            self.char_at(pos)
         } else {
             None
         }
@@ -113,7 +223,7 @@ impl<'a> Cursor<'a> {
     pub fn get_prev_char(&mut self) -> Option<char> {
         if let Some(offset) = self.prev::<BaseMetric>() {
             self.pos = offset;
-            self.data[offset..].chars().next()
+            self.char_at(offset)
         } else {
             None
         }
@@ -286,6 +396,59 @@ impl<'a> Cursor<'a> {
         return result;
     }
 
+    /// Return the horizontal position of point.
+    /// Beginning of line is column 0.
+    /// Tabs and wide characters are accounted for, see `next_column`.
+    ///
+    /// Corresponds to `current-column` in elisp
+    pub fn current_column(&self, tab_width: usize) -> usize {
+        let start = self
+            .data
+            .rfind_byte(b'\n', 0, self.pos)
+            .map_or(0, |p| p + 1);
+        self.data
+            .chunks(start, self.pos)
+            .flat_map(|(chunk, _)| chunk.chars())
+            .fold(0, |column, c| next_column(c, column, tab_width))
+    }
+
+    /// Return the indentation of the current line,
+    /// the column of its first non-blank character.
+    ///
+    /// Corresponds to `current-indentation` in elisp
+    pub fn current_indentation(&self, tab_width: usize) -> usize {
+        let start = self
+            .data
+            .rfind_byte(b'\n', 0, self.pos)
+            .map_or(0, |p| p + 1);
+        self.data
+            .chunks(start, self.data.len())
+            .flat_map(|(chunk, _)| chunk.chars())
+            .take_while(|&c| c == ' ' || c == '\t')
+            .fold(0, |column, c| next_column(c, column, tab_width))
+    }
+
+    /// Move point to `column` in the current line, or to the end of
+    /// the line if it is shorter. When `column` falls within a tab or
+    /// a wide character, point stops after it.
+    /// Returns the column point reached.
+    ///
+    /// Corresponds to `move-to-column` in elisp
+    pub fn move_to_column(&mut self, column: usize, tab_width: usize) -> usize {
+        self.goto_line_begin();
+        let mut current = 0;
+        while current < column {
+            match self.char_at(self.pos) {
+                Some(c) if c != '\n' && self.pos < self.data.len() => {
+                    current = next_column(c, current, tab_width);
+                    self.pos += c.len_utf8();
+                }
+                _ => break,
+            }
+        }
+        current
+    }
+
     pub fn char_after(&mut self, offset: usize) -> Option<char> {
         let pos = self.pos();
         self.set(offset);
@@ -294,6 +457,21 @@ impl<'a> Cursor<'a> {
         return result;
     }
 
+    /// Text the regex is matched against by `looking_at` and
+    /// `capturing_at`: the rest of the current line, or the text up to
+    /// `limit` for regexes that can match multiple lines.
+    fn match_window(&self, re: &Regex, limit: usize) -> Cow<'a, str> {
+        let limit = limit.min(self.data.len()).max(self.pos);
+        let end = if !is_multiline_regex(re.as_str()) {
+            LinesMetric::next(self.data, self.pos)
+                .map(|p| p - 1) // exclude '\n' from the string'
+                .map_or(limit, |p| p.min(limit))
+        } else {
+            limit
+        };
+        self.data.slice(self.pos, end)
+    }
+
     /// Checks if current line matches a given regex
     /// This function determines whether the text in
     /// the current buffer directly following cursor matches
@@ -301,33 +479,49 @@ impl<'a> Cursor<'a> {
     /// “Directly following” means precisely that:
     /// the search is “anchored” and it can succeed only
     /// starting with the first character following point.
-    /// The result is true if so, false otherwise.
+    /// Returns the position where the match ends.
     /// This function does not move cursor
     /// Use `capturing_at` if you need capture groups.
-    pub fn looking_at(&self, re: &Regex) -> Option<Match<'a>> {
-        let end = if !is_multiline_regex(re.as_str()) {
-            LinesMetric::next(self.data, self.pos)
-                .map(|p| p - 1) // exclude '\n' from the string'
-                .unwrap_or_else(|| self.data.len())
-        } else {
-            self.data.len()
-        };
-        re.find(&self.data[self.pos..end])
+    ///
+    /// Regexes matching multiple lines are matched against the rest of
+    /// the input, see `looking_at_within` to bound them.
+    pub fn looking_at(&self, re: &Regex) -> Option<usize> {
+        self.looking_at_within(re, self.data.len())
+    }
+
+    /// Same as `looking_at`, the match ending before `limit`,
+    /// e.g. the end of the element at point.
+    pub fn looking_at_within(&self, re: &Regex, limit: usize) -> Option<usize> {
+        re.find(&self.match_window(re, limit))
+            .filter(|m| m.start() == 0)
+            .map(|m| self.pos + m.end())
     }
 
     /// Acts exactly as `looking_at` but returns Captures
     /// This is slower than simple regex search so if you don't need
     /// capture groups use `looking_at` for better performance
-    pub fn capturing_at(&self, re: &Regex) -> Option<Captures<'a>> {
-        let end = if !is_multiline_regex(re.as_str()) {
-            LinesMetric::next(self.data, self.pos)
-                .map(|p| p - 1) // exclude '\n' from the string'
-                .unwrap_or_else(|| self.data.len())
-        } else {
-            self.data.len()
-        };
+    pub fn capturing_at<'r>(&self, re: &'r Regex) -> Option<Captures<'a, 'r>> {
+        let text = self.match_window(re, self.data.len());
+        let mut locations = re.capture_locations();
+        match re.captures_read(&mut locations, &text) {
+            Some(m) if m.start() == 0 => Some(Captures {
+                re,
+                text,
+                offset: self.pos,
+                locations,
+            }),
+            _ => None,
+        }
+    }
 
-        re.captures(&self.data[self.pos..end])
+    /// Returns true if a headline starts at `offset`, i.e.
+    /// `REGEX_HEADLINE_SHORT` matches the line starting there.
+    fn is_headline_at(&self, offset: usize) -> bool {
+        let mut pos = offset;
+        while pos < self.data.len() && self.data.byte_at(pos) == b'*' {
+            pos += 1;
+        }
+        pos > offset && pos < self.data.len() && matches!(self.data.byte_at(pos), b' ' | b'\t')
     }
 
     /// Possibly moves cursor to the beginning of the next headline
@@ -335,25 +529,38 @@ impl<'a> Cursor<'a> {
     /// If next headline is found returns it's start position
     pub fn next_headline(&mut self) -> Option<(usize)> {
         // make sure we don't match current headline
-        self.next::<LinesMetric>();
-        let beg = self.pos();
-        match REGEX_HEADLINE_MULTILINE.find(&self.data[beg..]) {
-            Some(p) => {
-                self.pos = beg + p.start();
-                Some(beg + p.start())
+        let mut line = self.next::<LinesMetric>()?;
+        loop {
+            if self.is_headline_at(line) {
+                self.pos = line;
+                return Some(line);
             }
-            None => None,
+            line = LinesMetric::next(self.data, line)?;
+        }
+    }
+
+    /// Possibly moves cursor to the beginning of the previous headline,
+    /// not counting the one cursor is on.
+    /// corresponds to `outline-previous-heading` in emacs
+    /// If previous headline is found returns it's start position
+    pub fn prev_headline(&mut self) -> Option<usize> {
+        let mut end = self.line_beginning_position(None);
+        while end > 0 {
+            let start = self.data.rfind_byte(b'\n', 0, end - 1).map_or(0, |p| p + 1);
+            if self.is_headline_at(start) {
+                self.pos = start;
+                return Some(start);
+            }
+            end = start;
         }
+        None
     }
 
     /// Return true if cursor is on a headline.
     /// corresponds to `org-at-heading-p`
     pub fn on_headline(&mut self) -> bool {
-        let pos = self.pos();
-        self.goto_line_begin();
-        let result = self.looking_at(&*REGEX_HEADLINE_SHORT).is_some();
-        self.set(pos);
-        return result;
+        let start = self.line_beginning_position(None);
+        self.is_headline_at(start)
     }
 
     pub fn is_bol(&self) -> bool {
@@ -384,7 +591,7 @@ impl<'a> Cursor<'a> {
         };
 
         let bound = match bound {
-            Some(bound) => bound,
+            Some(bound) => bound.min(self.data.len()),
             _ => self.data.len(),
         };
 
@@ -393,15 +600,12 @@ impl<'a> Cursor<'a> {
             return None;
         }
 
-        let mut iter = self.data[pos..].match_indices(str);
+        let text = self.data.slice(pos, bound);
+        let mut iter = text.match_indices(str);
         let mut i = 1;
         loop {
             match iter.next() {
                 Some(result) => {
-                    if result.0 + pos + str.len() > bound {
-                        return None;
-                    }
-
                     if count == i {
                         self.set(result.0 + pos + str.len());
                         return Some(result.0 + pos + str.len());
@@ -430,7 +634,7 @@ impl<'a> Cursor<'a> {
         }
 
         /// Set point to the end of the occurrence found, and return point.
-        match re.find(&self.data[self.pos..end]) {
+        match re.find(&self.data.slice(self.pos, end)) {
             None => None,
             Some(m) => {
                 self.set(self.pos + m.end());
@@ -439,6 +643,45 @@ impl<'a> Cursor<'a> {
         }
     }
 
+    /// Moves point backward, stopping after a char not in str, or at position limit.
+    /// Returns the number of chars skipped.
+    pub fn skip_chars_backward(&mut self, str: &str, limit: Option<usize>) -> usize {
+        let limit = limit.unwrap_or(0);
+        let mut count = 0;
+        while self.pos > limit {
+            let (chunk, start) = self.data.chunk_at(self.pos - 1);
+            match chunk[..self.pos - start].chars().next_back() {
+                Some(c) if str.contains(c) => {
+                    self.pos -= c.len_utf8();
+                    count += 1;
+                }
+                _ => break,
+            }
+        }
+        count
+    }
+
+    /// Return number of lines between `start` and `end`.
+    /// This is usually the number of newlines between them, but one
+    /// more if `end` is not at the beginning of a line.
+    ///
+    /// Corresponds to `count-lines` in elisp
+    pub fn count_lines(&self, start: usize, end: usize) -> usize {
+        if start >= end {
+            return 0;
+        }
+        let newlines: usize = self
+            .data
+            .chunks(start, end)
+            .map(|(chunk, _)| memchr::memchr_iter(b'\n', chunk.as_bytes()).count())
+            .sum();
+        if self.data.byte_at(end - 1) == b'\n' {
+            newlines
+        } else {
+            newlines + 1
+        }
+    }
+
     /// Moves point forward, stopping before a char not in str, or at position limit.
     pub fn skip_chars_forward(&mut self, str: &str, limit: Option<usize>) -> usize {
         let pos = self.pos();
@@ -467,6 +710,67 @@ impl<'a> Cursor<'a> {
     }
 }
 
+/// Capture groups of a match found by `Cursor::capturing_at`.
+/// Locations of the groups are offsets in the input, like the
+/// position of the cursor.
+pub struct Captures<'a, 'r> {
+    re: &'r Regex,
+    /// Text the regex matched against, starting at `offset`
+    text: Cow<'a, str>,
+    offset: usize,
+    locations: CaptureLocations,
+}
+
+/// Capture group of `Captures`
+pub struct Group<'c, 'a> {
+    text: &'c Cow<'a, str>,
+    offset: usize,
+    start: usize,
+    end: usize,
+}
+
+impl<'a, 'r> Captures<'a, 'r> {
+    /// Group number `i`, the whole match being group 0
+    pub fn get(&self, i: usize) -> Option<Group<'_, 'a>> {
+        self.locations.get(i).map(|(start, end)| Group {
+            text: &self.text,
+            offset: self.offset,
+            start,
+            end,
+        })
+    }
+
+    /// Group named `name`
+    pub fn name(&self, name: &str) -> Option<Group<'_, 'a>> {
+        let i = self.re.capture_names().position(|n| n == Some(name))?;
+        self.get(i)
+    }
+
+    /// Position where the whole match ends
+    pub fn end(&self) -> usize {
+        self.get(0).unwrap().end()
+    }
+}
+
+impl<'c, 'a> Group<'c, 'a> {
+    pub fn start(&self) -> usize {
+        self.offset + self.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.offset + self.end
+    }
+
+    pub fn as_str(&self) -> &'c str {
+        &self.text[self.start..self.end]
+    }
+
+    /// Text of the group, borrowed from the input when possible
+    pub fn text(&self) -> Cow<'a, str> {
+        source::sub(self.text, self.start, self.end)
+    }
+}
+
 /// Given the inital byte of a UTF-8 codepoint, returns the number of
 /// bytes required to represent the codepoint.
 /// RFC reference : https://tools.ietf.org/html/rfc3629#section-4
@@ -534,9 +838,11 @@ mod test {
 
         cursor.set(10);
 
-        let m = cursor.looking_at(&*REGEX_HEADLINE_SHORT).unwrap();
-        assert_eq!(0, m.start());
-        assert_eq!(5, m.end());
+        assert_eq!(Some(15), cursor.looking_at(&*REGEX_HEADLINE_SHORT));
+        let cap = cursor.capturing_at(&*REGEX_HEADLINE_SHORT).unwrap();
+        let m = cap.get(0).unwrap();
+        assert_eq!(10, m.start());
+        assert_eq!(15, m.end());
         assert_eq!("**** ", m.as_str());
         assert_eq!(10, cursor.pos());
     }
@@ -555,6 +861,17 @@ mod test {
         assert!(cursor.looking_at(&*REGEX_EMPTY_LINE).is_none());
     }
 
+    #[test]
+    fn looking_at_within() {
+        let text = "A\nB\nC\n";
+        let re = Regex::new(r"A\nB\n").unwrap();
+        let cursor = Cursor::new(&text, 0);
+
+        assert_eq!(Some(4), cursor.looking_at(&re));
+        assert_eq!(Some(4), cursor.looking_at_within(&re, 4));
+        assert!(cursor.looking_at_within(&re, 3).is_none());
+    }
+
     #[test]
     fn on_headline() {
         let rope = "Some text\n**** headline\n";
@@ -586,6 +903,10 @@ mod test {
         cursor = Cursor::new(&string2, 0);
         assert_eq!(Some(8), cursor.next_headline());
         assert_eq!(8, cursor.pos());
+
+        // Last line without newline, current headline is not the next one
+        cursor = Cursor::new("* Last", 0);
+        assert_eq!(None, cursor.next_headline());
     }
 
     #[test]
@@ -724,6 +1045,40 @@ mod test {
         assert_eq!(cursor.skip_chars_forward("* k\t", Some(2)), 3);
     }
 
+    #[test]
+    fn skip_chars_backward() {
+        let str = "hello** \t k  ";
+        let mut cursor = Cursor::new(&str, str.len());
+        assert_eq!(cursor.skip_chars_backward(" ", None), 2);
+        assert_eq!(cursor.pos(), 11);
+        assert_eq!(cursor.skip_chars_backward(" k\t", None), 4);
+        assert_eq!(cursor.pos(), 7);
+        assert_eq!(cursor.skip_chars_backward("*", Some(6)), 1);
+        assert_eq!(cursor.pos(), 6);
+    }
+
+    #[test]
+    fn count_lines() {
+        let text = "One\nTwo\n\nFour";
+        let cursor = Cursor::new(&text, 0);
+        assert_eq!(0, cursor.count_lines(0, 0));
+        assert_eq!(1, cursor.count_lines(0, 2));
+        assert_eq!(1, cursor.count_lines(0, 4));
+        assert_eq!(3, cursor.count_lines(0, 9));
+        assert_eq!(4, cursor.count_lines(0, text.len()));
+    }
+
+    #[test]
+    fn prev_headline() {
+        let text = "* First\ntext\n** Second\n\nmore";
+        let mut cursor = Cursor::new(&text, text.len());
+        assert_eq!(Some(13), cursor.prev_headline());
+        assert_eq!(13, cursor.pos());
+        assert_eq!(Some(0), cursor.prev_headline());
+        assert_eq!(None, cursor.prev_headline());
+        assert_eq!(0, cursor.pos());
+    }
+
     #[test]
     fn re_search_forward() {
         let text = "One\nTwo\nThi\nFo4\nFiv\nSix\n7en";
@@ -738,4 +1093,46 @@ mod test {
         assert_eq!(None, cursor.re_search_forward(&re, Some(24)));
         assert_eq!(25, cursor.pos());
     }
+
+    #[test]
+    fn graphemes() {
+        use super::GraphemeMetric;
+        // "e" with a combining acute accent, a family emoji, CRLF
+        let text = "e\u{301}x\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}\r\n";
+        let mut cursor = Cursor::new(text, 0);
+        let mut boundaries = vec![];
+        while let Some(pos) = cursor.next::<GraphemeMetric>() {
+            boundaries.push(pos);
+        }
+        assert_eq!(vec![3, 4, 22, 24], boundaries);
+        assert_eq!(Some(22), cursor.prev::<GraphemeMetric>());
+        assert_eq!(Some(4), cursor.prev::<GraphemeMetric>());
+        cursor.set(1);
+        assert!(!cursor.is_boundary::<GraphemeMetric>());
+        assert!(cursor.is_boundary::<BaseMetric>());
+        cursor.set(3);
+        assert!(cursor.is_boundary::<GraphemeMetric>());
+    }
+
+    #[test]
+    fn columns() {
+        let text = "\t- item\n  \t- sub\n\u{4E2D}\u{6587} text\n";
+        let mut cursor = Cursor::new(text, 1);
+        assert_eq!(8, cursor.current_column(8));
+        assert_eq!(4, cursor.current_column(4));
+        assert_eq!(8, cursor.current_indentation(8));
+
+        cursor.set(text.find("- sub").unwrap());
+        assert_eq!(8, cursor.current_column(8));
+        assert_eq!(4, cursor.current_indentation(4));
+
+        // CJK characters are two columns wide
+        let cjk = text.find('\u{4E2D}').unwrap();
+        cursor.set(text.find(" text").unwrap());
+        assert_eq!(4, cursor.current_column(8));
+        assert_eq!(4, cursor.move_to_column(3, 8));
+        assert_eq!(cjk + 6, cursor.pos());
+        assert_eq!(9, cursor.move_to_column(100, 8));
+        assert_eq!(text.len() - 1, cursor.pos());
+    }
 }
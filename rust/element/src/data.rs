@@ -16,40 +16,54 @@
 // https://orgmode.org/worg/dev/org-element-api.html
 // API page lists LineBreak as element, when both org-syntax page and source code list is as object
 
-use crate::affiliated::AffiliatedData;
-use crate::babel::BabelCallData;
-use crate::blocks::CommentBlockData;
-use crate::blocks::DynamicBlockData;
-use crate::blocks::ExampleBlockData;
-use crate::blocks::ExportBlockData;
-use crate::blocks::SpecialBlockData;
-use crate::blocks::SrcBlockData;
+pub use crate::affiliated::AffiliatedData;
+pub use crate::babel::BabelCallData;
+pub use crate::blocks::CommentBlockData;
+pub use crate::blocks::DynamicBlockData;
+pub use crate::blocks::ExampleBlockData;
+pub use crate::blocks::ExportBlockData;
+pub use crate::blocks::SpecialBlockData;
+pub use crate::blocks::SrcBlockData;
 use crate::data::Syntax::BabelCall;
-use crate::drawer::DrawerData;
-use crate::headline::{HeadlineData, InlineTaskData, NodePropertyData};
-use crate::keyword::KeywordData;
-use crate::latex::LatexEnvironmentData;
-use crate::latex::LatexFragmentData;
-use crate::list::*;
-use crate::markup::CommentData;
-use crate::markup::FixedWidthData;
-use crate::markup::FootnoteDefinitionData;
-use crate::table::{TableData, TableRowData};
+pub use crate::drawer::DrawerData;
+pub use crate::headline::{HeadlineData, InlineTaskData, NodePropertyData};
+pub use crate::keyword::KeywordData;
+pub use crate::latex::LatexEnvironmentData;
+pub use crate::latex::LatexFragmentData;
+pub use crate::list::*;
+pub use crate::markup::CommentData;
+pub use crate::markup::FixedWidthData;
+pub use crate::markup::FootnoteDefinitionData;
+use crate::source::TextSource;
+pub use crate::table::{TableData, TableRowData, TableRowType};
 use std::borrow::Cow;
 use std::cell::Cell;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fmt;
 use std::fmt::{Debug, Formatter};
-use std::rc::Rc;
-use std::rc::Weak;
+use std::num::NonZeroU32;
+use std::sync::OnceLock;
 
 use regex::Regex;
 
-/// Reference to a DOM node.
-pub type Handle<'a> = Rc<SyntaxNode<'a>>;
+/// Index of a node inside of a `SyntaxTree`.
+///
+/// Stored off by one, so that `Option<NodeId>` fits into four bytes
+/// and links do not dominate the size of a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(NonZeroU32);
 
-/// Weak reference to a DOM node, used for parent pointers.
-pub type WeakHandle<'a> = Weak<SyntaxNode<'a>>;
+impl NodeId {
+    fn new(index: usize) -> NodeId {
+        NodeId(NonZeroU32::new(index as u32 + 1).expect("too many nodes in the tree"))
+    }
+
+    /// Position of the node in the arena
+    pub fn index(self) -> usize {
+        self.0.get() as usize - 1
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Interval {
@@ -60,12 +74,17 @@ pub struct Interval {
 /// ParseTree node.
 /// https://orgmode.org/worg/dev/org-element-api.html#attributes
 /// Should be bound to the underlying rope's lifetime
-#[derive(Debug)]
+///
+/// Nodes do not own each other, they live in the `SyntaxTree` arena
+/// and refer to their relatives by `NodeId`.
+#[derive(Debug, Clone, PartialEq)]
 pub struct SyntaxNode<'a> {
     /// Parent node.
-    pub parent: RefCell<Option<WeakHandle<'a>>>,
-    /// Child nodes of this node.
-    pub children: RefCell<Vec<Handle<'a>>>,
+    pub(crate) parent: Option<NodeId>,
+    pub(crate) first_child: Option<NodeId>,
+    pub(crate) last_child: Option<NodeId>,
+    pub(crate) prev_sibling: Option<NodeId>,
+    pub(crate) next_sibling: Option<NodeId>,
 
     pub data: Syntax<'a>,
 
@@ -82,26 +101,489 @@ pub struct SyntaxNode<'a> {
     /// of the following one at the same level, if any.
     pub post_blank: usize,
 
-    // TODO affiliated keywords stub
-    pub affiliated: Option<()>,
+    /// Affiliated keywords attached to the element, if any
+    pub affiliated: Option<Box<AffiliatedData<'a>>>,
 }
 
 impl<'a> SyntaxNode<'a> {
-    pub fn create_root() -> SyntaxNode<'a> {
+    /// Creates a node that is not attached to any tree yet
+    pub fn new(
+        data: Syntax<'a>,
+        location: Interval,
+        content_location: Option<Interval>,
+        post_blank: usize,
+    ) -> SyntaxNode<'a> {
         SyntaxNode {
-            parent: RefCell::new(None),
-            children: RefCell::new(vec![]),
-            data: Syntax::OrgData,
-            location: Interval { start: 0, end: 0 },
-            content_location: None,
-            post_blank: 0,
+            parent: None,
+            first_child: None,
+            last_child: None,
+            prev_sibling: None,
+            next_sibling: None,
+            data,
+            location,
+            content_location,
+            post_blank,
             affiliated: None,
         }
     }
+
+    pub fn create_root() -> SyntaxNode<'a> {
+        SyntaxNode::new(Syntax::OrgData, Interval { start: 0, end: 0 }, None, 0)
+    }
+
+    pub fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    pub fn first_child(&self) -> Option<NodeId> {
+        self.first_child
+    }
+
+    pub fn last_child(&self) -> Option<NodeId> {
+        self.last_child
+    }
+
+    pub fn prev_sibling(&self) -> Option<NodeId> {
+        self.prev_sibling
+    }
+
+    pub fn next_sibling(&self) -> Option<NodeId> {
+        self.next_sibling
+    }
+
+    /// Top level objects of the parsed secondary strings of the node,
+    /// e.g. of its title or `CAPTION`. They are not children of the
+    /// node, so tree traversals do not visit them.
+    pub fn secondary_objects(&self) -> Vec<NodeId> {
+        let mut objects = vec![];
+        match &self.data {
+            Syntax::Headline(h) => objects.extend_from_slice(h.title.objects()),
+            Syntax::InlineTask(t) => objects.extend_from_slice(t.title.objects()),
+            Syntax::Item(i) => {
+                if let Some(tag) = &i.tag {
+                    objects.extend_from_slice(tag.objects());
+                }
+            }
+            _ => {}
+        }
+        if let Some(affiliated) = &self.affiliated {
+            for values in affiliated.keywords.values() {
+                for value in values {
+                    objects.extend_from_slice(value.value.objects());
+                    if let Some(secondary) = &value.secondary {
+                        objects.extend_from_slice(secondary.objects());
+                    }
+                }
+            }
+        }
+        objects
+    }
+
+    /// Same as `secondary_objects`, for moving nodes between arenas
+    pub(crate) fn secondary_objects_mut(&mut self) -> Vec<&mut NodeId> {
+        let mut objects = vec![];
+        match &mut self.data {
+            Syntax::Headline(h) => objects.extend(h.title.objects_mut()),
+            Syntax::InlineTask(t) => objects.extend(t.title.objects_mut()),
+            Syntax::Item(i) => {
+                if let Some(tag) = &mut i.tag {
+                    objects.extend(tag.objects_mut());
+                }
+            }
+            _ => {}
+        }
+        if let Some(affiliated) = &mut self.affiliated {
+            for values in affiliated.keywords.values_mut() {
+                for value in values {
+                    objects.extend(value.value.objects_mut());
+                    if let Some(secondary) = &mut value.secondary {
+                        objects.extend(secondary.objects_mut());
+                    }
+                }
+            }
+        }
+        objects
+    }
+
+    pub fn into_owned(self) -> SyntaxNode<'static> {
+        SyntaxNode {
+            parent: self.parent,
+            first_child: self.first_child,
+            last_child: self.last_child,
+            prev_sibling: self.prev_sibling,
+            next_sibling: self.next_sibling,
+            data: self.data.into_owned(),
+            location: self.location,
+            content_location: self.content_location,
+            post_blank: self.post_blank,
+            affiliated: self.affiliated.map(|a| Box::new(a.into_owned())),
+        }
+    }
+}
+
+/// Parse tree.
+///
+/// All nodes are stored in a single vector and link to their parent,
+/// children and siblings by `NodeId`, so building the tree costs one
+/// allocation per growth of the arena instead of one per node, and
+/// walking it does not touch reference counters.
+/// Root node is always `OrgData`.
+///
+/// The tree also keeps the text it describes, so that edits made
+/// through the mutation API (see `edit.rs`) can keep text and node
+/// intervals in sync.
+#[derive(Debug, Clone)]
+pub struct SyntaxTree<'a> {
+    pub(crate) nodes: Vec<SyntaxNode<'a>>,
+
+    /// Text covered by the root node
+    pub(crate) text: TreeText<'a>,
+
+    /// Text of the subtrees that are not attached to the root,
+    /// keyed by the top node of each subtree
+    pub(crate) fragments: HashMap<NodeId, String>,
+}
+
+/// Text of a tree, see `SyntaxTree::text`
+#[derive(Debug, Clone)]
+pub(crate) enum TreeText<'a> {
+    /// Text in one piece
+    Flat(Cow<'a, str>),
+    /// Chunks of the source the tree was parsed from, in order. They
+    /// are only joined when the text is needed as a single string.
+    Chunks(Vec<&'a str>, OnceLock<String>),
+}
+
+impl<'a> TreeText<'a> {
+    /// Whole text of `source`, borrowed from its chunks
+    pub(crate) fn from_source<S: TextSource<'a>>(source: S) -> TreeText<'a> {
+        let mut chunks: Vec<&'a str> = source.chunks(0, source.len()).map(|c| c.0).collect();
+        match chunks.len() {
+            0 => TreeText::Flat(Cow::from("")),
+            1 => TreeText::Flat(Cow::from(chunks.remove(0))),
+            _ => TreeText::Chunks(chunks, OnceLock::new()),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            TreeText::Flat(text) => text.len(),
+            TreeText::Chunks(chunks, _) => chunks.iter().map(|c| c.len()).sum(),
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        match self {
+            TreeText::Flat(text) => text,
+            TreeText::Chunks(chunks, joined) => joined.get_or_init(|| chunks.concat()),
+        }
+    }
+
+    pub(crate) fn to_mut(&mut self) -> &mut String {
+        if let TreeText::Chunks(..) = self {
+            *self = TreeText::Flat(Cow::from(self.as_str().to_string()));
+        }
+        match self {
+            TreeText::Flat(text) => text.to_mut(),
+            TreeText::Chunks(..) => unreachable!(),
+        }
+    }
+
+    fn into_owned(self) -> TreeText<'static> {
+        TreeText::Flat(match self {
+            TreeText::Flat(text) => owned(text),
+            TreeText::Chunks(chunks, joined) => {
+                Cow::Owned(joined.into_inner().unwrap_or_else(|| chunks.concat()))
+            }
+        })
+    }
+}
+
+/// Lifetime-free parse tree.
+///
+/// All strings are copied out of the input, so the tree is
+/// `Send + Sync` and may outlive the buffer it was parsed from.
+pub type OwnedSyntaxTree = SyntaxTree<'static>;
+
+impl<'a> Default for SyntaxTree<'a> {
+    fn default() -> SyntaxTree<'a> {
+        SyntaxTree::new()
+    }
+}
+
+impl<'a> SyntaxTree<'a> {
+    pub fn new() -> SyntaxTree<'a> {
+        SyntaxTree::with_text("")
+    }
+
+    /// Creates a tree holding only the root node spanning over `text`
+    pub fn with_text<T: Into<Cow<'a, str>>>(text: T) -> SyntaxTree<'a> {
+        SyntaxTree::with_tree_text(TreeText::Flat(text.into()))
+    }
+
+    /// Creates a tree holding only the root node spanning over the
+    /// whole text of `source`, without copying it
+    pub(crate) fn from_source<S: TextSource<'a>>(source: S) -> SyntaxTree<'a> {
+        SyntaxTree::with_tree_text(TreeText::from_source(source))
+    }
+
+    pub(crate) fn with_tree_text(text: TreeText<'a>) -> SyntaxTree<'a> {
+        let mut root = SyntaxNode::create_root();
+        let location = Interval {
+            start: 0,
+            end: text.len(),
+        };
+        root.location = location;
+        root.content_location = Some(location);
+        SyntaxTree {
+            nodes: vec![root],
+            text,
+            fragments: HashMap::new(),
+        }
+    }
+
+    pub fn root(&self) -> NodeId {
+        NodeId::new(0)
+    }
+
+    /// Number of nodes in the arena, including the root
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Text described by the tree. A tree parsed from a source made of
+    /// several chunks, e.g. a rope, joins them on the first call.
+    pub fn text(&self) -> &str {
+        self.text.as_str()
+    }
+
+    pub fn get(&self, id: NodeId) -> &SyntaxNode<'a> {
+        &self.nodes[id.index()]
+    }
+
+    pub fn get_mut(&mut self, id: NodeId) -> &mut SyntaxNode<'a> {
+        &mut self.nodes[id.index()]
+    }
+
+    /// Adds `node` to the arena without attaching it anywhere.
+    /// Objects of its secondary strings become its dependants.
+    pub fn alloc(&mut self, node: SyntaxNode<'a>) -> NodeId {
+        let id = NodeId::new(self.nodes.len());
+        let secondary = node.secondary_objects();
+        self.nodes.push(node);
+        for object in secondary {
+            self.nodes[object.index()].parent = Some(id);
+        }
+        id
+    }
+
+    /// Adds `node` to the arena as the last child of `parent`.
+    pub fn append(&mut self, parent: NodeId, node: SyntaxNode<'a>) -> NodeId {
+        let id = self.alloc(node);
+        self.link_last(parent, id);
+        id
+    }
+
+    /// Attaches a detached node as the last child of `parent`
+    pub(crate) fn link_last(&mut self, parent: NodeId, id: NodeId) {
+        let prev = self.nodes[parent.index()].last_child;
+        {
+            let node = &mut self.nodes[id.index()];
+            node.parent = Some(parent);
+            node.prev_sibling = prev;
+            node.next_sibling = None;
+        }
+        match prev {
+            Some(prev) => self.nodes[prev.index()].next_sibling = Some(id),
+            None => self.nodes[parent.index()].first_child = Some(id),
+        }
+        self.nodes[parent.index()].last_child = Some(id);
+    }
+
+    /// Iterates over direct children of `id`
+    pub fn children(&self, id: NodeId) -> Children<'_, 'a> {
+        Children {
+            tree: self,
+            next: self.nodes[id.index()].first_child,
+        }
+    }
+
+    /// Iterates over parents of `id`, starting from the closest one
+    pub fn ancestors(&self, id: NodeId) -> Ancestors<'_, 'a> {
+        Ancestors {
+            tree: self,
+            next: self.nodes[id.index()].parent,
+        }
+    }
+
+    /// Iterates over `id` and all its descendants in document order
+    pub fn descendants(&self, id: NodeId) -> Descendants<'_, 'a> {
+        Descendants {
+            tree: self,
+            root: id,
+            next: Some(id),
+        }
+    }
+
+    /// `id`, all its descendants and objects of their secondary
+    /// strings (with their descendants), in no particular order
+    pub fn subtree(&self, id: NodeId) -> Vec<NodeId> {
+        let mut nodes = vec![];
+        let mut stack = vec![id];
+        while let Some(top) = stack.pop() {
+            for node in self.descendants(top) {
+                nodes.push(node);
+                stack.extend(self.nodes[node.index()].secondary_objects());
+            }
+        }
+        nodes
+    }
+
+    /// Moves all the nodes of `other` but its root into this tree.
+    /// Children of the root of `other` become the last children of
+    /// `parent`. Nodes keep their order in the arena, so grafting
+    /// trees parsed from consecutive parts of a buffer gives the same
+    /// tree as parsing the whole buffer at once.
+    pub(crate) fn graft(&mut self, parent: NodeId, other: SyntaxTree<'a>) {
+        let offset = self.nodes.len() - 1;
+        let other_root = other.root();
+        let map = |id: NodeId| {
+            if id == other_root {
+                parent
+            } else {
+                NodeId::new(id.index() + offset)
+            }
+        };
+        let top: Vec<NodeId> = other.children(other_root).map(map).collect();
+        for (id, fragment) in other.fragments {
+            self.fragments.insert(map(id), fragment);
+        }
+        for mut node in other.nodes.into_iter().skip(1) {
+            for link in [
+                &mut node.parent,
+                &mut node.first_child,
+                &mut node.last_child,
+                &mut node.prev_sibling,
+                &mut node.next_sibling,
+            ] {
+                *link = link.map(map);
+            }
+            for object in node.secondary_objects_mut() {
+                *object = map(*object);
+            }
+            self.nodes.push(node);
+        }
+        for id in top {
+            self.link_last(parent, id);
+        }
+    }
+
+    /// Detaches the tree from the input it was parsed from.
+    pub fn into_owned(self) -> OwnedSyntaxTree {
+        SyntaxTree {
+            nodes: self.nodes.into_iter().map(SyntaxNode::into_owned).collect(),
+            text: self.text.into_owned(),
+            fragments: self.fragments,
+        }
+    }
+}
+
+impl<'a> std::ops::Index<NodeId> for SyntaxTree<'a> {
+    type Output = SyntaxNode<'a>;
+
+    fn index(&self, id: NodeId) -> &SyntaxNode<'a> {
+        self.get(id)
+    }
+}
+
+impl<'a> std::ops::IndexMut<NodeId> for SyntaxTree<'a> {
+    fn index_mut(&mut self, id: NodeId) -> &mut SyntaxNode<'a> {
+        self.get_mut(id)
+    }
+}
+
+/// Trees are equal when they hold the same nodes, under the same
+/// `NodeId`s, over the same text, however that text is stored.
+impl<'a, 'b> PartialEq<SyntaxTree<'b>> for SyntaxTree<'a> {
+    fn eq(&self, other: &SyntaxTree<'b>) -> bool {
+        self.nodes == other.nodes
+            && self.fragments == other.fragments
+            && self.text() == other.text()
+    }
+}
+
+pub struct Children<'t, 'a> {
+    tree: &'t SyntaxTree<'a>,
+    next: Option<NodeId>,
+}
+
+impl<'t, 'a> Iterator for Children<'t, 'a> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        let current = self.next?;
+        self.next = self.tree.nodes[current.index()].next_sibling;
+        Some(current)
+    }
+}
+
+pub struct Ancestors<'t, 'a> {
+    tree: &'t SyntaxTree<'a>,
+    next: Option<NodeId>,
+}
+
+impl<'t, 'a> Iterator for Ancestors<'t, 'a> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        let current = self.next?;
+        self.next = self.tree.nodes[current.index()].parent;
+        Some(current)
+    }
+}
+
+/// Pre-order traversal that does not leave the subtree it started from
+pub struct Descendants<'t, 'a> {
+    tree: &'t SyntaxTree<'a>,
+    root: NodeId,
+    next: Option<NodeId>,
+}
+
+impl<'t, 'a> Iterator for Descendants<'t, 'a> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        let current = self.next?;
+        let nodes = &self.tree.nodes;
+        self.next = match nodes[current.index()].first_child {
+            Some(child) => Some(child),
+            None => {
+                let mut cursor = current;
+                loop {
+                    if cursor == self.root {
+                        break None;
+                    }
+                    if let Some(sibling) = nodes[cursor.index()].next_sibling {
+                        break Some(sibling);
+                    }
+                    match nodes[cursor.index()].parent {
+                        Some(parent) => cursor = parent,
+                        None => break None,
+                    }
+                }
+            }
+        };
+        Some(current)
+    }
+}
+
+/// Detaches a string from the input it borrows
+pub fn owned(s: Cow<str>) -> Cow<'static, str> {
+    Cow::Owned(s.into_owned())
 }
 
 /// Complete list of syntax entities
-#[derive(Debug, EnumDiscriminants)]
+#[derive(Debug, Clone, PartialEq, EnumDiscriminants)]
 #[strum_discriminants(name(SyntaxT))]
 pub enum Syntax<'a> {
     /// Root of the parse tree
@@ -279,7 +761,71 @@ pub enum Syntax<'a> {
     Verbatim(Box<VerbatimData<'a>>),
 
     /// Special object
-    PlainText(&'a str),
+    PlainText(Cow<'a, str>),
+}
+
+impl<'a> Syntax<'a> {
+    /// Detaches syntax data from the input it was parsed from
+    /// by copying every borrowed string.
+    pub fn into_owned(self) -> Syntax<'static> {
+        use Syntax::*;
+        match self {
+            OrgData => OrgData,
+            BabelCall(d) => BabelCall(Box::new(d.into_owned())),
+            CenterBlock => CenterBlock,
+            Clock(d) => Clock(Box::new(d.into_owned())),
+            Comment(d) => Comment(Box::new(d.into_owned())),
+            CommentBlock(d) => CommentBlock(Box::new(d.into_owned())),
+            DiarySexp(d) => DiarySexp(Box::new(d.into_owned())),
+            Drawer(d) => Drawer(Box::new(d.into_owned())),
+            DynamicBlock(d) => DynamicBlock(Box::new(d.into_owned())),
+            ExampleBlock(d) => ExampleBlock(Box::new(d.into_owned())),
+            ExportBlock(d) => ExportBlock(Box::new(d.into_owned())),
+            FixedWidth(d) => FixedWidth(Box::new(d.into_owned())),
+            FootnoteDefinition(d) => FootnoteDefinition(Box::new(d.into_owned())),
+            Headline(d) => Headline(Box::new(d.into_owned())),
+            HorizontalRule => HorizontalRule,
+            InlineTask(d) => InlineTask(Box::new(d.into_owned())),
+            Item(d) => Item(Box::new(d.into_owned())),
+            Keyword(d) => Keyword(Box::new(d.into_owned())),
+            LatexEnvironment(d) => LatexEnvironment(Box::new(d.into_owned())),
+            NodeProperty(d) => NodeProperty(Box::new(d.into_owned())),
+            Paragraph => Paragraph,
+            PlainList(d) => PlainList(d),
+            Planning(d) => Planning(Box::new(d.into_owned())),
+            PropertyDrawer => PropertyDrawer,
+            QuoteBlock => QuoteBlock,
+            Section => Section,
+            SpecialBlock(d) => SpecialBlock(Box::new(d.into_owned())),
+            SrcBlock(d) => SrcBlock(Box::new(d.into_owned())),
+            Table(d) => Table(Box::new(d.into_owned())),
+            TableRow(d) => TableRow(d),
+            VerseBlock => VerseBlock,
+            Bold => Bold,
+            Code(d) => Code(Box::new(d.into_owned())),
+            Entity(d) => Entity(Box::new(d.into_owned())),
+            ExportSnippet(d) => ExportSnippet(Box::new(d.into_owned())),
+            FootnoteReference(d) => FootnoteReference(Box::new(d.into_owned())),
+            InlineBabelCall(d) => InlineBabelCall(Box::new(d.into_owned())),
+            InlineSrcBlock(d) => InlineSrcBlock(Box::new(d.into_owned())),
+            Italic => Italic,
+            LineBreak => LineBreak,
+            LatexFragment(d) => LatexFragment(Box::new(d.into_owned())),
+            Link(d) => Link(Box::new(d.into_owned())),
+            Macro(d) => Macro(Box::new(d.into_owned())),
+            RadioTarget(d) => RadioTarget(Box::new(d.into_owned())),
+            StatisticsCookie(d) => StatisticsCookie(Box::new(d.into_owned())),
+            StrikeThrough => StrikeThrough,
+            Subscript(d) => Subscript(d),
+            Superscript(d) => Superscript(d),
+            TableCell => TableCell,
+            Target(d) => Target(Box::new(d.into_owned())),
+            Timestamp(d) => Timestamp(Box::new(d.into_owned())),
+            Underline => Underline,
+            Verbatim(d) => Verbatim(Box::new(d.into_owned())),
+            PlainText(s) => PlainText(owned(s)),
+        }
+    }
 }
 
 impl SyntaxT {
@@ -306,7 +852,7 @@ impl SyntaxT {
     }
 
     #[rustfmt::skip]
-    fn is_element(self) -> bool {
+    pub(crate) fn is_element(self) -> bool {
         use SyntaxT::*;
         match self {
             BabelCall          => true,   // Element
@@ -345,7 +891,7 @@ impl SyntaxT {
     }
 
     #[rustfmt::skip]
-    fn is_object(self) -> bool {
+    pub fn is_object(self) -> bool {
         use SyntaxT::*;
         match self {
             Bold              => true,  // Recursive object
@@ -376,7 +922,7 @@ impl SyntaxT {
     }
 
     #[rustfmt::skip]
-    fn is_recursive_object(self) -> bool {
+    pub(crate) fn is_recursive_object(self) -> bool {
         use SyntaxT::*;
         match self {
             Bold              => true,  // Recursive object
@@ -418,6 +964,68 @@ impl SyntaxT {
         self.is_greater_element() || self.is_object_container()
     }
 
+    /// Name of the type as used by org-element, e.g. `src-block`
+    #[rustfmt::skip]
+    pub fn org_name(self) -> &'static str {
+        use SyntaxT::*;
+        match self {
+            OrgData            => "org-data",
+            BabelCall          => "babel-call",
+            CenterBlock        => "center-block",
+            Clock              => "clock",
+            Comment            => "comment",
+            CommentBlock       => "comment-block",
+            DiarySexp          => "diary-sexp",
+            Drawer             => "drawer",
+            DynamicBlock       => "dynamic-block",
+            ExampleBlock       => "example-block",
+            ExportBlock        => "export-block",
+            FixedWidth         => "fixed-width",
+            FootnoteDefinition => "footnote-definition",
+            Headline           => "headline",
+            HorizontalRule     => "horizontal-rule",
+            InlineTask         => "inlinetask",
+            Item               => "item",
+            Keyword            => "keyword",
+            LatexEnvironment   => "latex-environment",
+            NodeProperty       => "node-property",
+            Paragraph          => "paragraph",
+            PlainList          => "plain-list",
+            Planning           => "planning",
+            PropertyDrawer     => "property-drawer",
+            QuoteBlock         => "quote-block",
+            Section            => "section",
+            SpecialBlock       => "special-block",
+            SrcBlock           => "src-block",
+            Table              => "table",
+            TableRow           => "table-row",
+            VerseBlock         => "verse-block",
+            Bold               => "bold",
+            Code               => "code",
+            Entity             => "entity",
+            ExportSnippet      => "export-snippet",
+            FootnoteReference  => "footnote-reference",
+            InlineBabelCall    => "inline-babel-call",
+            InlineSrcBlock     => "inline-src-block",
+            Italic             => "italic",
+            LineBreak          => "line-break",
+            LatexFragment      => "latex-fragment",
+            Link               => "link",
+            Macro              => "macro",
+            RadioTarget        => "radio-target",
+            StatisticsCookie   => "statistics-cookie",
+            StrikeThrough      => "strike-through",
+            Subscript          => "subscript",
+            Superscript        => "superscript",
+            TableCell          => "table-cell",
+            Target             => "target",
+            Timestamp          => "timestamp",
+            Underline          => "underline",
+            Verbatim           => "verbatim",
+            PlainText          => "plain-text",
+        }
+    }
+
     /// Corresponds to `defconst org-element-object-restrictions` in org-element.el
     /// Original doc:
     /// "Alist of objects restrictions.
@@ -522,338 +1130,365 @@ impl SyntaxT {
     }
 }
 
-/// Some elements can contain objects directly in their value fields
+/// Some elements can contain objects directly in their value fields,
+/// called secondary strings in org-element (e.g. `CAPTION` values).
+///
+/// Objects of a parsed secondary string live in the same `SyntaxTree`
+/// as the node holding them. Their parent is that node, but they are
+/// not among its children. See `SyntaxNode::secondary_objects`.
+#[derive(Debug, Clone, PartialEq)]
 pub enum StringOrObject<'a> {
     Raw(Cow<'a, str>),
-    Parsed(SyntaxNode<'a>),
+    /// Top level objects, in order
+    Parsed(Vec<NodeId>),
 }
 
-impl<'a> Debug for StringOrObject<'a> {
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+impl<'a> StringOrObject<'a> {
+    pub fn into_owned(self) -> StringOrObject<'static> {
         match self {
-            StringOrObject::Raw(raw) => write!(f, "Raw: {:?}", raw),
-            StringOrObject::Parsed(p) => unimplemented!(),
+            StringOrObject::Raw(raw) => StringOrObject::Raw(owned(raw)),
+            StringOrObject::Parsed(p) => StringOrObject::Parsed(p),
         }
     }
-}
 
-impl<'a> PartialEq for StringOrObject<'a> {
-    fn eq(&self, other: &StringOrObject) -> bool {
+    /// Raw value, `None` for parsed secondary strings
+    pub fn as_raw(&self) -> Option<&str> {
         match self {
-            StringOrObject::Raw(raw) => match other {
-                StringOrObject::Parsed(..) => false,
-                StringOrObject::Raw(rhs) => raw.eq(rhs),
-            },
-            StringOrObject::Parsed(p) => unimplemented!(),
+            StringOrObject::Raw(raw) => Some(raw),
+            StringOrObject::Parsed(_) => None,
+        }
+    }
+
+    /// Objects of a parsed secondary string, empty for raw ones
+    pub fn objects(&self) -> &[NodeId] {
+        match self {
+            StringOrObject::Raw(_) => &[],
+            StringOrObject::Parsed(objects) => objects,
+        }
+    }
+
+    pub(crate) fn objects_mut(&mut self) -> &mut [NodeId] {
+        match self {
+            StringOrObject::Raw(_) => &mut [],
+            StringOrObject::Parsed(objects) => objects,
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct ClockData<'a> {
     /// Clock duration for a closed clock, or nil (string or nil).
-    duration: &'a str,
+    pub duration: Cow<'a, str>,
 
     /// Status of current clock (symbol closed or running).
-    status: ClockStatus,
+    pub status: ClockStatus,
 
     /// Timestamp associated to clock keyword (timestamp object).
-    value: TimestampData<'a>,
+    pub value: TimestampData<'a>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub enum ClockStatus {
     Running,
     Closed,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct DiarySexpData<'a> {
     /// Full Sexp (string).
-    value: &'a str,
+    pub value: Cow<'a, str>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub enum LineNumberingMode {
     New,
     Continued,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct PlanningData<'a> {
     /// Timestamp associated to closed keyword, if any
     /// (timestamp object or nil).
-    closed: Option<TimestampData<'a>>,
+    pub closed: Option<TimestampData<'a>>,
 
     /// Timestamp associated to deadline keyword, if any
     /// (timestamp object or nil).
-    deadline: Option<TimestampData<'a>>,
+    pub deadline: Option<TimestampData<'a>>,
 
     /// Timestamp associated to scheduled keyword, if any
     /// (timestamp object or nil).
-    scheduled: Option<TimestampData<'a>>,
+    pub scheduled: Option<TimestampData<'a>>,
 }
 
 // ===== Objects Data ======
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct CodeData<'a> {
     /// Contents (string).
-    value: &'a str,
+    pub value: Cow<'a, str>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct EntityData<'a> {
     /// Entity's ASCII representation (string).
-    ascii: &'a str,
+    pub ascii: Cow<'a, str>,
 
     /// Entity's HTML representation (string).
-    html: &'a str,
+    pub html: Cow<'a, str>,
 
     /// Entity's LaTeX representation (string).
-    latex: &'a str,
+    pub latex: Cow<'a, str>,
 
     /// Non-nil if entity's LaTeX representation should be
     /// in math mode (boolean).
-    latex_math_p: bool,
+    pub latex_math_p: bool,
 
     /// Entity's Latin-1 encoding representation (string).
-    latin1: &'a str,
+    pub latin1: Cow<'a, str>,
 
     /// Entity's name, without backslash nor brackets (string).
-    name: &'a str,
+    pub name: Cow<'a, str>,
 
     /// Non-nil if entity is written with optional
     /// brackets in original buffer (boolean).
-    use_brackets_p: bool,
+    pub use_brackets_p: bool,
 
     /// Entity's UTF-8 encoding representation (string).
-    utf_8: &'a str,
+    pub utf_8: Cow<'a, str>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct ExportSnippetData<'a> {
     /// Relative back_end's name (string).
-    back_end: &'a str,
+    pub back_end: Cow<'a, str>,
 
     /// Export code (string).
-    value: &'a str,
+    pub value: Cow<'a, str>,
 }
 
 /// Recursive object.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct FootnoteReferenceData<'a> {
     /// Footnote's label, if any (string or nil).
-    label: Option<&'a str>,
+    pub label: Option<Cow<'a, str>>,
 
     /// Determine whether reference has its
     /// definition inline, or not (symbol inline, standard).
-    type_s: &'a str,
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub type_s: Cow<'a, str>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct InlineBabelCallData<'a> {
     ///Name of code block being called (string).
-    call: &'a str,
+    pub call: Cow<'a, str>,
 
     ///Header arguments applied to the named code block (string or nil).
-    inside_header: Option<&'a str>,
+    pub inside_header: Option<Cow<'a, str>>,
 
     ///Arguments passed to the code block (string or nil).
-    arguments: Option<&'a str>,
+    pub arguments: Option<Cow<'a, str>>,
 
     ///Header arguments applied to the calling instance (string or nil).
-    end_header: Option<&'a str>,
+    pub end_header: Option<Cow<'a, str>>,
 
     ///Raw call, as Org syntax (string).
-    value: &'a str,
+    pub value: Cow<'a, str>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct InlineSrcBlockData<'a> {
     ///Language of the code in the block (string).
-    language: &'a str,
+    pub language: Cow<'a, str>,
 
     ///Optional header arguments (string or nil).
-    parameters: Option<&'a str>,
+    pub parameters: Option<Cow<'a, str>>,
 
     ///Source code (string).
-    value: &'a str,
+    pub value: Cow<'a, str>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub enum LinkFormat {
     Plain,
     Angle,
     Bracket,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct LinkData<'a> {
     /// Name of application requested to open the link
     /// in Emacs (string or nil).
     /// It only applies to "file" type links.
-    application: Option<&'a str>,
+    pub application: Option<Cow<'a, str>>,
 
     /// Format for link syntax (symbol plain, angle, bracket).
-    format: LinkFormat,
+    pub format: LinkFormat,
 
     /// Identifier for link's destination.
     /// It is usually the link part with type,
     /// if specified, removed (string).
-    path: &'a str,
+    pub path: Cow<'a, str>,
 
     ///Uninterpreted link part (string).
-    raw_link: &'a str,
+    pub raw_link: Cow<'a, str>,
 
     /// Additional information for file location (string or nil).
     /// It only applies to "file" type links.
-    search_option: Option<&'a str>,
-
-    /// Link type
-    link_type: LinkType,
+    pub search_option: Option<Cow<'a, str>>,
+
+    /// Link type (string): `coderef` for a line in some source code,
+    /// `custom-id` for a specific headline's custom-id, `file` for an
+    /// external file, `fuzzy` for a target, referring to a target object,
+    /// a named element or a headline in the current parse tree, `id` for
+    /// a specific headline's id, `radio` for a radio-target, or any type
+    /// from `ParserOptions::link_types`, e.g. `https`.
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub link_type: Cow<'a, str>,
 }
 
-#[derive(Debug)]
-pub enum LinkType {
-    /// Line in some source code,
-    Coderef,
-
-    ///Specific headline's custom-id,
-    CustomId,
-
-    /// External file,
-    File,
-
-    /// Target, referring to a target object, a named element or a headline in the current parse tree,
-    Fuzzy,
-
-    /// Specific headline's id,
-    Id,
-
-    /// Radio-target.
-    Radio,
-}
-
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct MacroData<'a> {
     /// Arguments passed to the macro (list of strings).
-    args: Vec<&'a str>,
+    pub args: Vec<Cow<'a, str>>,
 
     /// Macro's name (string).
-    key: &'a str,
+    pub key: Cow<'a, str>,
 
     /// Replacement text (string).
-    value: &'a str,
+    pub value: Cow<'a, str>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct RadioTargetData<'a> {
     /// Uninterpreted contents (string).
-    raw_value: &'a str,
+    pub raw_value: Cow<'a, str>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct StatisticsCookieData<'a> {
     /// Full cookie (string).
-    value: &'a str,
+    pub value: Cow<'a, str>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct SubscriptData {
     /// Non_nil if contents are enclosed in curly brackets (t, nil).
-    use_brackets_p: bool,
+    pub use_brackets_p: bool,
 }
 
 /// Recursive object.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct SuperscriptData {
     /// Non_nil if contents are enclosed in curly brackets (t, nil).
-    use_brackets_p: bool,
+    pub use_brackets_p: bool,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct TargetData<'a> {
     ///Target's ID (string).
-    value: &'a str,
+    pub value: Cow<'a, str>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct TimestampData<'a> {
     /// Day part from timestamp end.
     /// If no ending date is defined, it defaults to start day part (integer).
-    day_end: usize,
+    pub day_end: usize,
 
     /// Day part from timestamp start (integer).
-    day_start: usize,
+    pub day_start: usize,
 
     /// Hour part from timestamp end.
     /// If no ending date is defined, it defaults to start hour part,
     /// if any (integer or nil).
-    hour_end: Option<usize>,
+    pub hour_end: Option<usize>,
 
     /// Hour part from timestamp start, if specified (integer or nil).
-    hour_start: Option<usize>,
+    pub hour_start: Option<usize>,
 
     /// Minute part from timestamp end.
     /// If no ending date is defined, it defaults to start minute part,
     /// if any (integer or nil).
-    minute_end: Option<usize>,
+    pub minute_end: Option<usize>,
 
     /// Minute part from timestamp start, if specified (integer or nil).
-    minute_start: Option<usize>,
+    pub minute_start: Option<usize>,
 
     /// Month part from timestamp end.
     /// If no ending date is defined, it defaults to start month part
     /// (integer).
-    month_end: usize,
+    pub month_end: usize,
 
     /// Month part from timestamp start (integer).
-    month_start: usize,
+    pub month_start: usize,
 
     /// Raw timestamp (string).
-    raw_value: &'a str,
+    pub raw_value: Cow<'a, str>,
 
     // TODO maybe the following three fields can be combined into one
     /// Type of repeater, if any (symbol catch_up, restart, cumulate or nil)
-    repeater_type: Option<RepeaterType>,
+    pub repeater_type: Option<RepeaterType>,
 
     /// Unit of shift, if a repeater is defined
     /// (symbol year, month, week, day, hour or nil).
-    repeater_unit: Option<TimeUnit>,
+    pub repeater_unit: Option<TimeUnit>,
 
     /// Value of shift, if a repeater is defined (integer or nil).
-    repeater_value: Option<usize>,
+    pub repeater_value: Option<usize>,
 
     /// Type of timestamp:
     /// (symbol active, active_range, diary, inactive, inactive_range).
-    type_s: TimestampType,
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub type_s: TimestampType,
 
     /// Type of warning, if any (symbol all, first or nil)
-    warning_type: Option<WarningType>,
+    pub warning_type: Option<WarningType>,
 
     /// Unit of delay, if one is defined
     /// (symbol year, month, week, day, hour or nil).
-    warning_unit: Option<TimeUnit>,
+    pub warning_unit: Option<TimeUnit>,
 
     /// Value of delay, if one is defined (integer or nil).
-    warning_value: Option<usize>,
+    pub warning_value: Option<usize>,
 
     /// Year part from timestamp end.
     /// If no ending date is defined, it defaults to start year part (integer)
-    year_end: usize,
+    pub year_end: usize,
 
     /// Year part from timestamp start (integer).
-    year_start: usize,
+    pub year_start: usize,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub enum WarningType {
     All,
     First,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub enum TimestampType {
     Active,
     ActiveRange,
@@ -862,14 +1497,16 @@ pub enum TimestampType {
     InactiveRange,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub enum RepeaterType {
     CatchUp,
     Restart,
     Cumulate,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub enum TimeUnit {
     Year,
     Month,
@@ -878,15 +1515,189 @@ pub enum TimeUnit {
     Hour,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct VerbatimData<'a> {
     ///Contents (string).
-    value: &'a str,
+    pub value: Cow<'a, str>,
+}
+
+impl<'a> ClockData<'a> {
+    pub fn into_owned(self) -> ClockData<'static> {
+        ClockData {
+            duration: owned(self.duration),
+            status: self.status,
+            value: self.value.into_owned(),
+        }
+    }
+}
+
+impl<'a> DiarySexpData<'a> {
+    pub fn into_owned(self) -> DiarySexpData<'static> {
+        DiarySexpData {
+            value: owned(self.value),
+        }
+    }
+}
+
+impl<'a> PlanningData<'a> {
+    pub fn into_owned(self) -> PlanningData<'static> {
+        PlanningData {
+            closed: self.closed.map(TimestampData::into_owned),
+            deadline: self.deadline.map(TimestampData::into_owned),
+            scheduled: self.scheduled.map(TimestampData::into_owned),
+        }
+    }
+}
+
+impl<'a> CodeData<'a> {
+    pub fn into_owned(self) -> CodeData<'static> {
+        CodeData {
+            value: owned(self.value),
+        }
+    }
+}
+
+impl<'a> EntityData<'a> {
+    pub fn into_owned(self) -> EntityData<'static> {
+        EntityData {
+            ascii: owned(self.ascii),
+            html: owned(self.html),
+            latex: owned(self.latex),
+            latex_math_p: self.latex_math_p,
+            latin1: owned(self.latin1),
+            name: owned(self.name),
+            use_brackets_p: self.use_brackets_p,
+            utf_8: owned(self.utf_8),
+        }
+    }
+}
+
+impl<'a> ExportSnippetData<'a> {
+    pub fn into_owned(self) -> ExportSnippetData<'static> {
+        ExportSnippetData {
+            back_end: owned(self.back_end),
+            value: owned(self.value),
+        }
+    }
+}
+
+impl<'a> FootnoteReferenceData<'a> {
+    pub fn into_owned(self) -> FootnoteReferenceData<'static> {
+        FootnoteReferenceData {
+            label: self.label.map(owned),
+            type_s: owned(self.type_s),
+        }
+    }
+}
+
+impl<'a> InlineBabelCallData<'a> {
+    pub fn into_owned(self) -> InlineBabelCallData<'static> {
+        InlineBabelCallData {
+            call: owned(self.call),
+            inside_header: self.inside_header.map(owned),
+            arguments: self.arguments.map(owned),
+            end_header: self.end_header.map(owned),
+            value: owned(self.value),
+        }
+    }
+}
+
+impl<'a> InlineSrcBlockData<'a> {
+    pub fn into_owned(self) -> InlineSrcBlockData<'static> {
+        InlineSrcBlockData {
+            language: owned(self.language),
+            parameters: self.parameters.map(owned),
+            value: owned(self.value),
+        }
+    }
+}
+
+impl<'a> LinkData<'a> {
+    pub fn into_owned(self) -> LinkData<'static> {
+        LinkData {
+            application: self.application.map(owned),
+            format: self.format,
+            path: owned(self.path),
+            raw_link: owned(self.raw_link),
+            search_option: self.search_option.map(owned),
+            link_type: owned(self.link_type),
+        }
+    }
+}
+
+impl<'a> MacroData<'a> {
+    pub fn into_owned(self) -> MacroData<'static> {
+        MacroData {
+            args: self.args.into_iter().map(owned).collect(),
+            key: owned(self.key),
+            value: owned(self.value),
+        }
+    }
+}
+
+impl<'a> RadioTargetData<'a> {
+    pub fn into_owned(self) -> RadioTargetData<'static> {
+        RadioTargetData {
+            raw_value: owned(self.raw_value),
+        }
+    }
+}
+
+impl<'a> StatisticsCookieData<'a> {
+    pub fn into_owned(self) -> StatisticsCookieData<'static> {
+        StatisticsCookieData {
+            value: owned(self.value),
+        }
+    }
+}
+
+impl<'a> TargetData<'a> {
+    pub fn into_owned(self) -> TargetData<'static> {
+        TargetData {
+            value: owned(self.value),
+        }
+    }
+}
+
+impl<'a> TimestampData<'a> {
+    pub fn into_owned(self) -> TimestampData<'static> {
+        TimestampData {
+            day_end: self.day_end,
+            day_start: self.day_start,
+            hour_end: self.hour_end,
+            hour_start: self.hour_start,
+            minute_end: self.minute_end,
+            minute_start: self.minute_start,
+            month_end: self.month_end,
+            month_start: self.month_start,
+            raw_value: owned(self.raw_value),
+            repeater_type: self.repeater_type,
+            repeater_unit: self.repeater_unit,
+            repeater_value: self.repeater_value,
+            type_s: self.type_s,
+            warning_type: self.warning_type,
+            warning_unit: self.warning_unit,
+            warning_value: self.warning_value,
+            year_end: self.year_end,
+            year_start: self.year_start,
+        }
+    }
+}
+
+impl<'a> VerbatimData<'a> {
+    pub fn into_owned(self) -> VerbatimData<'static> {
+        VerbatimData {
+            value: owned(self.value),
+        }
+    }
 }
 
 mod test {
 
-    use crate::data::SyntaxT;
+    use crate::data::{Interval, OwnedSyntaxTree, Syntax, SyntaxNode, SyntaxT, SyntaxTree};
+    use std::borrow::Cow;
+    use std::thread;
 
     #[test]
     fn can_contain() {
@@ -905,4 +1716,70 @@ mod test {
         assert!(closure_test(br, |that| bold.can_contain(that)));
         assert!(!closure_test(verse, |that| bold.can_contain(that)));
     }
+
+    #[test]
+    fn tree_links() {
+        let mut tree = SyntaxTree::new();
+        let root = tree.root();
+        let section = tree.append(root, node(Syntax::Section, 0, 12));
+        let paragraph = tree.append(section, node(Syntax::Paragraph, 0, 6));
+        let text = tree.append(paragraph, node(Syntax::PlainText(Cow::from("Hello")), 0, 6));
+        let rule = tree.append(section, node(Syntax::HorizontalRule, 6, 12));
+
+        assert_eq!(5, tree.len());
+        assert_eq!(
+            vec![paragraph, rule],
+            tree.children(section).collect::<Vec<_>>()
+        );
+        assert_eq!(Some(rule), tree[paragraph].next_sibling());
+        assert_eq!(Some(paragraph), tree[rule].prev_sibling());
+        assert_eq!(Some(section), tree[rule].parent());
+        assert_eq!(
+            vec![paragraph, section, root],
+            tree.ancestors(text).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![root, section, paragraph, text, rule],
+            tree.descendants(root).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![paragraph, text],
+            tree.descendants(paragraph).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn owned_tree() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<OwnedSyntaxTree>();
+
+        let input = String::from("Some text");
+        let owned = {
+            let mut tree = SyntaxTree::new();
+            let root = tree.root();
+            let paragraph = tree.append(root, node(Syntax::Paragraph, 0, 9));
+            tree.append(
+                paragraph,
+                node(Syntax::PlainText(Cow::from(&input[..])), 0, 9),
+            );
+            tree.into_owned()
+        };
+        drop(input);
+
+        let text = thread::spawn(move || {
+            let paragraph = owned.children(owned.root()).next().unwrap();
+            let text = owned.children(paragraph).next().unwrap();
+            match &owned[text].data {
+                Syntax::PlainText(text) => text.to_string(),
+                _ => unreachable!(),
+            }
+        })
+        .join()
+        .unwrap();
+        assert_eq!("Some text", text);
+    }
+
+    fn node(data: Syntax, start: usize, end: usize) -> SyntaxNode {
+        SyntaxNode::new(data, Interval { start, end }, None, 0)
+    }
 }
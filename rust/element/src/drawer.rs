@@ -14,9 +14,11 @@
 //    along with org-rs.  If not, see <https://www.gnu.org/licenses/>.
 
 use crate::affiliated::AffiliatedData;
-use crate::data::SyntaxNode;
+use crate::data::{owned, SyntaxNode};
 use crate::parser::Parser;
+use crate::source::TextSource;
 use regex::Regex;
+use std::borrow::Cow;
 
 lazy_static! {
 
@@ -27,13 +29,22 @@ lazy_static! {
 
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct DrawerData<'a> {
     /// Drawer's name (string).
-    pub drawer_name: &'a str,
+    pub drawer_name: Cow<'a, str>,
 }
 
-impl<'a> Parser<'a> {
+impl<'a> DrawerData<'a> {
+    pub fn into_owned(self) -> DrawerData<'static> {
+        DrawerData {
+            drawer_name: owned(self.drawer_name),
+        }
+    }
+}
+
+impl<'a, S: TextSource<'a>> Parser<'a, S> {
     // TODO implement drawer_parser
     pub fn drawer_parser(
         &self,
@@ -0,0 +1,610 @@
+//    This file is part of org-rs.
+//
+//    org-rs is free software: you can redistribute it and/or modify
+//    it under the terms of the GNU General Public License as published by
+//    the Free Software Foundation, either version 3 of the License, or
+//    (at your option) any later version.
+//
+//    org-rs is distributed in the hope that it will be useful,
+//    but WITHOUT ANY WARRANTY; without even the implied warranty of
+//    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//    GNU General Public License for more details.
+//
+//    You should have received a copy of the GNU General Public License
+//    along with org-rs.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Tree mutation API
+//!
+//! Every node of a `SyntaxTree` covers a slice of the text stored in the
+//! tree. Operations in this module edit that text together with the tree,
+//! so after any of them intervals of all nodes still point at the text
+//! they describe and `SyntaxTree::text` is the serialized document.
+//!
+//! A node is either attached to the root, or is the top of a detached
+//! subtree. Detached subtrees carry their own text, and intervals of
+//! their nodes are relative to it. New content enters the tree as a
+//! detached subtree created with `SyntaxTree::create`, and is then placed
+//! with `insert_child`, `append_child` or `replace_with`. `detach` turns
+//! an attached node back into a detached subtree, so moving a node
+//! elsewhere (e.g. refiling a headline) is `detach` followed by an insert.
+//!
+//! Editing costs a walk over the nodes sharing the edited text, since
+//! every node located after the edit has to be shifted.
+//!
+//! Headline levels are not adjusted when headlines move, callers
+//! are responsible for keeping the stars of refiled subtrees right.
+
+use crate::data::{Interval, NodeId, Syntax, SyntaxNode, SyntaxT, SyntaxTree};
+use crate::headline::NodePropertyData;
+use std::borrow::Cow;
+
+impl<'a> SyntaxTree<'a> {
+    /// Text of the node, as it will appear in the serialized document
+    /// (or in its detached subtree).
+    pub fn text_of(&self, id: NodeId) -> &str {
+        let location = self[id].location;
+        &self.buffer(self.top(id))[location.start..location.end]
+    }
+
+    /// Adds `node` to the arena as the top of a new detached subtree.
+    /// Intervals of `node` must be relative to `text`.
+    pub fn create(&mut self, node: SyntaxNode<'a>, text: &str) -> NodeId {
+        let id = self.alloc(node);
+        self.fragments.insert(id, text.to_string());
+        id
+    }
+
+    /// Returns true if `id` is not reachable from the root.
+    pub fn is_detached(&self, id: NodeId) -> bool {
+        self.top(id) != self.root()
+    }
+
+    /// Moves `child` to the position `index` among the children of
+    /// `parent`. An attached `child` is detached first, so this also
+    /// reorders and moves nodes.
+    ///
+    /// Text of `child` is inserted before the node currently at `index`,
+    /// or at the end of `parent`'s contents when `index` is past the
+    /// last child.
+    pub fn insert_child(&mut self, parent: NodeId, index: usize, child: NodeId) {
+        assert!(
+            child != self.root(),
+            "root node cannot be inserted into the tree"
+        );
+        assert!(
+            !self.ancestors(parent).any(|a| a == child) && parent != child,
+            "node cannot be inserted into itself"
+        );
+        if !self.fragments.contains_key(&child) {
+            self.detach(child);
+        }
+        let text = self.fragments.remove(&child).unwrap_or_default();
+
+        let before = self.children(parent).nth(index);
+        let at = match before {
+            Some(sibling) => self[sibling].location.start,
+            None => self.append_position(parent),
+        };
+
+        let top = self.top(parent);
+        self.buffer_mut(top).insert_str(at, &text);
+        if self[parent].content_location.is_none() {
+            self[parent].content_location = Some(Interval { start: at, end: at });
+        }
+        self.shift(top, parent, at, text.len() as isize);
+        self.rebase(child, at as isize);
+
+        match before {
+            Some(sibling) => self.link_before(sibling, child),
+            None => self.link_last(parent, child),
+        }
+    }
+
+    /// Inserts `child` after the last child of `parent`.
+    pub fn append_child(&mut self, parent: NodeId, child: NodeId) {
+        let index = self.children(parent).count();
+        self.insert_child(parent, index, child);
+    }
+
+    /// Removes `id` from its parent, taking its text along.
+    ///
+    /// The node stays in the arena as the top of a detached subtree
+    /// and can be inserted back anywhere. Detaching an already detached
+    /// node does nothing.
+    pub fn detach(&mut self, id: NodeId) {
+        let parent = match self[id].parent {
+            Some(parent) => parent,
+            None => return,
+        };
+        let top = self.top(parent);
+        let location = self[id].location;
+
+        self.unlink(id);
+        let text: String = self
+            .buffer_mut(top)
+            .drain(location.start..location.end)
+            .collect();
+        self.shift(top, parent, location.start, -(text.len() as isize));
+        if let Some(contents) = self[parent].content_location {
+            if contents.start == contents.end {
+                self[parent].content_location = None;
+            }
+        }
+
+        self.rebase(id, -(location.start as isize));
+        self.fragments.insert(id, text);
+    }
+
+    /// Puts `new` in place of `old`, which becomes detached.
+    pub fn replace_with(&mut self, old: NodeId, new: NodeId) {
+        if old == new {
+            return;
+        }
+        let parent = self[old]
+            .parent
+            .expect("only attached nodes can be replaced");
+        if !self.fragments.contains_key(&new) {
+            self.detach(new);
+        }
+        let index = self.children(parent).position(|c| c == old).unwrap();
+        self.insert_child(parent, index, new);
+        self.detach(old);
+    }
+
+    /// Sets node property `key` of a headline or an inlinetask to `value`.
+    ///
+    /// Existing property with the same (case insensitive) key is replaced,
+    /// otherwise the property is added to the end of the property drawer.
+    /// Property drawer, and the section holding it, are created when
+    /// missing.
+    pub fn set_property(
+        &mut self,
+        headline: NodeId,
+        key: impl Into<Cow<'a, str>>,
+        value: impl Into<Cow<'a, str>>,
+    ) {
+        match SyntaxT::from(&self[headline].data) {
+            SyntaxT::Headline | SyntaxT::InlineTask => {}
+            other => panic!("{:?} cannot have node properties", other),
+        }
+
+        let (key, value) = (key.into(), value.into());
+        let line = format!(":{}: {}\n", key, value);
+        let property = self.create(
+            SyntaxNode::new(
+                Syntax::NodeProperty(Box::new(NodePropertyData {
+                    key: key.clone(),
+                    value,
+                })),
+                Interval {
+                    start: 0,
+                    end: line.len(),
+                },
+                None,
+                0,
+            ),
+            &line,
+        );
+
+        let drawer = match self.property_drawer(headline) {
+            Some(drawer) => drawer,
+            None => self.create_property_drawer(headline),
+        };
+
+        let existing = self.children(drawer).find(|&p| match &self[p].data {
+            Syntax::NodeProperty(d) => d.key.eq_ignore_ascii_case(&key),
+            _ => false,
+        });
+        match existing {
+            Some(existing) => self.replace_with(existing, property),
+            None => self.append_child(drawer, property),
+        }
+    }
+
+    /// Property drawer attached to a headline, if any.
+    /// It can only be found in the first section, after an optional planning line.
+    pub fn property_drawer(&self, headline: NodeId) -> Option<NodeId> {
+        let section = self
+            .children(headline)
+            .next()
+            .filter(|&s| SyntaxT::from(&self[s].data) == SyntaxT::Section)?;
+        self.children(section)
+            .take(2)
+            .find(|&d| SyntaxT::from(&self[d].data) == SyntaxT::PropertyDrawer)
+    }
+
+    fn create_property_drawer(&mut self, headline: NodeId) -> NodeId {
+        let begin = ":PROPERTIES:\n";
+        let text = format!("{}:END:\n", begin);
+        let drawer = self.create(
+            SyntaxNode::new(
+                Syntax::PropertyDrawer,
+                Interval {
+                    start: 0,
+                    end: text.len(),
+                },
+                Some(Interval {
+                    start: begin.len(),
+                    end: begin.len(),
+                }),
+                0,
+            ),
+            &text,
+        );
+
+        let first = self.children(headline).next();
+        let section = match first {
+            Some(s) if SyntaxT::from(&self[s].data) == SyntaxT::Section => s,
+            _ => {
+                let section = self.create(
+                    SyntaxNode::new(Syntax::Section, Interval { start: 0, end: 0 }, None, 0),
+                    "",
+                );
+                self.insert_child(headline, 0, section);
+                section
+            }
+        };
+
+        let after_planning = match self.children(section).next() {
+            Some(p) if SyntaxT::from(&self[p].data) == SyntaxT::Planning => 1,
+            _ => 0,
+        };
+        self.insert_child(section, after_planning, drawer);
+        drawer
+    }
+
+    /// Offset at which a new last child of `parent` begins.
+    ///
+    /// Without contents, this is the end of the first line of `parent`:
+    /// right after a headline title or a block opening line.
+    fn append_position(&self, parent: NodeId) -> usize {
+        let node = &self[parent];
+        match node.content_location {
+            Some(contents) => contents.end,
+            None => {
+                let text = &self.buffer(self.top(parent))[node.location.start..node.location.end];
+                node.location.start + text.find('\n').map(|p| p + 1).unwrap_or(text.len())
+            }
+        }
+    }
+
+    /// Top node of the subtree `id` belongs to: the root or a detached node.
+    fn top(&self, id: NodeId) -> NodeId {
+        self.ancestors(id).last().unwrap_or(id)
+    }
+
+    fn buffer(&self, top: NodeId) -> &str {
+        if top == self.root() {
+            self.text.as_str()
+        } else {
+            &self.fragments[&top]
+        }
+    }
+
+    fn buffer_mut(&mut self, top: NodeId) -> &mut String {
+        if top == self.root() {
+            self.text.to_mut()
+        } else {
+            self.fragments.get_mut(&top).unwrap()
+        }
+    }
+
+    /// Updates intervals after `delta` bytes were inserted (or removed,
+    /// when negative) at offset `at` of the text shared by subtree `top`.
+    ///
+    /// `parent` and its ancestors contain the edit, so only their ends move.
+    /// Any other node starting after the edit is moved as a whole.
+    fn shift(&mut self, top: NodeId, parent: NodeId, at: usize, delta: isize) {
+        let move_by = |offset: &mut usize| *offset = (*offset as isize + delta) as usize;
+
+        let containing: Vec<NodeId> = Some(parent)
+            .into_iter()
+            .chain(self.ancestors(parent))
+            .collect();
+        for id in self.subtree(top) {
+            let node = &mut self[id];
+            if containing.contains(&id) {
+                move_by(&mut node.location.end);
+                if let Some(contents) = node.content_location.as_mut() {
+                    if contents.end >= at {
+                        move_by(&mut contents.end);
+                    }
+                }
+            } else if node.location.start >= at {
+                move_by(&mut node.location.start);
+                move_by(&mut node.location.end);
+                if let Some(contents) = node.content_location.as_mut() {
+                    move_by(&mut contents.start);
+                    move_by(&mut contents.end);
+                }
+            }
+        }
+    }
+
+    /// Moves all intervals of the subtree `id` by `delta`
+    fn rebase(&mut self, id: NodeId, delta: isize) {
+        let move_by = |offset: &mut usize| *offset = (*offset as isize + delta) as usize;
+        for id in self.subtree(id) {
+            let node = &mut self[id];
+            move_by(&mut node.location.start);
+            move_by(&mut node.location.end);
+            if let Some(contents) = node.content_location.as_mut() {
+                move_by(&mut contents.start);
+                move_by(&mut contents.end);
+            }
+        }
+    }
+
+    /// Attaches a detached node right before `sibling`
+    fn link_before(&mut self, sibling: NodeId, id: NodeId) {
+        let parent = self[sibling].parent;
+        let prev = self[sibling].prev_sibling;
+        {
+            let node = &mut self[id];
+            node.parent = parent;
+            node.prev_sibling = prev;
+            node.next_sibling = Some(sibling);
+        }
+        self[sibling].prev_sibling = Some(id);
+        match prev {
+            Some(prev) => self[prev].next_sibling = Some(id),
+            None => self[parent.unwrap()].first_child = Some(id),
+        }
+    }
+
+    /// Removes all links between `id` and its parent and siblings
+    fn unlink(&mut self, id: NodeId) {
+        let (parent, prev, next) = {
+            let node = &self[id];
+            (node.parent, node.prev_sibling, node.next_sibling)
+        };
+        let parent = match parent {
+            Some(parent) => parent,
+            None => return,
+        };
+        match prev {
+            Some(prev) => self[prev].next_sibling = next,
+            None => self[parent].first_child = next,
+        }
+        match next {
+            Some(next) => self[next].prev_sibling = prev,
+            None => self[parent].last_child = prev,
+        }
+        let node = &mut self[id];
+        node.parent = None;
+        node.prev_sibling = None;
+        node.next_sibling = None;
+    }
+}
+
+mod test {
+    use crate::data::{Interval, NodeId, StringOrObject, Syntax, SyntaxNode, SyntaxTree};
+    use crate::headline::{HeadlineData, TodoType};
+    use std::borrow::Cow;
+
+    fn headline<'a>(level: usize, title: &'a str) -> Syntax<'a> {
+        Syntax::Headline(Box::new(HeadlineData {
+            archivedp: false,
+            closed: None,
+            commentedp: false,
+            deadline: None,
+            footnote_section_p: false,
+            level,
+            pre_blank: 0,
+            priority: 0,
+            quotedp: false,
+            raw_value: Cow::from(title),
+            scheduled: None,
+            tags: vec![],
+            title: StringOrObject::Raw(Cow::from(title)),
+            todo_keyword: Some(Cow::from("TODO")),
+            todo_type: Some(TodoType::Todo),
+        }))
+    }
+
+    fn node(
+        data: Syntax,
+        start: usize,
+        end: usize,
+        contents: Option<(usize, usize)>,
+    ) -> SyntaxNode {
+        SyntaxNode::new(
+            data,
+            Interval { start, end },
+            contents.map(|(start, end)| Interval { start, end }),
+            0,
+        )
+    }
+
+    /// Every node must cover its children, which follow each other
+    /// without gaps inside of the contents.
+    fn assert_consistent(tree: &SyntaxTree, id: NodeId) {
+        let node = &tree[id];
+        let mut expected = node.content_location.map(|c| c.start);
+        for child in tree.children(id) {
+            assert_eq!(expected, Some(tree[child].location.start));
+            expected = Some(tree[child].location.end);
+            assert_consistent(tree, child);
+        }
+        if tree.children(id).next().is_some() {
+            assert_eq!(expected, node.content_location.map(|c| c.end));
+        }
+    }
+
+    /// * A
+    /// ** A1
+    /// Text
+    /// * B
+    fn outline() -> (SyntaxTree<'static>, [NodeId; 6]) {
+        let mut tree = SyntaxTree::with_text("* A\n** A1\nText\n* B\n");
+        let root = tree.root();
+        let a = tree.append(root, node(headline(1, "A"), 0, 15, Some((4, 15))));
+        let a1 = tree.append(a, node(headline(2, "A1"), 4, 15, Some((10, 15))));
+        let section = tree.append(a1, node(Syntax::Section, 10, 15, Some((10, 15))));
+        let paragraph = tree.append(section, node(Syntax::Paragraph, 10, 15, Some((10, 15))));
+        tree.append(
+            paragraph,
+            node(Syntax::PlainText(Cow::from("Text\n")), 10, 15, None),
+        );
+        let b = tree.append(root, node(headline(1, "B"), 15, 19, None));
+        (tree, [root, a, a1, section, paragraph, b])
+    }
+
+    #[test]
+    fn refile() {
+        let (mut tree, [root, a, a1, section, _, b]) = outline();
+        assert_consistent(&tree, root);
+
+        tree.detach(a1);
+        assert!(tree.is_detached(a1));
+        assert_eq!("* A\n* B\n", tree.text());
+        assert_eq!("** A1\nText\n", tree.text_of(a1));
+        assert_eq!(Interval { start: 0, end: 4 }, tree[a].location);
+        assert_eq!(None, tree[a].content_location);
+        assert_eq!(Interval { start: 4, end: 8 }, tree[b].location);
+        assert_consistent(&tree, root);
+
+        tree.append_child(b, a1);
+        assert_eq!("* A\n* B\n** A1\nText\n", tree.text());
+        assert_eq!(vec![a1], tree.children(b).collect::<Vec<_>>());
+        assert_eq!(Interval { start: 4, end: 19 }, tree[b].location);
+        assert_eq!("Text\n", tree.text_of(section));
+        assert_consistent(&tree, root);
+    }
+
+    #[test]
+    fn reorder_and_replace() {
+        let (mut tree, [root, a, _, _, _, b]) = outline();
+
+        tree.insert_child(root, 0, b);
+        assert_eq!("* B\n* A\n** A1\nText\n", tree.text());
+        assert_eq!(vec![b, a], tree.children(root).collect::<Vec<_>>());
+        assert_consistent(&tree, root);
+
+        let c = tree.create(node(headline(1, "C"), 0, 4, None), "* C\n");
+        tree.replace_with(a, c);
+        assert_eq!("* B\n* C\n", tree.text());
+        assert!(tree.is_detached(a));
+        assert_eq!("* A\n** A1\nText\n", tree.text_of(a));
+        assert_consistent(&tree, root);
+    }
+
+    #[test]
+    fn set_property() {
+        let (mut tree, [root, a, a1, section, paragraph, b]) = outline();
+
+        tree.set_property(a1, "ID", "42");
+        assert_eq!(
+            "* A\n** A1\n:PROPERTIES:\n:ID: 42\n:END:\nText\n* B\n",
+            tree.text()
+        );
+        let drawer = tree.property_drawer(a1).unwrap();
+        assert_eq!(":PROPERTIES:\n:ID: 42\n:END:\n", tree.text_of(drawer));
+        assert_eq!("Text\n", tree.text_of(paragraph));
+        assert_consistent(&tree, root);
+
+        tree.set_property(a1, "id", "43");
+        tree.set_property(a1, "CATEGORY", "work");
+        assert_eq!(
+            "* A\n** A1\n:PROPERTIES:\n:id: 43\n:CATEGORY: work\n:END:\nText\n* B\n",
+            tree.text()
+        );
+        assert_consistent(&tree, root);
+
+        tree.set_property(b, "ID", "1");
+        assert!(tree.text().ends_with("* B\n:PROPERTIES:\n:ID: 1\n:END:\n"));
+        assert_consistent(&tree, root);
+        assert_eq!(tree.text().len(), tree[root].location.end);
+    }
+
+    #[test]
+    fn set_property_round_trip() {
+        use crate::options::ParserOptions;
+        use crate::parser::{ParseGranularity, Parser};
+
+        /// Node properties of every headline, in document order
+        fn properties(tree: &SyntaxTree) -> Vec<Vec<(String, String)>> {
+            tree.descendants(tree.root())
+                .filter(|&id| matches!(tree[id].data, Syntax::Headline(_)))
+                .map(|id| {
+                    tree.property_drawer(id)
+                        .into_iter()
+                        .flat_map(|drawer| tree.children(drawer))
+                        .map(|p| match &tree[p].data {
+                            Syntax::NodeProperty(d) => (d.key.to_string(), d.value.to_string()),
+                            data => panic!("not a node property: {:?}", data),
+                        })
+                        .collect()
+                })
+                .collect()
+        }
+
+        let text = "* A\n:PROPERTIES:\n:ID: 1\n:END:\nText\n* B\n";
+        let parse = |text: &str| {
+            Parser::new(text, ParseGranularity::Element, ParserOptions::default())
+                .parse_buffer()
+                .into_owned()
+        };
+        let mut tree = parse(text);
+        assert_eq!(
+            vec![vec![("ID".to_string(), "1".to_string())], vec![]],
+            properties(&tree)
+        );
+        let headlines: Vec<_> = tree
+            .children(tree.root())
+            .filter(|&id| matches!(tree[id].data, Syntax::Headline(_)))
+            .collect();
+
+        tree.set_property(headlines[0], "ID", "2");
+        tree.set_property(headlines[0], "CATEGORY", "work");
+        tree.set_property(headlines[1], "ID", "3");
+        assert_consistent(&tree, tree.root());
+        let text = tree.text().to_string();
+        assert_eq!(
+            "* A\n:PROPERTIES:\n:ID: 2\n:CATEGORY: work\n:END:\nText\n\
+             * B\n:PROPERTIES:\n:ID: 3\n:END:\n",
+            text
+        );
+        let reparsed = parse(&text);
+        assert_eq!(properties(&tree), properties(&reparsed));
+        assert_consistent(&reparsed, reparsed.root());
+    }
+
+    #[test]
+    fn set_computed_property() {
+        let (tree, [root, a, ..]) = outline();
+        let mut tree = tree.into_owned();
+        for n in 0..2 {
+            tree.set_property(a, "ID", format!("id-{}", n));
+        }
+        assert_eq!(
+            "* A\n:PROPERTIES:\n:ID: id-1\n:END:\n** A1\nText\n* B\n",
+            tree.text()
+        );
+        assert_consistent(&tree, root);
+    }
+
+    #[test]
+    fn secondary_strings_follow_edits() {
+        use crate::options::ParserOptions;
+        use crate::parser::{ParseGranularity, Parser};
+
+        let text = "* A\n#+CAPTION: *x*\nText\n";
+        let parser = Parser::new(text, ParseGranularity::Object, ParserOptions::default());
+        let mut tree = parser.parse_buffer();
+        let root = tree.root();
+        let a = tree.children(root).next().unwrap();
+        let paragraph = tree.descendants(a).last().unwrap();
+        let paragraph = tree[paragraph].parent().unwrap();
+        let caption = tree[paragraph].secondary_objects();
+        assert_eq!("*x*", tree.text_of(caption[0]));
+
+        let b = tree.create(node(headline(1, "B"), 0, 4, None), "* B\n");
+        tree.insert_child(root, 0, b);
+        assert_eq!("*x*", tree.text_of(caption[0]));
+
+        tree.detach(a);
+        assert_eq!("*x*", tree.text_of(caption[0]));
+        assert!(tree.is_detached(caption[0]));
+    }
+}
@@ -0,0 +1,1867 @@
+//    This file is part of org-rs.
+//
+//    org-rs is free software: you can redistribute it and/or modify
+//    it under the terms of the GNU General Public License as published by
+//    the Free Software Foundation, either version 3 of the License, or
+//    (at your option) any later version.
+//
+//    org-rs is distributed in the hope that it will be useful,
+//    but WITHOUT ANY WARRANTY; without even the implied warranty of
+//    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//    GNU General Public License for more details.
+//
+//    You should have received a copy of the GNU General Public License
+//    along with org-rs.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Generic export framework
+//!
+//! Port of the backend independent part of ox.el
+//! https://code.orgmode.org/bzg/org-mode/src/master/lisp/ox.el
+//!
+//! A `Backend` has one transcoder per `Syntax` variant. `export`
+//! transcodes a tree bottom-up, like `org-export-data`: contents of a
+//! node are transcoded first and handed to the transcoder of the node,
+//! along with `Info`, the communication channel. `Info` holds the
+//! export options and what is collected from the whole tree before
+//! transcoding starts: footnotes, headline numbers, the table of
+//! contents and link destinations.
+//!
+//! A backend derives from another one by returning it from
+//! `Backend::parent`. Transcoders it does not override fall back to
+//! the ones of the parent, see `org-export-define-derived-backend`.
+//! Without a parent, a node with contents exports its contents as they
+//! are and a node without contents is ignored.
+//!
+//! Unlike ox.el, export options do not prune a copy of the tree.
+//! `Info` collects the nodes they leave out, e.g. headlines with an
+//! exclude tag, before transcoding starts and `Info::data` exports
+//! them as an empty string. Options changing how a node is exported,
+//! like `tags:nil` or `todo:nil`, are read by the transcoders, tags
+//! through `Info::tags`. Either way every backend exports the same
+//! nodes with the same tags and keywords.
+
+pub mod ascii;
+pub mod html;
+pub mod icalendar;
+pub mod include;
+pub mod latex;
+pub mod markdown;
+pub mod pandoc;
+
+use crate::cursor::next_column;
+use crate::data::*;
+use crate::headline::Tag;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+lazy_static! {
+    /// Cell of a special row, e.g. `<r>` or `<l10>`
+    static ref REGEX_ALIGNMENT_COOKIE: Regex = Regex::new(r"^<([lrc])?[0-9]*>$").unwrap();
+
+    /// elisp: `org-table-number-regexp`
+    static ref REGEX_TABLE_NUMBER: Regex = Regex::new(
+        r"^[<>]?[-+^.0-9]*[0-9][-+^.0-9eEdDx()%:]*$|^[<>]?[-+]?0[xX][.[:xdigit:]]*$|^[<>]?[-+]?[0-9]+#[.A-Za-z0-9]+$|^nan$|^[-+u]?inf$|^[-+]?[.0-9]+[eE][-+]?[0-9]+$"
+    ).unwrap();
+}
+
+/// How sub and superscripts are exported
+/// elisp: `org-export-with-sub-superscripts`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SubSuperscripts {
+    /// `a_b` and `a_{b}` are subscripts. `^:t`
+    All,
+    /// Only `a_{b}` is a subscript, `a_b` is plain text. `^:{}`
+    Braces,
+    /// Sub and superscripts are plain text. `^:nil`
+    None,
+}
+
+/// What becomes of internal links without destination
+/// elisp: `org-export-with-broken-links`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BrokenLinks {
+    /// `try_export` fails, `export` lets backends handle them.
+    /// `broken-links:nil`
+    Error,
+    /// They are left out. `broken-links:t`
+    Ignore,
+    /// They are replaced with "[BROKEN LINK: path]". `broken-links:mark`
+    Mark,
+}
+
+/// Timestamps kept in the export
+/// elisp: `org-export-with-timestamps`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Timestamps {
+    /// `<:t`
+    All,
+    /// Active timestamps and ranges. `<:active`
+    Active,
+    /// Inactive timestamps and ranges. `<:inactive`
+    Inactive,
+    /// `<:nil`
+    None,
+}
+
+/// Export options, i.e. the part of `org-export-options-alist`
+/// backends share. Defaults match the defaults of Org mode.
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    /// Document title.
+    /// elisp: `:title`
+    pub title: Option<String>,
+
+    /// elisp: `:author`
+    pub author: Option<String>,
+
+    /// elisp: `:date`
+    pub date: Option<String>,
+
+    /// Language of the document, e.g. "en".
+    /// elisp: `org-export-default-language`
+    pub language: String,
+
+    /// Headlines deeper than this relative level are "low level",
+    /// backends usually export them as lists.
+    /// elisp: `org-export-headline-levels`
+    pub headline_levels: usize,
+
+    /// Relative level down to which headlines are numbered, `None`
+    /// turns numbering off. `Some(usize::MAX)` numbers all of them.
+    /// elisp: `org-export-with-section-numbers`
+    pub section_numbers: Option<usize>,
+
+    /// Depth of the table of contents, `None` for no table of contents.
+    /// elisp: `org-export-with-toc`
+    pub with_toc: Option<usize>,
+
+    /// elisp: `org-export-with-footnotes`
+    pub with_footnotes: bool,
+
+    /// elisp: `org-export-with-tags`
+    pub with_tags: bool,
+
+    /// elisp: `org-export-with-todo-keywords`
+    pub with_todo_keywords: bool,
+
+    /// elisp: `org-export-with-priority`
+    pub with_priority: bool,
+
+    /// elisp: `org-export-with-sub-superscripts`
+    pub with_sub_superscripts: SubSuperscripts,
+
+    /// elisp: `org-export-with-broken-links`
+    pub broken_links: BrokenLinks,
+
+    /// elisp: `org-export-with-timestamps`
+    pub with_timestamps: Timestamps,
+
+    /// When a headline has one of these tags, only headlines with one
+    /// of them, along with their ancestors and their subtrees, are
+    /// exported.
+    /// elisp: `org-export-select-tags`
+    pub select_tags: Vec<String>,
+
+    /// Headlines with one of these tags are left out, along with
+    /// their subtree.
+    /// elisp: `org-export-exclude-tags`
+    pub exclude_tags: Vec<String>,
+
+    /// Only export the body, without the template of the backend,
+    /// e.g. without `<html>` and `<head>` tags.
+    /// elisp: BODY-ONLY argument of `org-export-as`
+    pub body_only: bool,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        ExportOptions {
+            title: None,
+            author: None,
+            date: None,
+            language: "en".to_string(),
+            headline_levels: 3,
+            section_numbers: Some(usize::MAX),
+            with_toc: Some(3),
+            with_footnotes: true,
+            with_tags: true,
+            with_todo_keywords: true,
+            with_priority: false,
+            with_sub_superscripts: SubSuperscripts::All,
+            broken_links: BrokenLinks::Error,
+            with_timestamps: Timestamps::All,
+            select_tags: vec!["export".to_string()],
+            exclude_tags: vec!["noexport".to_string()],
+            body_only: false,
+        }
+    }
+}
+
+impl ExportOptions {
+    /// Default options, overridden by the export keywords of `tree`
+    ///
+    /// (defun org-export-get-environment (&optional backend subtreep ext-plist)
+    pub fn from_tree(tree: &SyntaxTree) -> ExportOptions {
+        let mut options = ExportOptions::default();
+        options.read_keywords(tree);
+        options
+    }
+
+    /// Reads the export keywords of `tree`, see `apply_keywords`
+    ///
+    /// (defun org-export--get-inbuffer-options (&optional backend)
+    pub fn read_keywords(&mut self, tree: &SyntaxTree) {
+        let keywords = tree
+            .descendants(tree.root())
+            .filter_map(|id| match &tree[id].data {
+                Syntax::Keyword(k) => Some((k.key.as_ref(), k.value.as_ref())),
+                _ => None,
+            });
+        self.apply_keywords(keywords);
+    }
+
+    /// Applies `#+TITLE`, `#+AUTHOR`, `#+DATE`, `#+LANGUAGE`,
+    /// `#+SELECT_TAGS`, `#+EXCLUDE_TAGS` and `#+OPTIONS` keywords, as
+    /// (key, value) pairs. Repeated title, author and date keywords are
+    /// joined with a space, tags of repeated tag keywords are gathered.
+    /// Items of later `#+OPTIONS` lines override the ones of earlier
+    /// lines. Other keywords are ignored.
+    pub fn apply_keywords<'k>(&mut self, keywords: impl IntoIterator<Item = (&'k str, &'k str)>) {
+        let mut title: Vec<&str> = vec![];
+        let mut author: Vec<&str> = vec![];
+        let mut date: Vec<&str> = vec![];
+        let mut select_tags: Option<Vec<String>> = None;
+        let mut exclude_tags: Option<Vec<String>> = None;
+        let split = |value: &str| {
+            value
+                .split_whitespace()
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        };
+        for (key, value) in keywords {
+            let value = value.trim();
+            match key.to_ascii_uppercase().as_str() {
+                "TITLE" => title.push(value),
+                "AUTHOR" => author.push(value),
+                "DATE" => date.push(value),
+                "LANGUAGE" if !value.is_empty() => self.language = value.to_string(),
+                "SELECT_TAGS" => select_tags
+                    .get_or_insert_with(Vec::new)
+                    .extend(split(value)),
+                "EXCLUDE_TAGS" => exclude_tags
+                    .get_or_insert_with(Vec::new)
+                    .extend(split(value)),
+                "OPTIONS" => self.parse_option_keyword(value),
+                _ => {}
+            }
+        }
+        if !title.is_empty() {
+            self.title = Some(title.join(" "));
+        }
+        if !author.is_empty() {
+            self.author = Some(author.join(" "));
+        }
+        if !date.is_empty() {
+            self.date = Some(date.join(" "));
+        }
+        if let Some(tags) = select_tags {
+            self.select_tags = tags;
+        }
+        if let Some(tags) = exclude_tags {
+            self.exclude_tags = tags;
+        }
+    }
+
+    /// Applies the items of an `#+OPTIONS:` value, e.g. `toc:nil H:2`.
+    /// Unknown items and invalid values are ignored. `toc:t` sets the
+    /// depth of the table of contents to `headline_levels`, after `H:`
+    /// is applied wherever it is on the line.
+    ///
+    /// (defun org-export--parse-option-keyword (options &optional backend)
+    pub fn parse_option_keyword(&mut self, value: &str) {
+        let items: Vec<(&str, &str)> = value
+            .split_whitespace()
+            .filter_map(|item| {
+                let colon = item.find(':')?;
+                Some((&item[..colon], &item[colon + 1..]))
+            })
+            .collect();
+        if let Some(levels) = items
+            .iter()
+            .filter(|(key, _)| *key == "H")
+            .filter_map(|(_, value)| value.parse().ok())
+            .last()
+        {
+            self.headline_levels = levels;
+        }
+        // Depth, with `t` for all levels
+        let depth = |value: &str, all: usize| match value {
+            "nil" => Some(None),
+            "t" => Some(Some(all)),
+            n => n.parse().ok().map(Some),
+        };
+        for &(key, value) in &items {
+            let flag = value != "nil";
+            match key {
+                "toc" => {
+                    if let Some(toc) = depth(value, self.headline_levels) {
+                        self.with_toc = toc;
+                    }
+                }
+                "num" => {
+                    if let Some(num) = depth(value, usize::MAX) {
+                        self.section_numbers = num;
+                    }
+                }
+                "^" => {
+                    self.with_sub_superscripts = match value {
+                        "nil" => SubSuperscripts::None,
+                        "{}" => SubSuperscripts::Braces,
+                        _ => SubSuperscripts::All,
+                    }
+                }
+                "todo" => self.with_todo_keywords = flag,
+                "tags" => self.with_tags = flag,
+                "pri" => self.with_priority = flag,
+                "f" => self.with_footnotes = flag,
+                "broken-links" => {
+                    self.broken_links = match value {
+                        "nil" => BrokenLinks::Error,
+                        "mark" => BrokenLinks::Mark,
+                        _ => BrokenLinks::Ignore,
+                    }
+                }
+                "<" => {
+                    self.with_timestamps = match value {
+                        "nil" => Timestamps::None,
+                        "active" => Timestamps::Active,
+                        "inactive" => Timestamps::Inactive,
+                        _ => Timestamps::All,
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+macro_rules! transcoders {
+    (
+        data { $($data_variant:ident => $data_method:ident($data_type:ty),)* }
+        unit { $($unit_variant:ident => $unit_method:ident,)* }
+    ) => {
+        /// Export backend, see module documentation.
+        ///
+        /// Transcoders get the id of the node, its data and its
+        /// transcoded contents, `None` when the node has no children.
+        /// They return `None` to leave the node out of the output.
+        /// `Syntax::Macro` is transcoded by `macro_`, as `macro` is
+        /// a reserved word.
+        pub trait Backend {
+            /// Name of the backend, e.g. `html`, as used in export
+            /// snippets, export blocks and `#+ATTR_BACKEND` keywords
+            fn name(&self) -> &str;
+
+            /// Backend this one is derived from, if any
+            fn parent(&self) -> Option<&dyn Backend> {
+                None
+            }
+
+            /// Transcodes `Syntax::PlainText`
+            fn plain_text(&self, info: &Info, text: &str) -> String {
+                match self.parent() {
+                    Some(parent) => parent.plain_text(info, text),
+                    None => text.to_string(),
+                }
+            }
+
+            /// Wraps the transcoded document, e.g. to add the table of
+            /// contents and footnotes. Applied even when only the body
+            /// is exported.
+            fn inner_template(&self, info: &Info, contents: String) -> String {
+                match self.parent() {
+                    Some(parent) => parent.inner_template(info, contents),
+                    None => contents,
+                }
+            }
+
+            /// Turns the output of `inner_template` into a standalone
+            /// document, e.g. with a preamble.
+            fn template(&self, info: &Info, contents: String) -> String {
+                match self.parent() {
+                    Some(parent) => parent.template(info, contents),
+                    None => contents,
+                }
+            }
+
+            /// Blank lines after element `id`, or spaces after object
+            /// `id`, in the output. Backends override it where ox.el
+            /// backends change `:post-blank` in a parse tree filter.
+            fn post_blank(&self, info: &Info, id: NodeId) -> usize {
+                match self.parent() {
+                    Some(parent) => parent.post_blank(info, id),
+                    None => info.tree[id].post_blank,
+                }
+            }
+
+            $(
+                #[doc = concat!("Transcodes `Syntax::", stringify!($data_variant), "`")]
+                fn $data_method(
+                    &self,
+                    info: &Info,
+                    id: NodeId,
+                    data: &$data_type,
+                    contents: Option<String>,
+                ) -> Option<String> {
+                    match self.parent() {
+                        Some(parent) => parent.$data_method(info, id, data, contents),
+                        None => contents,
+                    }
+                }
+            )*
+
+            $(
+                #[doc = concat!("Transcodes `Syntax::", stringify!($unit_variant), "`")]
+                fn $unit_method(
+                    &self,
+                    info: &Info,
+                    id: NodeId,
+                    contents: Option<String>,
+                ) -> Option<String> {
+                    match self.parent() {
+                        Some(parent) => parent.$unit_method(info, id, contents),
+                        None => contents,
+                    }
+                }
+            )*
+        }
+
+        fn dispatch(
+            backend: &dyn Backend,
+            info: &Info,
+            id: NodeId,
+            contents: Option<String>,
+        ) -> Option<String> {
+            match &info.tree[id].data {
+                $(Syntax::$data_variant(data) => backend.$data_method(info, id, data, contents),)*
+                $(Syntax::$unit_variant => backend.$unit_method(info, id, contents),)*
+                Syntax::PlainText(text) => Some(backend.plain_text(info, info.plain_text(id, text))),
+            }
+        }
+    };
+}
+
+transcoders! {
+    data {
+        BabelCall => babel_call(BabelCallData),
+        Clock => clock(ClockData),
+        Comment => comment(CommentData),
+        CommentBlock => comment_block(CommentBlockData),
+        DiarySexp => diary_sexp(DiarySexpData),
+        Drawer => drawer(DrawerData),
+        DynamicBlock => dynamic_block(DynamicBlockData),
+        ExampleBlock => example_block(ExampleBlockData),
+        ExportBlock => export_block(ExportBlockData),
+        FixedWidth => fixed_width(FixedWidthData),
+        FootnoteDefinition => footnote_definition(FootnoteDefinitionData),
+        Headline => headline(HeadlineData),
+        InlineTask => inline_task(InlineTaskData),
+        Item => item(ItemData),
+        Keyword => keyword(KeywordData),
+        LatexEnvironment => latex_environment(LatexEnvironmentData),
+        NodeProperty => node_property(NodePropertyData),
+        PlainList => plain_list(PlainListData),
+        Planning => planning(PlanningData),
+        SpecialBlock => special_block(SpecialBlockData),
+        SrcBlock => src_block(SrcBlockData),
+        Table => table(TableData),
+        TableRow => table_row(TableRowData),
+        Code => code(CodeData),
+        Entity => entity(EntityData),
+        ExportSnippet => export_snippet(ExportSnippetData),
+        FootnoteReference => footnote_reference(FootnoteReferenceData),
+        InlineBabelCall => inline_babel_call(InlineBabelCallData),
+        InlineSrcBlock => inline_src_block(InlineSrcBlockData),
+        LatexFragment => latex_fragment(LatexFragmentData),
+        Link => link(LinkData),
+        Macro => macro_(MacroData),
+        RadioTarget => radio_target(RadioTargetData),
+        StatisticsCookie => statistics_cookie(StatisticsCookieData),
+        Subscript => subscript(SubscriptData),
+        Superscript => superscript(SuperscriptData),
+        Target => target(TargetData),
+        Timestamp => timestamp(TimestampData),
+        Verbatim => verbatim(VerbatimData),
+    }
+    unit {
+        OrgData => org_data,
+        CenterBlock => center_block,
+        HorizontalRule => horizontal_rule,
+        Paragraph => paragraph,
+        PropertyDrawer => property_drawer,
+        QuoteBlock => quote_block,
+        Section => section,
+        VerseBlock => verse_block,
+        Bold => bold,
+        Italic => italic,
+        LineBreak => line_break,
+        StrikeThrough => strike_through,
+        TableCell => table_cell,
+        Underline => underline,
+    }
+}
+
+/// Horizontal alignment of a table column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    Left,
+    Right,
+    Center,
+}
+
+/// A footnote, as collected by `Info`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Footnote {
+    /// Number of the footnote, in order of first reference, from 1
+    pub number: usize,
+    /// Label of the footnote, `None` for anonymous inline footnotes
+    pub label: Option<String>,
+    /// Footnote definition, or the inline footnote reference holding
+    /// the definition. In both cases the definition is the contents of
+    /// the node. `None` when the definition could not be found.
+    pub definition: Option<NodeId>,
+}
+
+/// Communication channel between transcoders, i.e. the INFO plist
+/// of ox.el, see module documentation
+pub struct Info<'t, 'a> {
+    /// Backend the tree is exported with. Transcoders of a parent
+    /// backend see the derived one here.
+    pub backend: &'t dyn Backend,
+    pub tree: &'t SyntaxTree<'a>,
+    pub options: &'t ExportOptions,
+
+    /// Nodes left out of the export, along with their subtrees
+    /// elisp: `:ignore-list`
+    ignored: HashSet<NodeId>,
+
+    /// Exported headlines, in document order
+    headlines: Vec<NodeId>,
+    /// elisp: `:headline-numbering`
+    headline_numbers: HashMap<NodeId, Vec<usize>>,
+    /// Level of the top headlines, used to compute relative levels
+    min_level: usize,
+
+    footnotes: Vec<Footnote>,
+    /// Footnote definitions and labelled inline references, by label
+    footnote_definitions: HashMap<String, NodeId>,
+    /// Index of labelled footnotes in `footnotes`
+    footnote_labels: HashMap<String, usize>,
+    /// Index of anonymous footnotes in `footnotes`, by reference
+    footnote_anonymous: HashMap<NodeId, usize>,
+
+    /// Fuzzy link destinations: targets, then named elements
+    targets: HashMap<String, NodeId>,
+    /// Headlines by normalized title
+    titles: HashMap<String, NodeId>,
+    /// Headlines by CUSTOM_ID property
+    custom_ids: HashMap<String, NodeId>,
+    /// Headlines by ID property
+    ids: HashMap<String, NodeId>,
+    /// Radio targets by normalized, lower case, contents
+    radio_targets: HashMap<String, NodeId>,
+    /// Plain texts with their common indentation removed, see
+    /// `Info::collect_normalized`
+    normalized: HashMap<NodeId, String>,
+}
+
+/// Blanks at the beginning of a line in a plain text
+struct Indentation {
+    /// Plain text node
+    id: NodeId,
+    start: usize,
+    end: usize,
+    /// Width of the blanks, or None on blank lines
+    width: Option<usize>,
+}
+
+impl Indentation {
+    /// Blanks at `start` in `text` of node `id`
+    fn at(id: NodeId, text: &str, start: usize) -> Indentation {
+        let blanks = &text[start..];
+        let end = start + blanks.len() - blanks.trim_start_matches(|c| c == ' ' || c == '\t').len();
+        let width = match text[end..].chars().next() {
+            Some('\n') => None,
+            _ => Some(
+                text[start..end]
+                    .chars()
+                    .fold(0, |column, c| next_column(c, column, 8)),
+            ),
+        };
+        Indentation {
+            id,
+            start,
+            end,
+            width,
+        }
+    }
+}
+
+/// Returns true if one of `tags` is in `list`
+fn has_tag(tags: &[Tag], list: &[String]) -> bool {
+    tags.iter()
+        .any(|tag| list.iter().any(|t| t == tag.0.as_ref()))
+}
+
+/// Transcodes `tree` with `backend`
+///
+/// (defun org-export-as (backend &optional subtreep visible-only body-only ext-plist)
+pub fn export(tree: &SyntaxTree, backend: &dyn Backend, options: &ExportOptions) -> String {
+    let info = Info::new(tree, backend, options);
+    let body = info.data(tree.root());
+    let inner = backend.inner_template(&info, body);
+    if options.body_only {
+        inner
+    } else {
+        backend.template(&info, inner)
+    }
+}
+
+/// Error of `try_export`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExportError {
+    /// Path of an internal link without destination, when
+    /// `ExportOptions::broken_links` is `BrokenLinks::Error`
+    BrokenLink(String),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExportError::BrokenLink(path) => write!(f, "Unable to resolve link: \"{}\"", path),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+/// Like `export`, but fails on the first exported broken link when
+/// `ExportOptions::broken_links` is `BrokenLinks::Error`
+pub fn try_export(
+    tree: &SyntaxTree,
+    backend: &dyn Backend,
+    options: &ExportOptions,
+) -> Result<String, ExportError> {
+    if options.broken_links == BrokenLinks::Error {
+        let info = Info::new(tree, backend, options);
+        let exported = |id| {
+            !tree
+                .ancestors(id)
+                .chain(Some(id))
+                .any(|id| info.is_ignored(id))
+        };
+        let mut broken: Vec<NodeId> = tree
+            .subtree(tree.root())
+            .into_iter()
+            .filter(|&id| info.is_broken_link(id) && exported(id))
+            .collect();
+        broken.sort_by_key(|&id| tree[id].location.start);
+        if let Some(&id) = broken.first() {
+            if let Syntax::Link(link) = &tree[id].data {
+                return Err(ExportError::BrokenLink(link.path.to_string()));
+            }
+        }
+    }
+    Ok(export(tree, backend, options))
+}
+
+impl<'t, 'a> Info<'t, 'a> {
+    /// Collects everything transcoders may need from `tree`
+    pub fn new(
+        tree: &'t SyntaxTree<'a>,
+        backend: &'t dyn Backend,
+        options: &'t ExportOptions,
+    ) -> Info<'t, 'a> {
+        let mut info = Info {
+            backend,
+            tree,
+            options,
+            ignored: HashSet::new(),
+            headlines: vec![],
+            headline_numbers: HashMap::new(),
+            min_level: 1,
+            footnotes: vec![],
+            footnote_definitions: HashMap::new(),
+            footnote_labels: HashMap::new(),
+            footnote_anonymous: HashMap::new(),
+            targets: HashMap::new(),
+            titles: HashMap::new(),
+            custom_ids: HashMap::new(),
+            ids: HashMap::new(),
+            radio_targets: HashMap::new(),
+            normalized: HashMap::new(),
+        };
+        info.collect_ignored();
+        info.collect_normalized();
+        info.collect_headlines();
+        info.collect_targets(tree.root());
+        if options.with_footnotes {
+            info.collect_footnotes(tree.root());
+        }
+        info
+    }
+
+    /// Transcodes node `id` and its contents with the backend of the
+    /// export. Ignored nodes become an empty string.
+    ///
+    /// Blank lines after elements and spaces after objects are kept.
+    ///
+    /// (defun org-export-data (data info)
+    pub fn data(&self, id: NodeId) -> String {
+        if self.is_ignored(id) {
+            return String::new();
+        }
+        let node = &self.tree[id];
+        let contents = node.first_child().map(|_| self.contents(id));
+        let results = match self.uninterpreted(id, contents) {
+            Ok(results) => results,
+            Err(contents) => dispatch(self.backend, self, id, contents),
+        };
+        let results = match results {
+            Some(results) => results,
+            None => return String::new(),
+        };
+        match &node.data {
+            Syntax::OrgData | Syntax::PlainText(_) => results,
+            data => {
+                let post_blank = self.backend.post_blank(self, id);
+                if SyntaxT::from(data).is_object() {
+                    results + &" ".repeat(post_blank)
+                } else {
+                    normalize_string(results) + &"\n".repeat(post_blank)
+                }
+            }
+        }
+    }
+
+    /// Exports sub and superscripts as plain text when
+    /// `ExportOptions::with_sub_superscripts` does not interpret them,
+    /// and broken links according to `ExportOptions::broken_links`.
+    /// Gives `contents` back for the other nodes.
+    ///
+    /// (defun org-export--remove-uninterpreted-data (data info)
+    fn uninterpreted(
+        &self,
+        id: NodeId,
+        contents: Option<String>,
+    ) -> Result<Option<String>, Option<String>> {
+        let (mark, brackets) = match &self.tree[id].data {
+            Syntax::Subscript(s) => ("_", s.use_brackets_p),
+            Syntax::Superscript(s) => ("^", s.use_brackets_p),
+            Syntax::Link(link) if self.is_broken_link(id) => {
+                return match self.options.broken_links {
+                    BrokenLinks::Error => Err(contents),
+                    BrokenLinks::Ignore => Ok(None),
+                    BrokenLinks::Mark => {
+                        let text = format!("[BROKEN LINK: {}]", link.path);
+                        Ok(Some(self.backend.plain_text(self, &text)))
+                    }
+                };
+            }
+            _ => return Err(contents),
+        };
+        let interpreted = match self.options.with_sub_superscripts {
+            SubSuperscripts::All => true,
+            SubSuperscripts::Braces => brackets,
+            SubSuperscripts::None => false,
+        };
+        if interpreted {
+            return Err(contents);
+        }
+        let (open, close) = if brackets { ("{", "}") } else { ("", "") };
+        Ok(Some(format!(
+            "{}{}{}",
+            self.backend.plain_text(self, &format!("{}{}", mark, open)),
+            contents.unwrap_or_default(),
+            self.backend.plain_text(self, close),
+        )))
+    }
+
+    /// Transcoded children of `id`, concatenated
+    pub fn contents(&self, id: NodeId) -> String {
+        self.tree
+            .children(id)
+            .map(|child| self.data(child))
+            .collect()
+    }
+
+    /// Transcoded secondary string, e.g. a headline title
+    pub fn secondary(&self, value: &StringOrObject) -> String {
+        match value {
+            StringOrObject::Raw(raw) => self.backend.plain_text(self, raw),
+            StringOrObject::Parsed(objects) => objects.iter().map(|&id| self.data(id)).collect(),
+        }
+    }
+
+    /// Returns true if `id` is left out of the export. Nodes in the
+    /// subtree of an ignored node are not exported either.
+    pub fn is_ignored(&self, id: NodeId) -> bool {
+        self.ignored.contains(&id)
+    }
+
+    /// Exported headlines, in document order
+    pub fn headlines(&self) -> &[NodeId] {
+        &self.headlines
+    }
+
+    /// Relative level of headline `id`, i.e. its level as if the top
+    /// level headlines of the document were level one.
+    ///
+    /// (defun org-export-get-relative-level (headline info)
+    pub fn relative_level(&self, id: NodeId) -> usize {
+        match &self.tree[id].data {
+            Syntax::Headline(h) => (h.level + 1).saturating_sub(self.min_level).max(1),
+            _ => 1,
+        }
+    }
+
+    /// Returns true if headline `id` is deeper than
+    /// `ExportOptions::headline_levels`.
+    ///
+    /// (defun org-export-low-level-p (headline info)
+    pub fn low_level_p(&self, id: NodeId) -> bool {
+        self.relative_level(id) > self.options.headline_levels
+    }
+
+    /// Section number of headline `id`, e.g. `[1, 2]` for 1.2
+    ///
+    /// (defun org-export-get-headline-number (headline info)
+    pub fn headline_number(&self, id: NodeId) -> Option<&[usize]> {
+        self.headline_numbers.get(&id).map(Vec::as_slice)
+    }
+
+    /// Returns true if headline `id` should be numbered
+    ///
+    /// (defun org-export-numbered-headline-p (headline info)
+    pub fn numbered_headline_p(&self, id: NodeId) -> bool {
+        self.options
+            .section_numbers
+            .map_or(false, |depth| self.relative_level(id) <= depth)
+            && self
+                .property(id, "UNNUMBERED")
+                .map_or(true, |p| p.eq_ignore_ascii_case("nil"))
+    }
+
+    /// Headlines of the table of contents, down to relative level
+    /// `depth`, in document order
+    ///
+    /// (defun org-export-collect-headlines (info &optional n scope)
+    pub fn toc(&self, depth: usize) -> Vec<NodeId> {
+        self.headlines
+            .iter()
+            .cloned()
+            .filter(|&id| self.relative_level(id) <= depth)
+            .collect()
+    }
+
+    /// Closest headline containing `id`
+    ///
+    /// (defun org-export-get-parent-headline (blob)
+    pub fn parent_headline(&self, id: NodeId) -> Option<NodeId> {
+        self.tree
+            .ancestors(id)
+            .find(|&a| matches!(self.tree[a].data, Syntax::Headline(_)))
+    }
+
+    /// Tags of headline `id`, unless tags are not exported
+    ///
+    /// (defun org-export-get-tags (element info &optional tags inherited)
+    pub fn tags(&self, id: NodeId) -> Vec<&'t str> {
+        match &self.tree[id].data {
+            Syntax::Headline(h) if self.options.with_tags => {
+                h.tags.iter().map(|t| t.0.as_ref()).collect()
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Value of property `key` of headline `id`, from its property
+    /// drawer
+    ///
+    /// (defun org-export-get-node-property (property datum &optional inherited)
+    pub fn property(&self, id: NodeId, key: &str) -> Option<&'t str> {
+        Info::headline_property(self.tree, id, key)
+    }
+
+    /// Values of the `#+KEY:` keywords of the document, in order.
+    /// `key` is case insensitive.
+    ///
+    /// (defun org-export--get-inbuffer-options (&optional backend)
+    pub fn keywords(&self, key: &str) -> Vec<&'t str> {
+        self.tree
+            .descendants(self.tree.root())
+            .filter_map(|id| match &self.tree[id].data {
+                Syntax::Keyword(k) if k.key.eq_ignore_ascii_case(key) => Some(k.value.as_ref()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Unique identifier of node `id`, usable as an anchor
+    ///
+    /// (defun org-export-get-reference (datum info)
+    pub fn reference(&self, id: NodeId) -> String {
+        format!("org{:07x}", id.index())
+    }
+
+    /// Footnotes in order of first reference, references inside
+    /// footnote definitions included
+    ///
+    /// (defun org-export-collect-footnote-definitions (info &optional data body-first)
+    pub fn footnotes(&self) -> &[Footnote] {
+        &self.footnotes
+    }
+
+    /// Footnote of reference or definition `id`, if it is referenced
+    ///
+    /// (defun org-export-get-footnote-number (footnote info &optional data body-first)
+    pub fn footnote(&self, id: NodeId) -> Option<&Footnote> {
+        let index = match &self.tree[id].data {
+            Syntax::FootnoteReference(r) => match &r.label {
+                Some(label) => self.footnote_labels.get(label.as_ref()),
+                None => self.footnote_anonymous.get(&id),
+            },
+            Syntax::FootnoteDefinition(d) => self.footnote_labels.get(d.label.as_ref()),
+            _ => None,
+        };
+        index.map(|&i| &self.footnotes[i])
+    }
+
+    /// Returns true if reference `id` is the first one to its footnote
+    ///
+    /// (defun org-export-footnote-first-reference-p (footnote-reference info &optional data body-first)
+    pub fn footnote_first_reference_p(&self, id: NodeId) -> bool {
+        match &self.tree[id].data {
+            Syntax::FootnoteReference(r) => match &r.label {
+                Some(label) => self.first_reference(label) == Some(id),
+                None => true,
+            },
+            _ => false,
+        }
+    }
+
+    /// First exported reference to footnote `label`
+    fn first_reference(&self, label: &str) -> Option<NodeId> {
+        let mut stack = vec![self.tree.root()];
+        while let Some(id) = stack.pop() {
+            if self.is_ignored(id) {
+                continue;
+            }
+            if let Syntax::FootnoteReference(r) = &self.tree[id].data {
+                if r.label.as_ref().map_or(false, |l| l == label) {
+                    return Some(id);
+                }
+            }
+            let node = &self.tree[id];
+            let mut next: Vec<NodeId> = node.secondary_objects();
+            next.extend(self.tree.children(id));
+            stack.extend(next.into_iter().rev());
+        }
+        None
+    }
+
+    /// Destination of link `id`: a headline, a target, a named element
+    /// or a radio target, depending on the type of the link
+    ///
+    /// (defun org-export-resolve-fuzzy-link (link info &rest pseudo-types)
+    /// (defun org-export-resolve-id-link (link info)
+    /// (defun org-export-resolve-radio-link (link info)
+    pub fn resolve_link(&self, id: NodeId) -> Option<NodeId> {
+        let link = match &self.tree[id].data {
+            Syntax::Link(link) => link,
+            _ => return None,
+        };
+        let path = normalize_whitespace(&link.path);
+        let found = match link.link_type.as_ref() {
+            "fuzzy" => match path.strip_prefix('*') {
+                Some(title) => self.titles.get(title),
+                None => self.targets.get(&path).or_else(|| self.titles.get(&path)),
+            },
+            "custom-id" => self.custom_ids.get(&path),
+            "id" => self.ids.get(&path),
+            "radio" => self.radio_targets.get(&path.to_lowercase()),
+            _ => None,
+        };
+        found.cloned()
+    }
+
+    /// Returns true if `id` is an internal link, i.e. a fuzzy, custom
+    /// id, id or radio link, without destination in the document
+    pub fn is_broken_link(&self, id: NodeId) -> bool {
+        match &self.tree[id].data {
+            Syntax::Link(link) => {
+                matches!(
+                    link.link_type.as_ref(),
+                    "fuzzy" | "custom-id" | "id" | "radio"
+                ) && self.resolve_link(id).is_none()
+            }
+            _ => false,
+        }
+    }
+
+    /// Group of table row `id`, counting from 1. Rules and special
+    /// rows belong to no group, rows separated by rules belong to
+    /// different groups.
+    ///
+    /// (defun org-export-table-row-group (table-row info)
+    pub fn table_row_group(&self, id: NodeId) -> Option<usize> {
+        if self.table_row_is_rule_p(id) || self.table_row_is_special_p(id) {
+            return None;
+        }
+        let table = self.tree[id].parent()?;
+        let mut group = 0;
+        let mut after_rule = true;
+        for row in self.tree.children(table) {
+            if self.table_row_is_rule_p(row) {
+                after_rule = true;
+            } else if !self.table_row_is_special_p(row) {
+                if after_rule {
+                    group += 1;
+                    after_rule = false;
+                }
+                if row == id {
+                    return Some(group);
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns true if table `id` has a header, i.e. rows followed by
+    /// a rule and more rows
+    ///
+    /// (defun org-export-table-has-header-p (table info)
+    pub fn table_has_header_p(&self, id: NodeId) -> bool {
+        self.tree
+            .children(id)
+            .any(|row| self.table_row_group(row).map_or(false, |g| g > 1))
+    }
+
+    /// Returns true if table row `id` is part of the header
+    ///
+    /// (defun org-export-table-row-in-header-p (table-row info)
+    pub fn table_row_in_header_p(&self, id: NodeId) -> bool {
+        self.table_row_group(id) == Some(1)
+            && self.tree[id]
+                .parent()
+                .map_or(false, |table| self.table_has_header_p(table))
+    }
+
+    /// Returns true if table row `id` is the first row of its group
+    ///
+    /// (defun org-export-table-row-starts-rowgroup-p (table-row info)
+    pub fn table_row_starts_rowgroup_p(&self, id: NodeId) -> bool {
+        let group = self.table_row_group(id);
+        group.is_some() && self.neighbour_group(id, true) != group
+    }
+
+    /// Returns true if table row `id` is the last row of its group
+    ///
+    /// (defun org-export-table-row-ends-rowgroup-p (table-row info)
+    pub fn table_row_ends_rowgroup_p(&self, id: NodeId) -> bool {
+        let group = self.table_row_group(id);
+        group.is_some() && self.neighbour_group(id, false) != group
+    }
+
+    /// Group of the closest previous, or next, row in a group
+    fn neighbour_group(&self, id: NodeId, previous: bool) -> Option<usize> {
+        let mut row = id;
+        loop {
+            let node = &self.tree[row];
+            row = if previous {
+                node.prev_sibling()?
+            } else {
+                node.next_sibling()?
+            };
+            if let Some(group) = self.table_row_group(row) {
+                return Some(group);
+            }
+        }
+    }
+
+    /// Returns true if table row `id` is a rule, e.g. `|---+---|`
+    pub fn table_row_is_rule_p(&self, id: NodeId) -> bool {
+        match &self.tree[id].data {
+            Syntax::TableRow(r) => r.table_row_type == TableRowType::Rule,
+            _ => false,
+        }
+    }
+
+    /// Returns true if table row `id` only holds alignment cookies,
+    /// e.g. `| <r> | <l10> |`. Such rows are not exported.
+    ///
+    /// (defun org-export-table-row-is-special-p (table-row _)
+    pub fn table_row_is_special_p(&self, id: NodeId) -> bool {
+        if self.table_row_is_rule_p(id) {
+            return false;
+        }
+        let cells: Vec<String> = self.tree.children(id).map(|c| self.cell_text(c)).collect();
+        cells.iter().any(|c| !c.is_empty())
+            && cells
+                .iter()
+                .all(|c| c.is_empty() || REGEX_ALIGNMENT_COOKIE.is_match(c))
+    }
+
+    /// Alignment of the column of table cell `id`: the one of its
+    /// alignment cookie if any, otherwise right when most non-empty
+    /// cells of the column are numbers.
+    ///
+    /// (defun org-export-table-cell-alignment (table-cell info)
+    pub fn table_cell_alignment(&self, id: NodeId) -> Alignment {
+        let tree = self.tree;
+        let column = tree
+            .children(tree[id].parent().unwrap())
+            .position(|c| c == id);
+        let table = tree.ancestors(id).nth(1);
+        let (column, table) = match (column, table) {
+            (Some(column), Some(table)) => (column, table),
+            _ => return Alignment::Left,
+        };
+        let mut numbers = 0;
+        let mut cells = 0;
+        for row in tree.children(table) {
+            let cell = match tree.children(row).nth(column) {
+                Some(cell) if !self.table_row_is_rule_p(row) => self.cell_text(cell),
+                _ => continue,
+            };
+            if self.table_row_is_special_p(row) {
+                let cookie = REGEX_ALIGNMENT_COOKIE
+                    .captures(&cell)
+                    .and_then(|cap| cap.get(1));
+                match cookie.map(|m| m.as_str()) {
+                    Some("l") => return Alignment::Left,
+                    Some("r") => return Alignment::Right,
+                    Some("c") => return Alignment::Center,
+                    _ => continue,
+                }
+            }
+            if !cell.is_empty() {
+                cells += 1;
+                if REGEX_TABLE_NUMBER.is_match(&cell) {
+                    numbers += 1;
+                }
+            }
+        }
+        // elisp: `org-table-number-fraction`
+        if cells > 0 && numbers as f64 / cells as f64 >= 0.5 {
+            Alignment::Right
+        } else {
+            Alignment::Left
+        }
+    }
+
+    /// Text of table cell `id`, without surrounding blanks
+    fn cell_text(&self, id: NodeId) -> String {
+        let text: String = self
+            .tree
+            .descendants(id)
+            .filter_map(|d| match &self.tree[d].data {
+                Syntax::PlainText(t) => Some(t.as_ref()),
+                _ => None,
+            })
+            .collect();
+        text.trim().to_string()
+    }
+
+    /// Nodes left out of the export: commented headlines, headlines
+    /// with an exclude tag or outside the selected trees, footnote
+    /// definitions, exported where they are referenced, footnote
+    /// references when footnotes are not exported and timestamps
+    /// filtered out by `ExportOptions::with_timestamps`. They stay in
+    /// the tree, where `org-export--prune-tree` would remove them.
+    ///
+    /// (defun org-export--populate-ignore-list (data options)
+    fn collect_ignored(&mut self) {
+        let tree = self.tree;
+        let options = self.options;
+        let selected = self.selected_trees();
+        let unselected = |id| selected.as_ref().map_or(false, |s| !s.contains(&id));
+        for id in tree.subtree(tree.root()) {
+            let ignored = match &tree[id].data {
+                Syntax::Headline(h) => {
+                    h.commentedp || has_tag(&h.tags, &options.exclude_tags) || unselected(id)
+                }
+                Syntax::InlineTask(t) => has_tag(&t.tags, &options.exclude_tags) || unselected(id),
+                Syntax::FootnoteDefinition(_) => true,
+                Syntax::FootnoteReference(_) => !options.with_footnotes,
+                Syntax::Timestamp(t) => match options.with_timestamps {
+                    Timestamps::All => false,
+                    Timestamps::None => true,
+                    Timestamps::Active => {
+                        !matches!(t.type_s, TimestampType::Active | TimestampType::ActiveRange)
+                    }
+                    Timestamps::Inactive => !matches!(
+                        t.type_s,
+                        TimestampType::Inactive | TimestampType::InactiveRange
+                    ),
+                },
+                _ => false,
+            };
+            if ignored {
+                self.ignored.insert(id);
+            }
+        }
+    }
+
+    /// Removes the common indentation of the lines in elements directly
+    /// containing objects, e.g. paragraphs, from their plain texts.
+    /// Objects are searched recursively.
+    ///
+    /// (defun org-element-normalize-contents (element &optional ignore-first)
+    fn collect_normalized(&mut self) {
+        let tree = self.tree;
+        for id in tree.descendants(tree.root()) {
+            let syntax = SyntaxT::from(&tree[id].data);
+            if !syntax.is_element() || syntax.is_greater_element() {
+                continue;
+            }
+            // When normalizing first paragraph of an item or
+            // a footnote-definition, ignore first line's indentation.
+            let ignore_first = syntax == SyntaxT::Paragraph
+                && tree[id].parent().map_or(false, |parent| {
+                    let pre_blank = match &tree[parent].data {
+                        Syntax::Item(item) => Some(item.pre_blank),
+                        Syntax::FootnoteDefinition(d) => Some(d.pre_blank as usize),
+                        _ => None,
+                    };
+                    pre_blank == Some(0) && tree[parent].first_child() == Some(id)
+                });
+            let mut indentations = vec![];
+            if !self.indentations(id, &mut !ignore_first, &mut indentations) {
+                continue;
+            }
+            let min = match indentations.iter().filter_map(|i| i.width).min() {
+                Some(min) if min > 0 => min,
+                _ => continue,
+            };
+            // Build texts back, replacing each indentation with
+            // (- indentation MIN-IND), blank lines being emptied.
+            let mut text = String::new();
+            let mut last = 0;
+            for (n, indentation) in indentations.iter().enumerate() {
+                let value = match &tree[indentation.id].data {
+                    Syntax::PlainText(value) => value,
+                    _ => continue,
+                };
+                text.push_str(&value[last..indentation.start]);
+                text.push_str(&" ".repeat(indentation.width.map_or(0, |w| w - min)));
+                last = indentation.end;
+                if indentations
+                    .get(n + 1)
+                    .map_or(true, |i| i.id != indentation.id)
+                {
+                    text.push_str(&value[last..]);
+                    self.normalized
+                        .insert(indentation.id, std::mem::take(&mut text));
+                    last = 0;
+                }
+            }
+        }
+    }
+
+    /// Collects the indentation of lines in the plain texts of `id` in
+    /// `indentations`, recursively. `first` is true when the next
+    /// object is expected to be a string that doesn't start with a
+    /// newline, i.e. at the beginning of the contents or right after
+    /// a line break. Returns false when the common indentation is zero.
+    fn indentations(
+        &self,
+        id: NodeId,
+        first: &mut bool,
+        indentations: &mut Vec<Indentation>,
+    ) -> bool {
+        for child in self.tree.children(id) {
+            let data = &self.tree[child].data;
+            if *first {
+                *first = false;
+                // Objects cannot start with spaces: in this case,
+                // indentation is 0.
+                match data {
+                    Syntax::PlainText(text) if text.starts_with(|c| c == ' ' || c == '\t') => {
+                        indentations.push(Indentation::at(child, text, 0));
+                    }
+                    _ => return false,
+                }
+            }
+            match data {
+                Syntax::PlainText(text) => {
+                    for (newline, _) in text.match_indices('\n') {
+                        let indentation = Indentation::at(child, text, newline + 1);
+                        let next = text[indentation.end..].chars().next();
+                        if indentation.start < indentation.end {
+                            indentations.push(indentation);
+                        } else if next.map_or(false, |c| c != '\n') {
+                            return false;
+                        }
+                    }
+                }
+                Syntax::LineBreak => *first = true,
+                data if SyntaxT::from(data).is_recursive_object() => {
+                    if !self.indentations(child, first, indentations) {
+                        return false;
+                    }
+                }
+                _ => (),
+            }
+        }
+        true
+    }
+
+    /// Value of plain text `id`, with its indentation normalized
+    /// when it belongs to an element directly containing objects
+    pub fn plain_text<'s>(&'s self, id: NodeId, text: &'s str) -> &'s str {
+        self.normalized.get(&id).map_or(text, |t| t.as_str())
+    }
+
+    /// Headlines and inline tasks with a select tag, their ancestors
+    /// and their descendants, or None when no headline has one
+    ///
+    /// (defun org-export--selected-trees (data info)
+    fn selected_trees(&self) -> Option<HashSet<NodeId>> {
+        let tree = self.tree;
+        let mut selected = HashSet::new();
+        for id in tree.descendants(tree.root()) {
+            let tags = match &tree[id].data {
+                Syntax::Headline(h) => &h.tags,
+                Syntax::InlineTask(t) => &t.tags,
+                _ => continue,
+            };
+            if has_tag(tags, &self.options.select_tags) && !selected.contains(&id) {
+                selected.extend(tree.ancestors(id));
+                selected.extend(tree.descendants(id));
+            }
+        }
+        if selected.is_empty() {
+            None
+        } else {
+            Some(selected)
+        }
+    }
+
+    /// Collects headlines and their numbers.
+    ///
+    /// (defun org-export--get-min-level (data options)
+    /// (defun org-export--collect-headline-numbering (data options)
+    fn collect_headlines(&mut self) {
+        let tree = self.tree;
+        let root = tree.root();
+        let mut headlines = vec![];
+        let mut stack = vec![root];
+        while let Some(id) = stack.pop() {
+            if self.is_ignored(id) {
+                continue;
+            }
+            if let Syntax::Headline(_) = tree[id].data {
+                headlines.push(id);
+            }
+            let children: Vec<NodeId> = tree.children(id).collect();
+            stack.extend(children.into_iter().rev());
+        }
+        self.min_level = tree
+            .children(root)
+            .filter(|&id| !self.is_ignored(id))
+            .filter_map(|id| match &tree[id].data {
+                Syntax::Headline(h) => Some(h.level),
+                _ => None,
+            })
+            .min()
+            .unwrap_or(1);
+
+        let mut numbering: Vec<usize> = vec![];
+        for &id in &headlines {
+            let footnote_section_p = match &tree[id].data {
+                Syntax::Headline(h) => h.footnote_section_p,
+                _ => false,
+            };
+            if footnote_section_p || !self.numbered_headline_p(id) {
+                continue;
+            }
+            let level = self.relative_level(id);
+            numbering.resize(level, 0);
+            numbering[level - 1] += 1;
+            self.headline_numbers.insert(id, numbering.clone());
+        }
+        self.headlines = headlines;
+    }
+
+    /// Collects link destinations and footnote definitions
+    fn collect_targets(&mut self, id: NodeId) {
+        let tree = self.tree;
+        let node = &tree[id];
+        match &node.data {
+            Syntax::Headline(h) => {
+                if !self.is_ignored(id) {
+                    let title = normalize_whitespace(&h.raw_value);
+                    self.titles.entry(title).or_insert(id);
+                    for (key, map) in [("CUSTOM_ID", &mut self.custom_ids), ("ID", &mut self.ids)] {
+                        if let Some(value) = Info::headline_property(tree, id, key) {
+                            map.entry(value.to_string()).or_insert(id);
+                        }
+                    }
+                }
+            }
+            Syntax::Target(t) => {
+                let value = normalize_whitespace(&t.value);
+                self.targets.entry(value).or_insert(id);
+            }
+            Syntax::RadioTarget(r) => {
+                let value = normalize_whitespace(&r.raw_value).to_lowercase();
+                self.radio_targets.entry(value).or_insert(id);
+            }
+            Syntax::FootnoteDefinition(d) => {
+                self.footnote_definitions
+                    .entry(d.label.to_string())
+                    .or_insert(id);
+            }
+            Syntax::FootnoteReference(r) if node.first_child().is_some() => {
+                if let Some(label) = &r.label {
+                    self.footnote_definitions
+                        .entry(label.to_string())
+                        .or_insert(id);
+                }
+            }
+            _ => {}
+        }
+        if let Some(name) = node.affiliated.as_ref().and_then(|a| a.name()) {
+            self.targets.entry(normalize_whitespace(name)).or_insert(id);
+        }
+        for object in node.secondary_objects() {
+            self.collect_targets(object);
+        }
+        for child in tree.children(id) {
+            self.collect_targets(child);
+        }
+    }
+
+    /// Same as `property`, before `Info` is complete
+    fn headline_property(tree: &'t SyntaxTree<'a>, id: NodeId, key: &str) -> Option<&'t str> {
+        let section = tree
+            .children(id)
+            .find(|&c| matches!(tree[c].data, Syntax::Section))?;
+        let drawer = tree
+            .children(section)
+            .find(|&c| matches!(tree[c].data, Syntax::PropertyDrawer))?;
+        tree.children(drawer).find_map(|c| match &tree[c].data {
+            Syntax::NodeProperty(p) if p.key.eq_ignore_ascii_case(key) => Some(p.value.as_ref()),
+            _ => None,
+        })
+    }
+
+    /// Numbers footnotes in order of first reference. Definitions are
+    /// walked right after their first reference, so that references
+    /// they contain come next.
+    ///
+    /// (defun org-export--footnote-reference-map (function data info &optional body-first)
+    fn collect_footnotes(&mut self, id: NodeId) {
+        if self.is_ignored(id) {
+            return;
+        }
+        let tree = self.tree;
+        let node = &tree[id];
+        if let Syntax::FootnoteReference(r) = &node.data {
+            let new = match &r.label {
+                Some(label) => !self.footnote_labels.contains_key(label.as_ref()),
+                None => true,
+            };
+            if new {
+                let definition = match &r.label {
+                    _ if node.first_child().is_some() => Some(id),
+                    Some(label) => self.footnote_definitions.get(label.as_ref()).cloned(),
+                    None => None,
+                };
+                let index = self.footnotes.len();
+                match &r.label {
+                    Some(label) => self.footnote_labels.insert(label.to_string(), index),
+                    None => self.footnote_anonymous.insert(id, index),
+                };
+                self.footnotes.push(Footnote {
+                    number: index + 1,
+                    label: r.label.as_ref().map(|l| l.to_string()),
+                    definition,
+                });
+                if let Some(definition) = definition {
+                    for child in tree.children(definition) {
+                        self.collect_footnotes(child);
+                    }
+                }
+            }
+            return;
+        }
+        for object in node.secondary_objects() {
+            self.collect_footnotes(object);
+        }
+        for child in tree.children(id) {
+            self.collect_footnotes(child);
+        }
+    }
+}
+
+/// Ensures `s` ends with a single newline, unless it is empty
+///
+/// (defun org-element-normalize-string (s)
+pub fn normalize_string(s: String) -> String {
+    if s.is_empty() {
+        s
+    } else {
+        let mut s = s
+            .trim_end_matches(|c| c == ' ' || c == '\t' || c == '\n')
+            .to_string();
+        s.push('\n');
+        s
+    }
+}
+
+/// Collapses runs of whitespace into single spaces, to compare link
+/// paths with titles and targets the way ox.el does
+fn normalize_whitespace(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Fixtures shared by the tests of the export backends. Build them
+/// with `parse` whenever the parser supports the construct, and with
+/// `node` only for the others.
+pub(crate) mod fixture {
+    use crate::data::{Interval, Syntax, SyntaxNode, SyntaxTree};
+    use crate::options::ParserOptions;
+    use crate::parser::{ParseGranularity, Parser};
+    use std::borrow::Cow;
+
+    /// `text` parsed down to objects
+    pub(crate) fn parse(text: &str) -> SyntaxTree {
+        Parser::new(text, ParseGranularity::Object, ParserOptions::default()).parse_buffer()
+    }
+
+    /// Node holding `data`, to be appended to a tree
+    pub(crate) fn node(data: Syntax) -> SyntaxNode {
+        SyntaxNode::new(data, Interval { start: 0, end: 0 }, None, 0)
+    }
+
+    /// Plain text node holding `s`
+    pub(crate) fn text(s: &str) -> SyntaxNode {
+        node(Syntax::PlainText(Cow::from(s)))
+    }
+}
+
+mod test {
+    use super::fixture::{node, parse};
+    use super::{
+        export, try_export, Backend, BrokenLinks, ExportError, ExportOptions, Info,
+        SubSuperscripts, Timestamps,
+    };
+    use crate::data::{NodeId, SubscriptData, Syntax, SyntaxTree};
+
+    fn find(tree: &SyntaxTree, pred: impl Fn(&Syntax) -> bool) -> Vec<NodeId> {
+        tree.subtree(tree.root())
+            .into_iter()
+            .filter(|&id| pred(&tree[id].data))
+            .collect()
+    }
+
+    /// Upper cases text, brackets paragraphs, bold text and subscripts
+    struct Upper;
+
+    impl Backend for Upper {
+        fn name(&self) -> &str {
+            "upper"
+        }
+
+        fn plain_text(&self, info: &Info, text: &str) -> String {
+            text.to_uppercase()
+        }
+
+        fn paragraph(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+            Some(format!("[{}]", contents.unwrap_or_default().trim_end()))
+        }
+
+        fn bold(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+            Some(format!("<{}>", contents.unwrap_or_default()))
+        }
+
+        fn subscript(
+            &self,
+            info: &Info,
+            id: NodeId,
+            data: &SubscriptData,
+            contents: Option<String>,
+        ) -> Option<String> {
+            Some(format!("<sub>{}</sub>", contents.unwrap_or_default()))
+        }
+    }
+
+    /// Derived from `Upper`, only changes bold text
+    struct Derived(Upper);
+
+    impl Backend for Derived {
+        fn name(&self) -> &str {
+            "derived"
+        }
+
+        fn parent(&self) -> Option<&dyn Backend> {
+            Some(&self.0)
+        }
+
+        fn bold(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+            Some(format!("**{}**", contents.unwrap_or_default()))
+        }
+    }
+
+    #[test]
+    fn derived() {
+        let tree = parse("Some *bold*  text\n\n\nAgain\n");
+        let options = ExportOptions::default();
+        assert_eq!(
+            "[SOME <BOLD>  TEXT]\n\n\n[AGAIN]\n",
+            export(&tree, &Upper, &options)
+        );
+        assert_eq!(
+            "[SOME **BOLD**  TEXT]\n\n\n[AGAIN]\n",
+            export(&tree, &Derived(Upper), &options)
+        );
+    }
+
+    #[test]
+    fn headline_numbers() {
+        let tree = parse("** A\n*** B\n** COMMENT C\n*** Hidden\n** D\n*** E\n**** F\n");
+        let options = ExportOptions::default();
+        let info = Info::new(&tree, &Upper, &options);
+        let numbers: Vec<_> = info
+            .headlines()
+            .iter()
+            .map(|&id| info.headline_number(id).unwrap().to_vec())
+            .collect();
+        assert_eq!(
+            vec![vec![1], vec![1, 1], vec![2], vec![2, 1], vec![2, 1, 1]],
+            numbers
+        );
+        assert_eq!(4, info.toc(2).len());
+        let last = *info.headlines().last().unwrap();
+        assert_eq!(3, info.relative_level(last));
+        assert!(!info.low_level_p(last));
+    }
+
+    #[test]
+    fn footnotes() {
+        let tree = parse("A[fn:1] B[fn::inline [fn:2]] C[fn:1] D[fn:named: def]\n");
+        let options = ExportOptions::default();
+        let info = Info::new(&tree, &Upper, &options);
+        let labels: Vec<_> = info
+            .footnotes()
+            .iter()
+            .map(|f| (f.number, f.label.as_deref(), f.definition.is_some()))
+            .collect();
+        assert_eq!(
+            vec![
+                (1, Some("1"), false),
+                (2, None, true),
+                (3, Some("2"), false),
+                (4, Some("named"), true)
+            ],
+            labels
+        );
+        let references = find(&tree, |d| matches!(d, Syntax::FootnoteReference(_)));
+        let firsts = references
+            .iter()
+            .filter(|&&id| info.footnote_first_reference_p(id))
+            .count();
+        assert_eq!(4, firsts);
+    }
+
+    #[test]
+    fn links() {
+        let tree = parse("* Head one\n<<tgt>> [[tgt]] [[*Head   one]] [[Head one]] [[nowhere]]\n");
+        let options = ExportOptions::default();
+        let info = Info::new(&tree, &Upper, &options);
+        let links = find(&tree, |d| matches!(d, Syntax::Link(_)));
+        let mut resolved: Vec<_> = links
+            .iter()
+            .map(|&id| info.resolve_link(id).map(|dest| tree[dest].location.start))
+            .collect();
+        resolved.sort();
+        assert_eq!(vec![None, Some(0), Some(0), Some(11)], resolved);
+    }
+
+    #[test]
+    fn options() {
+        let tree = parse(
+            "#+TITLE: A\n\
+             #+title: title\n\
+             #+OPTIONS: toc:t num:2 ^:{} todo:nil\n\
+             #+OPTIONS: tags:nil f:nil broken-links:mark <:active timestamp:nil H:4 pri:t\n\
+             #+EXCLUDE_TAGS: hidden private\n\
+             Text\n",
+        );
+        let options = ExportOptions::from_tree(&tree);
+        assert_eq!(Some("A title"), options.title.as_deref());
+        assert_eq!(4, options.headline_levels);
+        assert_eq!(Some(3), options.with_toc);
+        assert_eq!(Some(2), options.section_numbers);
+        assert_eq!(SubSuperscripts::Braces, options.with_sub_superscripts);
+        assert!(!options.with_todo_keywords && !options.with_tags && !options.with_footnotes);
+        assert!(options.with_priority);
+        assert_eq!(BrokenLinks::Mark, options.broken_links);
+        assert_eq!(Timestamps::Active, options.with_timestamps);
+        assert_eq!(vec!["export"], options.select_tags);
+        assert_eq!(vec!["hidden", "private"], options.exclude_tags);
+
+        let mut options = ExportOptions::default();
+        options.parse_option_keyword("H:2 toc:t num:nil unknown:x");
+        assert_eq!((Some(2), None), (options.with_toc, options.section_numbers));
+
+        // `timestamp:` is about the creation time of the output file
+        for (value, timestamps) in [
+            ("<:nil", Timestamps::None),
+            ("<:inactive", Timestamps::Inactive),
+            ("<:t", Timestamps::All),
+            ("timestamp:nil", Timestamps::All),
+        ] {
+            let mut options = ExportOptions::default();
+            options.parse_option_keyword(value);
+            assert_eq!(timestamps, options.with_timestamps, "{}", value);
+        }
+    }
+
+    #[test]
+    fn prune() {
+        let tree = parse("* A\n* B :noexport:\n** C\n* D\n** E :export:\n*** F\n** G\n");
+        let titles = |options: &ExportOptions| {
+            let info = Info::new(&tree, &Upper, options);
+            info.headlines()
+                .iter()
+                .map(|&id| match &tree[id].data {
+                    Syntax::Headline(h) => h.raw_value.to_string(),
+                    _ => unreachable!(),
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec!["D", "E", "F"], titles(&ExportOptions::default()));
+        let options = ExportOptions {
+            select_tags: vec![],
+            ..ExportOptions::default()
+        };
+        assert_eq!(vec!["A", "D", "E", "F", "G"], titles(&options));
+
+        let tree = parse("<2020-01-01 Wed> [2020-01-02 Thu] [2020-01-02 Thu]--[2020-01-03 Fri]\n");
+        let timestamps = find(&tree, |d| matches!(d, Syntax::Timestamp(_)));
+        let kept = |with_timestamps| {
+            let options = ExportOptions {
+                with_timestamps,
+                ..ExportOptions::default()
+            };
+            let info = Info::new(&tree, &Upper, &options);
+            timestamps
+                .iter()
+                .filter(|&&id| !info.is_ignored(id))
+                .count()
+        };
+        assert_eq!(3, kept(Timestamps::All));
+        assert_eq!(1, kept(Timestamps::Active));
+        assert_eq!(2, kept(Timestamps::Inactive));
+        assert_eq!(0, kept(Timestamps::None));
+    }
+
+    #[test]
+    fn prune_every_backend() {
+        use super::ascii::Ascii;
+        use super::html::Html;
+        use super::icalendar::Icalendar;
+        use super::latex::Latex;
+        use super::markdown::Markdown;
+        use super::pandoc::Pandoc;
+
+        let tree = parse(
+            "#+OPTIONS: tags:nil todo:nil\n\
+             #+EXCLUDE_TAGS: secret\n\
+             * Skipped\n<2020-01-01 Wed>\n\
+             * Hidden :export:secret:\n<2020-01-02 Thu>\n\
+             * DONE Kept :export:work:\n<2020-01-03 Fri>\n\
+             ** Child\n<2020-01-04 Sat>\n",
+        );
+        let options = ExportOptions::from_tree(&tree);
+        let icalendar = Icalendar {
+            dtstamp: "20200101T000000Z".to_string(),
+            ..Icalendar::default()
+        };
+        let backends: Vec<Box<dyn Backend>> = vec![
+            Box::new(Ascii::default()),
+            Box::new(Html),
+            Box::new(icalendar),
+            Box::new(Latex::default()),
+            Box::new(Markdown::commonmark()),
+            Box::new(Pandoc),
+        ];
+        for backend in &backends {
+            let backend = backend.as_ref();
+            let exported = export(&tree, backend, &options);
+            for kept in &["Kept", "Child"] {
+                assert!(exported.contains(kept), "{}: {}", backend.name(), exported);
+            }
+            for left_out in &["Skipped", "Hidden", "secret", "DONE", "work"] {
+                assert!(
+                    !exported.contains(left_out),
+                    "{}: {}",
+                    backend.name(),
+                    exported
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn uninterpreted() {
+        let mut tree = parse("See [[nowhere]]\n");
+        let paragraph = find(&tree, |d| matches!(d, Syntax::Paragraph))[0];
+        let last = tree.children(paragraph).last().unwrap();
+        tree[last].data = Syntax::PlainText(" x".into());
+        // Sub and superscripts are not parsed yet: "See [[nowhere]] x_a y_{y}"
+        let subscript =
+            |use_brackets_p| Syntax::Subscript(Box::new(SubscriptData { use_brackets_p }));
+        let a = tree.append(paragraph, node(subscript(false)));
+        tree.append(a, node(Syntax::PlainText("a".into())));
+        tree.append(paragraph, node(Syntax::PlainText(" y".into())));
+        let y = tree.append(paragraph, node(subscript(true)));
+        tree.append(y, node(Syntax::PlainText("y".into())));
+        let export_with = |with_sub_superscripts, broken_links| {
+            let options = ExportOptions {
+                with_sub_superscripts,
+                broken_links,
+                ..ExportOptions::default()
+            };
+            try_export(&tree, &Upper, &options)
+        };
+        assert_eq!(
+            Err(ExportError::BrokenLink("nowhere".to_string())),
+            export_with(SubSuperscripts::All, BrokenLinks::Error)
+        );
+        assert_eq!(
+            Ok("[SEE [BROKEN LINK: NOWHERE] X_A Y_{Y}]\n".to_string()),
+            export_with(SubSuperscripts::None, BrokenLinks::Mark)
+        );
+        assert_eq!(
+            Ok("[SEE  X_A Y<sub>Y</sub>]\n".to_string()),
+            export_with(SubSuperscripts::Braces, BrokenLinks::Ignore)
+        );
+    }
+
+    #[test]
+    fn normalized_indentation() {
+        let tree = parse(
+            "- First\n  second *bold\n  line*\n\n  Other\n    paragraph\n\n\
+             Not\n  normalized\n",
+        );
+        assert_eq!(
+            "[FIRST\nSECOND <BOLD\nLINE>]\n\n[OTHER\n  PARAGRAPH]\n\n[NOT\n  NORMALIZED]\n",
+            export(&tree, &Upper, &ExportOptions::default())
+        );
+    }
+}
@@ -0,0 +1,1070 @@
+//    This file is part of org-rs.
+//
+//    org-rs is free software: you can redistribute it and/or modify
+//    it under the terms of the GNU General Public License as published by
+//    the Free Software Foundation, either version 3 of the License, or
+//    (at your option) any later version.
+//
+//    org-rs is distributed in the hope that it will be useful,
+//    but WITHOUT ANY WARRANTY; without even the implied warranty of
+//    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//    GNU General Public License for more details.
+//
+//    You should have received a copy of the GNU General Public License
+//    along with org-rs.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Plain text backend
+//!
+//! Port of ox-ascii.el
+//! https://code.orgmode.org/bzg/org-mode/src/master/lisp/ox-ascii.el
+//!
+//! Paragraphs are filled to `Ascii::text_width`, minus the margins of
+//! the elements they are in. Headline titles are underlined, tables
+//! are aligned and footnotes are collected at the end of the document.
+//! Links with a description show the description only, their
+//! destination is listed at the end of the section, as ox-ascii does
+//! with `org-ascii-links-to-notes`.
+//!
+//! `Charset` picks the characters used for underlines, bullets, tables
+//! and entities.
+
+use crate::data::*;
+use crate::export::html::{link_href, section_number};
+use crate::export::{normalize_string, Alignment, Backend, Info};
+use unicode_width::UnicodeWidthStr;
+
+/// Characters the output is restricted to
+/// elisp: `org-ascii-charset`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Charset {
+    Ascii,
+    Latin1,
+    Utf8,
+}
+
+/// Plain text backend, see module documentation
+#[derive(Debug, Clone)]
+pub struct Ascii {
+    pub charset: Charset,
+    /// Maximum width of the text, in columns
+    /// elisp: `org-ascii-text-width`
+    pub text_width: usize,
+    /// Indentation of the contents of a headline
+    /// elisp: `org-ascii-inner-margin`
+    pub inner_margin: usize,
+    /// Indentation of both sides of quote and verse blocks
+    /// elisp: `org-ascii-quote-margin`
+    pub quote_margin: usize,
+}
+
+impl Default for Ascii {
+    fn default() -> Self {
+        Ascii {
+            charset: Charset::Ascii,
+            text_width: 72,
+            inner_margin: 2,
+            quote_margin: 6,
+        }
+    }
+}
+
+impl Ascii {
+    pub fn new(charset: Charset) -> Self {
+        Ascii {
+            charset,
+            ..Ascii::default()
+        }
+    }
+}
+
+fn width(s: &str) -> usize {
+    UnicodeWidthStr::width(s)
+}
+
+/// Fills `text` to `width` columns. Spaces, tabs and newlines are
+/// collapsed, non-breaking spaces are kept within words. Words longer
+/// than `width` get a line of their own.
+///
+/// (defun org-ascii--fill-string (s text-width info &optional justify)
+fn fill(text: &str, width: usize) -> String {
+    let mut lines = vec![];
+    let mut line = String::new();
+    let mut line_width = 0;
+    let words = text
+        .split(|c| matches!(c, ' ' | '\t' | '\r' | '\n'))
+        .filter(|word| !word.is_empty());
+    for word in words {
+        let word_width = self::width(word);
+        if line_width > 0 && line_width + 1 + word_width > width {
+            lines.push(std::mem::take(&mut line));
+            line_width = 0;
+        }
+        if line_width > 0 {
+            line.push(' ');
+            line_width += 1;
+        }
+        line.push_str(word);
+        line_width += word_width;
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+/// Indents every non-empty line of `s` with `n` spaces
+///
+/// (defun org-ascii--indent-string (s width)
+fn indent(s: &str, n: usize) -> String {
+    indent_lines(s, n, 0)
+}
+
+/// Indents non-empty lines of `s` with `n` spaces, starting from line
+/// number `first`
+fn indent_lines(s: &str, n: usize, first: usize) -> String {
+    let margin = " ".repeat(n);
+    s.split('\n')
+        .enumerate()
+        .map(|(i, line)| {
+            if i < first || line.is_empty() {
+                line.to_string()
+            } else {
+                format!("{}{}", margin, line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Centers each line of `s` in `width` columns
+///
+/// (defun org-ascii--justify-lines (s text-width how)
+fn center(s: &str, width: usize) -> String {
+    s.split('\n')
+        .map(|line| {
+            let line = line.trim();
+            if line.is_empty() {
+                return String::new();
+            }
+            let margin = width.saturating_sub(self::width(line)) / 2;
+            format!("{}{}", " ".repeat(margin), line)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Returns `s` padded to `width` columns, following `alignment`
+fn align(s: &str, width: usize, alignment: Alignment) -> String {
+    let missing = width.saturating_sub(self::width(s));
+    let (left, right) = match alignment {
+        Alignment::Left => (0, missing),
+        Alignment::Right => (missing, 0),
+        Alignment::Center => (missing / 2, missing - missing / 2),
+    };
+    format!("{}{}{}", " ".repeat(left), s, " ".repeat(right))
+}
+
+impl Ascii {
+    /// Characters underlining headline titles, by level
+    /// elisp: `org-ascii-underline`
+    fn underlines(&self) -> &'static [char] {
+        match self.charset {
+            Charset::Utf8 => &['═', '─', '╌', '┄', '┈'],
+            _ => &['=', '~', '-'],
+        }
+    }
+
+    /// Bullets of low level headlines, by level
+    /// elisp: `org-ascii-bullets`
+    fn bullets(&self) -> &'static [char] {
+        match self.charset {
+            Charset::Ascii => &['*', '+', '-'],
+            Charset::Latin1 => &['§', '¶'],
+            Charset::Utf8 => &['◊'],
+        }
+    }
+
+    /// Bullet of a low level headline
+    fn headline_bullet(&self, info: &Info, id: NodeId) -> char {
+        let bullets = self.bullets();
+        let depth = info.relative_level(id) - info.options.headline_levels - 1;
+        bullets[depth % bullets.len()]
+    }
+
+    /// Bullet of an item, e.g. `-` or `2.`
+    fn item_bullet(&self, info: &Info, id: NodeId, data: &ItemData) -> String {
+        let list = info.tree[id].parent();
+        let kind = list.map_or(ListKind::Unordered, |list| match &info.tree[list].data {
+            Syntax::PlainList(l) => l.type_s,
+            _ => ListKind::Unordered,
+        });
+        match kind {
+            ListKind::Ordered if data.counter > 0 => format!("{}.", data.counter),
+            ListKind::Ordered => {
+                let position = list.map_or(0, |l| {
+                    info.tree.children(l).position(|i| i == id).unwrap_or(0)
+                });
+                format!("{}.", position + 1)
+            }
+            _ if self.charset == Charset::Utf8 => "•".to_string(),
+            _ => "-".to_string(),
+        }
+    }
+
+    fn checkbox(&self, checkbox: Option<CheckBox>) -> &'static str {
+        match (checkbox, self.charset) {
+            (Some(CheckBox::On), Charset::Utf8) => "☑ ",
+            (Some(CheckBox::Off), Charset::Utf8) => "☐ ",
+            (Some(CheckBox::Trans), Charset::Utf8) => "☒ ",
+            (Some(CheckBox::On), _) => "[X] ",
+            (Some(CheckBox::Off), _) => "[ ] ",
+            (Some(CheckBox::Trans), _) => "[-] ",
+            (None, _) => "",
+        }
+    }
+
+    /// Width available to the contents of element `id`: the text width
+    /// without the margins of the elements it is in
+    ///
+    /// (defun org-ascii--current-text-width (element info)
+    fn current_width(&self, info: &Info, id: NodeId) -> usize {
+        let margins: usize = info
+            .tree
+            .ancestors(id)
+            .map(|ancestor| match &info.tree[ancestor].data {
+                Syntax::Section if self.section_indented_p(info, ancestor) => self.inner_margin,
+                Syntax::Headline(_) if info.low_level_p(ancestor) => 2,
+                Syntax::Item(item) => width(&self.item_bullet(info, ancestor, item)) + 1,
+                Syntax::QuoteBlock => 2 * self.quote_margin,
+                _ => 0,
+            })
+            .sum();
+        self.text_width.saturating_sub(margins).max(1)
+    }
+
+    /// Returns true if the contents of section `id` are indented, as
+    /// it belongs to a headline that is not a low level one
+    fn section_indented_p(&self, info: &Info, id: NodeId) -> bool {
+        info.tree[id].parent().map_or(false, |parent| {
+            matches!(info.tree[parent].data, Syntax::Headline(_)) && !info.low_level_p(parent)
+        })
+    }
+
+    /// Title of a headline, with its number, TODO keyword, priority and
+    /// tags, and the width of the part before the tags
+    ///
+    /// (defun org-ascii--build-title (element info text-width &optional underline notags toc)
+    fn title(&self, info: &Info, id: NodeId, h: &HeadlineData, with_tags: bool) -> (String, usize) {
+        let mut title = String::new();
+        if let Some(number) = section_number(info, id) {
+            if !info.low_level_p(id) {
+                title.push_str(&number);
+                title.push(' ');
+            }
+        }
+        if let (Some(todo), true) = (&h.todo_keyword, info.options.with_todo_keywords) {
+            title.push_str(todo);
+            title.push(' ');
+        }
+        if h.priority > 0 && info.options.with_priority {
+            if let Some(p) = std::char::from_u32(h.priority as u32) {
+                title.push_str(&format!("[#{}] ", p));
+            }
+        }
+        title.push_str(&info.secondary(&h.title));
+        let title_width = width(&title);
+        let tags = info.tags(id);
+        if with_tags && !tags.is_empty() {
+            let tags = format!(":{}:", tags.join(":"));
+            let padding = self
+                .text_width
+                .saturating_sub(title_width + width(&tags))
+                .max(1);
+            title.push_str(&" ".repeat(padding));
+            title.push_str(&tags);
+        }
+        (title, title_width)
+    }
+
+    /// Title underlined with the character of `level`, if any
+    fn underlined(&self, title: &str, title_width: usize, level: usize) -> String {
+        match self.underlines().get(level - 1) {
+            Some(&c) => format!("{}\n{}", title, c.to_string().repeat(title_width)),
+            None => title.to_string(),
+        }
+    }
+
+    /// (defun org-ascii--build-toc (info &optional n keyword scope)
+    fn toc(&self, info: &Info, depth: usize) -> String {
+        let entries: Vec<String> = info
+            .toc(depth)
+            .into_iter()
+            .filter_map(|id| match &info.tree[id].data {
+                Syntax::Headline(h) if !h.footnote_section_p => {
+                    let (title, _) = self.title(info, id, h, false);
+                    Some(indent(
+                        &title,
+                        self.inner_margin * (info.relative_level(id) - 1),
+                    ))
+                }
+                _ => None,
+            })
+            .collect();
+        if entries.is_empty() {
+            return String::new();
+        }
+        let title = "Table of Contents";
+        format!(
+            "{}\n\n{}\n\n\n",
+            self.underlined(title, width(title), 1),
+            entries.join("\n")
+        )
+    }
+
+    /// (defun org-ascii-inner-template (contents info)
+    fn footnote_section(&self, info: &Info) -> String {
+        let definitions: Vec<String> = info
+            .footnotes()
+            .iter()
+            .filter_map(|f| {
+                let definition = info.contents(f.definition?);
+                let label = format!("[{}] ", f.number);
+                let paragraphs: Vec<String> = definition
+                    .split("\n\n")
+                    .filter(|p| !p.trim().is_empty())
+                    .enumerate()
+                    .map(|(i, p)| match i {
+                        0 => fill(&format!("{}{}", label, p), self.text_width),
+                        _ => indent(&fill(p, self.text_width - width(&label)), width(&label)),
+                    })
+                    .collect();
+                Some(paragraphs.join("\n\n"))
+            })
+            .collect();
+        if definitions.is_empty() {
+            return String::new();
+        }
+        let title = "Footnotes";
+        format!(
+            "\n\n\n{}\n\n{}\n",
+            self.underlined(title, width(title), 1),
+            definitions.join("\n\n")
+        )
+    }
+
+    /// Destinations of the links with a description in section `id`
+    ///
+    /// (defun org-ascii--describe-links (links width info)
+    fn link_notes(&self, info: &Info, id: NodeId) -> String {
+        let width = self.current_width(info, id);
+        info.tree
+            .descendants(id)
+            .filter_map(|node| match &info.tree[node].data {
+                Syntax::Link(link) if info.tree[node].first_child().is_some() => {
+                    let href = link_href(link, "txt")?;
+                    let note = format!("[{}] <{}>", info.contents(node), href);
+                    Some(fill(&note, width) + "\n")
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+impl Backend for Ascii {
+    fn name(&self) -> &str {
+        "ascii"
+    }
+
+    fn inner_template(&self, info: &Info, contents: String) -> String {
+        let toc = info
+            .options
+            .with_toc
+            .map_or(String::new(), |depth| self.toc(info, depth));
+        format!(
+            "{}{}{}",
+            toc,
+            contents.trim_end_matches('\n'),
+            self.footnote_section(info)
+        )
+    }
+
+    /// (defun org-ascii-template (contents info)
+    fn template(&self, info: &Info, contents: String) -> String {
+        let options = info.options;
+        let mut lines = vec![];
+        if let Some(title) = &options.title {
+            let rule = match self.charset {
+                Charset::Utf8 => '━',
+                _ => '=',
+            };
+            let rule = rule.to_string().repeat(width(title) + 4);
+            lines.push(rule.clone());
+            lines.push(title.clone());
+            lines.push(rule);
+            lines.push(String::new());
+        }
+        lines.extend(options.author.iter().cloned());
+        lines.extend(options.date.iter().cloned());
+        if lines.is_empty() {
+            return normalize_string(contents);
+        }
+        format!(
+            "{}\n\n\n{}",
+            center(lines.join("\n").trim_end(), self.text_width),
+            normalize_string(contents)
+        )
+    }
+
+    /// Headlines are followed by two blank lines, sections by one
+    ///
+    /// elisp: `org-ascii-headline-spacing`
+    fn post_blank(&self, info: &Info, id: NodeId) -> usize {
+        match info.tree[id].data {
+            Syntax::Headline(_) => 2,
+            Syntax::Section => 1,
+            _ => info.tree[id].post_blank,
+        }
+    }
+
+    /// (defun org-ascii-headline (headline contents info)
+    fn headline(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &HeadlineData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        if data.footnote_section_p {
+            return None;
+        }
+        let contents = contents.unwrap_or_default();
+        let (title, title_width) = self.title(info, id, data, true);
+        if info.low_level_p(id) {
+            let bullet = self.headline_bullet(info, id);
+            return Some(format!("{} {}\n{}", bullet, title, indent(&contents, 2)));
+        }
+        let level = info.relative_level(id);
+        Some(format!(
+            "{}\n\n{}",
+            self.underlined(&title, title_width, level),
+            contents
+        ))
+    }
+
+    /// (defun org-ascii-section (section contents info)
+    fn section(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        let contents = contents.unwrap_or_default();
+        let notes = self.link_notes(info, id);
+        let contents = match notes.is_empty() {
+            true => contents,
+            false => format!("{}\n{}", normalize_string(contents), notes),
+        };
+        if contents.is_empty() {
+            return None;
+        }
+        match self.section_indented_p(info, id) {
+            true => Some(indent(&contents, self.inner_margin)),
+            false => Some(contents),
+        }
+    }
+
+    /// Fills the paragraph. Line breaks split it in parts filled
+    /// separately.
+    ///
+    /// (defun org-ascii-paragraph (paragraph contents info)
+    fn paragraph(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        let width = self.current_width(info, id);
+        let mut parts = vec![String::new()];
+        for child in info.tree.children(id) {
+            match info.tree[child].data {
+                Syntax::LineBreak => parts.push(String::new()),
+                _ => parts.last_mut().unwrap().push_str(&info.data(child)),
+            }
+        }
+        let parts: Vec<String> = parts.iter().map(|part| fill(part, width)).collect();
+        Some(parts.join("\n"))
+    }
+
+    /// (defun org-ascii-plain-list (plain-list contents info)
+    fn plain_list(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &PlainListData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        contents
+    }
+
+    /// (defun org-ascii-item (item contents info)
+    fn item(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &ItemData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let bullet = self.item_bullet(info, id, data);
+        let tag = match &data.tag {
+            Some(tag) => format!("{}: ", info.secondary(tag)),
+            None => String::new(),
+        };
+        let contents = contents.unwrap_or_default();
+        Some(format!(
+            "{} {}{}{}",
+            bullet,
+            self.checkbox(data.checkbox),
+            tag,
+            indent_lines(contents.trim(), width(&bullet) + 1, 1)
+        ))
+    }
+
+    /// Aligns the columns, from the transcoded cells
+    ///
+    /// (defun org-ascii-table (table contents info)
+    fn table(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &TableData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let tree = info.tree;
+        let mut rows: Vec<Option<Vec<String>>> = vec![];
+        let mut alignments = vec![];
+        for row in tree.children(id) {
+            if info.table_row_is_rule_p(row) {
+                rows.push(None);
+            } else if !info.table_row_is_special_p(row) {
+                if alignments.is_empty() {
+                    alignments = tree
+                        .children(row)
+                        .map(|cell| info.table_cell_alignment(cell))
+                        .collect();
+                }
+                let cells = tree
+                    .children(row)
+                    .map(|cell| info.data(cell).trim().to_string())
+                    .collect();
+                rows.push(Some(cells));
+            }
+        }
+        let columns = rows.iter().flatten().map(Vec::len).max().unwrap_or(0);
+        let mut widths = vec![0; columns];
+        for cells in rows.iter().flatten() {
+            for (i, cell) in cells.iter().enumerate() {
+                widths[i] = widths[i].max(width(cell));
+            }
+        }
+        let (vertical, horizontal, left, cross, right) = match self.charset {
+            Charset::Utf8 => ('│', '─', '├', '┼', '┤'),
+            _ => ('|', '-', '|', '+', '|'),
+        };
+        let lines: Vec<String> = rows
+            .iter()
+            .map(|row| match row {
+                None => {
+                    let segments: Vec<String> = widths
+                        .iter()
+                        .map(|w| horizontal.to_string().repeat(w + 2))
+                        .collect();
+                    format!("{}{}{}", left, segments.join(&cross.to_string()), right)
+                }
+                Some(cells) => {
+                    let cells: Vec<String> = (0..columns)
+                        .map(|i| {
+                            let cell = cells.get(i).map_or("", String::as_str);
+                            let alignment = alignments.get(i).cloned().unwrap_or(Alignment::Left);
+                            format!(" {} ", align(cell, widths[i], alignment))
+                        })
+                        .collect();
+                    let separator = vertical.to_string();
+                    format!("{v}{}{v}", cells.join(&separator), v = vertical)
+                }
+            })
+            .collect();
+        Some(lines.join("\n"))
+    }
+
+    /// (defun org-ascii-src-block (src-block _contents info)
+    fn src_block(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &SrcBlockData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(data.value.to_string())
+    }
+
+    /// (defun org-ascii-example-block (example-block _contents info)
+    fn example_block(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &ExampleBlockData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(data.value.to_string())
+    }
+
+    /// (defun org-ascii-fixed-width (fixed-width _contents info)
+    fn fixed_width(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &FixedWidthData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(data.value.to_string())
+    }
+
+    /// (defun org-ascii-export-block (export-block _contents info)
+    fn export_block(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &ExportBlockData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        match data.type_s.eq_ignore_ascii_case("ascii") {
+            true => Some(data.value.to_string()),
+            false => None,
+        }
+    }
+
+    /// (defun org-ascii-export-snippet (export-snippet _contents _info)
+    fn export_snippet(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &ExportSnippetData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        match data.back_end.eq_ignore_ascii_case("ascii") {
+            true => Some(data.value.to_string()),
+            false => None,
+        }
+    }
+
+    /// (defun org-ascii-keyword (keyword _contents info)
+    fn keyword(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &KeywordData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        match data.key.eq_ignore_ascii_case("ascii") {
+            true => Some(data.value.to_string()),
+            false => None,
+        }
+    }
+
+    /// (defun org-ascii-quote-block (_quote-block contents info)
+    fn quote_block(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        Some(indent(&contents.unwrap_or_default(), self.quote_margin))
+    }
+
+    /// (defun org-ascii-verse-block (_verse-block contents info)
+    fn verse_block(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        Some(indent(&contents.unwrap_or_default(), self.quote_margin))
+    }
+
+    /// (defun org-ascii-center-block (_center-block contents _info)
+    fn center_block(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        let contents = contents.unwrap_or_default();
+        Some(center(
+            contents.trim_end_matches('\n'),
+            self.current_width(info, id),
+        ))
+    }
+
+    /// (defun org-ascii-inlinetask (inlinetask contents info)
+    fn inline_task(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &InlineTaskData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(format!(
+            "{}\n\n{}",
+            info.secondary(&data.title),
+            indent(&contents.unwrap_or_default(), self.inner_margin)
+        ))
+    }
+
+    /// (defun org-ascii-horizontal-rule (horizontal-rule _contents info)
+    fn horizontal_rule(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        let c = match self.charset {
+            Charset::Utf8 => '―',
+            _ => '-',
+        };
+        Some(c.to_string().repeat(self.current_width(info, id)))
+    }
+
+    /// (defun org-ascii-planning (planning _contents info)
+    fn planning(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &PlanningData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let stamps: Vec<String> = [
+            ("CLOSED:", &data.closed),
+            ("DEADLINE:", &data.deadline),
+            ("SCHEDULED:", &data.scheduled),
+        ]
+        .iter()
+        .filter_map(|(keyword, timestamp)| {
+            Some(format!("{} {}", keyword, timestamp.as_ref()?.raw_value))
+        })
+        .collect();
+        match stamps.is_empty() {
+            true => None,
+            false => Some(stamps.join(" ")),
+        }
+    }
+
+    /// (defun org-ascii-clock (clock _contents info)
+    fn clock(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &ClockData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(match data.duration.as_ref() {
+            "" => format!("CLOCK: {}", data.value.raw_value),
+            duration => format!("CLOCK: {} => {}", data.value.raw_value, duration),
+        })
+    }
+
+    fn property_drawer(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        None
+    }
+
+    /// (defun org-ascii-latex-environment (latex-environment _contents info)
+    fn latex_environment(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &LatexEnvironmentData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(data.value.to_string())
+    }
+
+    /// (defun org-ascii-latex-fragment (latex-fragment _contents info)
+    fn latex_fragment(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &LatexFragmentData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(data.value.to_string())
+    }
+
+    /// (defun org-ascii-entity (entity _contents info)
+    fn entity(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &EntityData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(
+            match self.charset {
+                Charset::Ascii => &data.ascii,
+                Charset::Latin1 => &data.latin1,
+                Charset::Utf8 => &data.utf_8,
+            }
+            .to_string(),
+        )
+    }
+
+    /// (defun org-ascii-bold (_bold contents _info)
+    fn bold(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        Some(format!("*{}*", contents.unwrap_or_default()))
+    }
+
+    /// (defun org-ascii-italic (_italic contents _info)
+    fn italic(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        Some(format!("/{}/", contents.unwrap_or_default()))
+    }
+
+    /// (defun org-ascii-underline (_underline contents _info)
+    fn underline(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        Some(format!("_{}_", contents.unwrap_or_default()))
+    }
+
+    /// (defun org-ascii-strike-through (_strike-through contents _info)
+    fn strike_through(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        Some(format!("+{}+", contents.unwrap_or_default()))
+    }
+
+    /// (defun org-ascii-code (code _contents info)
+    fn code(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &CodeData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(format!("`{}'", data.value))
+    }
+
+    /// (defun org-ascii-verbatim (verbatim _contents info)
+    fn verbatim(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &VerbatimData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(format!("`{}'", data.value))
+    }
+
+    /// (defun org-ascii-inline-src-block (inline-src-block _contents info)
+    fn inline_src_block(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &InlineSrcBlockData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(format!("`{}'", data.value))
+    }
+
+    /// (defun org-ascii-line-break (_line-break _contents _info)
+    fn line_break(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        Some("\n".to_string())
+    }
+
+    /// (defun org-ascii-statistics-cookie (statistics-cookie _contents _info)
+    fn statistics_cookie(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &StatisticsCookieData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(data.value.to_string())
+    }
+
+    /// (defun org-ascii-subscript (subscript contents _info)
+    fn subscript(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &SubscriptData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(format!("_{{{}}}", contents.unwrap_or_default()))
+    }
+
+    /// (defun org-ascii-superscript (superscript contents _info)
+    fn superscript(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &SuperscriptData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(format!("^{{{}}}", contents.unwrap_or_default()))
+    }
+
+    /// (defun org-ascii-target (_target _contents _info)
+    fn target(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &TargetData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(String::new())
+    }
+
+    /// (defun org-ascii-timestamp (timestamp _contents info)
+    fn timestamp(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &TimestampData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(data.raw_value.to_string())
+    }
+
+    /// (defun org-ascii-footnote-reference (footnote-reference _contents info)
+    fn footnote_reference(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &FootnoteReferenceData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(format!("[{}]", info.footnote(id)?.number))
+    }
+
+    /// (defun org-ascii-link (link desc info)
+    fn link(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &LinkData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        if let Some(destination) = info.resolve_link(id) {
+            return Some(
+                contents.unwrap_or_else(|| match &info.tree[destination].data {
+                    Syntax::Headline(h) => section_number(info, destination)
+                        .unwrap_or_else(|| info.secondary(&h.title)),
+                    _ => data.path.to_string(),
+                }),
+            );
+        }
+        Some(match (link_href(data, "txt"), contents) {
+            // The destination is listed at the end of the section
+            (Some(_), Some(description)) => format!("[{}]", description),
+            (Some(href), None) => format!("<{}>", href),
+            (None, description) => description.unwrap_or_else(|| data.path.to_string()),
+        })
+    }
+}
+
+mod test {
+    use super::{Ascii, Charset};
+    use crate::export::fixture::parse;
+    use crate::export::{export, ExportOptions};
+
+    #[test]
+    fn document() {
+        let text = "Intro paragraph with enough words to be wrapped on a narrow page.\n\
+                    * TODO Heading :work:\n\
+                    Some *bold* text, a note[fn:: Defined inline.] and\n\
+                    a [[https://orgmode.org][link]] to [[Sub heading]].\n\
+                    ** Sub heading\n\
+                    First part\\\\\n\
+                    second part\n";
+        let tree = parse(text);
+        let options = ExportOptions {
+            title: Some("Report".to_string()),
+            author: Some("Someone".to_string()),
+            ..ExportOptions::default()
+        };
+        let backend = Ascii {
+            text_width: 30,
+            ..Ascii::default()
+        };
+        assert_eq!(
+            "          ==========\n\
+             \x20           Report\n\
+             \x20         ==========\n\
+             \n\
+             \x20          Someone\n\
+             \n\
+             \n\
+             Table of Contents\n\
+             =================\n\
+             \n\
+             1 TODO Heading\n\
+             \x20 1.1 Sub heading\n\
+             \n\
+             \n\
+             Intro paragraph with enough\n\
+             words to be wrapped on a\n\
+             narrow page.\n\
+             \n\
+             1 TODO Heading          :work:\n\
+             ==============\n\
+             \n\
+             \x20 Some *bold* text, a note[1]\n\
+             \x20 and a [link] to 1.1.\n\
+             \n\
+             \x20 [link] <https://orgmode.org>\n\
+             \n\
+             1.1 Sub heading\n\
+             ~~~~~~~~~~~~~~~\n\
+             \n\
+             \x20 First part\n\
+             \x20 second part\n\
+             \n\
+             \n\
+             Footnotes\n\
+             =========\n\
+             \n\
+             [1] Defined inline.\n",
+            export(&tree, &backend, &options)
+        );
+    }
+
+    #[test]
+    fn elements() {
+        let tree = parse(
+            "- [X] Done with a rather long item text\n\
+             - Open\n\
+             \n\
+             | Name | Count |\n\
+             |-+-|\n\
+             | Straße | 1 |\n\
+             | b | 22 |\n\
+             \n\
+             Price: 5\\euro, \\alpha\\nbsp{}β\n",
+        );
+
+        let options = ExportOptions::default();
+        let backend = Ascii {
+            text_width: 24,
+            ..Ascii::default()
+        };
+        assert_eq!(
+            "- [X] Done with a rather\n  long item text\n\
+             - Open\n\
+             \n\
+             | Name   | Count |\n\
+             |--------+-------|\n\
+             | Straße |     1 |\n\
+             | b      |    22 |\n\
+             \n\
+             Price: 5EUR, alpha β\n",
+            export(&tree, &backend, &options)
+        );
+        let backend = Ascii {
+            charset: Charset::Latin1,
+            ..backend
+        };
+        assert_eq!(
+            "- [X] Done with a rather\n  long item text\n\
+             - Open\n\
+             \n\
+             | Name   | Count |\n\
+             |--------+-------|\n\
+             | Straße |     1 |\n\
+             | b      |    22 |\n\
+             \n\
+             Price: 5EUR, alpha\u{a0}β\n",
+            export(&tree, &backend, &options)
+        );
+        let backend = Ascii {
+            charset: Charset::Utf8,
+            ..backend
+        };
+        assert_eq!(
+            "• ☑ Done with a rather\n  long item text\n\
+             • Open\n\
+             \n\
+             │ Name   │ Count │\n\
+             ├────────┼───────┤\n\
+             │ Straße │     1 │\n\
+             │ b      │    22 │\n\
+             \n\
+             Price: 5€, α\u{a0}β\n",
+            export(&tree, &backend, &options)
+        );
+    }
+}
@@ -0,0 +1,1124 @@
+//    This file is part of org-rs.
+//
+//    org-rs is free software: you can redistribute it and/or modify
+//    it under the terms of the GNU General Public License as published by
+//    the Free Software Foundation, either version 3 of the License, or
+//    (at your option) any later version.
+//
+//    org-rs is distributed in the hope that it will be useful,
+//    but WITHOUT ANY WARRANTY; without even the implied warranty of
+//    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//    GNU General Public License for more details.
+//
+//    You should have received a copy of the GNU General Public License
+//    along with org-rs.  If not, see <https://www.gnu.org/licenses/>.
+
+//! HTML backend
+//!
+//! Port of ox-html.el
+//! https://code.orgmode.org/bzg/org-mode/src/master/lisp/ox-html.el
+//!
+//! Output follows ox-html defaults for HTML5: same structure, same
+//! classes and ids, so that existing Org stylesheets apply. LaTeX is
+//! left as is, for MathJax.
+
+use crate::data::*;
+use crate::export::{export, Alignment, Backend, ExportOptions, Info};
+
+/// HTML backend, see module documentation
+pub struct Html;
+
+impl Html {
+    /// Standalone HTML page of `tree`
+    ///
+    /// (defun org-html-export-as-html (&optional async subtreep visible-only body-only ext-plist)
+    pub fn page(&self, tree: &SyntaxTree, options: &ExportOptions) -> String {
+        let options = ExportOptions {
+            body_only: false,
+            ..options.clone()
+        };
+        export(tree, self, &options)
+    }
+
+    /// Contents of the `<body>` of the page of `tree`, to embed
+    /// into another document
+    pub fn fragment(&self, tree: &SyntaxTree, options: &ExportOptions) -> String {
+        let options = ExportOptions {
+            body_only: true,
+            ..options.clone()
+        };
+        export(tree, self, &options)
+    }
+}
+
+/// Escapes HTML special characters in `text`
+///
+/// (defun org-html-encode-plain-text (text)
+pub fn encode_plain_text(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => encoded.push_str("&amp;"),
+            '<' => encoded.push_str("&lt;"),
+            '>' => encoded.push_str("&gt;"),
+            c => encoded.push(c),
+        }
+    }
+    encoded
+}
+
+/// Same as `encode_plain_text`, for attribute values
+fn encode_attribute(text: &str) -> String {
+    encode_plain_text(text).replace('"', "&quot;")
+}
+
+/// Attributes from `pairs`, as a string to put in a tag
+///
+/// (defun org-html--make-attribute-string (attributes)
+fn attribute_string(pairs: &[(String, String)]) -> String {
+    pairs
+        .iter()
+        .map(|(key, value)| format!(" {}=\"{}\"", key, encode_attribute(value)))
+        .collect()
+}
+
+/// `#+ATTR_HTML` attributes of element `id`
+fn attributes(info: &Info, id: NodeId) -> Vec<(String, String)> {
+    info.tree[id]
+        .affiliated
+        .as_ref()
+        .map_or(vec![], |a| a.attr("html"))
+}
+
+/// `id` attribute of a named element, so that links can point to it
+fn name_attribute(info: &Info, id: NodeId) -> String {
+    match info.tree[id].affiliated.as_ref().and_then(|a| a.name()) {
+        Some(_) => format!(" id=\"{}\"", info.reference(id)),
+        None => String::new(),
+    }
+}
+
+/// Transcoded `#+CAPTION` of element `id`
+fn caption(info: &Info, id: NodeId) -> Option<String> {
+    let caption = info.tree[id].affiliated.as_ref()?.get("CAPTION")?;
+    Some(info.secondary(&caption.value))
+}
+
+/// Anchor of a headline: its `CUSTOM_ID` property, or a generated
+/// reference
+///
+/// (defun org-html--reference (datum info &optional named-only)
+pub(crate) fn anchor(info: &Info, id: NodeId) -> String {
+    match &info.tree[id].data {
+        Syntax::Headline(_) => info
+            .property(id, "CUSTOM_ID")
+            .map_or_else(|| info.reference(id), |c| c.to_string()),
+        _ => info.reference(id),
+    }
+}
+
+/// Section number of headline `id`, e.g. "1.2", if numbered
+pub(crate) fn section_number(info: &Info, id: NodeId) -> Option<String> {
+    let number = info.headline_number(id)?;
+    Some(
+        number
+            .iter()
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join("."),
+    )
+}
+
+/// Code, escaped and without its final newline
+///
+/// (defun org-html-format-code (element info)
+fn format_code(code: &str) -> String {
+    encode_plain_text(code.strip_suffix('\n').unwrap_or(code))
+}
+
+pub(crate) fn is_image(path: &str) -> bool {
+    // elisp: `org-html-inline-image-rules`
+    let extension = path.rsplit('.').next().unwrap_or("").to_ascii_lowercase();
+    match extension.as_str() {
+        "png" | "jpeg" | "jpg" | "gif" | "svg" | "webp" | "tif" | "tiff" | "xbm" | "xpm"
+        | "pbm" | "pgm" | "ppm" => path.contains('.'),
+        _ => false,
+    }
+}
+
+/// Destination of an external link, `None` for internal links.
+/// Links to Org files point to the exported file, with `extension`.
+pub(crate) fn link_href(link: &LinkData, extension: &str) -> Option<String> {
+    let path = link.path.as_ref();
+    Some(match link.link_type.as_ref() {
+        "fuzzy" | "custom-id" | "id" | "radio" | "coderef" => return None,
+        "file" => match path.strip_suffix(".org") {
+            Some(base) => format!("{}.{}", base, extension),
+            None => path.to_string(),
+        },
+        "doi" => format!("https://doi.org/{}", path),
+        t => format!("{}:{}", t, path),
+    })
+}
+
+/// Nested list of table of contents `entries`, as (level, text)
+///
+/// (defun org-html--toc-text (toc-entries)
+fn toc_text(entries: &[(usize, String)]) -> String {
+    let mut out = String::new();
+    let mut prev_level = 0;
+    for (level, text) in entries {
+        let cnt = *level as isize - prev_level as isize;
+        if cnt > 0 {
+            out.push_str(&"\n<ul>\n<li>".repeat(cnt as usize - 1));
+            out.push_str("\n<ul>\n<li>");
+        } else {
+            out.push_str(&"</li>\n</ul>\n".repeat(-cnt as usize));
+            out.push_str("</li>\n<li>");
+        }
+        out.push_str(text);
+        prev_level = *level;
+    }
+    out.push_str(&"</li>\n</ul>\n".repeat(prev_level));
+    out
+}
+
+impl Html {
+    /// TODO keyword, priority, title and tags of a headline
+    ///
+    /// (defun org-html-format-headline-default-function (todo todo-type priority text tags info)
+    fn format_headline(&self, info: &Info, id: NodeId, h: &HeadlineData) -> String {
+        let mut out = String::new();
+        if let (Some(todo), true) = (&h.todo_keyword, info.options.with_todo_keywords) {
+            let class = match h.todo_type {
+                Some(crate::headline::TodoType::Done) => "done",
+                _ => "todo",
+            };
+            out.push_str(&format!(
+                "<span class=\"{} {}\">{}</span> ",
+                class,
+                encode_attribute(todo),
+                encode_plain_text(todo)
+            ));
+        }
+        if h.priority > 0 && info.options.with_priority {
+            if let Some(p) = std::char::from_u32(h.priority as u32) {
+                out.push_str(&format!("<span class=\"priority\">[{}]</span> ", p));
+            }
+        }
+        out.push_str(&info.secondary(&h.title));
+        let tags = info.tags(id);
+        if !tags.is_empty() {
+            let tags: Vec<String> = tags
+                .iter()
+                .map(|t| format!("<span class=\"{}\">{}</span>", encode_attribute(t), t))
+                .collect();
+            out.push_str(&format!(
+                "&#xa0;&#xa0;&#xa0;<span class=\"tag\">{}</span>",
+                tags.join("&#xa0;")
+            ));
+        }
+        out
+    }
+
+    /// Table of contents, down to relative level `depth`
+    ///
+    /// (defun org-html-toc (depth info &optional scope)
+    fn toc(&self, info: &Info, depth: usize) -> String {
+        let entries: Vec<(usize, String)> = info
+            .toc(depth)
+            .into_iter()
+            .filter_map(|id| match &info.tree[id].data {
+                Syntax::Headline(h) if !h.footnote_section_p => {
+                    let number = match section_number(info, id) {
+                        Some(n) if !info.low_level_p(id) => format!("{}. ", n),
+                        _ => String::new(),
+                    };
+                    let text = format!(
+                        "<a href=\"#{}\">{}{}</a>",
+                        anchor(info, id),
+                        number,
+                        info.secondary(&h.title)
+                    );
+                    Some((info.relative_level(id), text))
+                }
+                _ => None,
+            })
+            .collect();
+        if entries.is_empty() {
+            return String::new();
+        }
+        format!(
+            "<div id=\"table-of-contents\" role=\"doc-toc\">\n\
+             <h2>Table of Contents</h2>\n\
+             <div id=\"text-table-of-contents\" role=\"doc-toc\">{}</div>\n\
+             </div>\n",
+            toc_text(&entries)
+        )
+    }
+
+    /// Footnote definitions, at the end of the document
+    ///
+    /// (defun org-html-footnote-section (info)
+    fn footnote_section(&self, info: &Info) -> String {
+        let definitions: String = info
+            .footnotes()
+            .iter()
+            .filter_map(|f| {
+                let definition = f.definition?;
+                let contents = info.contents(definition);
+                let contents = match info.tree[definition].data {
+                    Syntax::FootnoteReference(_) => {
+                        format!("<p class=\"footpara\">{}</p>", contents.trim())
+                    }
+                    _ => contents.replace("<p>", "<p class=\"footpara\">"),
+                };
+                Some(format!(
+                    "<div class=\"footdef\"><sup><a id=\"fn.{n}\" class=\"footnum\" \
+                     href=\"#fnr.{n}\" role=\"doc-backlink\">{n}</a></sup> \
+                     <div class=\"footpara\" role=\"doc-footnote\">{}</div></div>\n",
+                    contents.trim(),
+                    n = f.number
+                ))
+            })
+            .collect();
+        if definitions.is_empty() {
+            return String::new();
+        }
+        format!(
+            "<div id=\"footnotes\">\n\
+             <h2 class=\"footnotes\">Footnotes: </h2>\n\
+             <div id=\"text-footnotes\">\n{}</div>\n</div>\n",
+            definitions
+        )
+    }
+
+    /// Link to an internal destination
+    fn internal_link(
+        &self,
+        info: &Info,
+        link: &LinkData,
+        destination: NodeId,
+        desc: Option<String>,
+    ) -> String {
+        let description = desc.unwrap_or_else(|| match &info.tree[destination].data {
+            Syntax::Headline(h) => match section_number(info, destination) {
+                Some(n) => n,
+                None => info.secondary(&h.title),
+            },
+            _ => encode_plain_text(&link.path),
+        });
+        format!(
+            "<a href=\"#{}\">{}</a>",
+            anchor(info, destination),
+            description
+        )
+    }
+}
+
+impl Backend for Html {
+    fn name(&self) -> &str {
+        "html"
+    }
+
+    /// (defun org-html-plain-text (text info)
+    fn plain_text(&self, info: &Info, text: &str) -> String {
+        encode_plain_text(text)
+    }
+
+    /// (defun org-html-inner-template (contents info)
+    fn inner_template(&self, info: &Info, contents: String) -> String {
+        let toc = info
+            .options
+            .with_toc
+            .map_or(String::new(), |depth| self.toc(info, depth));
+        format!("{}{}{}", toc, contents, self.footnote_section(info))
+    }
+
+    /// (defun org-html-template (contents info)
+    fn template(&self, info: &Info, contents: String) -> String {
+        let options = info.options;
+        let title = options.title.as_deref().map(encode_plain_text);
+        let mut head = format!(
+            "<meta charset=\"utf-8\" />\n<title>{}</title>\n",
+            title.as_deref().unwrap_or("")
+        );
+        if let Some(author) = &options.author {
+            head.push_str(&format!(
+                "<meta name=\"author\" content=\"{}\" />\n",
+                encode_attribute(author)
+            ));
+        }
+        let title = title.map_or(String::new(), |t| {
+            format!("<h1 class=\"title\">{}</h1>\n", t)
+        });
+        format!(
+            "<!DOCTYPE html>\n<html lang=\"{}\">\n<head>\n{}</head>\n<body>\n\
+             <div id=\"content\" class=\"content\">\n{}{}</div>\n</body>\n</html>\n",
+            encode_attribute(&options.language),
+            head,
+            title,
+            contents
+        )
+    }
+
+    /// (defun org-html-headline (headline contents info)
+    fn headline(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &HeadlineData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        if data.footnote_section_p {
+            return None;
+        }
+        let contents = contents.unwrap_or_default();
+        let anchor = anchor(info, id);
+        let full = self.format_headline(info, id, data);
+        if info.low_level_p(id) {
+            let tag = if info.headline_number(id).is_some() {
+                "ol"
+            } else {
+                "ul"
+            };
+            return Some(format!(
+                "<{tag} class=\"org-{tag}\">\n<li>\n<a id=\"{}\"></a>{}<br />\n{}</li>\n</{tag}>\n",
+                anchor,
+                full,
+                contents,
+                tag = tag
+            ));
+        }
+        let level = (info.relative_level(id) + 1).min(6);
+        let number = section_number(info, id).map_or(String::new(), |n| {
+            format!("<span class=\"section-number-{}\">{}</span> ", level, n)
+        });
+        Some(format!(
+            "<div id=\"outline-container-{a}\" class=\"outline-{l}\">\n\
+             <h{l} id=\"{a}\">{}{}</h{l}>\n{}</div>\n",
+            number,
+            full,
+            contents,
+            a = anchor,
+            l = level
+        ))
+    }
+
+    /// (defun org-html-section (section contents info)
+    fn section(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        let contents = contents?;
+        let headline = match info.parent_headline(id) {
+            Some(headline) if !info.low_level_p(headline) => headline,
+            _ => return Some(contents),
+        };
+        let level = (info.relative_level(headline) + 1).min(6);
+        let text_id = section_number(info, headline).unwrap_or_else(|| anchor(info, headline));
+        Some(format!(
+            "<div class=\"outline-text-{}\" id=\"text-{}\">\n{}</div>\n",
+            level,
+            text_id.replace('.', "-"),
+            contents
+        ))
+    }
+
+    /// (defun org-html-paragraph (paragraph contents info)
+    fn paragraph(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        let contents = contents.unwrap_or_default();
+        let node = &info.tree[id];
+        let in_item = node
+            .parent()
+            .map_or(false, |p| matches!(info.tree[p].data, Syntax::Item(_)));
+        if in_item && node.prev_sibling().is_none() {
+            return Some(contents);
+        }
+        Some(format!("<p>\n{}</p>", contents))
+    }
+
+    /// (defun org-html-plain-list (plain-list contents info)
+    fn plain_list(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &PlainListData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let tag = match data.type_s {
+            ListKind::Ordered => "ol",
+            ListKind::Unordered => "ul",
+            ListKind::Descriptive => "dl",
+        };
+        let mut attrs = vec![("class".to_string(), format!("org-{}", tag))];
+        attrs.extend(attributes(info, id));
+        Some(format!(
+            "<{tag}{}>\n{}</{tag}>",
+            attribute_string(&attrs),
+            contents.unwrap_or_default(),
+            tag = tag
+        ))
+    }
+
+    /// (defun org-html-item (item contents info)
+    fn item(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &ItemData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let checkbox = match data.checkbox {
+            Some(CheckBox::On) => "<code>[X]</code> ",
+            Some(CheckBox::Off) => "<code>[&#xa0;]</code> ",
+            Some(CheckBox::Trans) => "<code>[-]</code> ",
+            None => "",
+        };
+        let contents = contents.unwrap_or_default();
+        let contents = contents.trim();
+        let kind = info.tree[id]
+            .parent()
+            .and_then(|list| match &info.tree[list].data {
+                Syntax::PlainList(l) => Some(l.type_s),
+                _ => None,
+            })
+            .unwrap_or(ListKind::Unordered);
+        Some(match kind {
+            ListKind::Descriptive => {
+                let term = data
+                    .tag
+                    .as_ref()
+                    .map_or_else(|| "(no term)".to_string(), |t| info.secondary(t));
+                format!("<dt>{}{}</dt><dd>{}</dd>", checkbox, term, contents)
+            }
+            ListKind::Ordered if data.counter > 0 => {
+                format!(
+                    "<li value=\"{}\">{}{}</li>",
+                    data.counter, checkbox, contents
+                )
+            }
+            _ => format!("<li>{}{}</li>", checkbox, contents),
+        })
+    }
+
+    /// (defun org-html-table (table contents info)
+    fn table(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &TableData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        // elisp: `org-html-table-default-attributes`
+        let mut attrs: Vec<(String, String)> = [
+            ("border", "2"),
+            ("cellspacing", "0"),
+            ("cellpadding", "6"),
+            ("rules", "groups"),
+            ("frame", "hsides"),
+        ]
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        for (key, value) in attributes(info, id) {
+            match attrs.iter_mut().find(|(k, _)| *k == key) {
+                Some(attr) => attr.1 = value,
+                None => attrs.push((key, value)),
+            }
+        }
+        let caption = caption(info, id).map_or(String::new(), |c| {
+            format!("<caption class=\"t-above\">{}</caption>\n", c)
+        });
+        Some(format!(
+            "<table{}{}>\n{}{}</table>",
+            name_attribute(info, id),
+            attribute_string(&attrs),
+            caption,
+            contents.unwrap_or_default()
+        ))
+    }
+
+    /// (defun org-html-table-row (table-row contents info)
+    fn table_row(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &TableRowData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        if info.table_row_group(id).is_none() {
+            return None;
+        }
+        let group = if info.table_row_in_header_p(id) {
+            "thead"
+        } else {
+            "tbody"
+        };
+        let open = if info.table_row_starts_rowgroup_p(id) {
+            format!("<{}>\n", group)
+        } else {
+            String::new()
+        };
+        let close = if info.table_row_ends_rowgroup_p(id) {
+            format!("</{}>\n", group)
+        } else {
+            String::new()
+        };
+        Some(format!(
+            "{}<tr>\n{}</tr>\n{}",
+            open,
+            contents.unwrap_or_default(),
+            close
+        ))
+    }
+
+    /// (defun org-html-table-cell (table-cell contents info)
+    fn table_cell(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        let class = match info.table_cell_alignment(id) {
+            Alignment::Left => "org-left",
+            Alignment::Right => "org-right",
+            Alignment::Center => "org-center",
+        };
+        let contents = contents.unwrap_or_default();
+        let contents = match contents.trim() {
+            "" => "&#xa0;",
+            c => c,
+        };
+        let in_header = info.tree[id]
+            .parent()
+            .map_or(false, |row| info.table_row_in_header_p(row));
+        Some(if in_header {
+            format!("<th scope=\"col\" class=\"{}\">{}</th>\n", class, contents)
+        } else {
+            format!("<td class=\"{}\">{}</td>\n", class, contents)
+        })
+    }
+
+    /// (defun org-html-src-block (src-block _contents info)
+    fn src_block(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &SrcBlockData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let code = format_code(&data.value);
+        let attrs = attribute_string(&attributes(info, id));
+        let language = match &data.language {
+            Some(language) => language,
+            None => {
+                return Some(format!(
+                    "<pre class=\"example\"{}{}>\n{}</pre>",
+                    name_attribute(info, id),
+                    attrs,
+                    code
+                ))
+            }
+        };
+        let label = caption(info, id).map_or(String::new(), |c| {
+            format!("<label class=\"org-src-name\">{}</label>", c)
+        });
+        Some(format!(
+            "<div class=\"org-src-container\">\n{}<pre class=\"src src-{}\"{}{}>{}</pre>\n</div>",
+            label,
+            encode_attribute(language),
+            name_attribute(info, id),
+            attrs,
+            code
+        ))
+    }
+
+    /// (defun org-html-example-block (example-block _contents info)
+    fn example_block(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &ExampleBlockData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(format!(
+            "<pre class=\"example\"{}{}>\n{}</pre>",
+            name_attribute(info, id),
+            attribute_string(&attributes(info, id)),
+            format_code(&data.value)
+        ))
+    }
+
+    /// (defun org-html-fixed-width (fixed-width _contents _info)
+    fn fixed_width(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &FixedWidthData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(format!(
+            "<pre class=\"example\">\n{}</pre>",
+            format_code(&data.value)
+        ))
+    }
+
+    /// (defun org-html-export-block (export-block _contents _info)
+    fn export_block(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &ExportBlockData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        if data.type_s.eq_ignore_ascii_case("html") {
+            Some(data.value.to_string())
+        } else {
+            None
+        }
+    }
+
+    /// (defun org-html-export-snippet (export-snippet _contents _info)
+    fn export_snippet(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &ExportSnippetData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        if data.back_end.eq_ignore_ascii_case("html") {
+            Some(data.value.to_string())
+        } else {
+            None
+        }
+    }
+
+    /// (defun org-html-keyword (keyword _contents info)
+    fn keyword(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &KeywordData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        if data.key.eq_ignore_ascii_case("html") {
+            Some(data.value.to_string())
+        } else {
+            None
+        }
+    }
+
+    /// (defun org-html-special-block (special-block contents info)
+    fn special_block(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &SpecialBlockData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let mut attrs = vec![("class".to_string(), data.type_s.to_string())];
+        for (key, value) in attributes(info, id) {
+            match attrs.iter_mut().find(|(k, _)| *k == key) {
+                Some(class) if key == "class" => class.1 = format!("{} {}", value, class.1),
+                _ => attrs.push((key, value)),
+            }
+        }
+        Some(format!(
+            "<div{}{}>\n{}</div>",
+            name_attribute(info, id),
+            attribute_string(&attrs),
+            contents.unwrap_or_default()
+        ))
+    }
+
+    /// (defun org-html-quote-block (quote-block contents info)
+    fn quote_block(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        Some(format!(
+            "<blockquote{}{}>\n{}</blockquote>",
+            name_attribute(info, id),
+            attribute_string(&attributes(info, id)),
+            contents.unwrap_or_default()
+        ))
+    }
+
+    /// (defun org-html-center-block (_center-block contents _info)
+    fn center_block(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        Some(format!(
+            "<div class=\"org-center\">\n{}</div>",
+            contents.unwrap_or_default()
+        ))
+    }
+
+    /// (defun org-html-verse-block (_verse-block contents info)
+    fn verse_block(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        let contents = contents.unwrap_or_default();
+        let lines: Vec<String> = contents
+            .trim_end_matches('\n')
+            .split('\n')
+            .map(|line| {
+                let text = line.trim_start();
+                "&#xa0;".repeat(line.len() - text.len()) + text
+            })
+            .collect();
+        Some(format!(
+            "<p class=\"verse\">\n{}<br /></p>",
+            lines.join("<br />\n")
+        ))
+    }
+
+    /// (defun org-html-inlinetask (inlinetask contents info)
+    fn inline_task(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &InlineTaskData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(format!(
+            "<div class=\"inlinetask\">\n<b>{}</b><br />\n{}</div>",
+            info.secondary(&data.title),
+            contents.unwrap_or_default()
+        ))
+    }
+
+    /// (defun org-html-horizontal-rule (_horizontal-rule _contents _info)
+    fn horizontal_rule(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        Some("<hr />".to_string())
+    }
+
+    /// Not exported, see `org-export-with-properties`
+    fn property_drawer(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        None
+    }
+
+    /// (defun org-html-latex-environment (latex-environment _contents info)
+    fn latex_environment(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &LatexEnvironmentData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(data.value.to_string())
+    }
+
+    /// (defun org-html-bold (_bold contents info)
+    fn bold(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        Some(format!("<b>{}</b>", contents.unwrap_or_default()))
+    }
+
+    /// (defun org-html-italic (_italic contents info)
+    fn italic(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        Some(format!("<i>{}</i>", contents.unwrap_or_default()))
+    }
+
+    /// (defun org-html-underline (_underline contents info)
+    fn underline(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        Some(format!(
+            "<span class=\"underline\">{}</span>",
+            contents.unwrap_or_default()
+        ))
+    }
+
+    /// (defun org-html-strike-through (_strike-through contents info)
+    fn strike_through(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        Some(format!("<del>{}</del>", contents.unwrap_or_default()))
+    }
+
+    /// (defun org-html-code (code _contents info)
+    fn code(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &CodeData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(format!("<code>{}</code>", encode_plain_text(&data.value)))
+    }
+
+    /// (defun org-html-verbatim (verbatim _contents info)
+    fn verbatim(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &VerbatimData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(format!("<code>{}</code>", encode_plain_text(&data.value)))
+    }
+
+    /// (defun org-html-inline-src-block (inline-src-block _contents info)
+    fn inline_src_block(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &InlineSrcBlockData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(format!(
+            "<code class=\"src src-{}\">{}</code>",
+            encode_attribute(&data.language),
+            encode_plain_text(&data.value)
+        ))
+    }
+
+    /// (defun org-html-entity (entity _contents _info)
+    fn entity(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &EntityData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(data.html.to_string())
+    }
+
+    /// (defun org-html-latex-fragment (latex-fragment _contents info)
+    fn latex_fragment(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &LatexFragmentData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(data.value.to_string())
+    }
+
+    /// (defun org-html-line-break (_line-break _contents info)
+    fn line_break(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        Some("<br />\n".to_string())
+    }
+
+    /// (defun org-html-statistics-cookie (statistics-cookie _contents _info)
+    fn statistics_cookie(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &StatisticsCookieData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(format!("<code>{}</code>", data.value))
+    }
+
+    /// (defun org-html-subscript (_subscript contents _info)
+    fn subscript(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &SubscriptData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(format!("<sub>{}</sub>", contents.unwrap_or_default()))
+    }
+
+    /// (defun org-html-superscript (_superscript contents _info)
+    fn superscript(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &SuperscriptData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(format!("<sup>{}</sup>", contents.unwrap_or_default()))
+    }
+
+    /// (defun org-html-target (target _contents info)
+    fn target(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &TargetData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(format!("<a id=\"{}\"></a>", info.reference(id)))
+    }
+
+    /// (defun org-html-radio-target (radio-target text info)
+    fn radio_target(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &RadioTargetData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(format!(
+            "<a id=\"{}\">{}</a>",
+            info.reference(id),
+            contents.unwrap_or_default()
+        ))
+    }
+
+    /// (defun org-html-timestamp (timestamp _contents info)
+    fn timestamp(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &TimestampData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(format!(
+            "<span class=\"timestamp-wrapper\"><span class=\"timestamp\">{}</span></span>",
+            encode_plain_text(&data.raw_value)
+        ))
+    }
+
+    /// (defun org-html-footnote-reference (footnote-reference _contents info)
+    fn footnote_reference(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &FootnoteReferenceData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let n = info.footnote(id)?.number;
+        let suffix = if info.footnote_first_reference_p(id) {
+            ""
+        } else {
+            ".100"
+        };
+        Some(format!(
+            "<sup><a id=\"fnr.{n}{}\" class=\"footref\" href=\"#fn.{n}\" role=\"doc-backlink\">{n}</a></sup>",
+            suffix,
+            n = n
+        ))
+    }
+
+    /// (defun org-html-link (link desc info)
+    fn link(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &LinkData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        if let Some(destination) = info.resolve_link(id) {
+            return Some(self.internal_link(info, data, destination, contents));
+        }
+        let path = data.path.as_ref();
+        let href = match link_href(data, "html") {
+            Some(href) => href,
+            None => {
+                // Unresolved internal link
+                let text = contents.unwrap_or_else(|| encode_plain_text(path));
+                return Some(format!("<i>{}</i>", text));
+            }
+        };
+        if contents.is_none() && is_image(path) {
+            // Attributes of the paragraph apply to the standalone image.
+            let attrs = info.tree[id]
+                .parent()
+                .map_or(vec![], |parent| attributes(info, parent));
+            let alt = path.rsplit('/').next().unwrap_or(path);
+            let mut pairs = vec![
+                ("src".to_string(), href),
+                ("alt".to_string(), alt.to_string()),
+            ];
+            for (key, value) in attrs {
+                match pairs.iter_mut().find(|(k, _)| *k == key) {
+                    Some(pair) => pair.1 = value,
+                    None => pairs.push((key, value)),
+                }
+            }
+            return Some(format!("<img{} />", attribute_string(&pairs)));
+        }
+        let description = contents.unwrap_or_else(|| encode_plain_text(&href));
+        Some(format!(
+            "<a href=\"{}\">{}</a>",
+            encode_attribute(&href),
+            description
+        ))
+    }
+}
+
+mod test {
+    use super::Html;
+    use crate::export::fixture::parse;
+    use crate::export::ExportOptions;
+
+    #[test]
+    fn document() {
+        let text = "Intro with *bold* & <tags>.\n\
+                    * TODO Heading :work:\n\
+                    Text[fn:1] with [[Sub heading][a link]] and[fn::Inline note.]\n\
+                    ** Sub heading\n\
+                    <<here>>Target [[here]] and [[https://orgmode.org][Org]]\n";
+        let tree = parse(text);
+        let options = ExportOptions {
+            title: Some("A & B".to_string()),
+            ..ExportOptions::default()
+        };
+        let body = Html.fragment(&tree, &options);
+
+        assert!(body.starts_with("<div id=\"table-of-contents\" role=\"doc-toc\">"));
+        assert!(body.contains(
+            "\n<ul>\n<li><a href=\"#org0000008\">1. Heading</a>\n\
+             <ul>\n<li><a href=\"#org0000015\">1.1. Sub heading</a></li>\n</ul>\n</li>\n</ul>\n"
+        ));
+        assert!(body.contains("<p>\nIntro with <b>bold</b> &amp; &lt;tags&gt;.\n</p>\n"));
+        assert!(body.contains(
+            "<h2 id=\"org0000008\"><span class=\"section-number-2\">1</span> \
+             <span class=\"todo TODO\">TODO</span> Heading&#xa0;&#xa0;&#xa0;\
+             <span class=\"tag\"><span class=\"work\">work</span></span></h2>"
+        ));
+        assert!(body.contains("<div class=\"outline-text-2\" id=\"text-1\">"));
+        assert!(body.contains(
+            "<sup><a id=\"fnr.1\" class=\"footref\" href=\"#fn.1\" role=\"doc-backlink\">1</a></sup>"
+        ));
+        assert!(body.contains("<a href=\"#org0000015\">a link</a>"));
+        assert!(body.contains("<a id=\"org0000018\"></a>Target <a href=\"#org0000018\">here</a>"));
+        assert!(body.contains("<a href=\"https://orgmode.org\">Org</a>"));
+        // Only the inline footnote has a definition
+        assert!(body.contains(
+            "<div class=\"footdef\"><sup><a id=\"fn.2\" class=\"footnum\" href=\"#fnr.2\" \
+             role=\"doc-backlink\">2</a></sup> <div class=\"footpara\" role=\"doc-footnote\">\
+             <p class=\"footpara\">Inline note.</p></div></div>"
+        ));
+        assert!(!body.contains("<html"));
+
+        let page = Html.page(&tree, &options);
+        assert!(page.starts_with("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n"));
+        assert!(page.contains("<title>A &amp; B</title>"));
+        assert!(page.contains("<h1 class=\"title\">A &amp; B</h1>\n"));
+        assert!(page.contains(&body));
+        assert!(page.ends_with("</div>\n</body>\n</html>\n"));
+    }
+
+    #[test]
+    fn elements() {
+        let text = "- [X] Term :: Definition\n\
+                    #+ATTR_HTML: :border 0 :class data\n\
+                    | Name | Count |\n\
+                    |------+-------|\n\
+                    | a    |     1 |\n\
+                    | b    |    22 |\n\
+                    #+ATTR_HTML: :data-lang rust\n\
+                    #+BEGIN_SRC rust\n\
+                    if a < b {}\n\
+                    #+END_SRC\n";
+        let tree = parse(text);
+        let options = ExportOptions::default();
+        let body = Html.fragment(&tree, &options);
+        assert_eq!(
+            "<dl class=\"org-dl\">\n<dt><code>[X]</code> Term</dt><dd>Definition</dd>\n</dl>\n\
+             <table border=\"0\" cellspacing=\"0\" cellpadding=\"6\" rules=\"groups\" \
+             frame=\"hsides\" class=\"data\">\n\
+             <thead>\n<tr>\n<th scope=\"col\" class=\"org-left\">Name</th>\n\
+             <th scope=\"col\" class=\"org-right\">Count</th>\n</tr>\n</thead>\n\
+             <tbody>\n<tr>\n<td class=\"org-left\">a</td>\n<td class=\"org-right\">1</td>\n</tr>\n\
+             <tr>\n<td class=\"org-left\">b</td>\n<td class=\"org-right\">22</td>\n</tr>\n\
+             </tbody>\n</table>\n\
+             <div class=\"org-src-container\">\n\
+             <pre class=\"src src-rust\" data-lang=\"rust\">if a &lt; b {}</pre>\n</div>\n",
+            body
+        );
+    }
+
+    #[test]
+    fn footnotes_and_entities() {
+        let text = "Text\\nbsp{}with \\alpha and a note[fn:1].\n\n\
+                    [fn:1] The *definition*.\n";
+        let tree = parse(text);
+        let body = Html.fragment(&tree, &ExportOptions::default());
+        assert!(body.contains("<p>\nText&nbsp;with &alpha; and a note<sup><a id=\"fnr.1\""));
+        assert!(body.contains(
+            "<div class=\"footdef\"><sup><a id=\"fn.1\" class=\"footnum\" href=\"#fnr.1\" \
+             role=\"doc-backlink\">1</a></sup> <div class=\"footpara\" role=\"doc-footnote\">\
+             <p class=\"footpara\">\nThe <b>definition</b>.\n</p></div></div>"
+        ));
+        // The definition is only exported with the footnotes
+        assert_eq!(1, body.matches("<b>definition</b>").count());
+    }
+}
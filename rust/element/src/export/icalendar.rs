@@ -0,0 +1,619 @@
+//    This file is part of org-rs.
+//
+//    org-rs is free software: you can redistribute it and/or modify
+//    it under the terms of the GNU General Public License as published by
+//    the Free Software Foundation, either version 3 of the License, or
+//    (at your option) any later version.
+//
+//    org-rs is distributed in the hope that it will be useful,
+//    but WITHOUT ANY WARRANTY; without even the implied warranty of
+//    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//    GNU General Public License for more details.
+//
+//    You should have received a copy of the GNU General Public License
+//    along with org-rs.  If not, see <https://www.gnu.org/licenses/>.
+
+//! iCalendar backend
+//!
+//! Port of ox-icalendar.el, see RFC 5545
+//! https://code.orgmode.org/bzg/org-mode/src/master/lisp/ox-icalendar.el
+//!
+//! Each headline gives calendar entries:
+//!
+//! - a VEVENT for its DEADLINE and one for its SCHEDULED timestamp,
+//!   unless it is a task (`event-if-not-todo` in ox-icalendar),
+//! - a VEVENT for each active timestamp in its title and section,
+//! - a VTODO for an open task, starting at its SCHEDULED timestamp,
+//!   or else at its DEADLINE or now, and due at its DEADLINE.
+//!
+//! The `ID` property of the headline is the UID of its entries,
+//! prefixed with the kind of entry, e.g. `DL-` for deadlines. Without
+//! one, the UID is a hash of the outline path, title and timestamps of
+//! the headline.
+//! Repeaters become RRULEs and warning delays VALARMs.
+//!
+//! Like ox-icalendar, the backend derives from the plain text backend,
+//! which transcodes titles and sections into summaries and
+//! descriptions.
+
+use crate::data::*;
+use crate::export::ascii::{Ascii, Charset};
+use crate::export::{Backend, Info};
+use crate::headline::TodoType;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// iCalendar backend, see module documentation
+#[derive(Debug, Clone)]
+pub struct Icalendar {
+    /// Backend summaries and descriptions are exported with
+    pub ascii: Ascii,
+    /// Creation time of the entries, in UTC, e.g. `20240301T120000Z`.
+    /// Defaults to the current time.
+    pub dtstamp: String,
+    /// Time zone of the calendar
+    /// elisp: `org-icalendar-timezone`
+    pub timezone: Option<String>,
+    /// Export open tasks as VTODO entries
+    /// elisp: `org-icalendar-include-todo`
+    pub include_todo: bool,
+}
+
+impl Default for Icalendar {
+    fn default() -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as i64);
+        let (year, month, day) = civil_from_days(now.div_euclid(86400));
+        let seconds = now.rem_euclid(86400);
+        Icalendar {
+            ascii: Ascii::new(Charset::Utf8),
+            dtstamp: format!(
+                "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+                year,
+                month,
+                day,
+                seconds / 3600,
+                seconds % 3600 / 60,
+                seconds % 60
+            ),
+            timezone: None,
+            include_todo: true,
+        }
+    }
+}
+
+/// Days since 1970-01-01 of a date of the proleptic Gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// Date of the day `days` after 1970-01-01
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Start, or end, of `timestamp` as the value of a date-time property,
+/// with its leading `:` or parameters. The end of a timestamp without
+/// time is the day after, as DTEND is exclusive.
+///
+/// (defun org-icalendar-convert-timestamp (timestamp keyword &optional end tz)
+fn convert_timestamp(timestamp: &TimestampData, end: bool) -> String {
+    let t = timestamp;
+    let (year, month, day, hour, minute) = match end {
+        false => (
+            t.year_start,
+            t.month_start,
+            t.day_start,
+            t.hour_start,
+            t.minute_start,
+        ),
+        true => (
+            t.year_end,
+            t.month_end,
+            t.day_end,
+            t.hour_end.or(t.hour_start),
+            t.minute_end,
+        ),
+    };
+    match hour {
+        Some(hour) => format!(
+            ":{:04}{:02}{:02}T{:02}{:02}00",
+            year,
+            month,
+            day,
+            hour,
+            minute.unwrap_or(0)
+        ),
+        None => {
+            let days = days_from_civil(year as i64, month as i64, day as i64);
+            let (year, month, day) = civil_from_days(if end { days + 1 } else { days });
+            format!(";VALUE=DATE:{:04}{:02}{:02}", year, month, day)
+        }
+    }
+}
+
+/// RRULE of the repeater of `timestamp`, if any
+///
+/// (defun org-icalendar--rrule (unit value)
+fn rrule(timestamp: &TimestampData) -> Option<String> {
+    let frequency = match timestamp.repeater_unit.as_ref()? {
+        TimeUnit::Hour => "HOURLY",
+        TimeUnit::Day => "DAILY",
+        TimeUnit::Week => "WEEKLY",
+        TimeUnit::Month => "MONTHLY",
+        TimeUnit::Year => "YEARLY",
+    };
+    Some(format!(
+        "RRULE:FREQ={};INTERVAL={}\n",
+        frequency,
+        timestamp.repeater_value.unwrap_or(1)
+    ))
+}
+
+/// VALARM for the warning delay of `timestamp`, if any. Durations
+/// have no months nor years, they count as 30 and 365 days.
+///
+/// (defun org-icalendar--valarm (entry timestamp summary)
+fn valarm(timestamp: &TimestampData, summary: &str) -> Option<String> {
+    let value = timestamp.warning_value?;
+    let trigger = match timestamp.warning_unit.as_ref()? {
+        TimeUnit::Hour => format!("-PT{}H", value),
+        TimeUnit::Day => format!("-P{}D", value),
+        TimeUnit::Week => format!("-P{}W", value),
+        TimeUnit::Month => format!("-P{}D", value * 30),
+        TimeUnit::Year => format!("-P{}D", value * 365),
+    };
+    Some(format!(
+        "BEGIN:VALARM\nACTION:DISPLAY\nDESCRIPTION:{}\nTRIGGER:{}\nEND:VALARM\n",
+        summary, trigger
+    ))
+}
+
+/// Escapes `s` for a TEXT property value
+///
+/// (defun org-icalendar-cleanup-string (s)
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.trim().chars() {
+        match c {
+            '\\' | ',' | ';' => {
+                escaped.push('\\');
+                escaped.push(c)
+            }
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Folds lines longer than 75 octets and ends lines with CRLF, as
+/// RFC 5545 requires
+///
+/// (defun org-icalendar-fold-calendar (&optional pos)
+fn fold(s: &str) -> String {
+    let mut folded = String::with_capacity(s.len());
+    for line in s.lines() {
+        let mut length = 0;
+        for c in line.chars() {
+            if length + c.len_utf8() > 75 {
+                folded.push_str("\r\n ");
+                length = 1;
+            }
+            folded.push(c);
+            length += c.len_utf8();
+        }
+        folded.push_str("\r\n");
+    }
+    folded
+}
+
+/// Properties shared by the entries of a headline
+struct Entry {
+    uid: String,
+    summary: String,
+    description: String,
+    categories: String,
+    location: Option<String>,
+}
+
+impl Entry {
+    /// SUMMARY and the properties following it
+    fn properties(&self, prefix: &str) -> String {
+        let mut out = format!("SUMMARY:{}{}\n", prefix, self.summary);
+        if let Some(location) = &self.location {
+            out.push_str(&format!("LOCATION:{}\n", location));
+        }
+        if !self.description.is_empty() {
+            out.push_str(&format!("DESCRIPTION:{}\n", self.description));
+        }
+        if !self.categories.is_empty() {
+            out.push_str(&format!("CATEGORIES:{}\n", self.categories));
+        }
+        out
+    }
+}
+
+impl Icalendar {
+    /// Properties of the entries of headline `id`
+    fn entry(&self, info: &Info, id: NodeId, data: &HeadlineData) -> Entry {
+        let uid = info
+            .property(id, "ID")
+            .map_or_else(|| self.uid(info, id, data), |uid| uid.trim().to_string());
+        let description: String = info
+            .tree
+            .children(id)
+            .filter(|&c| matches!(info.tree[c].data, Syntax::Section))
+            .flat_map(|section| info.tree.children(section))
+            .filter(|&c| {
+                !matches!(
+                    info.tree[c].data,
+                    Syntax::Planning(_) | Syntax::PropertyDrawer | Syntax::Clock(_)
+                )
+            })
+            .map(|c| info.data(c))
+            .collect();
+        let categories: Vec<String> = info.tags(id).iter().map(|t| escape(t)).collect();
+        Entry {
+            uid,
+            summary: escape(&info.secondary(&data.title)),
+            description: escape(&description),
+            categories: categories.join(","),
+            location: info.property(id, "LOCATION").map(escape),
+        }
+    }
+
+    /// UID of headline `id` when it has no `ID` property: a hash of its
+    /// outline path, its title and its timestamps, so that it survives
+    /// changes elsewhere in the document. ox-icalendar rather stores a
+    /// new ID in the headline, see `org-icalendar-store-UID`.
+    fn uid(&self, info: &Info, id: NodeId, data: &HeadlineData) -> String {
+        let tree = info.tree;
+        let mut path: Vec<&str> = tree
+            .ancestors(id)
+            .filter_map(|a| match &tree[a].data {
+                Syntax::Headline(h) => Some(h.raw_value.as_ref()),
+                _ => None,
+            })
+            .collect();
+        path.reverse();
+        path.push(&data.raw_value);
+        path.extend(data.scheduled.iter().map(|t| t.raw_value.as_ref()));
+        path.extend(data.deadline.iter().map(|t| t.raw_value.as_ref()));
+        path.extend(
+            self.timestamps(info, id)
+                .into_iter()
+                .map(|t| t.raw_value.as_ref()),
+        );
+        // 64-bit FNV-1a, fields separated by a null byte
+        let hash = path.iter().fold(0xcbf2_9ce4_8422_2325u64, |hash, field| {
+            field.bytes().chain(Some(0)).fold(hash, |hash, b| {
+                (hash ^ b as u64).wrapping_mul(0x100_0000_01b3)
+            })
+        });
+        format!("{:016x}", hash)
+    }
+
+    /// Active timestamps of headline `id`, in its title and its
+    /// section, but not in its children
+    fn timestamps<'t>(&self, info: &Info<'t, '_>, id: NodeId) -> Vec<&'t TimestampData<'t>> {
+        let tree = info.tree;
+        let mut roots: Vec<NodeId> = tree[id].secondary_objects();
+        roots.extend(
+            tree.children(id)
+                .filter(|&c| matches!(tree[c].data, Syntax::Section)),
+        );
+        roots
+            .into_iter()
+            .flat_map(|root| tree.descendants(root))
+            .filter(|&node| !info.is_ignored(node))
+            .filter_map(|node| match &tree[node].data {
+                Syntax::Timestamp(t) => match t.type_s {
+                    TimestampType::Active | TimestampType::ActiveRange => Some(t.as_ref()),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// (defun org-icalendar--vevent (entry timestamp uid summary location description categories timezone class)
+    fn vevent(
+        &self,
+        entry: &Entry,
+        uid_prefix: &str,
+        summary_prefix: &str,
+        timestamp: &TimestampData,
+    ) -> String {
+        let mut out = format!(
+            "BEGIN:VEVENT\nDTSTAMP:{}\nUID:{}{}\nDTSTART{}\nDTEND{}\n",
+            self.dtstamp,
+            uid_prefix,
+            entry.uid,
+            convert_timestamp(timestamp, false),
+            convert_timestamp(timestamp, true)
+        );
+        out.extend(rrule(timestamp));
+        out.push_str(&entry.properties(summary_prefix));
+        out.extend(valarm(timestamp, &entry.summary));
+        out.push_str("END:VEVENT\n");
+        out
+    }
+
+    /// (defun org-icalendar--vtodo (entry uid summary location description categories timezone class)
+    fn vtodo(&self, entry: &Entry, data: &HeadlineData) -> String {
+        let mut out = format!(
+            "BEGIN:VTODO\nDTSTAMP:{}\nUID:TODO-{}\n",
+            self.dtstamp, entry.uid
+        );
+        // Unscheduled tasks start at their deadline, or now
+        // elisp: `org-icalendar-todo-unscheduled-start`
+        let start = match data.scheduled.as_ref().or(data.deadline.as_ref()) {
+            Some(start) => convert_timestamp(start, false),
+            None => format!(":{}", self.dtstamp),
+        };
+        out.push_str(&format!("DTSTART{}\n", start));
+        if let Some(deadline) = &data.deadline {
+            out.push_str(&format!("DUE{}\n", convert_timestamp(deadline, false)));
+        }
+        out.extend(
+            data.scheduled
+                .as_ref()
+                .or(data.deadline.as_ref())
+                .and_then(rrule),
+        );
+        out.push_str(&entry.properties(""));
+        // Priorities A, B and C are 1, 5 and 9, B being the default
+        let priority = match data.priority {
+            0 => b'B' as usize,
+            p => p.clamp(b'A' as usize, b'C' as usize),
+        };
+        out.push_str(&format!(
+            "SEQUENCE:1\nPRIORITY:{}\nSTATUS:NEEDS-ACTION\n",
+            1 + 4 * (priority - b'A' as usize)
+        ));
+        out.extend(
+            data.deadline
+                .as_ref()
+                .and_then(|d| valarm(d, &entry.summary)),
+        );
+        out.push_str("END:VTODO\n");
+        out
+    }
+}
+
+impl Backend for Icalendar {
+    fn name(&self) -> &str {
+        "icalendar"
+    }
+
+    fn parent(&self) -> Option<&dyn Backend> {
+        Some(&self.ascii)
+    }
+
+    fn inner_template(&self, info: &Info, contents: String) -> String {
+        fold(&contents)
+    }
+
+    /// (defun org-icalendar--vcalendar (name owner tz description contents)
+    fn template(&self, info: &Info, contents: String) -> String {
+        let options = info.options;
+        let owner = options
+            .author
+            .as_deref()
+            .map_or(String::new(), |author| format!("{}//", escape(author)));
+        let mut header = format!(
+            "BEGIN:VCALENDAR\nVERSION:2.0\nX-WR-CALNAME:{}\nPRODID:-//{}org-rs//EN\n",
+            escape(options.title.as_deref().unwrap_or("")),
+            owner
+        );
+        if let Some(timezone) = &self.timezone {
+            header.push_str(&format!("X-WR-TIMEZONE:{}\n", timezone));
+        }
+        header.push_str("CALSCALE:GREGORIAN\n");
+        format!("{}{}{}", fold(&header), contents, fold("END:VCALENDAR"))
+    }
+
+    fn post_blank(&self, info: &Info, id: NodeId) -> usize {
+        match info.tree[id].data {
+            Syntax::Headline(_) => 0,
+            _ => self.ascii.post_blank(info, id),
+        }
+    }
+
+    /// Entries of the headline, followed by the ones of its children
+    ///
+    /// (defun org-icalendar-entry (entry contents info)
+    fn headline(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &HeadlineData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let entry = self.entry(info, id, data);
+        let mut out = String::new();
+        let todo = data.todo_keyword.is_some();
+        if !todo {
+            if let Some(deadline) = &data.deadline {
+                out.push_str(&self.vevent(&entry, "DL-", "DL: ", deadline));
+            }
+            if let Some(scheduled) = &data.scheduled {
+                out.push_str(&self.vevent(&entry, "SC-", "S: ", scheduled));
+            }
+        }
+        for (n, timestamp) in self.timestamps(info, id).into_iter().enumerate() {
+            out.push_str(&self.vevent(&entry, &format!("TS{}-", n + 1), "", timestamp));
+        }
+        if todo && self.include_todo && data.todo_type == Some(TodoType::Todo) {
+            out.push_str(&self.vtodo(&entry, data));
+        }
+        out.push_str(&contents.unwrap_or_default());
+        match out.is_empty() {
+            true => None,
+            false => Some(out),
+        }
+    }
+
+    /// Sections are part of the description of their headline
+    fn section(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        None
+    }
+
+    fn inline_task(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &InlineTaskData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        None
+    }
+}
+
+mod test {
+    use super::Icalendar;
+    use crate::export::fixture::parse;
+    use crate::export::{export, ExportOptions};
+
+    #[test]
+    fn entries() {
+        let text = "* Weekly meeting :work:\n\
+                    SCHEDULED: <2024-03-01 Fri 10:00-11:30 +1w>\n\
+                    :PROPERTIES:\n\
+                    :ID: 5f1c-meeting\n\
+                    :END:\n\
+                    Agenda; minutes, notes\n\
+                    * TODO [#A] Send report\n\
+                    DEADLINE: <2024-03-08 Fri -2d>\n\
+                    * TODO Call back\n\
+                    * DONE Old task\n\
+                    DEADLINE: <2024-01-31 Wed>\n\
+                    * Trip\n\
+                    Leaving <2024-12-30 Mon>--<2025-01-01 Wed>\n";
+        let tree = parse(text);
+
+        let backend = Icalendar {
+            dtstamp: "20240229T080000Z".to_string(),
+            ..Icalendar::default()
+        };
+        let options = ExportOptions {
+            title: Some("Work".to_string()),
+            ..ExportOptions::default()
+        };
+        let expected = "BEGIN:VCALENDAR\n\
+                        VERSION:2.0\n\
+                        X-WR-CALNAME:Work\n\
+                        PRODID:-//org-rs//EN\n\
+                        CALSCALE:GREGORIAN\n\
+                        BEGIN:VEVENT\n\
+                        DTSTAMP:20240229T080000Z\n\
+                        UID:SC-5f1c-meeting\n\
+                        DTSTART:20240301T100000\n\
+                        DTEND:20240301T113000\n\
+                        RRULE:FREQ=WEEKLY;INTERVAL=1\n\
+                        SUMMARY:S: Weekly meeting\n\
+                        DESCRIPTION:Agenda\\; minutes\\, notes\n\
+                        CATEGORIES:work\n\
+                        END:VEVENT\n\
+                        BEGIN:VTODO\n\
+                        DTSTAMP:20240229T080000Z\n\
+                        UID:TODO-1f4c758b34a27062\n\
+                        DTSTART;VALUE=DATE:20240308\n\
+                        DUE;VALUE=DATE:20240308\n\
+                        SUMMARY:Send report\n\
+                        SEQUENCE:1\n\
+                        PRIORITY:1\n\
+                        STATUS:NEEDS-ACTION\n\
+                        BEGIN:VALARM\n\
+                        ACTION:DISPLAY\n\
+                        DESCRIPTION:Send report\n\
+                        TRIGGER:-P2D\n\
+                        END:VALARM\n\
+                        END:VTODO\n\
+                        BEGIN:VTODO\n\
+                        DTSTAMP:20240229T080000Z\n\
+                        UID:TODO-42bfde994ccd66ec\n\
+                        DTSTART:20240229T080000Z\n\
+                        SUMMARY:Call back\n\
+                        SEQUENCE:1\n\
+                        PRIORITY:5\n\
+                        STATUS:NEEDS-ACTION\n\
+                        END:VTODO\n\
+                        BEGIN:VEVENT\n\
+                        DTSTAMP:20240229T080000Z\n\
+                        UID:TS1-0d249b1c59f9ca1d\n\
+                        DTSTART;VALUE=DATE:20241230\n\
+                        DTEND;VALUE=DATE:20250102\n\
+                        SUMMARY:Trip\n\
+                        DESCRIPTION:Leaving <2024-12-30 Mon>--<2025-01-01 Wed>\n\
+                        END:VEVENT\n\
+                        END:VCALENDAR\n";
+        let calendar = export(&tree, &backend, &options);
+        assert_eq!(expected.replace('\n', "\r\n"), calendar);
+    }
+
+    /// UID lines of the calendar of `text`
+    fn uids(text: &str) -> Vec<String> {
+        export(
+            &parse(text),
+            &Icalendar::default(),
+            &ExportOptions::default(),
+        )
+        .lines()
+        .filter(|l| l.starts_with("UID:"))
+        .map(|l| l.to_string())
+        .collect()
+    }
+
+    /// UIDs without ID property don't depend on the position of the
+    /// headline in the document
+    #[test]
+    fn stable_uids() {
+        let call = "** Call\nDEADLINE: <2024-03-08 Fri>\n";
+        let uid = uids(&format!("* Project\n{}", call));
+        assert_eq!(1, uid.len());
+        assert_eq!(uid, uids(&format!("* Project\n** Inserted\n{}", call)));
+        assert_eq!(uid, uids(&format!("* Before\n* Project\n{}", call)));
+        assert_ne!(uid, uids(&format!("* Other project\n{}", call)));
+    }
+
+    #[test]
+    fn fold() {
+        let line = format!("DESCRIPTION:{}", "é".repeat(40));
+        let folded = super::fold(&line);
+        let lines: Vec<&str> = folded.split("\r\n").collect();
+        assert_eq!(
+            vec![&line[..75 - 1], &format!(" {}", &line[74..])[..], ""],
+            lines
+        );
+        assert!(lines.iter().all(|l| l.len() <= 75));
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(0, super::days_from_civil(1970, 1, 1));
+        assert_eq!(
+            (2024, 3, 1),
+            super::civil_from_days(super::days_from_civil(2024, 2, 29) + 1)
+        );
+        assert_eq!(
+            (2025, 1, 1),
+            super::civil_from_days(super::days_from_civil(2024, 12, 31) + 1)
+        );
+    }
+}
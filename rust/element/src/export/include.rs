@@ -0,0 +1,539 @@
+//    This file is part of org-rs.
+//
+//    org-rs is free software: you can redistribute it and/or modify
+//    it under the terms of the GNU General Public License as published by
+//    the Free Software Foundation, either version 3 of the License, or
+//    (at your option) any later version.
+//
+//    org-rs is distributed in the hope that it will be useful,
+//    but WITHOUT ANY WARRANTY; without even the implied warranty of
+//    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//    GNU General Public License for more details.
+//
+//    You should have received a copy of the GNU General Public License
+//    along with org-rs.  If not, see <https://www.gnu.org/licenses/>.
+
+//! `#+INCLUDE` and `#+SETUPFILE` expansion
+//!
+//! Port of `org-export-expand-include-keyword` and of the setup file
+//! handling of `org-export--get-inbuffer-options`, from ox.el
+//! https://code.orgmode.org/bzg/org-mode/src/master/lisp/ox.el
+//!
+//! Like in ox.el, includes are expanded in the text of the document,
+//! before it is parsed:
+//!
+//! ```text
+//! #+INCLUDE: "chapter.org" :minlevel 2
+//! #+INCLUDE: "main.rs" src rust :lines "5-10"
+//! #+INCLUDE: "notes.txt" example
+//! ```
+//!
+//! Files are read through a `Resolver`, relative to the directory of
+//! the file including them. Included org files are expanded in turn,
+//! a file including itself, directly or not, is an error.
+
+use crate::export::ExportOptions;
+use regex::Regex;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+lazy_static! {
+    /// `#+KEY: value` line
+    static ref REGEX_KEYWORD: Regex = Regex::new(r"^[ \t]*#\+(\S+?):[ \t]*(.*?)[ \t]*\r?\n?$").unwrap();
+
+    /// First or last line of a block, e.g. `#+BEGIN_SRC` or `#+END_SRC`
+    static ref REGEX_BLOCK: Regex = Regex::new(r"(?i)^[ \t]*#\+(BEGIN|END)_(\S+)").unwrap();
+
+    /// Lines to escape with a comma in blocks
+    /// elisp: `org-escape-code-in-string`
+    static ref REGEX_ESCAPE: Regex = Regex::new(r"(?m)^([ \t]*)(,*(?:\*|#\+))").unwrap();
+
+    /// Stars of a headline
+    static ref REGEX_HEADLINE: Regex = Regex::new(r"^(\*+)(?:[ \t]|\r?\n|$)").unwrap();
+}
+
+/// Reads included files and setup files
+pub trait Resolver {
+    /// Contents of the file at `path`. Relative paths are already
+    /// relative to the directory of the document.
+    fn read(&self, path: &Path) -> io::Result<String>;
+}
+
+/// Reads files from the local file system
+pub struct FileSystem;
+
+impl Resolver for FileSystem {
+    fn read(&self, path: &Path) -> io::Result<String> {
+        fs::read_to_string(path)
+    }
+}
+
+/// Error of `expand_includes` and `read_setupfiles`
+#[derive(Debug)]
+pub enum IncludeError {
+    /// A file could not be read
+    Read { path: PathBuf, error: io::Error },
+    /// Files including each other, from the first file of the cycle
+    /// back to it
+    Cycle(Vec<PathBuf>),
+}
+
+impl fmt::Display for IncludeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IncludeError::Read { path, error } => {
+                write!(f, "Cannot include file {}: {}", path.display(), error)
+            }
+            IncludeError::Cycle(paths) => {
+                let paths: Vec<_> = paths.iter().map(|p| p.display().to_string()).collect();
+                write!(f, "Recursive file inclusion: {}", paths.join(" -> "))
+            }
+        }
+    }
+}
+
+impl Error for IncludeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            IncludeError::Read { error, .. } => Some(error),
+            IncludeError::Cycle(_) => None,
+        }
+    }
+}
+
+/// Value of an `#+INCLUDE` keyword
+#[derive(Debug, PartialEq)]
+struct Include {
+    file: String,
+    /// First included line and the line ending the range, excluded,
+    /// counting from 1, from `:lines "5-10"`
+    lines: Option<(Option<usize>, Option<usize>)>,
+    /// Type of the block wrapping the contents, `src`, `example` or
+    /// `export`, and its argument, e.g. the language of the code
+    block: Option<(String, Option<String>)>,
+    /// Level of the top headlines of the contents, from `:minlevel 2`
+    minlevel: Option<usize>,
+}
+
+impl Include {
+    /// Parses the value of an `#+INCLUDE` keyword, None when there is
+    /// no file
+    ///
+    /// (defun org-export-parse-include-value (value &optional dir)
+    fn parse(value: &str) -> Option<Include> {
+        let mut tokens = tokens(value).into_iter().peekable();
+        let file = tokens.next().filter(|file| !file.is_empty())?;
+        let mut include = Include {
+            file,
+            lines: None,
+            block: None,
+            minlevel: None,
+        };
+        while let Some(token) = tokens.next() {
+            match token.to_ascii_lowercase().as_str() {
+                ":lines" => {
+                    include.lines = tokens.next().and_then(|range| {
+                        let (start, end) = range.split_once('-')?;
+                        Some((start.parse().ok(), end.parse().ok()))
+                    })
+                }
+                ":minlevel" => include.minlevel = tokens.next().and_then(|l| l.parse().ok()),
+                block @ ("src" | "example" | "export") => {
+                    let argument = tokens.next_if(|t| !t.starts_with(':'));
+                    include.block = Some((block.to_string(), argument));
+                }
+                _ => {}
+            }
+        }
+        Some(include)
+    }
+
+    /// Contents of the included file, wrapped in a block or expanded
+    /// and with shifted headlines
+    fn expand(
+        &self,
+        stack: &mut Vec<PathBuf>,
+        resolver: &dyn Resolver,
+    ) -> Result<String, IncludeError> {
+        let path = relative_path(stack, &self.file);
+        if self.block.is_none() {
+            check_cycle(stack, &path)?;
+        }
+        let contents = read(resolver, &path)?;
+        let mut contents: String = match self.lines {
+            Some((start, end)) => {
+                let start = start.unwrap_or(1).max(1);
+                let end = end.unwrap_or(usize::MAX);
+                contents
+                    .split_inclusive('\n')
+                    .skip(start - 1)
+                    .take(end.saturating_sub(start))
+                    .collect()
+            }
+            None => contents,
+        };
+        if !contents.is_empty() && !contents.ends_with('\n') {
+            contents.push('\n');
+        }
+        if let Some((block, argument)) = &self.block {
+            let block = block.to_ascii_uppercase();
+            let argument = argument
+                .as_ref()
+                .map(|a| format!(" {}", a))
+                .unwrap_or_default();
+            return Ok(format!(
+                "#+BEGIN_{}{}\n{}#+END_{}\n",
+                block,
+                argument,
+                REGEX_ESCAPE.replace_all(&contents, "$1,$2"),
+                block
+            ));
+        }
+        stack.push(path);
+        let expanded = expand(&contents, stack, resolver);
+        stack.pop();
+        let expanded = expanded?;
+        Ok(match self.minlevel {
+            Some(level) => shift_headlines(&expanded, level),
+            None => expanded,
+        })
+    }
+}
+
+/// Expands the `#+INCLUDE` keywords of `text`, the contents of the file
+/// at `path`, outside of blocks
+///
+/// (defun org-export-expand-include-keyword (&optional included dir footnotes)
+pub fn expand_includes(
+    text: &str,
+    path: &Path,
+    resolver: &dyn Resolver,
+) -> Result<String, IncludeError> {
+    expand(text, &mut vec![normalize(path)], resolver)
+}
+
+/// Applies the keywords of the `#+SETUPFILE` files of `text`, the
+/// contents of the file at `path`, to `options`. Setup files can have
+/// their own setup files.
+///
+/// Keywords of the document are not read: apply them afterwards, e.g.
+/// with `ExportOptions::read_keywords`, for them to take precedence.
+///
+/// (defun org-export--get-inbuffer-options (&optional backend)
+pub fn read_setupfiles(
+    options: &mut ExportOptions,
+    text: &str,
+    path: &Path,
+    resolver: &dyn Resolver,
+) -> Result<(), IncludeError> {
+    let mut keywords = vec![];
+    setup_keywords(
+        text,
+        false,
+        &mut vec![normalize(path)],
+        resolver,
+        &mut keywords,
+    )?;
+    options.apply_keywords(keywords.iter().map(|(k, v)| (k.as_str(), v.as_str())));
+    Ok(())
+}
+
+/// Expands includes of `text`, the contents of the last file of `stack`
+fn expand(
+    text: &str,
+    stack: &mut Vec<PathBuf>,
+    resolver: &dyn Resolver,
+) -> Result<String, IncludeError> {
+    let mut expanded = String::with_capacity(text.len());
+    for (line, in_block) in lines(text) {
+        let include = match REGEX_KEYWORD.captures(line) {
+            Some(c) if !in_block && c[1].eq_ignore_ascii_case("INCLUDE") => Include::parse(&c[2]),
+            _ => None,
+        };
+        match include {
+            Some(include) => expanded += &include.expand(stack, resolver)?,
+            None => expanded.push_str(line),
+        }
+    }
+    Ok(expanded)
+}
+
+/// Collects in `keywords` the keywords of the setup files of `text`,
+/// the contents of the last file of `stack`, and its own keywords when
+/// `own` is true
+fn setup_keywords(
+    text: &str,
+    own: bool,
+    stack: &mut Vec<PathBuf>,
+    resolver: &dyn Resolver,
+    keywords: &mut Vec<(String, String)>,
+) -> Result<(), IncludeError> {
+    for (line, in_block) in lines(text) {
+        let c = match REGEX_KEYWORD.captures(line) {
+            Some(c) if !in_block => c,
+            _ => continue,
+        };
+        if !c[1].eq_ignore_ascii_case("SETUPFILE") {
+            if own {
+                keywords.push((c[1].to_string(), c[2].to_string()));
+            }
+            continue;
+        }
+        let file = c[2].trim_matches('"');
+        if file.is_empty() {
+            continue;
+        }
+        let path = relative_path(stack, file);
+        check_cycle(stack, &path)?;
+        let contents = read(resolver, &path)?;
+        stack.push(path);
+        let result = setup_keywords(&contents, true, stack, resolver, keywords);
+        stack.pop();
+        result?;
+    }
+    Ok(())
+}
+
+/// Lines of `text`, with their line ending, and whether they are part of
+/// a block
+fn lines(text: &str) -> Vec<(&str, bool)> {
+    let mut block: Option<String> = None;
+    text.split_inclusive('\n')
+        .map(|line| {
+            let in_block = block.is_some();
+            if let Some(c) = REGEX_BLOCK.captures(line) {
+                let name = c[2].to_ascii_uppercase();
+                let begin = c[1].eq_ignore_ascii_case("BEGIN");
+                match &block {
+                    None if begin => block = Some(name),
+                    Some(b) if !begin && *b == name => block = None,
+                    _ => {}
+                }
+            }
+            (line, in_block || block.is_some())
+        })
+        .collect()
+}
+
+/// Changes the level of the headlines of `text`, so that the top ones
+/// are at `level`
+fn shift_headlines(text: &str, level: usize) -> String {
+    let lines = lines(text);
+    let stars = |line: &str| REGEX_HEADLINE.captures(line).map(|c| c[1].len());
+    let min = match lines
+        .iter()
+        .filter(|(_, in_block)| !in_block)
+        .filter_map(|(line, _)| stars(line))
+        .min()
+    {
+        Some(min) => min,
+        None => return text.to_string(),
+    };
+    lines
+        .into_iter()
+        .map(|(line, in_block)| match stars(line) {
+            Some(n) if !in_block => {
+                let shifted = (n + level).saturating_sub(min).max(1);
+                format!("{}{}", "*".repeat(shifted), &line[n..])
+            }
+            _ => line.to_string(),
+        })
+        .collect()
+}
+
+/// Words of `value`, double quoted strings being single words
+fn tokens(value: &str) -> Vec<String> {
+    let mut tokens = vec![];
+    let mut rest = value.trim_start();
+    while !rest.is_empty() {
+        let (token, after) = match rest.strip_prefix('"') {
+            Some(quoted) => quoted.split_once('"').unwrap_or((quoted, "")),
+            None => rest.split_once(char::is_whitespace).unwrap_or((rest, "")),
+        };
+        tokens.push(token.to_string());
+        rest = after.trim_start();
+    }
+    tokens
+}
+
+/// Path of `file`, relative to the directory of the last file of `stack`
+fn relative_path(stack: &[PathBuf], file: &str) -> PathBuf {
+    let dir = stack
+        .last()
+        .and_then(|p| p.parent())
+        .unwrap_or(Path::new(""));
+    normalize(&dir.join(file))
+}
+
+/// `path` without `.` components and with `..` components applied, so
+/// that cycles are found whatever the path of the files
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if normalized.file_name().is_some() => {
+                normalized.pop();
+            }
+            c => normalized.push(c),
+        }
+    }
+    normalized
+}
+
+fn check_cycle(stack: &[PathBuf], path: &Path) -> Result<(), IncludeError> {
+    match stack.iter().position(|p| p == path) {
+        Some(start) => {
+            let mut cycle = stack[start..].to_vec();
+            cycle.push(path.to_path_buf());
+            Err(IncludeError::Cycle(cycle))
+        }
+        None => Ok(()),
+    }
+}
+
+fn read(resolver: &dyn Resolver, path: &Path) -> Result<String, IncludeError> {
+    resolver.read(path).map_err(|error| IncludeError::Read {
+        path: path.to_path_buf(),
+        error,
+    })
+}
+
+mod test {
+    use super::{expand_includes, read_setupfiles, Include, IncludeError, Resolver};
+    use crate::export::ExportOptions;
+    use std::collections::HashMap;
+    use std::io;
+    use std::path::{Path, PathBuf};
+
+    /// Files in memory
+    struct Files(HashMap<PathBuf, &'static str>);
+
+    impl Files {
+        fn new(files: &[(&str, &'static str)]) -> Files {
+            Files(
+                files
+                    .iter()
+                    .map(|&(path, contents)| (PathBuf::from(path), contents))
+                    .collect(),
+            )
+        }
+    }
+
+    impl Resolver for Files {
+        fn read(&self, path: &Path) -> io::Result<String> {
+            match self.0.get(path) {
+                Some(contents) => Ok(contents.to_string()),
+                None => Err(io::Error::from(io::ErrorKind::NotFound)),
+            }
+        }
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            Some(Include {
+                file: "my file.rs".to_string(),
+                lines: Some((Some(5), None)),
+                block: Some(("src".to_string(), Some("rust".to_string()))),
+                minlevel: None,
+            }),
+            Include::parse(r#""my file.rs" src rust :lines "5-""#)
+        );
+        assert_eq!(
+            Some(Include {
+                file: "a.org".to_string(),
+                lines: None,
+                block: Some(("example".to_string(), None)),
+                minlevel: Some(2),
+            }),
+            Include::parse("a.org example :minlevel 2")
+        );
+        assert_eq!(None, Include::parse(""));
+    }
+
+    #[test]
+    fn includes() {
+        let files = Files::new(&[
+            (
+                "doc/chapters/one.org",
+                "* One\n** Sub\n#+INCLUDE: \"../code.rs\" src rust :lines \"2-3\"\n\
+                 #+INCLUDE: \"../code.rs\" example :lines \"3-4\"\n",
+            ),
+            (
+                "doc/code.rs",
+                "fn main() {\n    // * stars\n    #+ text\n}\n",
+            ),
+            ("doc/notes.txt", "Notes"),
+        ]);
+        let text = "* Book\n#+INCLUDE: \"chapters/one.org\" :minlevel 2\n\
+                    #+BEGIN_EXAMPLE\n#+INCLUDE: \"notes.txt\"\n#+END_EXAMPLE\n\
+                    #+include: notes.txt example\n";
+        assert_eq!(
+            Ok(
+                "* Book\n** One\n*** Sub\n#+BEGIN_SRC rust\n    // * stars\n#+END_SRC\n\
+                #+BEGIN_EXAMPLE\n    ,#+ text\n#+END_EXAMPLE\n\
+                #+BEGIN_EXAMPLE\n#+INCLUDE: \"notes.txt\"\n#+END_EXAMPLE\n\
+                #+BEGIN_EXAMPLE\nNotes\n#+END_EXAMPLE\n"
+                    .to_string()
+            ),
+            expand_includes(text, Path::new("doc/book.org"), &files).map_err(|e| e.to_string())
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let files = Files::new(&[
+            ("a.org", "#+INCLUDE: \"dir/b.org\"\n"),
+            ("dir/b.org", "#+INCLUDE: \"./../a.org\"\n"),
+        ]);
+        let error = expand_includes("#+INCLUDE: a.org\n", Path::new("main.org"), &files);
+        match error {
+            Err(IncludeError::Cycle(paths)) => assert_eq!(
+                vec![
+                    Path::new("a.org"),
+                    Path::new("dir/b.org"),
+                    Path::new("a.org")
+                ],
+                paths
+            ),
+            _ => panic!("{:?}", error),
+        }
+        let error = expand_includes("#+INCLUDE: missing.org\n", Path::new("main.org"), &files);
+        assert_eq!(
+            "Cannot include file missing.org: entity not found",
+            error.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn setupfiles() {
+        let files = Files::new(&[
+            (
+                "setup/theme.org",
+                "#+TITLE: Theme\n#+OPTIONS: toc:nil\n#+SETUPFILE: tags.org\n",
+            ),
+            ("setup/tags.org", "#+EXCLUDE_TAGS: draft\n"),
+        ]);
+        let text = "#+SETUPFILE: \"setup/theme.org\"\n* Text\n";
+        let mut options = ExportOptions::default();
+        read_setupfiles(&mut options, text, Path::new("doc.org"), &files).unwrap();
+        assert_eq!(Some("Theme"), options.title.as_deref());
+        assert_eq!(None, options.with_toc);
+        assert_eq!(vec!["draft"], options.exclude_tags);
+
+        let files = Files::new(&[("setup.org", "#+SETUPFILE: doc.org\n")]);
+        let error = read_setupfiles(&mut options, text, Path::new("doc.org"), &files);
+        assert!(matches!(error, Err(IncludeError::Read { .. })));
+        let error = read_setupfiles(
+            &mut options,
+            "#+SETUPFILE: setup.org",
+            Path::new("doc.org"),
+            &files,
+        );
+        assert!(matches!(error, Err(IncludeError::Cycle(_))));
+    }
+}
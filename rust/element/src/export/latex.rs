@@ -0,0 +1,1102 @@
+//    This file is part of org-rs.
+//
+//    org-rs is free software: you can redistribute it and/or modify
+//    it under the terms of the GNU General Public License as published by
+//    the Free Software Foundation, either version 3 of the License, or
+//    (at your option) any later version.
+//
+//    org-rs is distributed in the hope that it will be useful,
+//    but WITHOUT ANY WARRANTY; without even the implied warranty of
+//    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//    GNU General Public License for more details.
+//
+//    You should have received a copy of the GNU General Public License
+//    along with org-rs.  If not, see <https://www.gnu.org/licenses/>.
+
+//! LaTeX backend
+//!
+//! Port of ox-latex.el
+//! https://code.orgmode.org/bzg/org-mode/src/master/lisp/ox-latex.el
+//!
+//! The document class comes from `#+LATEX_CLASS`, looked up in
+//! `Latex::classes`, with options from `#+LATEX_CLASS_OPTIONS`. Lines
+//! of `#+LATEX_HEADER` keywords go to the preamble. LaTeX environments
+//! and fragments are exported verbatim. Source blocks use the
+//! `verbatim` environment, as ox-latex does without listings.
+
+use crate::data::*;
+use crate::export::html::{is_image, link_href};
+use crate::export::{Alignment, Backend, Info};
+
+/// LaTeX class, see `org-latex-classes`
+#[derive(Debug, Clone)]
+pub struct LatexClass {
+    /// Name used in `#+LATEX_CLASS`
+    pub name: String,
+    /// First line of the preamble, e.g. `\documentclass[11pt]{article}`
+    pub header: String,
+    /// Sectioning command of each headline level, without backslash,
+    /// e.g. `section` for top level headlines of an article. Starred
+    /// versions are used for unnumbered headlines.
+    pub sections: Vec<String>,
+}
+
+impl LatexClass {
+    pub fn new(name: &str, header: &str, sections: &[&str]) -> Self {
+        LatexClass {
+            name: name.to_string(),
+            header: header.to_string(),
+            sections: sections.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+/// LaTeX backend, see module documentation
+#[derive(Debug, Clone)]
+pub struct Latex {
+    /// elisp: `org-latex-classes`
+    pub classes: Vec<LatexClass>,
+    /// Class used without `#+LATEX_CLASS`
+    /// elisp: `org-latex-default-class`
+    pub default_class: String,
+    /// Packages loaded before `#+LATEX_HEADER` lines, as
+    /// (options, package) pairs
+    /// elisp: `org-latex-default-packages-alist`
+    pub packages: Vec<(String, String)>,
+}
+
+impl Default for Latex {
+    fn default() -> Self {
+        let subsections = ["subsection", "subsubsection", "paragraph", "subparagraph"];
+        let article: Vec<&str> = ["section"].iter().chain(&subsections).cloned().collect();
+        let report: Vec<&str> =
+            ["part", "chapter", "section", "subsection", "subsubsection"].to_vec();
+        let packages = [
+            ("utf8", "inputenc"),
+            ("T1", "fontenc"),
+            ("", "graphicx"),
+            ("", "longtable"),
+            ("", "wrapfig"),
+            ("", "rotating"),
+            ("normalem", "ulem"),
+            ("", "amsmath"),
+            ("", "amssymb"),
+            ("", "capt-of"),
+            ("", "hyperref"),
+        ];
+        Latex {
+            classes: vec![
+                LatexClass::new("article", "\\documentclass[11pt]{article}", &article),
+                LatexClass::new("report", "\\documentclass[11pt]{report}", &report),
+                LatexClass::new("book", "\\documentclass[11pt]{book}", &report),
+            ],
+            default_class: "article".to_string(),
+            packages: packages
+                .iter()
+                .map(|(o, p)| (o.to_string(), p.to_string()))
+                .collect(),
+        }
+    }
+}
+
+/// Protects characters with a special meaning in LaTeX
+///
+/// (defun org-latex--protect-text (text)
+pub fn protect_text(text: &str) -> String {
+    let mut protected = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => protected.push_str("\\textbackslash{}"),
+            '~' => protected.push_str("\\textasciitilde{}"),
+            '^' => protected.push_str("\\^{}"),
+            '%' | '$' | '#' | '&' | '{' | '}' | '_' => {
+                protected.push('\\');
+                protected.push(c)
+            }
+            c => protected.push(c),
+        }
+    }
+    protected
+}
+
+/// `#+ATTR_LATEX` attribute `key` of element `id`
+fn attribute(info: &Info, id: NodeId, key: &str) -> Option<String> {
+    info.tree[id]
+        .affiliated
+        .as_ref()?
+        .attr("latex")
+        .into_iter()
+        .rev()
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v)
+}
+
+/// Label of element `id`, as used by `\label` and `\ref`
+///
+/// (defun org-latex--label (datum info &optional force full)
+fn label(info: &Info, id: NodeId) -> String {
+    let prefix = match &info.tree[id].data {
+        Syntax::Headline(_) => match info.property(id, "CUSTOM_ID") {
+            Some(custom_id) => return custom_id.to_string(),
+            None => "sec:",
+        },
+        Syntax::Table(_) => "tab:",
+        Syntax::SrcBlock(_) => "code:",
+        Syntax::Paragraph => "fig:",
+        Syntax::LatexEnvironment(_) => "eq:",
+        _ => "",
+    };
+    format!("{}{}", prefix, info.reference(id))
+}
+
+/// `\caption{...}\label{...}` of element `id`, empty when it has
+/// neither a caption nor a name
+///
+/// (defun org-latex--caption/label-string (element info)
+fn caption_label(info: &Info, id: NodeId) -> String {
+    let affiliated = match &info.tree[id].affiliated {
+        Some(affiliated) => affiliated,
+        None => return String::new(),
+    };
+    let label = match affiliated.name() {
+        Some(_) => format!("\\label{{{}}}", label(info, id)),
+        None => String::new(),
+    };
+    match affiliated.get("CAPTION") {
+        Some(caption) => format!("\\caption{{{}}}{}\n", info.secondary(&caption.value), label),
+        None if label.is_empty() => String::new(),
+        None => label + "\n",
+    }
+}
+
+/// Returns `value` in a `\verb` command, with a delimiter that does
+/// not appear in it
+fn verb(value: &str) -> String {
+    let delimiter = "|!@=+/~:;-"
+        .chars()
+        .find(|&c| !value.contains(c))
+        .unwrap_or('|');
+    format!("\\verb{d}{}{d}", value, d = delimiter)
+}
+
+/// Returns true if `id` and `other` are both headlines
+fn both_headlines(info: &Info, id: NodeId, other: Option<NodeId>) -> bool {
+    matches!(info.tree[id].data, Syntax::Headline(_))
+        && other.map_or(false, |o| matches!(info.tree[o].data, Syntax::Headline(_)))
+}
+
+impl Latex {
+    /// Class of the document, from `#+LATEX_CLASS`
+    fn class(&self, info: &Info) -> Option<&LatexClass> {
+        let name = info
+            .keywords("LATEX_CLASS")
+            .last()
+            .map(|n| n.trim().to_string())
+            .unwrap_or_else(|| self.default_class.clone());
+        self.classes.iter().find(|c| c.name == name)
+    }
+
+    /// (defun org-latex-format-headline-default-function (todo todo-type priority text tags info)
+    fn format_headline(&self, info: &Info, id: NodeId, h: &HeadlineData) -> String {
+        let mut out = String::new();
+        if let (Some(todo), true) = (&h.todo_keyword, info.options.with_todo_keywords) {
+            out.push_str(&format!("\\textbf{{{}}} ", protect_text(todo)));
+        }
+        if h.priority > 0 && info.options.with_priority {
+            if let Some(p) = std::char::from_u32(h.priority as u32) {
+                out.push_str(&format!("\\framebox{{\\#{}}} ", p));
+            }
+        }
+        out.push_str(&info.secondary(&h.title));
+        let tags = info.tags(id);
+        if !tags.is_empty() {
+            let tags: Vec<String> = tags.iter().map(|t| protect_text(t)).collect();
+            out.push_str(&format!("\\hfill{{}}\\textsc{{{}}}", tags.join(":")));
+        }
+        out
+    }
+
+    /// Replaces the options of the `\documentclass` line of `header`
+    /// with `options`, e.g. `[a4paper]`
+    fn class_options(header: &str, options: &str) -> String {
+        let start = match header.find("\\documentclass") {
+            Some(start) => start + "\\documentclass".len(),
+            None => return header.to_string(),
+        };
+        let rest = &header[start..];
+        let rest = match rest.strip_prefix('[') {
+            Some(bracketed) => bracketed
+                .find(']')
+                .map_or(rest, |end| &bracketed[end + 1..]),
+            None => rest,
+        };
+        format!("{}{}{}", &header[..start], options, rest)
+    }
+}
+
+impl Backend for Latex {
+    fn name(&self) -> &str {
+        "latex"
+    }
+
+    /// (defun org-latex-plain-text (text info)
+    fn plain_text(&self, info: &Info, text: &str) -> String {
+        protect_text(text)
+    }
+
+    /// (defun org-latex-template (contents info)
+    fn template(&self, info: &Info, contents: String) -> String {
+        let options = info.options;
+        let mut out = String::new();
+        let header = self
+            .class(info)
+            .map_or("\\documentclass{article}", |c| c.header.as_str());
+        match info.keywords("LATEX_CLASS_OPTIONS").last() {
+            Some(class_options) => {
+                out.push_str(&Latex::class_options(header, class_options.trim()))
+            }
+            None => out.push_str(header),
+        }
+        out.push('\n');
+        for (package_options, package) in &self.packages {
+            match package_options.as_str() {
+                "" => out.push_str(&format!("\\usepackage{{{}}}\n", package)),
+                o => out.push_str(&format!("\\usepackage[{}]{{{}}}\n", o, package)),
+            }
+        }
+        for line in info.keywords("LATEX_HEADER") {
+            out.push_str(line);
+            out.push('\n');
+        }
+        let author = options
+            .author
+            .as_deref()
+            .map_or(String::new(), protect_text);
+        out.push_str(&format!("\\author{{{}}}\n", author));
+        let date = options
+            .date
+            .as_deref()
+            .map_or("\\today".to_string(), protect_text);
+        out.push_str(&format!("\\date{{{}}}\n", date));
+        let title = options.title.as_deref().map_or(String::new(), protect_text);
+        out.push_str(&format!("\\title{{{}}}\n", title));
+        out.push_str(&format!(
+            "\\hypersetup{{\n pdfauthor={{{}}},\n pdftitle={{{}}},\n pdflang={{{}}}}}\n",
+            author, title, options.language
+        ));
+        out.push_str("\\begin{document}\n\n");
+        if options.title.is_some() {
+            out.push_str("\\maketitle\n");
+        }
+        if let Some(depth) = options.with_toc {
+            out.push_str(&format!(
+                "\\setcounter{{tocdepth}}{{{}}}\n\\tableofcontents\n\n",
+                depth
+            ));
+        }
+        out.push_str(&contents);
+        out.push_str("\\end{document}\n");
+        out
+    }
+
+    /// (defun org-latex-headline (headline contents info)
+    fn headline(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &HeadlineData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        if data.footnote_section_p {
+            return None;
+        }
+        let contents = contents.unwrap_or_default();
+        let title = self.format_headline(info, id, data);
+        let level = info.relative_level(id);
+        let section = self
+            .class(info)
+            .and_then(|c| c.sections.get(level - 1))
+            .filter(|_| !info.low_level_p(id));
+        let section = match section {
+            Some(section) => section,
+            None => {
+                // Low level headlines are items of a list
+                let node = &info.tree[id];
+                let env = if info.numbered_headline_p(id) {
+                    "enumerate"
+                } else {
+                    "itemize"
+                };
+                let begin = match both_headlines(info, id, node.prev_sibling()) {
+                    true => String::new(),
+                    false => format!("\\begin{{{}}}\n", env),
+                };
+                let end = match both_headlines(info, id, node.next_sibling()) {
+                    true => String::new(),
+                    false => format!("\\end{{{}}}", env),
+                };
+                return Some(format!(
+                    "{}\\item {}\\label{{{}}}\n{}{}",
+                    begin,
+                    title,
+                    label(info, id),
+                    contents,
+                    end
+                ));
+            }
+        };
+        let star = if info.numbered_headline_p(id) {
+            ""
+        } else {
+            "*"
+        };
+        Some(format!(
+            "\\{}{}{{{}}}\n\\label{{{}}}\n{}",
+            section,
+            star,
+            title,
+            label(info, id),
+            contents
+        ))
+    }
+
+    /// (defun org-latex-section (_section contents _info)
+    fn section(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        contents
+    }
+
+    /// (defun org-latex-paragraph (_paragraph contents _info)
+    fn paragraph(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        contents
+    }
+
+    /// (defun org-latex-plain-list (plain-list contents info)
+    fn plain_list(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &PlainListData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let env = attribute(info, id, "environment").unwrap_or_else(|| {
+            match data.type_s {
+                ListKind::Ordered => "enumerate",
+                ListKind::Unordered => "itemize",
+                ListKind::Descriptive => "description",
+            }
+            .to_string()
+        });
+        let options = attribute(info, id, "options").unwrap_or_default();
+        Some(format!(
+            "\\begin{{{env}}}{}\n{}\\end{{{env}}}",
+            options,
+            contents.unwrap_or_default(),
+            env = env
+        ))
+    }
+
+    /// (defun org-latex-item (item contents info)
+    fn item(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &ItemData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let counter = if data.counter > 0 {
+            // Items are numbered up to 4 levels deep, see
+            // `org-latex-item`
+            let depth = info
+                .tree
+                .ancestors(id)
+                .filter(|&a| match &info.tree[a].data {
+                    Syntax::PlainList(l) => l.type_s == ListKind::Ordered,
+                    _ => false,
+                })
+                .count();
+            ["i", "ii", "iii", "iv"]
+                .get(depth.saturating_sub(1))
+                .map_or(String::new(), |n| {
+                    format!("\\setcounter{{enum{}}}{{{}}}\n", n, data.counter - 1)
+                })
+        } else {
+            String::new()
+        };
+        let checkbox = match data.checkbox {
+            Some(CheckBox::On) => "$\\boxtimes$ ",
+            Some(CheckBox::Off) => "$\\square$ ",
+            Some(CheckBox::Trans) => "$\\boxminus$ ",
+            None => "",
+        };
+        let descriptive = info.tree[id].parent().map_or(false, |list| {
+            matches!(&info.tree[list].data, Syntax::PlainList(l) if l.type_s == ListKind::Descriptive)
+        });
+        let tag = match (&data.tag, descriptive) {
+            (Some(tag), true) => format!("[{}{}] ", checkbox, info.secondary(tag)),
+            _ => format!(" {}", checkbox),
+        };
+        let contents = contents.unwrap_or_default();
+        Some(format!("{}\\item{}{}", counter, tag, contents.trim()))
+    }
+
+    /// (defun org-latex-table (table contents info)
+    fn table(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &TableData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let env = attribute(info, id, "environment").unwrap_or_else(|| "tabular".to_string());
+        let align = attribute(info, id, "align").unwrap_or_else(|| {
+            info.tree
+                .children(id)
+                .find(|&row| info.table_row_group(row).is_some())
+                .map_or(String::new(), |row| {
+                    info.tree
+                        .children(row)
+                        .map(|cell| match info.table_cell_alignment(cell) {
+                            Alignment::Left => 'l',
+                            Alignment::Right => 'r',
+                            Alignment::Center => 'c',
+                        })
+                        .collect()
+                })
+        });
+        let tabular = format!(
+            "\\begin{{{env}}}{{{}}}\n{}\\end{{{env}}}",
+            align,
+            contents.unwrap_or_default(),
+            env = env
+        );
+        let caption = caption_label(info, id);
+        let float = attribute(info, id, "float");
+        let centered = attribute(info, id, "center").map_or(true, |c| c != "nil");
+        if caption.is_empty() && float.is_none() || float.as_deref() == Some("nil") {
+            return Some(match centered {
+                true => format!("\\begin{{center}}\n{}\n\\end{{center}}", tabular),
+                false => tabular,
+            });
+        }
+        let float = match float.as_deref() {
+            Some("multicolumn") => "table*",
+            Some("sideways") => "sidewaystable",
+            _ => "table",
+        };
+        let placement = attribute(info, id, "placement").unwrap_or_else(|| "[htbp]".to_string());
+        Some(format!(
+            "\\begin{{{float}}}{}\n{}{}{}\n\\end{{{float}}}",
+            placement,
+            if centered { "\\centering\n" } else { "" },
+            caption,
+            tabular,
+            float = float
+        ))
+    }
+
+    /// (defun org-latex-table-row (table-row contents info)
+    fn table_row(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &TableRowData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        if info.table_row_is_special_p(id) {
+            return None;
+        }
+        if info.table_row_is_rule_p(id) {
+            return Some("\\hline".to_string());
+        }
+        Some(format!("{} \\\\", contents.unwrap_or_default()))
+    }
+
+    /// (defun org-latex-table-cell (table-cell contents info)
+    fn table_cell(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        let contents = contents.unwrap_or_default();
+        let separator = match info.tree[id].next_sibling() {
+            Some(_) => " & ",
+            None => "",
+        };
+        Some(format!("{}{}", contents.trim(), separator))
+    }
+
+    /// (defun org-latex-src-block (src-block _contents info)
+    fn src_block(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &SrcBlockData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let code = format!(
+            "\\begin{{verbatim}}\n{}\\end{{verbatim}}",
+            crate::export::normalize_string(data.value.to_string())
+        );
+        let caption = caption_label(info, id);
+        if caption.is_empty() {
+            return Some(code);
+        }
+        Some(format!(
+            "\\begin{{figure}}[htbp]\n{}\n{}\\end{{figure}}",
+            code, caption
+        ))
+    }
+
+    /// (defun org-latex-example-block (example-block _contents info)
+    fn example_block(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &ExampleBlockData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(format!(
+            "\\begin{{verbatim}}\n{}\\end{{verbatim}}",
+            crate::export::normalize_string(data.value.to_string())
+        ))
+    }
+
+    /// (defun org-latex-fixed-width (fixed-width _contents info)
+    fn fixed_width(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &FixedWidthData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(format!(
+            "\\begin{{verbatim}}\n{}\\end{{verbatim}}",
+            crate::export::normalize_string(data.value.to_string())
+        ))
+    }
+
+    /// (defun org-latex-export-block (export-block _contents _info)
+    fn export_block(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &ExportBlockData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        match data.type_s.to_uppercase().as_str() {
+            "LATEX" | "TEX" => Some(data.value.to_string()),
+            _ => None,
+        }
+    }
+
+    /// (defun org-latex-export-snippet (export-snippet _contents _info)
+    fn export_snippet(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &ExportSnippetData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        match data.back_end.to_lowercase().as_str() {
+            "latex" | "tex" => Some(data.value.to_string()),
+            _ => None,
+        }
+    }
+
+    /// `#+LATEX_HEADER`, `#+LATEX_CLASS` and `#+LATEX_CLASS_OPTIONS`
+    /// are used by the template and not exported.
+    ///
+    /// (defun org-latex-keyword (keyword _contents info)
+    fn keyword(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &KeywordData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        match data.key.to_uppercase().as_str() {
+            "LATEX" => Some(data.value.to_string()),
+            "TOC" if data.value.trim() == "headlines" => Some("\\tableofcontents".to_string()),
+            _ => None,
+        }
+    }
+
+    /// (defun org-latex-latex-environment (latex-environment _contents info)
+    fn latex_environment(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &LatexEnvironmentData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let value = data.value.trim_end();
+        let named = info.tree[id]
+            .affiliated
+            .as_ref()
+            .map_or(false, |a| a.name().is_some());
+        if !named {
+            return Some(value.to_string());
+        }
+        // The label goes right after `\begin{...}`
+        let (begin, rest) = value.split_at(value.find('\n').unwrap_or(value.len()));
+        Some(format!("{}\n\\label{{{}}}{}", begin, label(info, id), rest))
+    }
+
+    /// (defun org-latex-latex-fragment (latex-fragment _contents _info)
+    fn latex_fragment(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &LatexFragmentData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(data.value.to_string())
+    }
+
+    /// (defun org-latex-entity (entity _contents _info)
+    fn entity(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &EntityData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(match data.latex_math_p {
+            true => format!("${}$", data.latex),
+            false => data.latex.to_string(),
+        })
+    }
+
+    /// (defun org-latex-bold (_bold contents info)
+    fn bold(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        Some(format!("\\textbf{{{}}}", contents.unwrap_or_default()))
+    }
+
+    /// (defun org-latex-italic (_italic contents info)
+    fn italic(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        Some(format!("\\emph{{{}}}", contents.unwrap_or_default()))
+    }
+
+    /// (defun org-latex-underline (_underline contents info)
+    fn underline(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        Some(format!("\\uline{{{}}}", contents.unwrap_or_default()))
+    }
+
+    /// (defun org-latex-strike-through (_strike-through contents info)
+    fn strike_through(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        Some(format!("\\sout{{{}}}", contents.unwrap_or_default()))
+    }
+
+    /// (defun org-latex-code (code _contents info)
+    fn code(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &CodeData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(verb(&data.value))
+    }
+
+    /// (defun org-latex-verbatim (verbatim _contents info)
+    fn verbatim(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &VerbatimData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(format!("\\texttt{{{}}}", protect_text(&data.value)))
+    }
+
+    /// (defun org-latex-inline-src-block (inline-src-block _contents info)
+    fn inline_src_block(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &InlineSrcBlockData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(verb(&data.value))
+    }
+
+    /// (defun org-latex-line-break (_line-break _contents _info)
+    fn line_break(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        Some("\\\\\n".to_string())
+    }
+
+    /// (defun org-latex-horizontal-rule (horizontal-rule _contents info)
+    fn horizontal_rule(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        Some("\\noindent\\rule{\\textwidth}{0.5pt}".to_string())
+    }
+
+    /// (defun org-latex-quote-block (quote-block contents info)
+    fn quote_block(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        Some(format!(
+            "\\begin{{quote}}\n{}\\end{{quote}}",
+            contents.unwrap_or_default()
+        ))
+    }
+
+    /// (defun org-latex-center-block (center-block contents info)
+    fn center_block(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        Some(format!(
+            "\\begin{{center}}\n{}\\end{{center}}",
+            contents.unwrap_or_default()
+        ))
+    }
+
+    /// (defun org-latex-special-block (special-block contents info)
+    fn special_block(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &SpecialBlockData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let options = attribute(info, id, "options").unwrap_or_default();
+        Some(format!(
+            "\\begin{{{ty}}}{}\n{}{}\\end{{{ty}}}",
+            options,
+            caption_label(info, id),
+            contents.unwrap_or_default(),
+            ty = data.type_s
+        ))
+    }
+
+    /// (defun org-latex-verse-block (verse-block contents info)
+    fn verse_block(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        let contents = contents.unwrap_or_default();
+        let lines: Vec<&str> = contents.trim_end_matches('\n').split('\n').collect();
+        Some(format!(
+            "\\begin{{verse}}\n{}\\\\\n\\end{{verse}}",
+            lines.join("\\\\\n")
+        ))
+    }
+
+    /// (defun org-latex-format-inlinetask-default-function (todo todo-type priority title tags contents info)
+    fn inline_task(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &InlineTaskData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(format!(
+            "\\begin{{center}}\n\\fbox{{\n\\begin{{minipage}}[c]{{.6\\textwidth}}\n{}\n\
+             \\rule[.8em]{{\\textwidth}}{{2pt}}\n{}\\end{{minipage}}\n}}\n\\end{{center}}",
+            info.secondary(&data.title),
+            contents.unwrap_or_default()
+        ))
+    }
+
+    /// (defun org-latex-planning (planning _contents info)
+    fn planning(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &PlanningData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let stamps: Vec<String> = [
+            ("CLOSED:", &data.closed),
+            ("DEADLINE:", &data.deadline),
+            ("SCHEDULED:", &data.scheduled),
+        ]
+        .iter()
+        .filter_map(|(keyword, timestamp)| {
+            let timestamp = timestamp.as_ref()?;
+            Some(format!(
+                "\\textbf{{{}}} \\emph{{{}}}",
+                keyword,
+                protect_text(&timestamp.raw_value)
+            ))
+        })
+        .collect();
+        if stamps.is_empty() {
+            return None;
+        }
+        Some(format!("\\noindent{}\\\\", stamps.join("\\hspace*{1em}")))
+    }
+
+    /// (defun org-latex-property-drawer (_property-drawer contents _info)
+    fn property_drawer(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        None
+    }
+
+    /// (defun org-latex-statistics-cookie (statistics-cookie _contents _info)
+    fn statistics_cookie(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &StatisticsCookieData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(protect_text(&data.value))
+    }
+
+    /// (defun org-latex-subscript (_smth contents _info)
+    fn subscript(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &SubscriptData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(format!(
+            "\\textsubscript{{{}}}",
+            contents.unwrap_or_default()
+        ))
+    }
+
+    /// (defun org-latex-superscript (_smth contents _info)
+    fn superscript(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &SuperscriptData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(format!(
+            "\\textsuperscript{{{}}}",
+            contents.unwrap_or_default()
+        ))
+    }
+
+    /// (defun org-latex-target (target _contents info)
+    fn target(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &TargetData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(format!("\\label{{{}}}", label(info, id)))
+    }
+
+    /// (defun org-latex-radio-target (radio-target text info)
+    fn radio_target(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &RadioTargetData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(format!(
+            "\\label{{{}}}{}",
+            label(info, id),
+            contents.unwrap_or_default()
+        ))
+    }
+
+    /// (defun org-latex-timestamp (timestamp _contents info)
+    fn timestamp(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &TimestampData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(format!("\\textit{{{}}}", protect_text(&data.raw_value)))
+    }
+
+    /// The definition goes with the first reference, later ones only
+    /// repeat the number.
+    ///
+    /// (defun org-latex-footnote-reference (footnote-reference _contents info)
+    fn footnote_reference(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &FootnoteReferenceData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let footnote = info.footnote(id)?;
+        if !info.footnote_first_reference_p(id) {
+            return Some(format!("\\footnotemark[{}]", footnote.number));
+        }
+        let definition = footnote
+            .definition
+            .map_or(String::new(), |d| info.contents(d));
+        Some(format!("\\footnote{{{}}}", definition.trim()))
+    }
+
+    /// (defun org-latex-link (link desc info)
+    fn link(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &LinkData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        if let Some(destination) = info.resolve_link(id) {
+            let label = label(info, destination);
+            return Some(match contents {
+                Some(description) => format!("\\hyperref[{}]{{{}}}", label, description),
+                None => match &info.tree[destination].data {
+                    Syntax::Headline(h) if !info.numbered_headline_p(destination) => {
+                        format!("\\hyperref[{}]{{{}}}", label, info.secondary(&h.title))
+                    }
+                    _ => format!("\\ref{{{}}}", label),
+                },
+            });
+        }
+        let href = match link_href(data, "pdf") {
+            Some(href) => href,
+            None => return Some(format!("\\texttt{{{}}}", protect_text(&data.path))),
+        };
+        if contents.is_none() && is_image(&data.path) {
+            // Inline image: `#+ATTR_LATEX` and `#+CAPTION` of the
+            // paragraph apply to it
+            let paragraph = info.tree[id].parent()?;
+            let width =
+                attribute(info, paragraph, "width").unwrap_or_else(|| ".9\\linewidth".to_string());
+            let options =
+                attribute(info, paragraph, "options").map_or(String::new(), |o| format!(",{}", o));
+            let image = format!(
+                "\\includegraphics[width={}{}]{{{}}}",
+                width, options, data.path
+            );
+            let caption = caption_label(info, paragraph);
+            if caption.is_empty() {
+                return Some(image);
+            }
+            let placement =
+                attribute(info, paragraph, "placement").unwrap_or_else(|| "[htbp]".to_string());
+            return Some(format!(
+                "\\begin{{figure}}{}\n\\centering\n{}\n{}\\end{{figure}}",
+                placement, image, caption
+            ));
+        }
+        let href = href.replace('%', "\\%").replace('#', "\\#");
+        Some(match contents {
+            Some(description) => format!("\\href{{{}}}{{{}}}", href, description),
+            None => format!("\\url{{{}}}", href),
+        })
+    }
+}
+
+mod test {
+    use super::Latex;
+    use crate::data::*;
+    use crate::export::fixture::{node, parse};
+    use crate::export::{export, ExportOptions};
+    use std::borrow::Cow;
+
+    #[test]
+    fn document() {
+        let text = "Costs 5% of $10_000 & more[fn:: See \\cite.]\n\
+                    * TODO Introduction :draft:\n\
+                    See [[Details]] and [[https://orgmode.org/#top][Org]].\n\
+                    ** Details\n\
+                    *bold* /it/ =a{b}= ~x|y~\n\
+                    *** Deep\n\
+                    **** Deeper\n\
+                    Text\n\
+                    **** Deepest\n";
+        let tree = parse(text);
+        let options = ExportOptions {
+            title: Some("A_B".to_string()),
+            with_toc: None,
+            body_only: true,
+            ..ExportOptions::default()
+        };
+        let body = export(&tree, &Latex::default(), &options);
+        assert!(body.starts_with(
+            "Costs 5\\% of \\$10\\_000 \\& more\\footnote{See \\textbackslash{}cite.}\n"
+        ));
+        assert!(body.contains(
+            "\\section{\\textbf{TODO} Introduction\\hfill{}\\textsc{draft}}\n\\label{sec:org"
+        ));
+        assert!(body.contains("See \\ref{sec:org"));
+        assert!(body.contains("and \\href{https://orgmode.org/\\#top}{Org}.\n"));
+        assert!(body.contains("\\subsection{Details}\n"));
+        assert!(body.contains("\\textbf{bold} \\emph{it} \\texttt{a\\{b\\}} \\verb!x|y!\n"));
+        assert!(body.contains("\\subsubsection{Deep}\n"));
+        assert!(body.contains("\\begin{enumerate}\n\\item Deeper\\label{sec:org"));
+        assert!(body.contains("\\item Deepest\\label{sec:org"));
+        assert!(body.trim_end().ends_with("\\end{enumerate}"));
+        assert_eq!(1, body.matches("\\begin{enumerate}").count());
+    }
+
+    #[test]
+    fn template() {
+        let tree = parse(
+            "#+LATEX_CLASS: report\n\
+             #+LATEX_CLASS_OPTIONS: [a4paper]\n\
+             #+LATEX_HEADER: \\usepackage{tikz}\n\
+             #+LATEX_HEADER: \\usepackage{booktabs}\n\
+             * Chapter\n",
+        );
+
+        let options = ExportOptions {
+            title: Some("Report".to_string()),
+            ..ExportOptions::default()
+        };
+        let document = export(&tree, &Latex::default(), &options);
+        assert!(document
+            .starts_with("\\documentclass[a4paper]{report}\n\\usepackage[utf8]{inputenc}\n"));
+        assert!(document.contains(
+            "\\usepackage{hyperref}\n\\usepackage{tikz}\n\\usepackage{booktabs}\n\\author{}\n\\date{\\today}\n\\title{Report}\n"
+        ));
+        assert!(document.contains(
+            "\\begin{document}\n\n\\maketitle\n\\setcounter{tocdepth}{3}\n\\tableofcontents\n\n\\part{Chapter}\n"
+        ));
+        assert!(document.ends_with("\\end{document}\n"));
+    }
+
+    #[test]
+    fn elements() {
+        let mut tree = parse(
+            "#+CAPTION: Counts\n\
+             #+NAME: counts\n\
+             #+ATTR_LATEX: :environment longtable :align |l|r|\n\
+             | Name | Count |\n\
+             |------+-------|\n\
+             | a_1  |     1 |\n\
+             #+CAPTION: Code\n\
+             #+NAME: code\n\
+             #+BEGIN_SRC rust\n\
+             let s = \"\\n\";\n\
+             #+END_SRC\n\
+             #+NAME: square\n\
+             \\begin{equation}\n\
+             x^2\n\
+             \\end{equation}\n\
+             By [[square]] \\alpha \\euro\n",
+        );
+        // LaTeX fragments are not parsed yet
+        let root = tree.root();
+        let paragraph = tree.append(root, node(Syntax::Paragraph));
+        tree.append(
+            paragraph,
+            node(Syntax::LatexFragment(Box::new(LatexFragmentData {
+                value: Cow::from("\\(e^{i\\pi}\\)"),
+            }))),
+        );
+        let named = |name: &str| {
+            tree.descendants(root)
+                .find(|&id| tree[id].affiliated.as_ref().and_then(|aff| aff.name()) == Some(name))
+                .unwrap()
+        };
+        let (table, code, environment) = (named("counts"), named("code"), named("square"));
+
+        let options = ExportOptions {
+            body_only: true,
+            ..ExportOptions::default()
+        };
+        let reference = |id: NodeId| format!("org{:07x}", id.index());
+        assert_eq!(
+            format!(
+                "\\begin{{table}}[htbp]\n\\centering\n\\caption{{Counts}}\\label{{tab:{}}}\n\
+                 \\begin{{longtable}}{{|l|r|}}\nName & Count \\\\\n\\hline\na\\_1 & 1 \\\\\n\\end{{longtable}}\n\
+                 \\end{{table}}\n\
+                 \\begin{{figure}}[htbp]\n\\begin{{verbatim}}\nlet s = \"\\n\";\n\\end{{verbatim}}\n\
+                 \\caption{{Code}}\\label{{code:{}}}\n\\end{{figure}}\n\
+                 \\begin{{equation}}\n\\label{{eq:{eq}}}\nx^2\n\\end{{equation}}\n\
+                 By \\ref{{eq:{eq}}} $\\alpha$ \\texteuro{{}}\n\
+                 \\(e^{{i\\pi}}\\)\n",
+                reference(table),
+                reference(code),
+                eq = reference(environment)
+            ),
+            export(&tree, &Latex::default(), &options)
+        );
+    }
+}
@@ -0,0 +1,841 @@
+//    This file is part of org-rs.
+//
+//    org-rs is free software: you can redistribute it and/or modify
+//    it under the terms of the GNU General Public License as published by
+//    the Free Software Foundation, either version 3 of the License, or
+//    (at your option) any later version.
+//
+//    org-rs is distributed in the hope that it will be useful,
+//    but WITHOUT ANY WARRANTY; without even the implied warranty of
+//    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//    GNU General Public License for more details.
+//
+//    You should have received a copy of the GNU General Public License
+//    along with org-rs.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Markdown backend
+//!
+//! Port of ox-md.el, and of ox-gfm for GitHub flavoured Markdown
+//! https://code.orgmode.org/bzg/org-mode/src/master/lisp/ox-md.el
+//! https://github.com/larstvei/ox-gfm
+//!
+//! Like ox-md, the backend derives from the HTML backend: Markdown
+//! allows raw HTML, so what Markdown cannot express falls back to
+//! HTML, and what HTML would not render well is simplified:
+//!
+//! - Tables are GFM pipe tables. CommonMark has no tables, they are
+//!   exported as HTML tables.
+//! - Strike-through is `~~text~~` in GFM and `<del>` in CommonMark.
+//!   Underline, subscript and superscript are always HTML.
+//! - Footnotes are `[^1]` references and definitions in GFM. In
+//!   CommonMark they are HTML links to a "Footnotes" section.
+//! - Checkboxes become GFM task list items. As GFM has no partial
+//!   state, `[-]` is exported as unchecked.
+//! - Headlines deeper than 6 levels, or than `headline_levels`, become
+//!   list items.
+//! - Drawers are replaced by their contents. Property drawers,
+//!   planning lines, clocks and comments are left out.
+//! - Center and special blocks are replaced by their contents, inline
+//!   tasks by their title in bold followed by their contents.
+//! - Timestamps and statistics cookies are plain text, entities are
+//!   their UTF-8 character.
+//! - Only headlines, targets and radio targets get anchors that links
+//!   can point to. Links to other named elements have no anchor.
+//! - The title is not part of the output, see `org-md-template`.
+
+use crate::data::*;
+use crate::export::html::{anchor, is_image, link_href, section_number, Html};
+use crate::export::{Alignment, Backend, Info};
+
+/// Markdown dialect of the output
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Flavor {
+    /// https://commonmark.org
+    CommonMark,
+    /// GitHub flavoured Markdown, CommonMark with tables, task lists,
+    /// strike-through and footnotes
+    Gfm,
+}
+
+/// Markdown backend, see module documentation
+pub struct Markdown {
+    pub flavor: Flavor,
+}
+
+impl Markdown {
+    pub fn commonmark() -> Self {
+        Markdown {
+            flavor: Flavor::CommonMark,
+        }
+    }
+
+    pub fn gfm() -> Self {
+        Markdown {
+            flavor: Flavor::Gfm,
+        }
+    }
+}
+
+/// Returns `code` in a code span, with enough backticks around it
+fn code_span(code: &str) -> String {
+    let fence = "`".repeat(longest_backtick_run(code) + 1);
+    let padding = if code.starts_with('`') || code.ends_with('`') {
+        " "
+    } else {
+        ""
+    };
+    format!("{f}{p}{}{p}{f}", code, f = fence, p = padding)
+}
+
+/// Returns `code` in a fenced code block, with enough backticks
+/// around it
+fn code_block(code: &str, language: &str) -> String {
+    let fence = "`".repeat((longest_backtick_run(code) + 1).max(3));
+    let code = code.strip_suffix('\n').unwrap_or(code);
+    format!("{f}{}\n{}\n{f}", language, code, f = fence)
+}
+
+fn longest_backtick_run(s: &str) -> usize {
+    s.split(|c| c != '`').map(str::len).max().unwrap_or(0)
+}
+
+/// Prefixes every non-empty line of `s` but the first with `prefix`
+fn indent_rest(s: &str, prefix: &str) -> String {
+    s.split('\n')
+        .enumerate()
+        .map(|(i, line)| match i {
+            0 => line.to_string(),
+            _ if line.is_empty() => String::new(),
+            _ => format!("{}{}", prefix, line),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Returns true if headline `id` needs an anchor: it has a
+/// `CUSTOM_ID`, it is in the table of contents or a link points to it
+///
+/// (defun org-md--headline-referred-p (headline info)
+fn headline_referred_p(info: &Info, id: NodeId) -> bool {
+    if info.property(id, "CUSTOM_ID").is_some() {
+        return true;
+    }
+    if let Some(depth) = info.options.with_toc {
+        if info.toc(depth).contains(&id) {
+            return true;
+        }
+    }
+    info.tree
+        .subtree(info.tree.root())
+        .into_iter()
+        .any(|node| match info.tree[node].data {
+            Syntax::Link(_) => info.resolve_link(node) == Some(id),
+            _ => false,
+        })
+}
+
+/// Delimiter row of a pipe table, with the alignment of each column
+fn delimiter_row(info: &Info, table: NodeId) -> String {
+    let first = info
+        .tree
+        .children(table)
+        .find(|&row| info.table_row_group(row).is_some());
+    let cells: String = first
+        .map(|row| {
+            info.tree
+                .children(row)
+                .map(|cell| match info.table_cell_alignment(cell) {
+                    Alignment::Left => " --- |",
+                    Alignment::Right => " --: |",
+                    Alignment::Center => " :-: |",
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    format!("|{}\n", cells)
+}
+
+impl Markdown {
+    /// (defun org-md--headline-title (style level title &optional anchor tags)
+    fn headline_title(&self, info: &Info, id: NodeId, h: &HeadlineData) -> String {
+        let mut title = String::new();
+        if let (Some(todo), true) = (&h.todo_keyword, info.options.with_todo_keywords) {
+            title.push_str(todo);
+            title.push(' ');
+        }
+        if h.priority > 0 && info.options.with_priority {
+            if let Some(p) = std::char::from_u32(h.priority as u32) {
+                title.push_str(&format!("[#{}] ", p));
+            }
+        }
+        title.push_str(&info.secondary(&h.title));
+        let tags = info.tags(id);
+        if !tags.is_empty() {
+            title.push_str(&format!("     :{}:", tags.join(":")));
+        }
+        title
+    }
+
+    /// (defun org-md--build-toc (info &optional n _keyword scope)
+    fn toc(&self, info: &Info, depth: usize) -> String {
+        let entries: Vec<String> = info
+            .toc(depth)
+            .into_iter()
+            .filter_map(|id| match &info.tree[id].data {
+                Syntax::Headline(h) if !h.footnote_section_p => {
+                    let indentation = " ".repeat(4 * (info.relative_level(id) - 1));
+                    let bullet = match info.headline_number(id).and_then(|n| n.last()) {
+                        Some(n) => format!("{:<4}", format!("{}.", n)),
+                        None => "-   ".to_string(),
+                    };
+                    Some(format!(
+                        "{}{}[{}](#{})",
+                        indentation,
+                        bullet,
+                        info.secondary(&h.title),
+                        anchor(info, id)
+                    ))
+                }
+                _ => None,
+            })
+            .collect();
+        if entries.is_empty() {
+            return String::new();
+        }
+        format!("# Table of Contents\n\n{}\n\n", entries.join("\n"))
+    }
+
+    /// (defun org-md--footnote-section (info)
+    fn footnote_section(&self, info: &Info) -> String {
+        let definitions: Vec<String> = info
+            .footnotes()
+            .iter()
+            .filter_map(|f| {
+                let definition = info.contents(f.definition?);
+                let definition = definition.trim();
+                Some(match self.flavor {
+                    Flavor::Gfm => format!("[^{}]: {}", f.number, indent_rest(definition, "    ")),
+                    Flavor::CommonMark => format!(
+                        "<sup><a id=\"fn.{n}\" href=\"#fnr.{n}\">{n}</a></sup> {}",
+                        definition,
+                        n = f.number
+                    ),
+                })
+            })
+            .collect();
+        if definitions.is_empty() {
+            return String::new();
+        }
+        match self.flavor {
+            Flavor::Gfm => format!("{}\n", definitions.join("\n\n")),
+            Flavor::CommonMark => format!("# Footnotes\n\n{}\n", definitions.join("\n\n")),
+        }
+    }
+}
+
+impl Backend for Markdown {
+    fn name(&self) -> &str {
+        "md"
+    }
+
+    fn parent(&self) -> Option<&dyn Backend> {
+        Some(&Html)
+    }
+
+    /// Protects characters with a meaning in Markdown, and the ones of
+    /// HTML, as Markdown passes HTML through.
+    ///
+    /// (defun org-md-plain-text (text info)
+    fn plain_text(&self, info: &Info, text: &str) -> String {
+        let mut escaped = String::with_capacity(text.len());
+        let mut line_start = true;
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '`' | '*' | '_' | '\\' => escaped.push('\\'),
+                '#' if line_start => escaped.push('\\'),
+                '!' if chars.peek() == Some(&'[') => escaped.push('\\'),
+                _ => (),
+            }
+            match c {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                c => escaped.push(c),
+            }
+            line_start = c == '\n' || (line_start && (c == ' ' || c == '\t'));
+        }
+        escaped
+    }
+
+    /// (defun org-md-inner-template (contents info)
+    fn inner_template(&self, info: &Info, contents: String) -> String {
+        let toc = info
+            .options
+            .with_toc
+            .map_or(String::new(), |depth| self.toc(info, depth));
+        format!("{}{}{}", toc, contents, self.footnote_section(info))
+    }
+
+    /// (defun org-md-template (contents _info)
+    fn template(&self, info: &Info, contents: String) -> String {
+        contents
+    }
+
+    /// Separates elements with a blank line, except for items, and
+    /// paragraphs followed by a sub-list in an item. Rows of a pipe
+    /// table can not be separated.
+    ///
+    /// (defun org-md-separate-elements (tree _backend info)
+    fn post_blank(&self, info: &Info, id: NodeId) -> usize {
+        let node = &info.tree[id];
+        match &node.data {
+            Syntax::Item(_) | Syntax::TableRow(_) => node.post_blank,
+            Syntax::Paragraph => {
+                let in_item = node
+                    .parent()
+                    .map_or(false, |p| matches!(info.tree[p].data, Syntax::Item(_)));
+                let before_list = node
+                    .next_sibling()
+                    .map_or(false, |s| matches!(info.tree[s].data, Syntax::PlainList(_)));
+                if in_item && before_list {
+                    0
+                } else {
+                    1
+                }
+            }
+            data if SyntaxT::from(data).is_object() => node.post_blank,
+            _ => 1,
+        }
+    }
+
+    /// (defun org-md-headline (headline contents info)
+    fn headline(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &HeadlineData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        if data.footnote_section_p {
+            return None;
+        }
+        let contents = contents.unwrap_or_default();
+        let level = info.relative_level(id);
+        let title = self.headline_title(info, id, data);
+        let anchor = if headline_referred_p(info, id) {
+            format!("<a id=\"{}\"></a>", anchor(info, id))
+        } else {
+            String::new()
+        };
+        if info.low_level_p(id) || level > 6 {
+            let bullet = match info.headline_number(id).and_then(|n| n.last()) {
+                Some(n) => format!("{:<4}", format!("{}.", n)),
+                None => "-   ".to_string(),
+            };
+            return Some(format!(
+                "{}{}{}\n\n{}",
+                bullet,
+                anchor,
+                title,
+                indent_rest(&format!("\n{}", contents), "    ").trim_start()
+            ));
+        }
+        let number = section_number(info, id).map_or(String::new(), |n| format!("{}. ", n));
+        Some(format!(
+            "{} {}{}{}\n\n{}",
+            "#".repeat(level),
+            anchor,
+            number,
+            title,
+            contents
+        ))
+    }
+
+    /// (defun org-md-section (_section contents _info)
+    fn section(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        contents
+    }
+
+    /// (defun org-md-paragraph (paragraph contents _info)
+    fn paragraph(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        contents
+    }
+
+    /// (defun org-md-plain-list (_plain-list contents _info)
+    fn plain_list(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &PlainListData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        contents
+    }
+
+    /// (defun org-md-item (item contents info)
+    fn item(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &ItemData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let list = info.tree[id].parent()?;
+        let kind = match &info.tree[list].data {
+            Syntax::PlainList(l) => l.type_s,
+            _ => ListKind::Unordered,
+        };
+        let bullet = match kind {
+            ListKind::Ordered if data.counter > 0 => format!("{}.", data.counter),
+            ListKind::Ordered => {
+                let position = info.tree.children(list).position(|i| i == id).unwrap_or(0);
+                format!("{}.", position + 1)
+            }
+            _ => "-".to_string(),
+        };
+        let checkbox = match (data.checkbox, self.flavor) {
+            (Some(CheckBox::On), Flavor::Gfm) => "[x] ",
+            (Some(CheckBox::On), Flavor::CommonMark) => "[X] ",
+            (Some(CheckBox::Trans), Flavor::CommonMark) => "[-] ",
+            (Some(_), _) => "[ ] ",
+            (None, _) => "",
+        };
+        let tag = match &data.tag {
+            Some(tag) => format!("**{}:** ", info.secondary(tag)),
+            None => String::new(),
+        };
+        let contents = contents.unwrap_or_default();
+        Some(format!(
+            "{:<4}{}{}{}",
+            bullet,
+            checkbox,
+            tag,
+            indent_rest(contents.trim(), "    ")
+        ))
+    }
+
+    fn table(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &TableData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        if self.flavor == Flavor::CommonMark {
+            return Html.table(info, id, data, contents);
+        }
+        let contents = contents.unwrap_or_default();
+        if info.table_has_header_p(id) {
+            return Some(contents);
+        }
+        // Pipe tables always have a header, leave it empty
+        let columns = info
+            .tree
+            .children(id)
+            .find(|&row| info.table_row_group(row).is_some())
+            .map_or(0, |row| info.tree.children(row).count());
+        Some(format!(
+            "|{}\n{}{}",
+            "   |".repeat(columns),
+            delimiter_row(info, id),
+            contents
+        ))
+    }
+
+    fn table_row(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &TableRowData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        if self.flavor == Flavor::CommonMark {
+            return Html.table_row(info, id, data, contents);
+        }
+        if info.table_row_is_special_p(id) {
+            return None;
+        }
+        if info.table_row_is_rule_p(id) {
+            // Only the rule below the header is kept, as the
+            // delimiter row
+            let previous = info.tree[id].prev_sibling()?;
+            if info.table_row_in_header_p(previous) {
+                return Some(delimiter_row(info, info.tree[id].parent()?));
+            }
+            return None;
+        }
+        Some(format!("|{}\n", contents.unwrap_or_default()))
+    }
+
+    fn table_cell(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        if self.flavor == Flavor::CommonMark {
+            return Html.table_cell(info, id, contents);
+        }
+        let contents = contents.unwrap_or_default();
+        Some(format!(" {} |", contents.trim().replace('|', "\\|")))
+    }
+
+    /// (defun org-gfm-src-block (src-block _contents info)
+    fn src_block(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &SrcBlockData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(code_block(
+            &data.value,
+            data.language.as_deref().unwrap_or(""),
+        ))
+    }
+
+    /// (defun org-md-example-block (example-block _contents info)
+    fn example_block(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &ExampleBlockData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(code_block(&data.value, ""))
+    }
+
+    fn fixed_width(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &FixedWidthData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(code_block(&data.value, ""))
+    }
+
+    /// (defun org-md-export-block (export-block contents info)
+    fn export_block(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &ExportBlockData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        match data.type_s.to_uppercase().as_str() {
+            "MARKDOWN" | "MD" => Some(data.value.to_string()),
+            _ => Html.export_block(info, id, data, contents),
+        }
+    }
+
+    fn export_snippet(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &ExportSnippetData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        match data.back_end.to_lowercase().as_str() {
+            "markdown" | "md" => Some(data.value.to_string()),
+            _ => Html.export_snippet(info, id, data, contents),
+        }
+    }
+
+    /// (defun org-md-keyword (keyword contents info)
+    fn keyword(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &KeywordData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        match data.key.to_uppercase().as_str() {
+            "MARKDOWN" | "MD" => Some(data.value.to_string()),
+            _ => Html.keyword(info, id, data, contents),
+        }
+    }
+
+    fn special_block(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &SpecialBlockData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        contents
+    }
+
+    /// (defun org-md-quote-block (_quote-block contents _info)
+    fn quote_block(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        let contents = contents.unwrap_or_default();
+        let quoted: Vec<String> = contents
+            .trim_end_matches('\n')
+            .split('\n')
+            .map(|line| match line {
+                "" => ">".to_string(),
+                line => format!("> {}", line),
+            })
+            .collect();
+        Some(quoted.join("\n"))
+    }
+
+    fn center_block(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        contents
+    }
+
+    fn verse_block(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        let contents = contents.unwrap_or_default();
+        let lines: Vec<&str> = contents.trim_end_matches('\n').split('\n').collect();
+        Some(lines.join("  \n"))
+    }
+
+    fn inline_task(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &InlineTaskData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(format!(
+            "**{}**\n\n{}",
+            info.secondary(&data.title),
+            contents.unwrap_or_default()
+        ))
+    }
+
+    /// (defun org-md-horizontal-rule (_horizontal-rule _contents _info)
+    fn horizontal_rule(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        Some("---".to_string())
+    }
+
+    /// (defun org-md-bold (_bold contents _info)
+    fn bold(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        Some(format!("**{}**", contents.unwrap_or_default()))
+    }
+
+    /// (defun org-md-italic (_italic contents _info)
+    fn italic(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        Some(format!("*{}*", contents.unwrap_or_default()))
+    }
+
+    /// (defun org-gfm-strike-through (_strike-through contents _info)
+    fn strike_through(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        match self.flavor {
+            Flavor::Gfm => Some(format!("~~{}~~", contents.unwrap_or_default())),
+            Flavor::CommonMark => Html.strike_through(info, id, contents),
+        }
+    }
+
+    /// (defun org-md-verbatim (verbatim _contents _info)
+    fn code(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &CodeData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(code_span(&data.value))
+    }
+
+    /// (defun org-md-verbatim (verbatim _contents _info)
+    fn verbatim(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &VerbatimData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(code_span(&data.value))
+    }
+
+    fn inline_src_block(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &InlineSrcBlockData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(code_span(&data.value))
+    }
+
+    fn entity(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &EntityData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(data.utf_8.to_string())
+    }
+
+    /// (defun org-md-line-break (_line-break _contents _info)
+    fn line_break(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        Some("  \n".to_string())
+    }
+
+    fn statistics_cookie(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &StatisticsCookieData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(data.value.to_string())
+    }
+
+    fn timestamp(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &TimestampData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        Some(self.plain_text(info, &data.raw_value))
+    }
+
+    /// (defun org-md-footnote-reference (footnote-reference _contents info)
+    fn footnote_reference(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &FootnoteReferenceData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        match self.flavor {
+            Flavor::Gfm => Some(format!("[^{}]", info.footnote(id)?.number)),
+            Flavor::CommonMark => Html.footnote_reference(info, id, data, contents),
+        }
+    }
+
+    /// (defun org-md-link (link desc info)
+    fn link(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &LinkData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        if let Some(destination) = info.resolve_link(id) {
+            let description = contents.unwrap_or_else(|| match &info.tree[destination].data {
+                Syntax::Headline(h) => {
+                    section_number(info, destination).unwrap_or_else(|| info.secondary(&h.title))
+                }
+                _ => self.plain_text(info, &data.path),
+            });
+            return Some(format!("[{}](#{})", description, anchor(info, destination)));
+        }
+        let href = match link_href(data, "md") {
+            Some(href) => href,
+            None => {
+                // Unresolved internal link
+                let text = contents.unwrap_or_else(|| self.plain_text(info, &data.path));
+                return Some(format!("*{}*", text));
+            }
+        };
+        match contents {
+            None if is_image(&data.path) => {
+                let alt = data.path.rsplit('/').next().unwrap_or(&data.path);
+                Some(format!("![{}]({})", alt, href))
+            }
+            None => Some(format!("<{}>", href)),
+            Some(description) => Some(format!("[{}]({})", description, href)),
+        }
+    }
+}
+
+mod test {
+    use super::Markdown;
+    use crate::export::fixture::parse;
+    use crate::export::{export, ExportOptions};
+
+    #[test]
+    fn document() {
+        let tree = parse(
+            "Some *bold*, /italic/, ~a`b~ and +gone+ text_with_underscores.\n\
+             * TODO Heading :work:\n\
+             See [[Sub heading]] and [[https://orgmode.org][Org]][fn:1].\n\
+             \n\
+             [[https://orgmode.org]] and [[file:other.org][other]]\n\
+             ** Sub heading\n\
+             Note[fn::Inline note.]\n",
+        );
+        let options = ExportOptions {
+            with_toc: None,
+            ..ExportOptions::default()
+        };
+        assert_eq!(
+            "Some **bold**, *italic*, ``a`b`` and ~~gone~~ text\\_with\\_underscores.\n\
+             \n\
+             # 1. TODO Heading     :work:\n\
+             \n\
+             See [1.1](#org0000021) and [Org](https://orgmode.org)[^1].\n\
+             \n\
+             <https://orgmode.org> and [other](other.md)\n\
+             \n\
+             ## <a id=\"org0000021\"></a>1.1. Sub heading\n\
+             \n\
+             Note[^2]\n\
+             \n\
+             [^2]: Inline note.\n",
+            export(&tree, &Markdown::gfm(), &options)
+        );
+
+        let commonmark = export(&tree, &Markdown::commonmark(), &options);
+        assert!(commonmark.contains(" and <del>gone</del> "));
+        assert!(commonmark.contains(
+            "Note<sup><a id=\"fnr.2\" class=\"footref\" href=\"#fn.2\" role=\"doc-backlink\">2</a></sup>\n"
+        ));
+        assert!(commonmark.ends_with(
+            "# Footnotes\n\n<sup><a id=\"fn.2\" href=\"#fnr.2\">2</a></sup> Inline note.\n"
+        ));
+
+        let with_toc = export(&tree, &Markdown::gfm(), &ExportOptions::default());
+        assert!(with_toc.starts_with(
+            "# Table of Contents\n\n\
+             1.  [Heading](#org0000010)\n    \
+             1.  [Sub heading](#org0000021)\n\n"
+        ));
+        assert!(with_toc.contains("# <a id=\"org0000010\"></a>1. TODO Heading"));
+    }
+
+    #[test]
+    fn elements() {
+        let tree = parse(
+            "- [X] Done\n\
+             - [-] Half\n\
+             - First line\n  \
+               second line\n\
+             \n\
+             | Name      | Count |\n\
+             |-----------+-------|\n\
+             | a\\vert{}b |     1 |\n\
+             \n\
+             #+BEGIN_SRC rust\n\
+             let s = \"```\";\n\
+             #+END_SRC\n\
+             \n\
+             - plain\n\
+             - Term :: described\n\
+             \n\
+             Costs 5\\euro, \\alpha{}x.\n",
+        );
+        let options = ExportOptions::default();
+        assert_eq!(
+            "-   [x] Done\n\
+             -   [ ] Half\n\
+             -   First line\n    \
+             second line\n\
+             \n\
+             | Name | Count |\n\
+             | --- | --: |\n\
+             | a\\|b | 1 |\n\
+             \n\
+             ````rust\n\
+             let s = \"```\";\n\
+             ````\n\
+             \n\
+             -   plain\n\
+             -   **Term:** described\n\
+             \n\
+             Costs 5€, αx.\n\n",
+            export(&tree, &Markdown::gfm(), &options)
+        );
+    }
+}
@@ -0,0 +1,1055 @@
+//    This file is part of org-rs.
+//
+//    org-rs is free software: you can redistribute it and/or modify
+//    it under the terms of the GNU General Public License as published by
+//    the Free Software Foundation, either version 3 of the License, or
+//    (at your option) any later version.
+//
+//    org-rs is distributed in the hope that it will be useful,
+//    but WITHOUT ANY WARRANTY; without even the implied warranty of
+//    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//    GNU General Public License for more details.
+//
+//    You should have received a copy of the GNU General Public License
+//    along with org-rs.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Pandoc JSON backend
+//!
+//! Exports the tree as the JSON serialization of a Pandoc document,
+//! version 1.23 of pandoc-types, for `pandoc -f json`:
+//! https://hackage.haskell.org/package/pandoc-types
+//!
+//! There is no ox equivalent. Where Pandoc's own Org reader has a
+//! representation for an Org construct, the same one is used, so that
+//! Pandoc writers handle both alike:
+//!
+//! - TODO keywords and tags are spans of class "todo"/"done" and
+//!   "tag" in the header.
+//! - `#+NAME` becomes the identifier of the element, `#+ATTR_HTML`
+//!   attributes its classes (`:class`) and key-value pairs.
+//! - Footnotes are notes at each of their references.
+//! - LaTeX fragments in `$`, `\(`, `$$` and `\[` are math, other
+//!   fragments, LaTeX environments, export blocks and snippets are raw.
+//! - Checkboxes are "☒" and "☐" at the start of the item.
+//!
+//! Headlines are headers whatever their level, `headline_levels` does
+//! not apply. Title, author, date and language are the metadata of the
+//! document. With `body_only` the output is the list of blocks only.
+//!
+//! Transcoders produce JSON values followed by a comma. Contents of a
+//! node are therefore a comma separated sequence, turned into an array
+//! by `array`.
+
+use crate::data::*;
+use crate::export::html::{anchor, is_image, link_href, section_number};
+use crate::export::{Alignment, Backend, Info};
+
+/// Pandoc backend, see module documentation
+pub struct Pandoc;
+
+/// Version of pandoc-types the output conforms to
+const API_VERSION: &str = "[1,23,1]";
+
+const SPACE: &str = "{\"t\":\"Space\"},";
+const SOFT_BREAK: &str = "{\"t\":\"SoftBreak\"},";
+
+/// Attributes of an element without identifier, classes or pairs
+const NO_ATTR: &str = "[\"\",[],[]]";
+
+/// `s` as a JSON string
+fn string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Array of the values of a comma separated sequence
+fn array(sequence: &str) -> String {
+    format!("[{}]", sequence.trim_end().trim_end_matches(','))
+}
+
+/// Value of constructor `t`, with arguments `c`
+fn tagged(t: &str, c: &str) -> String {
+    format!("{{\"t\":\"{}\",\"c\":{}}}", t, c)
+}
+
+/// Value of a constructor without arguments, e.g. `AlignLeft`
+fn constant(t: &str) -> String {
+    format!("{{\"t\":\"{}\"}}", t)
+}
+
+/// A block, as returned by transcoders of elements
+fn block(value: String) -> Option<String> {
+    Some(value + ",")
+}
+
+/// An inline of object `id`, followed by a space when there are blanks
+/// after the object
+fn inline(info: &Info, id: NodeId, value: String) -> Option<String> {
+    let space = if info.tree[id].post_blank > 0 {
+        SPACE
+    } else {
+        ""
+    };
+    Some(format!("{},{}", value, space))
+}
+
+/// Inlines of `sequence` without the trailing spaces and line breaks
+fn inlines(sequence: &str) -> String {
+    let mut sequence = sequence.trim_end();
+    while let Some(rest) = sequence
+        .strip_suffix(SPACE)
+        .or_else(|| sequence.strip_suffix(SOFT_BREAK))
+    {
+        sequence = rest;
+    }
+    array(sequence)
+}
+
+/// Attributes from an identifier, classes and key-value pairs
+fn attr(identifier: &str, classes: &[String], pairs: &[(String, String)]) -> String {
+    let classes: Vec<String> = classes.iter().map(|c| string(c)).collect();
+    let pairs: Vec<String> = pairs
+        .iter()
+        .map(|(k, v)| format!("[{},{}]", string(k), string(v)))
+        .collect();
+    format!(
+        "[{},[{}],[{}]]",
+        string(identifier),
+        classes.join(","),
+        pairs.join(",")
+    )
+}
+
+/// Attributes of element `id`: its reference when named, `classes`
+/// and the `#+ATTR_HTML` attributes
+fn attributes(info: &Info, id: NodeId, mut classes: Vec<String>) -> String {
+    let affiliated = info.tree[id].affiliated.as_ref();
+    let identifier = match affiliated.and_then(|a| a.name()) {
+        Some(_) => info.reference(id),
+        None => String::new(),
+    };
+    let mut pairs = vec![];
+    for (key, value) in affiliated.map_or(vec![], |a| a.attr("html")) {
+        if key == "class" {
+            classes.extend(value.split_whitespace().map(str::to_string));
+        } else {
+            pairs.push((key, value));
+        }
+    }
+    attr(&identifier, &classes, &pairs)
+}
+
+/// Name of the Pandoc format of raw Org `backend` contents
+fn raw_format(backend: &str) -> String {
+    match backend.to_ascii_lowercase().as_str() {
+        "md" => "markdown".to_string(),
+        "odt" => "opendocument".to_string(),
+        backend => backend.to_string(),
+    }
+}
+
+/// Code of a block, without its final newline
+fn code(value: &str) -> String {
+    string(value.strip_suffix('\n').unwrap_or(value))
+}
+
+impl Pandoc {
+    /// TODO keyword, priority, title and tags of a headline, as in
+    /// headers read by Pandoc
+    fn format_headline(&self, info: &Info, id: NodeId, h: &HeadlineData) -> String {
+        let mut out = String::new();
+        if let (Some(todo), true) = (&h.todo_keyword, info.options.with_todo_keywords) {
+            let class = match h.todo_type {
+                Some(crate::headline::TodoType::Done) => "done",
+                _ => "todo",
+            };
+            let classes = [class.to_string(), todo.to_string()];
+            let todo = tagged("Str", &string(todo));
+            out.push_str(&tagged(
+                "Span",
+                &format!("[{},[{}]]", attr("", &classes, &[]), todo),
+            ));
+            out.push(',');
+            out.push_str(SPACE);
+        }
+        if h.priority > 0 && info.options.with_priority {
+            if let Some(p) = std::char::from_u32(h.priority as u32) {
+                out.push_str(&tagged("Str", &string(&format!("[#{}]", p))));
+                out.push(',');
+                out.push_str(SPACE);
+            }
+        }
+        out.push_str(&info.secondary(&h.title));
+        if info.options.with_tags {
+            for tag in info.tags(id) {
+                let pairs = [("tag-name".to_string(), tag.to_string())];
+                let name = tagged("SmallCaps", &format!("[{}]", tagged("Str", &string(tag))));
+                out.push_str(SPACE);
+                out.push_str(&tagged(
+                    "Span",
+                    &format!("[{},[{}]]", attr("", &["tag".to_string()], &pairs), name),
+                ));
+                out.push(',');
+            }
+        }
+        out
+    }
+
+    /// Column specifications, header rows and body rows of table `id`
+    fn table_rows(&self, info: &Info, id: NodeId) -> (Vec<String>, Vec<String>, Vec<String>) {
+        let mut head = vec![];
+        let mut body = vec![];
+        let mut columns = vec![];
+        for row in info.tree.children(id) {
+            if info.table_row_group(row).is_none() {
+                continue;
+            }
+            if columns.is_empty() {
+                columns = info
+                    .tree
+                    .children(row)
+                    .map(|cell| {
+                        let alignment = match info.table_cell_alignment(cell) {
+                            Alignment::Left => "AlignLeft",
+                            Alignment::Right => "AlignRight",
+                            Alignment::Center => "AlignCenter",
+                        };
+                        format!("[{},{}]", constant(alignment), constant("ColWidthDefault"))
+                    })
+                    .collect();
+            }
+            let row_value = info.data(row);
+            if info.table_row_in_header_p(row) {
+                head.push(row_value);
+            } else {
+                body.push(row_value);
+            }
+        }
+        (columns, head, body)
+    }
+}
+
+impl Backend for Pandoc {
+    fn name(&self) -> &str {
+        "pandoc"
+    }
+
+    /// Words of `text` as `Str`, separated by `Space`, or by
+    /// `SoftBreak` at the end of lines
+    fn plain_text(&self, info: &Info, text: &str) -> String {
+        let blank = |c: char| c == ' ' || c == '\t' || c == '\n';
+        let mut out = String::new();
+        let mut rest = text;
+        while !rest.is_empty() {
+            let end = rest.find(blank).unwrap_or(rest.len());
+            if end > 0 {
+                out.push_str(&tagged("Str", &string(&rest[..end])));
+                out.push(',');
+                rest = &rest[end..];
+                continue;
+            }
+            let end = rest.find(|c| !blank(c)).unwrap_or(rest.len());
+            out.push_str(if rest[..end].contains('\n') {
+                SOFT_BREAK
+            } else {
+                SPACE
+            });
+            rest = &rest[end..];
+        }
+        out
+    }
+
+    fn inner_template(&self, info: &Info, contents: String) -> String {
+        array(&contents)
+    }
+
+    fn template(&self, info: &Info, contents: String) -> String {
+        let options = info.options;
+        let meta_inlines =
+            |text: &str| tagged("MetaInlines", &inlines(&self.plain_text(info, text)));
+        // Keys in alphabetical order, as Pandoc writes them
+        let mut meta = vec![];
+        if let Some(author) = &options.author {
+            let author = tagged("MetaList", &format!("[{}]", meta_inlines(author)));
+            meta.push(format!("\"author\":{}", author));
+        }
+        if let Some(date) = &options.date {
+            meta.push(format!("\"date\":{}", meta_inlines(date)));
+        }
+        meta.push(format!(
+            "\"lang\":{}",
+            tagged("MetaString", &string(&options.language))
+        ));
+        if let Some(title) = &options.title {
+            meta.push(format!("\"title\":{}", meta_inlines(title)));
+        }
+        format!(
+            "{{\"pandoc-api-version\":{},\"meta\":{{{}}},\"blocks\":{}}}\n",
+            API_VERSION,
+            meta.join(","),
+            contents
+        )
+    }
+
+    /// Objects are followed by a `Space` instead, see `inline`
+    fn post_blank(&self, info: &Info, id: NodeId) -> usize {
+        0
+    }
+
+    fn headline(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &HeadlineData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        if data.footnote_section_p {
+            return None;
+        }
+        let mut classes = vec![];
+        if info.options.section_numbers.is_some() && !info.numbered_headline_p(id) {
+            classes.push("unnumbered".to_string());
+        }
+        let header = tagged(
+            "Header",
+            &format!(
+                "[{},{},{}]",
+                info.relative_level(id),
+                attr(&anchor(info, id), &classes, &[]),
+                inlines(&self.format_headline(info, id, data))
+            ),
+        );
+        Some(format!("{},\n{}", header, contents.unwrap_or_default()))
+    }
+
+    fn paragraph(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        let contents = contents.unwrap_or_default();
+        let node = &info.tree[id];
+        let item = node
+            .parent()
+            .filter(|&p| matches!(info.tree[p].data, Syntax::Item(_)))
+            .filter(|_| node.prev_sibling().is_none());
+        let item = match item.map(|p| &info.tree[p].data) {
+            Some(Syntax::Item(item)) => item,
+            _ => return block(tagged("Para", &inlines(&contents))),
+        };
+        // First paragraph of an item, as in a tight list
+        let checkbox = match item.checkbox {
+            Some(CheckBox::On) => format!("{},{}", tagged("Str", "\"☒\""), SPACE),
+            Some(_) => format!("{},{}", tagged("Str", "\"☐\""), SPACE),
+            None => String::new(),
+        };
+        block(tagged("Plain", &inlines(&(checkbox + &contents))))
+    }
+
+    fn plain_list(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &PlainListData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let items = array(&contents.unwrap_or_default());
+        block(match data.type_s {
+            ListKind::Unordered => tagged("BulletList", &items),
+            ListKind::Descriptive => tagged("DefinitionList", &items),
+            ListKind::Ordered => {
+                let first =
+                    info.tree[id]
+                        .first_child()
+                        .and_then(|item| match &info.tree[item].data {
+                            Syntax::Item(item) => Some(item),
+                            _ => None,
+                        });
+                let start = first.map_or(1, |item| item.counter.max(1));
+                let delimiter = match first {
+                    Some(item) if item.bullet.trim_end().ends_with(')') => "OneParen",
+                    _ => "Period",
+                };
+                tagged(
+                    "OrderedList",
+                    &format!(
+                        "[[{},{},{}],{}]",
+                        start,
+                        constant("Decimal"),
+                        constant(delimiter),
+                        items
+                    ),
+                )
+            }
+        })
+    }
+
+    fn item(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &ItemData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let blocks = array(&contents.unwrap_or_default());
+        let descriptive = info.tree[id].parent().map_or(false, |list| {
+            matches!(&info.tree[list].data, Syntax::PlainList(l) if l.type_s == ListKind::Descriptive)
+        });
+        if !descriptive {
+            return block(blocks);
+        }
+        let term = data
+            .tag
+            .as_ref()
+            .map_or_else(|| self.plain_text(info, "(no term)"), |t| info.secondary(t));
+        block(format!("[{},[{}]]", inlines(&term), blocks))
+    }
+
+    fn table(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &TableData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let (columns, head, body) = self.table_rows(info, id);
+        let caption = info.tree[id]
+            .affiliated
+            .as_ref()
+            .and_then(|a| a.get("CAPTION"))
+            .map_or(String::new(), |c| {
+                tagged("Plain", &inlines(&info.secondary(&c.value)))
+            });
+        let bodies = if body.is_empty() {
+            String::new()
+        } else {
+            format!("[{},0,[],{}]", NO_ATTR, array(&body.concat()))
+        };
+        block(tagged(
+            "Table",
+            &format!(
+                "[{},[null,[{}]],[{}],[{},{}],[{}],[{},[]]]",
+                attributes(info, id, vec![]),
+                caption,
+                columns.join(","),
+                NO_ATTR,
+                array(&head.concat()),
+                bodies,
+                NO_ATTR
+            ),
+        ))
+    }
+
+    fn table_row(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &TableRowData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        info.table_row_group(id)?;
+        block(format!(
+            "[{},{}]",
+            NO_ATTR,
+            array(&contents.unwrap_or_default())
+        ))
+    }
+
+    fn table_cell(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        let contents = contents.unwrap_or_default();
+        let blocks = if contents.trim().is_empty() {
+            String::new()
+        } else {
+            tagged("Plain", &inlines(&contents))
+        };
+        Some(format!(
+            "[{},{},1,1,[{}]],",
+            NO_ATTR,
+            constant("AlignDefault"),
+            blocks
+        ))
+    }
+
+    fn src_block(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &SrcBlockData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let classes = data.language.iter().map(|l| l.to_string()).collect();
+        block(tagged(
+            "CodeBlock",
+            &format!("[{},{}]", attributes(info, id, classes), code(&data.value)),
+        ))
+    }
+
+    fn example_block(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &ExampleBlockData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        block(tagged(
+            "CodeBlock",
+            &format!("[{},{}]", attributes(info, id, vec![]), code(&data.value)),
+        ))
+    }
+
+    fn fixed_width(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &FixedWidthData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        block(tagged(
+            "CodeBlock",
+            &format!("[{},{}]", NO_ATTR, code(&data.value)),
+        ))
+    }
+
+    fn export_block(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &ExportBlockData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        block(tagged(
+            "RawBlock",
+            &format!(
+                "[{},{}]",
+                string(&raw_format(&data.type_s)),
+                string(&data.value)
+            ),
+        ))
+    }
+
+    fn export_snippet(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &ExportSnippetData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        inline(
+            info,
+            id,
+            tagged(
+                "RawInline",
+                &format!(
+                    "[{},{}]",
+                    string(&raw_format(&data.back_end)),
+                    string(&data.value)
+                ),
+            ),
+        )
+    }
+
+    /// Keywords of export backends, e.g. `#+HTML:`, are raw blocks
+    fn keyword(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &KeywordData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        match data.key.to_ascii_lowercase().as_str() {
+            format @ ("html" | "latex" | "beamer" | "texinfo") => block(tagged(
+                "RawBlock",
+                &format!("[{},{}]", string(format), string(&data.value)),
+            )),
+            _ => None,
+        }
+    }
+
+    fn latex_environment(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &LatexEnvironmentData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        block(tagged(
+            "RawBlock",
+            &format!("[\"latex\",{}]", code(&data.value)),
+        ))
+    }
+
+    fn special_block(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &SpecialBlockData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let attr = attributes(info, id, vec![data.type_s.to_string()]);
+        block(tagged(
+            "Div",
+            &format!("[{},{}]", attr, array(&contents.unwrap_or_default())),
+        ))
+    }
+
+    fn quote_block(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        block(tagged("BlockQuote", &array(&contents.unwrap_or_default())))
+    }
+
+    fn center_block(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        let attr = attributes(info, id, vec!["center".to_string()]);
+        block(tagged(
+            "Div",
+            &format!("[{},{}]", attr, array(&contents.unwrap_or_default())),
+        ))
+    }
+
+    fn verse_block(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        let contents = contents.unwrap_or_default();
+        let lines: Vec<String> = contents
+            .trim_end()
+            .trim_end_matches(SOFT_BREAK)
+            .split(SOFT_BREAK)
+            .map(inlines)
+            .collect();
+        block(tagged("LineBlock", &format!("[{}]", lines.join(","))))
+    }
+
+    fn inline_task(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &InlineTaskData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let title = tagged("Strong", &inlines(&info.secondary(&data.title)));
+        let title = tagged("Para", &format!("[{}]", title));
+        block(tagged(
+            "Div",
+            &format!(
+                "[{},{}]",
+                attr("", &["inlinetask".to_string()], &[]),
+                array(&format!("{},{}", title, contents.unwrap_or_default()))
+            ),
+        ))
+    }
+
+    fn horizontal_rule(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        block(constant("HorizontalRule"))
+    }
+
+    /// Not exported, see `org-export-with-properties`
+    fn property_drawer(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        None
+    }
+
+    /// Definitions are exported at their references
+    fn footnote_definition(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &FootnoteDefinitionData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        None
+    }
+
+    fn bold(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        inline(
+            info,
+            id,
+            tagged("Strong", &inlines(&contents.unwrap_or_default())),
+        )
+    }
+
+    fn italic(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        inline(
+            info,
+            id,
+            tagged("Emph", &inlines(&contents.unwrap_or_default())),
+        )
+    }
+
+    fn underline(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        inline(
+            info,
+            id,
+            tagged("Underline", &inlines(&contents.unwrap_or_default())),
+        )
+    }
+
+    fn strike_through(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        inline(
+            info,
+            id,
+            tagged("Strikeout", &inlines(&contents.unwrap_or_default())),
+        )
+    }
+
+    fn code(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &CodeData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let value = format!("[{},{}]", NO_ATTR, string(&data.value));
+        inline(info, id, tagged("Code", &value))
+    }
+
+    fn verbatim(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &VerbatimData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let value = format!("[{},{}]", NO_ATTR, string(&data.value));
+        inline(info, id, tagged("Code", &value))
+    }
+
+    fn inline_src_block(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &InlineSrcBlockData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let attr = attr("", &[data.language.to_string()], &[]);
+        let value = format!("[{},{}]", attr, string(&data.value));
+        inline(info, id, tagged("Code", &value))
+    }
+
+    fn entity(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &EntityData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        inline(info, id, tagged("Str", &string(&data.utf_8)))
+    }
+
+    fn latex_fragment(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &LatexFragmentData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let value = data.value.as_ref();
+        let delimited =
+            |open: &str, close: &str| value.strip_prefix(open).and_then(|v| v.strip_suffix(close));
+        let math = |kind: &str, math: &str| {
+            tagged("Math", &format!("[{},{}]", constant(kind), string(math)))
+        };
+        let fragment = if let Some(m) = delimited("$$", "$$").or_else(|| delimited("\\[", "\\]")) {
+            math("DisplayMath", m)
+        } else if let Some(m) = delimited("$", "$").or_else(|| delimited("\\(", "\\)")) {
+            math("InlineMath", m)
+        } else {
+            tagged("RawInline", &format!("[\"latex\",{}]", string(value)))
+        };
+        inline(info, id, fragment)
+    }
+
+    fn line_break(&self, info: &Info, id: NodeId, contents: Option<String>) -> Option<String> {
+        inline(info, id, constant("LineBreak"))
+    }
+
+    fn statistics_cookie(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &StatisticsCookieData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        inline(info, id, tagged("Str", &string(&data.value)))
+    }
+
+    fn subscript(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &SubscriptData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        inline(
+            info,
+            id,
+            tagged("Subscript", &inlines(&contents.unwrap_or_default())),
+        )
+    }
+
+    fn superscript(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &SuperscriptData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        inline(
+            info,
+            id,
+            tagged("Superscript", &inlines(&contents.unwrap_or_default())),
+        )
+    }
+
+    fn target(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &TargetData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let attr = attr(&info.reference(id), &[], &[]);
+        inline(info, id, tagged("Span", &format!("[{},[]]", attr)))
+    }
+
+    fn radio_target(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &RadioTargetData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let attr = attr(&info.reference(id), &[], &[]);
+        let contents = inlines(&contents.unwrap_or_default());
+        inline(
+            info,
+            id,
+            tagged("Span", &format!("[{},{}]", attr, contents)),
+        )
+    }
+
+    fn timestamp(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &TimestampData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let attr = attr("", &["timestamp".to_string()], &[]);
+        let text = inlines(&self.plain_text(info, &data.raw_value));
+        inline(info, id, tagged("Span", &format!("[{},{}]", attr, text)))
+    }
+
+    /// A note holding the definition, at every reference. Without a
+    /// definition, the number of the footnote stands for the note.
+    fn footnote_reference(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &FootnoteReferenceData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let footnote = info.footnote(id);
+        let definition = match footnote.and_then(|f| f.definition) {
+            Some(definition) => definition,
+            None => {
+                let label = footnote.map_or_else(
+                    || data.label.as_deref().unwrap_or_default().to_string(),
+                    |f| f.number.to_string(),
+                );
+                let placeholder = string(&format!("[{}]", label));
+                return inline(info, id, tagged("Str", &placeholder));
+            }
+        };
+        let blocks = match info.tree[definition].data {
+            Syntax::FootnoteReference(_) => {
+                tagged("Para", &inlines(&info.contents(definition))) + ","
+            }
+            _ => info.contents(definition),
+        };
+        inline(info, id, tagged("Note", &array(&blocks)))
+    }
+
+    fn link(
+        &self,
+        info: &Info,
+        id: NodeId,
+        data: &LinkData,
+        contents: Option<String>,
+    ) -> Option<String> {
+        let link = |description: String, target: &str| {
+            tagged(
+                "Link",
+                &format!(
+                    "[{},{},[{},\"\"]]",
+                    NO_ATTR,
+                    inlines(&description),
+                    string(target)
+                ),
+            )
+        };
+        if let Some(destination) = info.resolve_link(id) {
+            let description = contents.unwrap_or_else(|| match &info.tree[destination].data {
+                Syntax::Headline(h) => match section_number(info, destination) {
+                    Some(n) => self.plain_text(info, &n),
+                    None => info.secondary(&h.title),
+                },
+                _ => self.plain_text(info, &data.path),
+            });
+            let target = format!("#{}", anchor(info, destination));
+            return inline(info, id, link(description, &target));
+        }
+        let href = match link_href(data, "org") {
+            Some(href) => href,
+            None => {
+                // Unresolved internal link
+                let text = contents.unwrap_or_else(|| self.plain_text(info, &data.path));
+                return inline(info, id, tagged("Emph", &inlines(&text)));
+            }
+        };
+        if contents.is_none() && is_image(&data.path) {
+            // Attributes of the paragraph apply to the standalone image.
+            let attr = info.tree[id]
+                .parent()
+                .map_or(NO_ATTR.to_string(), |parent| {
+                    attributes(info, parent, vec![])
+                });
+            let image = format!("[{},[],[{},\"\"]]", attr, string(&href));
+            return inline(info, id, tagged("Image", &image));
+        }
+        let description = contents.unwrap_or_else(|| self.plain_text(info, &href));
+        inline(info, id, link(description, &href))
+    }
+}
+
+mod test {
+    use super::Pandoc;
+    use crate::data::*;
+    use crate::export::fixture::{node, parse};
+    use crate::export::{export, ExportOptions};
+    use std::borrow::Cow;
+
+    /// Lines of the export of `tree`, one per top level block
+    fn pandoc(tree: &SyntaxTree, options: &ExportOptions) -> Vec<String> {
+        export(tree, &Pandoc, options)
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn document() {
+        let text = "Intro with *bold* and\n\"quoted\" text.\n\
+                    * TODO Heading :work:\n\
+                    Text[fn::Inline note.] and [[https://orgmode.org][Org]]\n";
+        let tree = parse(text);
+        let options = ExportOptions {
+            title: Some("A title".to_string()),
+            author: Some("Jane".to_string()),
+            ..ExportOptions::default()
+        };
+        assert_eq!(
+            vec![
+                r#"{"pandoc-api-version":[1,23,1],"meta":{"#.to_string()
+                    + r#""author":{"t":"MetaList","c":[{"t":"MetaInlines","c":[{"t":"Str","c":"Jane"}]}]},"#
+                    + r#""lang":{"t":"MetaString","c":"en"},"#
+                    + r#""title":{"t":"MetaInlines","c":[{"t":"Str","c":"A"},{"t":"Space"},{"t":"Str","c":"title"}]}},"#
+                    + r#""blocks":[{"t":"Para","c":[{"t":"Str","c":"Intro"},{"t":"Space"},{"t":"Str","c":"with"},{"t":"Space"},"#
+                    + r#"{"t":"Strong","c":[{"t":"Str","c":"bold"}]},{"t":"Space"},{"t":"Str","c":"and"},{"t":"SoftBreak"},"#
+                    + r#"{"t":"Str","c":"\"quoted\""},{"t":"Space"},{"t":"Str","c":"text."}]},"#,
+                r#"{"t":"Header","c":[1,["org0000008",[],[]],["#.to_string()
+                    + r#"{"t":"Span","c":[["",["todo","TODO"],[]],[{"t":"Str","c":"TODO"}]]},{"t":"Space"},"#
+                    + r#"{"t":"Str","c":"Heading"},{"t":"Space"},"#
+                    + r#"{"t":"Span","c":[["",["tag"],[["tag-name","work"]]],[{"t":"SmallCaps","c":[{"t":"Str","c":"work"}]}]]}]]},"#,
+                r#"{"t":"Para","c":[{"t":"Str","c":"Text"},"#.to_string()
+                    + r#"{"t":"Note","c":[{"t":"Para","c":[{"t":"Str","c":"Inline"},{"t":"Space"},{"t":"Str","c":"note."}]}]},"#
+                    + r#"{"t":"Space"},{"t":"Str","c":"and"},{"t":"Space"},"#
+                    + r#"{"t":"Link","c":[["",[],[]],[{"t":"Str","c":"Org"}],["https://orgmode.org",""]]}]}]}"#,
+            ],
+            pandoc(&tree, &options)
+        );
+    }
+
+    #[test]
+    fn elements() {
+        let mut tree = parse(
+            "1) [@3] [X] Done\n\
+             2) Next\n\
+             #+NAME: counts\n\
+             #+ATTR_HTML: :class data :border 0\n\
+             | Name | Count |\n\
+             |------+-------|\n\
+             | a    |     1 |\n\
+             #+BEGIN_SRC rust\n\
+             let s = \"\\n\";\n\
+             #+END_SRC\n\
+             Costs 5\\euro, \\alpha{}x.\n",
+        );
+        // LaTeX fragments are not parsed yet
+        let root = tree.root();
+        let paragraph = tree.append(root, node(Syntax::Paragraph));
+        tree.append(
+            paragraph,
+            node(Syntax::LatexFragment(Box::new(LatexFragmentData {
+                value: Cow::from("$x^2$"),
+            }))),
+        );
+
+        let options = ExportOptions {
+            body_only: true,
+            ..ExportOptions::default()
+        };
+        let cell = |text: &str| {
+            format!(
+                r#"[["",[],[]],{{"t":"AlignDefault"}},1,1,[{{"t":"Plain","c":[{{"t":"Str","c":"{}"}}]}}]]"#,
+                text
+            )
+        };
+        assert_eq!(
+            vec![
+                r#"[{"t":"OrderedList","c":[[3,{"t":"Decimal"},{"t":"OneParen"}],["#.to_string()
+                    + r#"[{"t":"Plain","c":[{"t":"Str","c":"☒"},{"t":"Space"},{"t":"Str","c":"Done"}]}],"#,
+                r#"[{"t":"Plain","c":[{"t":"Str","c":"Next"}]}]]]},"#.to_string(),
+                r#"{"t":"Table","c":[["org0000009",["data"],[["border","0"]]],[null,[]],"#
+                    .to_string()
+                    + r#"[[{"t":"AlignLeft"},{"t":"ColWidthDefault"}],[{"t":"AlignRight"},{"t":"ColWidthDefault"}]],"#
+                    + &format!(
+                        r#"[["",[],[]],[[["",[],[]],[{},{}]]]],"#,
+                        cell("Name"),
+                        cell("Count")
+                    )
+                    + &format!(
+                        r#"[[["",[],[]],0,[],[[["",[],[]],[{},{}]]]]],"#,
+                        cell("a"),
+                        cell("1")
+                    )
+                    + r#"[["",[],[]],[]]]},"#,
+                r#"{"t":"CodeBlock","c":[["",["rust"],[]],"let s = \"\\n\";"]},"#.to_string(),
+                r#"{"t":"Para","c":[{"t":"Str","c":"Costs"},{"t":"Space"},{"t":"Str","c":"5"},"#
+                    .to_string()
+                    + r#"{"t":"Str","c":"€"},{"t":"Str","c":","},{"t":"Space"},"#
+                    + r#"{"t":"Str","c":"α"},{"t":"Str","c":"x."}]},"#,
+                r#"{"t":"Para","c":[{"t":"Math","c":[{"t":"InlineMath"},"x^2"]}]}]"#.to_string(),
+            ],
+            pandoc(&tree, &options)
+        );
+    }
+
+    /// A reference without definition keeps its place in the text
+    #[test]
+    fn missing_footnote_definition() {
+        let tree = parse("foo[fn:1] bar\n");
+        assert_eq!(
+            vec![
+                r#"{"pandoc-api-version":[1,23,1],"meta":{"lang":{"t":"MetaString","c":"en"}},"#
+                    .to_string()
+                    + r#""blocks":[{"t":"Para","c":[{"t":"Str","c":"foo"},{"t":"Str","c":"[1]"},"#
+                    + r#"{"t":"Space"},{"t":"Str","c":"bar"}]}]}"#
+            ],
+            pandoc(&tree, &ExportOptions::default())
+        );
+    }
+}
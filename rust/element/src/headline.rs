@@ -87,13 +87,19 @@
 //!    (headline))))
 //!
 
-use crate::data::{SyntaxNode, TimestampData};
+use crate::cursor::Cursor;
+use crate::data::{owned, Interval, StringOrObject, Syntax, SyntaxNode, SyntaxT, TimestampData};
+use crate::paragraph::REGEX_DRAWER_END;
 use crate::parser::Parser;
+use crate::source::{self, TextSource};
 use regex::Regex;
+use std::borrow::Cow;
 
 const ORG_CLOSED_STRING: &str = "CLOSED";
 const ORG_DEADLINE_STRING: &str = "DEADLINE";
 const ORG_SCHEDULED_STRING: &str = "SCHEDULED";
+const ORG_COMMENT_STRING: &str = "COMMENT";
+const ORG_ARCHIVE_TAG: &str = "ARCHIVE";
 
 lazy_static! {
     pub static ref REGEX_HEADLINE_SHORT: Regex = Regex::new(r"^\*+\s").unwrap();
@@ -112,93 +118,119 @@ lazy_static! {
     /// Requires multiline match
     /// correspond to org-property-drawer-re in org.el
     pub static ref REGEX_PROPERTY_DRAWER: Regex = Regex::new(
-        r"^[ \t]*:PROPERTIES:[ \t]*\n(?:[ \t]*:\S+:(?: .*)?[ \t]*\n)*?[ \t]*:END:[ \t]*")
+        r"(?i)^[ \t]*:PROPERTIES:[ \t]*\n(?:[ \t]*:\S+:(?: .*)?[ \t]*\n)*?[ \t]*:END:[ \t]*")
             .unwrap();
 
+    /// Matches a node property, name is in group 1 and value in group 2
+    /// elisp: `org-property-re`
+    pub(crate) static ref REGEX_NODE_PROPERTY: Regex =
+        Regex::new(r"^[ \t]*:((?:\S+|:)+?)\+?:(?:[ \t]+(.*?))?[ \t]*$").unwrap();
+
     pub static ref REGEX_CLOCK_LINE: Regex = Regex::new(r"^[ \t]*CLOCK:").unwrap();
 
+    /// Matches headline's priority cookie, priority character is in group 1
+    pub static ref REGEX_PRIORITY: Regex = Regex::new(r"^\[#(.)\][ \t]*").unwrap();
+
+    /// Matches COMMENT keyword at the beginning of the headline title
+    pub static ref REGEX_COMMENT_KEYWORD: Regex =
+        Regex::new(&format!(r"^{}(?: |$)", ORG_COMMENT_STRING)).unwrap();
+
+    /// Matches tags at the end of the headline, tags are in group 1
+    pub static ref REGEX_TAGS: Regex = Regex::new(r"[ \t]+(:[[:alnum:]_@#%:]+:)[ \t]*$").unwrap();
+
+    /// Matches the END line closing an inlinetask, after its stars
+    pub static ref REGEX_INLINETASK_END: Regex = Regex::new(r"^\*+[ \t]+END[ \t]*$").unwrap();
+
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct HeadlineData<'a> {
     /// Non_nil if the headline has an archive tag (boolean).
-    archivedp: bool,
+    pub archivedp: bool,
 
     /// Headline's CLOSED reference, if any (timestamp object or nil)
-    closed: Option<TimestampData<'a>>,
+    pub closed: Option<TimestampData<'a>>,
 
     /// Non_nil if the headline has a comment keyword (boolean).
-    commentedp: bool,
+    pub commentedp: bool,
 
     /// Headline's DEADLINE reference, if any (timestamp object or nil).
-    deadline: Option<TimestampData<'a>>,
+    pub deadline: Option<TimestampData<'a>>,
 
     /// Non_nil if the headline is a footnote section (boolean).
-    footnote_section_p: bool,
+    pub footnote_section_p: bool,
 
     /// Reduced level of the headline (integer).
-    level: usize,
+    pub level: usize,
 
     /// Number of blank lines between the headline
     /// and the first non_blank line of its contents (integer).
-    pre_blank: usize,
+    pub pre_blank: usize,
 
     /// Headline's priority, as a character (integer).
-    priority: usize,
+    pub priority: usize,
 
     /// Non_nil if the headline contains a quote keyword (boolean).
-    quotedp: bool,
+    pub quotedp: bool,
 
     /// Raw headline's text, without the stars and the tags (string).
-    raw_value: &'a str,
+    pub raw_value: Cow<'a, str>,
 
     /// Headline's SCHEDULED reference, if any (timestamp object or nil).
-    scheduled: Option<TimestampData<'a>>,
+    pub scheduled: Option<TimestampData<'a>>,
 
     /// Headline's tags, if any, without
     /// the archive tag. (list of strings).
-    tags: Vec<Tag<'a>>,
+    pub tags: Vec<Tag<'a>>,
 
     /// Parsed headline's text, without the stars
     /// and the tags (secondary string).
-    title: &'a str,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub title: StringOrObject<'a>,
 
     /// Headline's TODO keyword without quote and comment
     /// strings, if any (string or nil).
-    /// also used instead of todo-type
-    todo_keyword: TodoKeyword,
+    pub todo_keyword: Option<Cow<'a, str>>,
+
+    /// Type of headline's TODO keyword, if any (symbol done, todo).
+    pub todo_type: Option<TodoType>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct InlineTaskData<'a> {
     /// Inlinetask's CLOSED reference, if any (timestamp object or nil)
-    closed: Option<TimestampData<'a>>,
+    pub closed: Option<TimestampData<'a>>,
 
     /// Inlinetask's DEADLINE reference, if any (timestamp object or nil).
-    deadline: Option<TimestampData<'a>>,
+    pub deadline: Option<TimestampData<'a>>,
 
     /// Reduced level of the inlinetask (integer).
-    level: usize,
+    pub level: usize,
 
     /// Headline's priority, as a character (integer).
-    priority: usize,
+    pub priority: usize,
 
     /// Raw inlinetask's text, without the stars and the tags (string).
-    raw_value: &'a str,
+    pub raw_value: Cow<'a, str>,
 
     /// Inlinetask's SCHEDULED reference, if any (timestamp object or nil).
-    scheduled: Option<TimestampData<'a>>,
+    pub scheduled: Option<TimestampData<'a>>,
 
     /// Inlinetask's tags, if any (list of strings).
-    tags: Vec<Tag<'a>>,
+    pub tags: Vec<Tag<'a>>,
 
     /// Parsed inlinetask's text, without the stars
     /// and the tags (secondary string).
-    title: &'a str,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub title: StringOrObject<'a>,
 
     /// Inlinetask's TODO keyword, if any (string or nil).
+    pub todo_keyword: Option<Cow<'a, str>>,
+
     /// Type of inlinetask's TODO keyword, if any (symbol done, todo).
-    todo_keyword: Option<TodoKeyword>,
+    pub todo_type: Option<TodoType>,
 }
 
 // A planning is an element with the following pattern:
@@ -213,39 +245,773 @@ pub struct InlineTaskData<'a> {
 //
 // In particular, no blank line is allowed between PLANNING and HEADLINE.
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct NodePropertyData<'a> {
-    key: &'a str,
-    value: &'a str,
+    pub key: Cow<'a, str>,
+    pub value: Cow<'a, str>,
 }
 
-#[derive(Debug)]
-pub struct Tag<'a>(&'a str);
+/// First line of a headline or an inlinetask, see `Parser::heading_line`
+pub(crate) struct HeadingLine<'a> {
+    pub(crate) level: usize,
+    pub(crate) todo_keyword: Option<Cow<'a, str>>,
+    pub(crate) todo_type: Option<TodoType>,
+    pub(crate) priority: usize,
+    pub(crate) commentedp: bool,
+    /// Title without surrounding blanks
+    pub(crate) title: Interval,
+    pub(crate) tags: Vec<Tag<'a>>,
+    /// End of the line, before the newline
+    pub(crate) end: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Tag<'a>(pub Cow<'a, str>);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
+pub enum TodoType {
+    Todo,
+    Done,
+}
 
-#[derive(Debug)]
-pub enum TodoKeyword {
-    TODO,
-    DONE,
+impl<'a> HeadlineData<'a> {
+    pub fn into_owned(self) -> HeadlineData<'static> {
+        HeadlineData {
+            archivedp: self.archivedp,
+            closed: self.closed.map(TimestampData::into_owned),
+            commentedp: self.commentedp,
+            deadline: self.deadline.map(TimestampData::into_owned),
+            footnote_section_p: self.footnote_section_p,
+            level: self.level,
+            pre_blank: self.pre_blank,
+            priority: self.priority,
+            quotedp: self.quotedp,
+            raw_value: owned(self.raw_value),
+            scheduled: self.scheduled.map(TimestampData::into_owned),
+            tags: self.tags.into_iter().map(Tag::into_owned).collect(),
+            title: self.title.into_owned(),
+            todo_keyword: self.todo_keyword.map(owned),
+            todo_type: self.todo_type,
+        }
+    }
 }
 
-impl<'a> Parser<'a> {
-    // TODO implement headline_parser
-    pub fn headline_parser(&self) -> SyntaxNode<'a> {
-        unimplemented!()
+impl<'a> InlineTaskData<'a> {
+    pub fn into_owned(self) -> InlineTaskData<'static> {
+        InlineTaskData {
+            closed: self.closed.map(TimestampData::into_owned),
+            deadline: self.deadline.map(TimestampData::into_owned),
+            level: self.level,
+            priority: self.priority,
+            raw_value: owned(self.raw_value),
+            scheduled: self.scheduled.map(TimestampData::into_owned),
+            tags: self.tags.into_iter().map(Tag::into_owned).collect(),
+            title: self.title.into_owned(),
+            todo_keyword: self.todo_keyword.map(owned),
+            todo_type: self.todo_type,
+        }
     }
+}
 
-    // TODO implement inlinetask_parser
+impl<'a> NodePropertyData<'a> {
+    pub fn into_owned(self) -> NodePropertyData<'static> {
+        NodePropertyData {
+            key: owned(self.key),
+            value: owned(self.value),
+        }
+    }
+}
+
+impl<'a> Tag<'a> {
+    pub fn into_owned(self) -> Tag<'static> {
+        Tag(owned(self.0))
+    }
+}
+
+/// Returns the number of stars of a headline starting at `pos`
+pub(crate) fn headline_level<'a, S: TextSource<'a>>(input: S, pos: usize) -> usize {
+    (pos..input.len())
+        .take_while(|&p| input.byte_at(p) == b'*')
+        .count()
+}
+
+/// Returns the position where the subtree of a headline of the
+/// given `level` ends: the beginning of the next headline of the same
+/// or upper level, or the end of the input.
+/// Corresponds to `(org-end-of-subtree t t)`
+fn end_of_subtree<'a, S: TextSource<'a>>(input: S, pos: usize, level: usize) -> usize {
+    let mut c = Cursor::from_source(input, pos);
+    while let Some(headline) = c.next_headline() {
+        if headline_level(input, headline) <= level {
+            return headline;
+        }
+    }
+    input.len()
+}
+
+impl<'a, S: TextSource<'a>> Parser<'a, S> {
+    /// Returns true if a headline of this level is an inlinetask
+    pub(crate) fn is_inlinetask_level(&self, level: usize) -> bool {
+        self.options
+            .inlinetask_min_level
+            .map_or(false, |min| level >= min)
+    }
+
+    /// Return true if cursor is on a headline, inlinetasks
+    /// are not considered headlines.
+    /// corresponds to `(org-with-limited-levels (org-at-heading-p))`
+    pub(crate) fn on_headline(&self) -> bool {
+        let mut c = self.cursor.borrow_mut();
+        c.on_headline()
+            && !self
+                .is_inlinetask_level(headline_level(self.input, c.line_beginning_position(None)))
+    }
+
+    /// Possibly moves cursor to the beginning of the next headline,
+    /// skipping inlinetasks.
+    /// corresponds to `(org-with-limited-levels (outline-next-heading))`
+    pub(crate) fn next_headline(&self) -> Option<usize> {
+        let mut c = self.cursor.borrow_mut();
+        let pos = c.pos();
+        while let Some(start) = c.next_headline() {
+            if !self.is_inlinetask_level(headline_level(self.input, start)) {
+                return Some(start);
+            }
+        }
+        c.set(pos);
+        None
+    }
+
+    /// Possibly moves cursor to the beginning of the previous headline,
+    /// skipping inlinetasks.
+    /// corresponds to `(org-with-limited-levels (outline-previous-heading))`
+    pub(crate) fn prev_headline(&self) -> Option<usize> {
+        let mut c = self.cursor.borrow_mut();
+        let pos = c.pos();
+        while let Some(start) = c.prev_headline() {
+            if !self.is_inlinetask_level(headline_level(self.input, start)) {
+                return Some(start);
+            }
+        }
+        c.set(pos);
+        None
+    }
+
+    /// Parse a headline.
+    ///
+    /// Return a list whose CAR is `headline' and CDR is a plist
+    /// containing `:raw-value', `:title', `:begin', `:end',
+    /// `:pre-blank', `:contents-begin' and `:contents-end', `:level',
+    /// `:priority', `:tags', `:todo-keyword',`:todo-type', `:scheduled',
+    /// `:deadline', `:closed', `:archivedp', `:commentedp'
+    /// `:footnote-section-p', `:post-blank' and `:post-affiliated'
+    /// keywords.
+    ///
+    /// The plist also contains any property set in the property drawer,
+    /// with its name in upper cases and colons added at the
+    /// beginning (e.g., `:CUSTOM_ID').
+    ///
+    /// LIMIT is a buffer position bounding the search.
+    ///
+    /// When RAW-SECONDARY-P is non-nil, headline's title will not be
+    /// parsed as a secondary string, but as a plain string instead.
+    ///
+    /// Assume point is at beginning of the headline."
+    /// (defun org-element-headline-parser (limit &optional raw-secondary-p)
+    pub fn headline_parser(&self, limit: usize, raw_secondary_p: bool) -> SyntaxNode<'a> {
+        let begin = self.cursor.borrow().pos();
+        let line = self.heading_line(begin);
+        let raw_value = self.input.slice(line.title.start, line.title.end);
+        let archivedp = line.tags.iter().any(|t: &Tag| t.0 == ORG_ARCHIVE_TAG);
+        let footnote_section_p =
+            self.options.footnote_section.as_ref().map(String::as_str) == Some(&*raw_value);
+
+        let time = self.time_properties(line.end);
+        let mut c = self.cursor.borrow_mut();
+        // TODO add the properties of the property drawer
+        let end = end_of_subtree(self.input, begin, line.level).min(limit);
+
+        let contents_begin = {
+            c.set(line.end);
+            c.goto_next_line();
+            c.skip_chars_forward(" \r\t\n", Some(end));
+            if c.pos() < end {
+                Some(c.line_beginning_position(None))
+            } else {
+                None
+            }
+        };
+        let contents_end = contents_begin.map(|_| {
+            c.set(end);
+            c.skip_chars_backward(" \r\t\n", None);
+            c.line_beginning_position(Some(2))
+        });
+
+        let pre_blank = match contents_begin {
+            Some(cbeg) => c.count_lines(begin, cbeg) - 1,
+            None => 0,
+        };
+        let post_blank = match contents_end {
+            Some(cend) => c.count_lines(cend, end),
+            None => c.count_lines(begin, end) - 1,
+        };
+        drop(c);
+
+        let title = self.parse_secondary_string(line.title, SyntaxT::Headline, raw_secondary_p);
+        self.cursor.borrow_mut().set(begin);
+
+        let data = HeadlineData {
+            archivedp,
+            closed: time.closed,
+            commentedp: line.commentedp,
+            deadline: time.deadline,
+            footnote_section_p,
+            level: line.level,
+            pre_blank,
+            priority: line.priority,
+            quotedp: false,
+            raw_value,
+            scheduled: time.scheduled,
+            tags: line.tags,
+            title,
+            todo_keyword: line.todo_keyword,
+            todo_type: line.todo_type,
+        };
+
+        SyntaxNode::new(
+            Syntax::Headline(Box::new(data)),
+            Interval { start: begin, end },
+            contents_begin.map(|start| Interval {
+                start,
+                end: contents_end.unwrap(),
+            }),
+            post_blank,
+        )
+    }
+
+    /// Parse an inline task.
+    ///
+    /// Return a list whose CAR is `inlinetask' and CDR is a plist
+    /// containing `:title', `:begin', `:end', `:pre-blank',
+    /// `:contents-begin' and `:contents-end', `:level', `:priority',
+    /// `:raw-value', `:tags', `:todo-keyword', `:todo-type',
+    /// `:scheduled', `:deadline', `:closed', `:post-blank' and
+    /// `:post-affiliated' keywords.
+    ///
+    /// The plist also contains any property set in the property drawer,
+    /// with its name in upper cases and colons added at the
+    /// beginning (e.g., `:CUSTOM_ID').
+    ///
+    /// When optional argument RAW-SECONDARY-P is non-nil, inline-task's
+    /// title will not be parsed as a secondary string, but as a plain
+    /// string instead.
+    ///
+    /// Assume point is at beginning of the inline task."
+    /// (defun org-element-inlinetask-parser (limit &optional raw-secondary-p)
     pub fn inlinetask_parser(&self, limit: usize, raw_secondary_p: bool) -> SyntaxNode<'a> {
-        unimplemented!()
+        let begin = self.cursor.borrow().pos();
+        let line = self.heading_line(begin);
+        let time = self.time_properties(line.end);
+
+        let mut c = self.cursor.borrow_mut();
+        // The task is closed by the next headline when it is an END line.
+        let task_end = {
+            c.set(line.end);
+            match c.re_search_forward(&*REGEX_HEADLINE_MULTILINE, Some(limit)) {
+                Some(_) => {
+                    let bol = c.line_beginning_position(None);
+                    c.set(bol);
+                    c.looking_at(&*REGEX_INLINETASK_END).map(|_| bol)
+                }
+                None => None,
+            }
+        };
+        // TODO add the properties of the property drawer
+        let contents_begin = task_end.and_then(|task_end| {
+            c.set(line.end);
+            c.goto_next_line();
+            c.skip_chars_forward(" \t\n", None);
+            if line.end < task_end {
+                Some(c.line_beginning_position(None))
+            } else {
+                None
+            }
+        });
+        let contents_end = contents_begin.and(task_end);
+        let end = {
+            c.set(task_end.unwrap_or(line.end));
+            c.goto_next_line();
+            c.skip_chars_forward(" \r\t\n", Some(limit));
+            if c.pos() == self.input.len() {
+                c.pos()
+            } else {
+                c.line_beginning_position(None)
+            }
+        };
+        let post_blank = c.count_lines(task_end.unwrap_or(begin), end).max(1) - 1;
+        drop(c);
+
+        let title = self.parse_secondary_string(line.title, SyntaxT::InlineTask, raw_secondary_p);
+        self.cursor.borrow_mut().set(begin);
+
+        let data = InlineTaskData {
+            closed: time.closed,
+            deadline: time.deadline,
+            level: line.level,
+            priority: line.priority,
+            raw_value: self.input.slice(line.title.start, line.title.end),
+            scheduled: time.scheduled,
+            tags: line.tags,
+            title,
+            todo_keyword: line.todo_keyword,
+            todo_type: line.todo_type,
+        };
+
+        SyntaxNode::new(
+            Syntax::InlineTask(Box::new(data)),
+            Interval { start: begin, end },
+            contents_begin.map(|start| Interval {
+                start,
+                end: contents_end.unwrap(),
+            }),
+            post_blank,
+        )
     }
 
-    // TODO implement property_drawer_parser
+    /// Parse the first line of a headline or an inlinetask starting
+    /// at `begin`: stars, TODO keyword, priority, COMMENT keyword,
+    /// title and tags. Common part of `headline_parser` and
+    /// `inlinetask_parser`, leaves the cursor where it was.
+    pub(crate) fn heading_line(&self, begin: usize) -> HeadingLine<'a> {
+        let mut c = self.cursor.borrow_mut();
+        let saved = c.pos();
+
+        let level = headline_level(self.input, begin);
+        c.set(begin + level);
+        c.skip_chars_forward(" \t", None);
+
+        let todo_keyword = match self.re.todo.as_ref().and_then(|re| c.capturing_at(re)) {
+            Some(cap) => {
+                c.set(cap.end());
+                c.skip_chars_forward(" \t", None);
+                Some(cap.get(1).unwrap().text())
+            }
+            None => None,
+        };
+        let todo_type = todo_keyword.as_ref().map(|k| {
+            if self.options.done_keywords.iter().any(|d| d == k.as_ref()) {
+                TodoType::Done
+            } else {
+                TodoType::Todo
+            }
+        });
+
+        let priority = match c.capturing_at(&*REGEX_PRIORITY) {
+            Some(cap) => {
+                c.set(cap.end());
+                cap.get(1).unwrap().as_str().chars().next().unwrap() as usize
+            }
+            None => 0,
+        };
+
+        let commentedp = match c.looking_at(&*REGEX_COMMENT_KEYWORD) {
+            Some(end) => {
+                c.set(end);
+                true
+            }
+            None => false,
+        };
+
+        let title_start = c.pos();
+        let line_end = self
+            .input
+            .find_byte(b'\n', title_start, self.input.len())
+            .unwrap_or_else(|| self.input.len());
+        let line = self.input.slice(title_start, line_end);
+        let (title_end, tags) = match REGEX_TAGS.captures(&line) {
+            Some(cap) => {
+                let tags = cap.get(1).unwrap();
+                let mut tags_start = tags.start();
+                let mut tags_list = vec![];
+                for tag in tags.as_str().split(':') {
+                    if !tag.is_empty() {
+                        let tag_end = tags_start + tag.len();
+                        tags_list.push(Tag(source::sub(&line, tags_start, tag_end)));
+                    }
+                    tags_start += tag.len() + 1;
+                }
+                (title_start + cap.get(0).unwrap().start(), tags_list)
+            }
+            None => (line_end, vec![]),
+        };
+        let title = &line[..title_end - title_start];
+        let start = title_start + title.len() - title.trim_start().len();
+        let end = title_start + title.trim_end().len();
+        c.set(saved);
+
+        HeadingLine {
+            level,
+            todo_keyword,
+            todo_type,
+            priority,
+            commentedp,
+            title: Interval {
+                start,
+                end: end.max(start),
+            },
+            tags,
+            end: line_end,
+        }
+    }
+
+    /// Parse a property drawer.
+    ///
+    /// LIMIT bounds the search.
+    ///
+    /// Return a list whose car is `property-drawer' and cdr is a plist
+    /// containing `:begin', `:end', `:contents-begin', `:contents-end',
+    /// `:post-blank' and `:post-affiliated' keywords.
+    ///
+    /// Assume point is at the beginning of the property drawer.
+    /// (defun org-element-property-drawer-parser (limit)
     pub fn property_drawer_parser(&self, limit: usize) -> SyntaxNode<'a> {
-        unimplemented!()
+        let mut c = self.cursor.borrow_mut();
+        let begin = c.pos();
+        let contents_begin = c.line_beginning_position(Some(2));
+        let drawer_end = self
+            .lines(contents_begin, limit)
+            .find(|&(line, end)| REGEX_DRAWER_END.is_match(&self.input.slice(line, end)))
+            .map_or(limit, |(line, _)| line);
+        let contents_end = Some(drawer_end).filter(|&end| end > contents_begin);
+
+        c.set(drawer_end);
+        let before_blank = c.goto_next_line();
+        c.skip_chars_forward(" \r\t\n", Some(limit));
+        let end = if c.pos() >= self.input.len() {
+            self.input.len()
+        } else {
+            c.line_beginning_position(None)
+        }
+        .min(limit);
+        let post_blank = c.count_lines(before_blank, end);
+        c.set(begin);
+
+        SyntaxNode::new(
+            Syntax::PropertyDrawer,
+            Interval { start: begin, end },
+            contents_end.map(|end| Interval {
+                start: contents_begin,
+                end,
+            }),
+            post_blank,
+        )
     }
 
-    // TODO implement node_property_parser
+    /// Parse a node-property at point.
+    ///
+    /// LIMIT bounds the search.
+    ///
+    /// Return a list whose car is `node-property' and cdr is a plist
+    /// containing `:key', `:value', `:begin', `:end', `:post-blank' and
+    /// `:post-affiliated' keywords.
+    /// (defun org-element-node-property-parser (limit)
     pub fn node_property_parser(&self, limit: usize) -> SyntaxNode<'a> {
-        unimplemented!()
+        let mut c = self.cursor.borrow_mut();
+        let begin = c.pos();
+        let line_end = c.line_end_position(None).min(limit);
+        let line = self.input.slice(begin, line_end);
+        let (key, value) = match REGEX_NODE_PROPERTY.captures(&line) {
+            Some(cap) => {
+                let key = cap.get(1).unwrap();
+                let value = cap.get(2).map_or((0, 0), |m| (m.start(), m.end()));
+                (
+                    source::sub(&line, key.start(), key.end()),
+                    source::sub(&line, value.0, value.1),
+                )
+            }
+            None => (Cow::from(""), Cow::from("")),
+        };
+        let from = c.line_beginning_position(Some(2)).min(limit);
+        let end = self
+            .lines(from, limit)
+            .find(|&(line, end)| REGEX_NODE_PROPERTY.is_match(&self.input.slice(line, end)))
+            .map_or(limit, |(line, _)| line);
+
+        SyntaxNode::new(
+            Syntax::NodeProperty(Box::new(NodePropertyData { key, value })),
+            Interval { start: begin, end },
+            None,
+            0,
+        )
+    }
+}
+
+mod test {
+    use crate::data::{Interval, Syntax};
+    use crate::headline::TodoType;
+    use crate::options::ParserOptions;
+    use crate::parser::{ParseGranularity, Parser};
+
+    #[test]
+    fn headline_parser() {
+        let text = "** DONE [#A] COMMENT Title text  :a:ARCHIVE:\n\nBody\n\n*** Child\n** Next\n";
+        let parser = Parser::new(text, ParseGranularity::Element, ParserOptions::default());
+        let node = parser.headline_parser(text.len(), true);
+        let next = text.find("** Next").unwrap();
+        assert_eq!(
+            Interval {
+                start: 0,
+                end: next
+            },
+            node.location
+        );
+        assert_eq!(
+            Some(Interval {
+                start: text.find("Body").unwrap(),
+                end: next
+            }),
+            node.content_location
+        );
+        assert_eq!(0, node.post_blank);
+        match node.data {
+            Syntax::Headline(h) => {
+                assert_eq!(2, h.level);
+                assert_eq!(1, h.pre_blank);
+                assert_eq!('A' as usize, h.priority);
+                assert!(h.commentedp);
+                assert!(h.archivedp);
+                assert_eq!("Title text", h.raw_value);
+                let tags: Vec<_> = h.tags.iter().map(|t| t.0.as_ref()).collect();
+                assert_eq!(vec!["a", "ARCHIVE"], tags);
+                assert_eq!(Some("DONE"), h.todo_keyword.as_ref().map(|k| k.as_ref()));
+                assert_eq!(Some(TodoType::Done), h.todo_type);
+            }
+            _ => panic!("expected headline"),
+        }
+
+        let empty = "* \n\n\n* Next";
+        let parser = Parser::new(empty, ParseGranularity::Element, ParserOptions::default());
+        let node = parser.headline_parser(empty.len(), true);
+        assert_eq!(5, node.location.end);
+        assert_eq!(None, node.content_location);
+        assert_eq!(2, node.post_blank);
+    }
+
+    #[test]
+    fn configured() {
+        let options = ParserOptions {
+            footnote_section: Some("Notes".to_string()),
+            inlinetask_min_level: Some(3),
+            todo_keywords: vec!["NEXT".to_string()],
+            done_keywords: vec!["CANCELLED".to_string()],
+            ..ParserOptions::default()
+        };
+        let text = "* CANCELLED Notes\n*** TODO inlinetask\n** B\n";
+        let parser = Parser::new(text, ParseGranularity::Element, options);
+        match parser.headline_parser(text.len(), true).data {
+            Syntax::Headline(h) => {
+                assert!(h.footnote_section_p);
+                assert_eq!(Some(TodoType::Done), h.todo_type);
+                assert_eq!("Notes", h.raw_value);
+            }
+            _ => panic!("expected headline"),
+        }
+
+        // Inlinetasks are not headlines
+        let b = text.find("** B").unwrap();
+        assert_eq!(Some(b), parser.next_headline());
+        parser.cursor.borrow_mut().set(b - 1);
+        assert!(!parser.on_headline());
+        assert_eq!(Some(0), parser.prev_headline());
+        parser.cursor.borrow_mut().set(b);
+        match parser.headline_parser(text.len(), true).data {
+            Syntax::Headline(h) => {
+                assert_eq!(None, h.todo_keyword);
+                assert_eq!("B", h.raw_value);
+            }
+            _ => panic!("expected headline"),
+        }
+    }
+
+    #[test]
+    fn parsed_title() {
+        use crate::data::{StringOrObject, SyntaxT};
+
+        let text = "* TODO [[https://orgmode.org][Org]] is *fun* [1/2] :tag:\nBody\n";
+        let parser = Parser::new(text, ParseGranularity::Object, ParserOptions::default());
+        let tree = parser.parse_buffer();
+        let headline = tree.children(tree.root()).next().unwrap();
+        let title = match &tree[headline].data {
+            Syntax::Headline(h) => {
+                assert_eq!("[[https://orgmode.org][Org]] is *fun* [1/2]", h.raw_value);
+                h.title.objects().to_vec()
+            }
+            _ => panic!("expected headline"),
+        };
+        let types: Vec<_> = title
+            .iter()
+            .map(|&id| SyntaxT::from(&tree[id].data))
+            .collect();
+        assert_eq!(
+            vec![
+                SyntaxT::Link,
+                SyntaxT::PlainText,
+                SyntaxT::Bold,
+                SyntaxT::StatisticsCookie
+            ],
+            types
+        );
+        assert!(title.iter().all(|&id| tree[id].parent == Some(headline)));
+        let bold = tree.children(title[2]).next().unwrap();
+        assert_eq!("fun", tree.text_of(bold));
+
+        let parser = Parser::new(text, ParseGranularity::Element, ParserOptions::default());
+        match parser.headline_parser(text.len(), true).data {
+            Syntax::Headline(h) => assert_eq!(
+                StringOrObject::Raw("[[https://orgmode.org][Org]] is *fun* [1/2]".into()),
+                h.title
+            ),
+            _ => panic!("expected headline"),
+        }
+    }
+
+    #[test]
+    fn property_drawer() {
+        use crate::data::{NodePropertyData, SyntaxT};
+
+        let text = "* H\n:PROPERTIES:\n:ID:  42 \n:EMPTY:\n:Key+: more\n:END:\n\nText\n\
+                    * Empty\n  :properties:\n  :end:\n";
+        let parser = Parser::new(text, ParseGranularity::Element, ParserOptions::default());
+        let tree = parser.parse_buffer();
+        let nodes: Vec<_> = tree
+            .descendants(tree.root())
+            .map(|id| {
+                let node = &tree[id];
+                (
+                    SyntaxT::from(&node.data),
+                    node.location.start,
+                    node.location.end,
+                    node.post_blank,
+                )
+            })
+            .filter(|node| node.0 == SyntaxT::PropertyDrawer || node.0 == SyntaxT::NodeProperty)
+            .collect();
+        let empty = text.find("  :properties:").unwrap();
+        assert_eq!(
+            vec![
+                (SyntaxT::PropertyDrawer, 4, 54, 1),
+                (SyntaxT::NodeProperty, 17, 27, 0),
+                (SyntaxT::NodeProperty, 27, 35, 0),
+                (SyntaxT::NodeProperty, 35, 47, 0),
+                (SyntaxT::PropertyDrawer, empty, text.len(), 0),
+            ],
+            nodes
+        );
+        let properties: Vec<_> = tree
+            .descendants(tree.root())
+            .filter_map(|id| match &tree[id].data {
+                Syntax::NodeProperty(p) => Some((p.key.to_string(), p.value.to_string())),
+                _ => None,
+            })
+            .collect();
+        let property = |key: &str, value: &str| (key.to_string(), value.to_string());
+        assert_eq!(
+            vec![
+                property("ID", "42"),
+                property("EMPTY", ""),
+                property("Key+", "more")
+            ],
+            properties
+        );
+    }
+
+    #[test]
+    fn timestamp_in_title() {
+        use crate::data::{SyntaxT, TimestampType};
+
+        let text = "* TODO Hello <2024-01-01 Mon> [1/2]\n";
+        let parser = Parser::new(text, ParseGranularity::Object, ParserOptions::default());
+        let tree = parser.parse_buffer();
+        let headline = tree.children(tree.root()).next().unwrap();
+        let title = match &tree[headline].data {
+            Syntax::Headline(h) => h.title.objects().to_vec(),
+            _ => panic!("expected headline"),
+        };
+        let types: Vec<_> = title
+            .iter()
+            .map(|&id| SyntaxT::from(&tree[id].data))
+            .collect();
+        assert_eq!(
+            vec![
+                SyntaxT::PlainText,
+                SyntaxT::Timestamp,
+                SyntaxT::StatisticsCookie
+            ],
+            types
+        );
+        match &tree[title[1]].data {
+            Syntax::Timestamp(t) => {
+                assert_eq!("<2024-01-01 Mon>", t.raw_value);
+                assert_eq!(TimestampType::Active, t.type_s);
+                assert_eq!((2024, 1, 1), (t.year_start, t.month_start, t.day_start));
+            }
+            _ => panic!("expected timestamp"),
+        }
+        assert_eq!(1, tree[title[1]].post_blank);
+    }
+
+    #[test]
+    fn inlinetask_parser() {
+        use crate::data::SyntaxT;
+
+        let options = ParserOptions {
+            inlinetask_min_level: Some(3),
+            ..ParserOptions::default()
+        };
+        let text = "*** TODO Task /now/ :t:\nInside\n*** END\n\nAfter\n";
+        let parser = Parser::new(text, ParseGranularity::Object, options.clone());
+        let node = parser.inlinetask_parser(text.len(), false);
+        let after = text.find("After").unwrap();
+        assert_eq!(
+            Interval {
+                start: 0,
+                end: after
+            },
+            node.location
+        );
+        let inside = text.find("Inside").unwrap();
+        assert_eq!(
+            Some(Interval {
+                start: inside,
+                end: text.find("*** END").unwrap()
+            }),
+            node.content_location
+        );
+        assert_eq!(1, node.post_blank);
+        match &node.data {
+            Syntax::InlineTask(t) => {
+                assert_eq!(3, t.level);
+                assert_eq!("Task /now/", t.raw_value);
+                assert_eq!(Some(TodoType::Todo), t.todo_type);
+                assert_eq!(2, t.title.objects().len());
+                let italic = t.title.objects()[1];
+                assert_eq!(
+                    SyntaxT::Italic,
+                    SyntaxT::from(&parser.tree.borrow()[italic].data)
+                );
+            }
+            _ => panic!("expected inlinetask"),
+        }
+
+        // Without an END line, the task is a single line
+        let text = "*** Task\nText\n";
+        let parser = Parser::new(text, ParseGranularity::Element, options);
+        let node = parser.inlinetask_parser(text.len(), true);
+        assert_eq!(Interval { start: 0, end: 9 }, node.location);
+        assert_eq!(None, node.content_location);
+        assert_eq!(0, node.post_blank);
     }
 }
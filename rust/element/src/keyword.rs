@@ -15,26 +15,37 @@
 //
 
 use crate::affiliated::AffiliatedData;
-use crate::data::SyntaxNode;
 use crate::data::SyntaxT;
+use crate::data::{owned, Interval, Syntax, SyntaxNode};
 use crate::parser::Parser;
+use crate::source::TextSource;
 use regex::{Match, Regex};
 use std::borrow::Cow;
 
 lazy_static! {
     pub static ref REGEX_KEYWORD: Regex = Regex::new(r"\+\S+:").unwrap();
+    static ref REGEX_KEYWORD_LINE: Regex = Regex::new(r"^[ \t]*#\+(\S*)[ \t]*:").unwrap();
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct KeywordData<'a> {
     /// Keyword's name (string).
-    key: &'a str,
+    pub key: Cow<'a, str>,
     /// Keyword's value (string).
-    value: &'a str,
+    pub value: Cow<'a, str>,
 }
 
-impl<'a> Parser<'a> {
-    // TODO implement keyword_parser
+impl<'a> KeywordData<'a> {
+    pub fn into_owned(self) -> KeywordData<'static> {
+        KeywordData {
+            key: owned(self.key),
+            value: owned(self.value),
+        }
+    }
+}
+
+impl<'a, S: TextSource<'a>> Parser<'a, S> {
     /// Parse a keyword at point.
     ///
     /// LIMIT bounds the search.  AFFILIATED is a list of which CAR is
@@ -45,17 +56,88 @@ impl<'a> Parser<'a> {
     /// Return a list whose CAR is `keyword' and CDR is a plist
     /// containing `:key', `:value', `:begin', `:end', `:post-blank' and
     /// `:post-affiliated' keywords."
-
+    /// (defun org-element-keyword-parser (limit affiliated)
     pub fn keyword_parser(
         &self,
         limit: usize,
         start: usize,
-        maybe_aff: Option<AffiliatedData>,
+        maybe_aff: Option<AffiliatedData<'a>>,
     ) -> SyntaxNode<'a> {
-        // (save-excursion
-        //   ;; An orphaned affiliated keyword is considered as a regular
-        //   ;; keyword.  In this case AFFILIATED is nil, so we take care of
-        //   ;; this corner case.
-        unimplemented!()
+        // An orphaned affiliated keyword is considered as a regular
+        // keyword.  In this case AFFILIATED is nil, so we take care of
+        // this corner case.
+        let mut c = self.cursor.borrow_mut();
+        let post_affiliated = c.pos();
+        let cap = c
+            .capturing_at(&*REGEX_KEYWORD_LINE)
+            .expect("keyword parser called outside of a keyword");
+        let key = cap.get(1).unwrap().text();
+        let key = if key.chars().any(char::is_lowercase) {
+            Cow::Owned(key.to_uppercase())
+        } else {
+            key
+        };
+        let value_start = cap.end();
+        let line_end = c.line_end_position(None);
+        let value = match self.input.slice(value_start, line_end) {
+            Cow::Borrowed(value) => Cow::Borrowed(value.trim()),
+            Cow::Owned(value) => Cow::Owned(value.trim().to_string()),
+        };
+        c.goto_next_line();
+        let pos_before_blank = c.pos();
+        c.skip_chars_forward(" \r\t\n", Some(limit));
+        let end = if c.pos() >= self.input.len() {
+            self.input.len()
+        } else {
+            c.line_beginning_position(None)
+        }
+        .min(limit);
+        let post_blank = c.count_lines(pos_before_blank, end);
+        c.set(post_affiliated);
+
+        let mut node = SyntaxNode::new(
+            Syntax::Keyword(Box::new(KeywordData { key, value })),
+            Interval { start, end },
+            None,
+            post_blank,
+        );
+        node.affiliated = maybe_aff
+            .filter(|aff| *aff != AffiliatedData::default())
+            .map(Box::new);
+        node
+    }
+}
+
+mod test {
+    use crate::data::Syntax;
+    use crate::options::ParserOptions;
+    use crate::parser::{ParseGranularity, Parser};
+
+    #[test]
+    fn keywords() {
+        let text = "#+TITLE:  A title \n#+options: toc:nil <:nil\n\n#+EMPTY:\nText\n";
+        let parser = Parser::new(text, ParseGranularity::Element, ParserOptions::default());
+        let tree = parser.parse_buffer();
+        let keywords: Vec<_> = tree
+            .descendants(tree.root())
+            .filter_map(|id| match &tree[id].data {
+                Syntax::Keyword(k) => Some((
+                    k.key.as_ref(),
+                    k.value.as_ref(),
+                    tree[id].location.end,
+                    tree[id].post_blank,
+                )),
+                _ => None,
+            })
+            .collect();
+        let at = |s: &str| text.find(s).unwrap();
+        assert_eq!(
+            vec![
+                ("TITLE", "A title", at("#+options"), 0),
+                ("OPTIONS", "toc:nil <:nil", at("#+EMPTY"), 1),
+                ("EMPTY", "", at("Text"), 0),
+            ],
+            keywords
+        );
     }
 }
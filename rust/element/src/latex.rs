@@ -23,9 +23,11 @@ use crate::affiliated::AffiliatedData;
 /// NAME is constituted of alpha-numeric or asterisk characters.
 ///
 /// CONTENTS can contain anything but the “\end{NAME}” string.
-use crate::data::SyntaxNode;
+use crate::data::{owned, Interval, Syntax, SyntaxNode};
 use crate::parser::Parser;
+use crate::source::TextSource;
 use regex::Regex;
+use std::borrow::Cow;
 
 // TODO wirte latex regexes
 lazy_static! {
@@ -33,7 +35,7 @@ lazy_static! {
 
     /// Regexp matching the beginning of a LaTeX environment.
     /// The environment is captured by the first group.
-    pub static ref REGEX_LATEX_BEGIN_ENVIRIONMENT: Regex = Regex::new(r"^[ \t]*\\begin{([A-Za-z0-9*]+)}").unwrap();
+    pub static ref REGEX_LATEX_BEGIN_ENVIRIONMENT: Regex = Regex::new(r"^[ \t]*\\begin\{([A-Za-z0-9*]+)\}").unwrap();
 }
 
 /// Format string matching the ending of a LaTeX environment
@@ -41,34 +43,60 @@ lazy_static! {
 /// regex can't be made static as it should match the opening part
 ///
 /// In ideal world this should be replaced by a proper parser
-pub static FMTSTR_LATEX_END_ENVIRONMENT: &str = r"\\end{%s}[ \t]*$";
+pub static FMTSTR_LATEX_END_ENVIRONMENT: &str = r"(?i)\\end\{%s\}[ \t]*$";
 
-#[derive(Debug)]
+/// Regexp matching the ending of the LaTeX environment `name`,
+/// to be compiled with `Parser::regex`
+pub(crate) fn latex_end_environment(name: &str) -> String {
+    FMTSTR_LATEX_END_ENVIRONMENT.replace("%s", &regex::escape(name))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct LatexEnvironmentData<'a> {
     /// Buffer position at first affiliated keyword or
     /// at the beginning of the first line of environment (integer).
-    begin: usize,
+    pub begin: usize,
 
     /// Buffer position at the first non_blank line
     /// after last line of the environment, or buffer's end (integer).
-    end: usize,
+    pub end: usize,
 
     /// Number of blank lines between last environment's
     /// line and next non_blank line or buffer's end (integer).
-    post_blank: usize,
+    pub post_blank: usize,
 
     ///LaTeX code (string).
-    value: &'a str,
+    pub value: Cow<'a, str>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct LatexFragmentData<'a> {
     ///LaTeX code (string).
-    value: &'a str,
+    pub value: Cow<'a, str>,
+}
+
+impl<'a> LatexEnvironmentData<'a> {
+    pub fn into_owned(self) -> LatexEnvironmentData<'static> {
+        LatexEnvironmentData {
+            begin: self.begin,
+            end: self.end,
+            post_blank: self.post_blank,
+            value: owned(self.value),
+        }
+    }
+}
+
+impl<'a> LatexFragmentData<'a> {
+    pub fn into_owned(self) -> LatexFragmentData<'static> {
+        LatexFragmentData {
+            value: owned(self.value),
+        }
+    }
 }
 
-impl<'a> Parser<'a> {
-    // TODO implement latext_environment_parser
+impl<'a, S: TextSource<'a>> Parser<'a, S> {
     /// Parse a LaTeX environment.
     /// LIMIT bounds the search.  AFFILIATED is a list of which CAR is
     /// the buffer position at the beginning of the first affiliated
@@ -80,12 +108,97 @@ impl<'a> Parser<'a> {
     /// `:post-affiliated' keywords.
     ///
     /// Assume point is at the beginning of the latex environment."
+    /// (defun org-element-latex-environment-parser (limit affiliated)
     pub fn latex_environment_parser(
         &self,
         limit: usize,
         start: usize,
-        maybe_aff: Option<AffiliatedData>,
+        maybe_aff: Option<AffiliatedData<'a>>,
     ) -> SyntaxNode<'a> {
-        unimplemented!()
+        let code_begin = self.cursor.borrow().pos();
+        let name = self
+            .cursor
+            .borrow_mut()
+            .capturing_at(&*REGEX_LATEX_BEGIN_ENVIRIONMENT)
+            .expect("latex environment parser called outside of an environment")
+            .get(1)
+            .unwrap()
+            .as_str()
+            .to_string();
+        let end_re = self.regex(latex_end_environment(&name));
+        let end_line = self
+            .lines(code_begin, limit)
+            .find(|&(line, end)| end_re.is_match(&self.input.slice(line, end)));
+        let code_end = match end_line {
+            Some((_, end)) => (end + 1).min(self.input.len()),
+            // Incomplete latex environment: parse it as a paragraph.
+            None => return self.paragraph_parser(limit, start, maybe_aff),
+        };
+        let value = self.input.slice(code_begin, code_end);
+
+        let mut c = self.cursor.borrow_mut();
+        c.set(code_end);
+        c.skip_chars_forward(" \r\t\n", Some(limit));
+        let end = if c.pos() >= self.input.len() {
+            self.input.len()
+        } else {
+            c.line_beginning_position(None)
+        }
+        .min(limit);
+        let post_blank = c.count_lines(code_end, end);
+        c.set(code_begin);
+
+        let mut node = SyntaxNode::new(
+            Syntax::LatexEnvironment(Box::new(LatexEnvironmentData {
+                begin: start,
+                end,
+                post_blank,
+                value,
+            })),
+            Interval { start, end },
+            None,
+            post_blank,
+        );
+        node.affiliated = maybe_aff
+            .filter(|aff| *aff != AffiliatedData::default())
+            .map(Box::new);
+        node
+    }
+}
+
+mod test {
+    use crate::data::Syntax;
+    use crate::options::ParserOptions;
+    use crate::parser::{ParseGranularity, Parser};
+
+    #[test]
+    fn latex_environments() {
+        let text = "#+NAME: eq\n\\begin{equation*}\nx = 1\n\\END{equation*}  \n\n\
+                    \\begin{align}\nno end\n";
+        let parser = Parser::new(text, ParseGranularity::Element, ParserOptions::default());
+        let tree = parser.parse_buffer();
+        let section = tree.children(tree.root()).next().unwrap();
+        let mut elements = tree.children(section);
+
+        let environment = elements.next().unwrap();
+        match &tree[environment].data {
+            Syntax::LatexEnvironment(data) => assert_eq!(
+                "\\begin{equation*}\nx = 1\n\\END{equation*}  \n",
+                data.value
+            ),
+            data => panic!("not a LaTeX environment: {:?}", data),
+        }
+        assert!(tree[environment].affiliated.is_some());
+        let second = text.find("\\begin{align}").unwrap();
+        assert_eq!((0, second), {
+            let location = &tree[environment].location;
+            (location.start, location.end)
+        });
+        assert_eq!(1, tree[environment].post_blank);
+
+        // Incomplete environment: parsed as a paragraph
+        let paragraph = elements.next().unwrap();
+        assert_eq!(Syntax::Paragraph, tree[paragraph].data);
+        assert_eq!(second, tree[paragraph].location.start);
     }
 }
@@ -24,22 +24,42 @@ extern crate strum_macros;
 
 extern crate memchr;
 extern crate regex;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(test)]
+extern crate serde_json;
 extern crate strum;
 
 #[macro_use]
-mod parser;
+pub mod parser;
 mod affiliated;
+pub mod at_point;
 mod babel;
 mod blocks;
 mod cursor;
-mod data;
+pub mod data;
 mod drawer;
+mod edit;
+mod entities;
+pub mod export;
 mod fixed_width;
 mod headline;
 mod keyword;
 mod latex;
 mod list;
 mod markup;
+mod objects;
+pub mod options;
+pub mod outline;
 mod paragraph;
+#[cfg(feature = "parallel")]
+mod parallel;
 mod planning;
+#[cfg(feature = "serde")]
+pub mod serialize;
+#[cfg(feature = "sexp")]
+pub mod sexp;
+pub mod source;
 mod table;
+pub mod visit;
@@ -63,29 +63,41 @@
 //!
 
 use crate::affiliated::AffiliatedData;
-use crate::data::SyntaxNode;
+use crate::blocks::block_end_re;
+use crate::cursor::{Cursor, REGEX_EMPTY_LINE};
+use crate::data::{owned, Interval, StringOrObject, Syntax, SyntaxNode, SyntaxT};
+use crate::drawer::REGEX_DRAWER;
+use crate::paragraph::REGEX_DRAWER_END;
 use crate::parser::Parser;
+use crate::source::TextSource;
 use regex::Regex;
 use std::borrow::Cow;
-use std::cell::Cell;
-use std::rc::Rc;
+use std::sync::Arc;
 
 lazy_static! {
 
-//TODO implement all regexes
+    /// Matches a list item and puts everything into groups:
+    /// group 1: bullet
+    /// group 2: counter
+    /// group 3: checkbox
+    /// group 4: description tag
+    /// elisp: `org-list-full-item-re`
+    pub static ref REGEX_FULL_ITEM: Regex = Regex::new(concat!(
+        r"(?i)^[ \t]*((?:[-+*]|(?:[0-9]+|[A-Za-z])[.)])(?:[ \t]+|$))",
+        r"(?:\[@(?:start:)?([0-9]+|[A-Za-z])\][ \t]*)?",
+        r"(?:(\[[ X-]\])(?:[ \t]+|$))?",
+        r"(?:(.*)[ \t]+::(?:[ \t]+|$))?"
+    ))
+    .unwrap();
+
+    static ref REGEX_BLOCK_BEGIN: Regex = Regex::new(r"(?i)^[ \t]*#\+BEGIN(:|_\S+)").unwrap();
+    static ref REGEX_ORDERED_BULLET: Regex = Regex::new(r"^[ \t]*[A-Za-z0-9]").unwrap();
+    static ref REGEX_INLINETASK_END: Regex = Regex::new(r"^END[ \t]*$").unwrap();
+
 // (defconst org-list-end-re "^[ \t]*\n[ \t]*\n"
 //   "Regex matching the end of a plain list.")
 //
-// (defconst org-list-full-item-re
-//   (concat "^[ \t]*\\(\\(?:[-+*]\\|\\(?:[0-9]+\\|[A-Za-z]\\)[.)]\\)\\(?:[ \t]+\\|$\\)\\)"
-// 	  "\\(?:\\[@\\(?:start:\\)?\\([0-9]+\\|[A-Za-z]\\)\\][ \t]*\\)?"
-// 	  "\\(?:\\(\\[[ X-]\\]\\)\\(?:[ \t]+\\|$\\)\\)?"
-// 	  "\\(?:\\(.*\\)[ \t]+::\\(?:[ \t]+\\|$\\)\\)?")
-//   "Matches a list item and puts everything into groups:
-// group 1: bullet
-// group 2: counter
-// group 3: checkbox
-// group 4: description tag")
+// See `at_list_end`
 //
 // (defun org-item-re ()
 //   "Return the correct regular expression for plain lists."
@@ -101,107 +113,552 @@ lazy_static! {
 // (defsubst org-item-beginning-re ()
 //   "Regexp matching the beginning of a plain list item."
 //   (concat "^" (org-item-re)))
-
-    pub static ref REGEX_ITEM : Regex = Regex::new(r"([ \t]*([-+]|(([0-9]+)[.)]))|[ \t]+\*)([ \t]|$)").unwrap();
+//
+// See `ParserOptions::item_re`
 
 }
 
 /// List structure
 /// This looks like an intermediate list representation, required both by
 /// plain list itself and items in the list.
-#[derive(Debug)]
+///
+/// Items of all the nested lists, sorted by their beginning.
+#[derive(Debug, Clone, PartialEq)]
 pub struct ListStruct {
-    // stub
+    pub items: Vec<ListItem>,
+}
+
+/// An item of `ListStruct`.
+/// elisp: a list (BEGIN INDENT BULLET COUNTER CHECKBOX TAG END)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListItem {
+    pub begin: usize,
+    /// Column of the bullet, tabs and wide characters accounted for
+    pub indent: usize,
+    /// Bullet, with the blanks following it
+    pub bullet: String,
+    pub counter: Option<String>,
+    pub checkbox: Option<String>,
+    /// Description tag, only in items with unordered bullets
+    pub tag: Option<String>,
+    pub end: usize,
+}
+
+/// Return true if cursor is on an empty line followed by another
+/// one, i.e. at the end of a plain list.
+/// elisp: `(looking-at org-list-end-re)`
+fn at_list_end<'a, S: TextSource<'a>>(c: &mut Cursor<'a, S>) -> bool {
+    fn blank_line_end<'a, S: TextSource<'a>>(c: &mut Cursor<'a, S>) -> Option<usize> {
+        let end = c.looking_at(&*REGEX_EMPTY_LINE)?;
+        if c.char_after(end) == Some('\n') {
+            Some(end + 1)
+        } else {
+            None
+        }
+    }
+    let pos = c.pos();
+    let result = blank_line_end(c).map_or(false, |next| {
+        c.set(next);
+        blank_line_end(c).is_some()
+    });
+    c.set(pos);
+    result
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct ItemData<'rope> {
     /// Item's bullet (string).
-    bullet: Cow<'rope, str>,
+    pub bullet: Cow<'rope, str>,
     /// Item's check_box, if any (symbol on, off, trans, nil).
-    checkbox: Option<CheckBox>,
+    pub checkbox: Option<CheckBox>,
     /// Item's counter, if any. Literal counters become ordinals (integer).
-    counter: usize,
+    pub counter: usize,
     /// Number of newline characters between the beginning
     /// of the item and the beginning of the contents (0, 1 or 2).
-    pre_blank: usize,
+    pub pre_blank: usize,
     /// Uninterpreted item's tag, if any (string or nil).
-    raw_tag: Option<Cow<'rope, str>>,
+    pub raw_tag: Option<Cow<'rope, str>>,
     /// Parsed item's tag, if any (secondary string or nil).
-    tag: Option<Cow<'rope, str>>,
-    // TODO figure out what is list structure
-    // /// Full list's structure, as returned by org_list_struct (alist).
-    structure: ListStruct,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub tag: Option<StringOrObject<'rope>>,
+    /// Full list's structure, as returned by org_list_struct (alist).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub structure: Arc<ListStruct>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct PlainListData {
     /// Full list's structure, as returned by org_list_struct (alist).
-    pub structure: Rc<ListStruct>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub structure: Arc<ListStruct>,
 
     ///List's type (symbol descriptive, ordered, unordered).
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
     pub type_s: ListKind,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub enum ListKind {
     Descriptive,
     Ordered,
     Unordered,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub enum CheckBox {
     On,
     Off,
     Trans,
 }
 
-impl<'a> Parser<'a> {
-    // TODO implement item_parser
-    //https://code.orgmode.org/bzg/org-mode/src/master/lisp/org-element.el#L1253
+impl<'rope> ItemData<'rope> {
+    pub fn into_owned(self) -> ItemData<'static> {
+        ItemData {
+            bullet: owned(self.bullet),
+            checkbox: self.checkbox,
+            counter: self.counter,
+            pre_blank: self.pre_blank,
+            raw_tag: self.raw_tag.map(owned),
+            tag: self.tag.map(StringOrObject::into_owned),
+            structure: self.structure,
+        }
+    }
+}
+
+impl<'a, S: TextSource<'a>> Parser<'a, S> {
+    /// Parse an item, the cursor being on its first line.
+    /// `structure` is the structure of the list the item belongs to,
+    /// it is computed when missing.
+    ///
+    /// When `raw_secondary_p` is set, the tag is not parsed into
+    /// objects.
+    ///
+    /// https://code.orgmode.org/bzg/org-mode/src/master/lisp/org-element.el#L1253
+    /// (defun org-element-item-parser (_ struct &optional raw-secondary-p)
     pub fn item_parser(
         &self,
-        structure: Option<Rc<ListStruct>>,
+        structure: Option<Arc<ListStruct>>,
         raw_secondary_p: bool,
     ) -> SyntaxNode<'a> {
-        //   let mut item_data = ItemData {
-        //       bullet: (),
-        //       checkbox: None,
-        //       counter: 0,
-        //       pre_blank: 0,
-        //       raw_tag: None,
-        //       tag: None,
-        //       structure: ListStruct {}
-        //   }
-
-        //    let mut node = SyntaxNode {
-        //        parent: Cell::new(None),
-        //        children: (),
-        //        data: item_data
-        //        location: Interval {},
-        //        content_location: None,
-        //        post_blank: 0,
-        //        affiliated: None
-        //    }
-        unimplemented!()
+        let structure = match structure {
+            Some(structure) => structure,
+            None => self.list_struct(self.input.len()),
+        };
+        let mut c = self.cursor.borrow_mut();
+        let begin = c.goto_line_begin();
+        let cap = c
+            .capturing_at(&*REGEX_FULL_ITEM)
+            .expect("item parser called outside of an item");
+        let bullet = cap.get(1).unwrap().text();
+        let checkbox = match cap.get(3).as_ref().map(|g| g.as_str()) {
+            Some("[ ]") => Some(CheckBox::Off),
+            Some("[X]") => Some(CheckBox::On),
+            Some("[-]") => Some(CheckBox::Trans),
+            _ => None,
+        };
+        // Literal counters become ordinals
+        let counter = cap.get(2).map_or(0, |g| match g.as_str().parse() {
+            Ok(n) => n,
+            Err(_) => (g.as_str().as_bytes()[0].to_ascii_uppercase() - b'A' + 1) as usize,
+        });
+        // Tags of ordered items are just a part of their contents
+        let ordered = bullet.contains(&['.', ')'][..]);
+        let tag = cap.get(4).filter(|_| !ordered).map(|g| Interval {
+            start: g.start(),
+            end: g.end(),
+        });
+        let meta_end = match cap.get(4) {
+            Some(tag) if ordered => tag.start(),
+            _ => cap.end(),
+        };
+
+        let end = structure
+            .items
+            .iter()
+            .find(|item| item.begin == begin)
+            .expect("item is missing from the list structure")
+            .end;
+        c.set(end);
+        let end = if c.is_bol() {
+            end
+        } else {
+            c.line_beginning_position(Some(2))
+        };
+
+        let mut pre_blank = 0;
+        c.set(meta_end);
+        c.skip_chars_forward(" \r\t\n", Some(end));
+        let contents_begin = if c.pos() >= end {
+            None
+        } else if c.line_beginning_position(None) == begin {
+            // If first line isn't empty, contents really start at the
+            // text after item's meta-data.
+            Some(c.pos())
+        } else {
+            let line_begin = c.line_beginning_position(None);
+            pre_blank = c.count_lines(begin, line_begin);
+            Some(line_begin)
+        };
+        let contents_end = contents_begin.map(|_| {
+            c.set(end);
+            c.skip_chars_backward(" \r\t\n", None);
+            c.line_beginning_position(Some(2))
+        });
+        let post_blank = c.count_lines(contents_end.unwrap_or(begin), end);
+        c.set(begin);
+        drop(c);
+
+        let raw_tag = tag.map(|t| self.input.slice(t.start, t.end));
+        let tag = tag.map(|t| self.parse_secondary_string(t, SyntaxT::Item, raw_secondary_p));
+        let data = ItemData {
+            bullet,
+            checkbox,
+            counter,
+            pre_blank,
+            raw_tag,
+            tag,
+            structure,
+        };
+        SyntaxNode::new(
+            Syntax::Item(Box::new(data)),
+            Interval { start: begin, end },
+            contents_begin.map(|start| Interval {
+                start,
+                end: contents_end.unwrap(),
+            }),
+            post_blank,
+        )
     }
 
-    // TODO implement plain_list_parser
+    /// Parse a plain list, the cursor being on its first item.
+    /// `start` is the beginning of its affiliated keywords.
+    ///
+    /// https://code.orgmode.org/bzg/org-mode/src/master/lisp/org-element.el#L1439
+    /// (defun org-element-plain-list-parser (limit affiliated structure)
     pub fn plain_list_parser(
         &self,
         limit: usize,
         start: usize,
-        affiliated: Option<AffiliatedData>,
-        structure: Rc<ListStruct>,
+        affiliated: Option<AffiliatedData<'a>>,
+        structure: Arc<ListStruct>,
     ) -> SyntaxNode<'a> {
-        unimplemented!()
+        let mut c = self.cursor.borrow_mut();
+        let contents_begin = c.pos();
+        let item = |begin| structure.items.iter().find(|item| item.begin == begin);
+        let first = item(contents_begin).expect("plain list is missing from its structure");
+        let type_s = if c.looking_at(&*REGEX_ORDERED_BULLET).is_some() {
+            ListKind::Ordered
+        } else if first.tag.is_some() {
+            ListKind::Descriptive
+        } else {
+            ListKind::Unordered
+        };
+        // End of the last item of the same indentation as the first one
+        let mut contents_end = first.end;
+        while let Some(next) = item(contents_end).filter(|next| next.indent == first.indent) {
+            contents_end = next.end;
+        }
+        c.set(contents_end);
+        c.skip_chars_forward(" \r\t\n", Some(limit));
+        let end = if c.pos() >= limit {
+            limit
+        } else {
+            c.line_beginning_position(None)
+        };
+        let post_blank = c.count_lines(contents_end, end);
+        c.set(contents_begin);
+
+        let mut node = SyntaxNode::new(
+            Syntax::PlainList(Box::new(PlainListData {
+                structure: structure.clone(),
+                type_s,
+            })),
+            Interval { start, end },
+            Some(Interval {
+                start: contents_begin,
+                end: contents_end,
+            }),
+            post_blank,
+        );
+        node.affiliated = affiliated
+            .filter(|aff| *aff != AffiliatedData::default())
+            .map(Box::new);
+        node
     }
 
     //(defun org-element--list-struct (limit)
     ///  ;; Return structure of list at point.  Internal function.  See
     ///  ;; `org-list-struct' for details.
-    pub fn list_struct(&self, limit: usize) -> Rc<ListStruct> {
-        unimplemented!()
+    ///
+    /// Indentation is compared in display columns, see `Cursor::current_indentation`,
+    /// so that lists indented with tabs nest the way they are displayed.
+    pub fn list_struct(&self, limit: usize) -> Arc<ListStruct> {
+        let tab_width = self.options.tab_width;
+        let mut c = self.cursor.borrow_mut();
+        let origin = c.pos();
+        let mut top_ind = usize::MAX;
+        // Items not ended yet, innermost last
+        let mut items: Vec<ListItem> = vec![];
+        let mut structure: Vec<ListItem> = vec![];
+
+        loop {
+            // At limit: end all items.
+            if c.pos() >= limit {
+                c.skip_chars_backward(" \r\t\n", None);
+                let end = c.line_beginning_position(Some(2));
+                for mut item in items.drain(..) {
+                    item.end = end;
+                    structure.push(item);
+                }
+                break;
+            }
+            // At list end: end all items.
+            if at_list_end(&mut c) {
+                let end = c.pos();
+                for mut item in items.drain(..) {
+                    item.end = end;
+                    structure.push(item);
+                }
+                break;
+            }
+            if c.looking_at(&self.re.item).is_some() {
+                // At a new item: end previous sibling.
+                let ind = c.current_indentation(tab_width);
+                top_ind = top_ind.min(ind);
+                while items.last().map_or(false, |item| ind <= item.indent) {
+                    let mut item = items.pop().unwrap();
+                    item.end = c.pos();
+                    structure.push(item);
+                }
+                if let Some(cap) = c.capturing_at(&*REGEX_FULL_ITEM) {
+                    let group = |i| cap.get(i).map(|m| m.as_str().to_string());
+                    let bullet = group(1).unwrap();
+                    let tag = if bullet.contains(&['-', '+', '*'][..]) {
+                        group(4)
+                    } else {
+                        None
+                    };
+                    items.push(ListItem {
+                        begin: c.pos(),
+                        indent: ind,
+                        counter: group(2),
+                        checkbox: group(3),
+                        bullet,
+                        tag,
+                        // Ending position, unknown so far.
+                        end: 0,
+                    });
+                }
+                c.goto_next_line();
+            } else if c.looking_at(&*REGEX_EMPTY_LINE).is_some() {
+                // Skip empty lines.
+                c.goto_next_line();
+            } else if let Some(inlinetask) = self
+                .re
+                .inlinetask
+                .as_ref()
+                .filter(|re| c.looking_at(re).is_some())
+            {
+                // Skip inline tasks and blank lines along the way.
+                c.goto_next_line();
+                let origin = c.pos();
+                if c.re_search_forward(inlinetask, Some(limit)).is_some() {
+                    if c.looking_at(&*REGEX_INLINETASK_END).is_some() {
+                        c.goto_next_line();
+                    } else {
+                        c.set(origin);
+                    }
+                }
+            } else {
+                // At some text line.  Check if it ends any previous item.
+                let ind = c.current_indentation(tab_width);
+                if ind <= top_ind {
+                    c.skip_chars_backward(" \r\t\n", None);
+                    c.goto_next_line();
+                }
+                while items.last().map_or(false, |item| ind <= item.indent) {
+                    let mut item = items.pop().unwrap();
+                    item.end = c.line_beginning_position(None);
+                    structure.push(item);
+                }
+                if items.is_empty() {
+                    break;
+                }
+                // Skip blocks (any type) and drawers contents.
+                if let Some(cap) = c.capturing_at(&*REGEX_BLOCK_BEGIN) {
+                    let end = self.regex(block_end_re(cap.get(1).unwrap().as_str()));
+                    c.re_search_forward(&end, Some(limit));
+                } else if c.looking_at(&*REGEX_DRAWER).is_some() {
+                    c.re_search_forward(&*REGEX_DRAWER_END, Some(limit));
+                }
+                c.goto_next_line();
+            }
+        }
+
+        c.set(origin);
+        structure.sort_by_key(|item| item.begin);
+        Arc::new(ListStruct { items: structure })
+    }
+}
+
+mod test {
+    use super::{CheckBox, ListItem, ListKind};
+    use crate::data::{Syntax, SyntaxT};
+    use crate::options::ParserOptions;
+    use crate::parser::{ParseGranularity, Parser};
+
+    /// Beginning, indentation and end of items in the structure of
+    /// the list starting `text`
+    fn items(text: &str, tab_width: usize) -> Vec<(usize, usize, usize)> {
+        let options = ParserOptions {
+            tab_width,
+            ..ParserOptions::default()
+        };
+        let parser = Parser::new(text, ParseGranularity::Element, options);
+        let structure = parser.list_struct(text.len());
+        assert_eq!(0, parser.cursor.borrow().pos());
+        structure
+            .items
+            .iter()
+            .map(|item| (item.begin, item.indent, item.end))
+            .collect()
+    }
+
+    #[test]
+    fn list_struct() {
+        let text = "- [X] tag :: a\n  text\n  1. [@3] b\n\n\nafter\n";
+        let parser = Parser::new(text, ParseGranularity::Element, ParserOptions::default());
+        let structure = parser.list_struct(text.len());
+        assert_eq!(
+            vec![
+                ListItem {
+                    begin: 0,
+                    indent: 0,
+                    bullet: "- ".to_string(),
+                    counter: None,
+                    checkbox: Some("[X]".to_string()),
+                    tag: Some("tag".to_string()),
+                    end: 34,
+                },
+                ListItem {
+                    begin: 22,
+                    indent: 2,
+                    bullet: "1. ".to_string(),
+                    counter: Some("3".to_string()),
+                    checkbox: None,
+                    tag: None,
+                    end: 34,
+                },
+            ],
+            structure.items
+        );
+    }
+
+    #[test]
+    fn tab_indentation() {
+        let text = "- a\n\t- b\n        - c\n    - d\n\ttext of d\n- e\n";
+        let b = text.find("- b").unwrap() - 1;
+        let c = text.find("        - c").unwrap();
+        let d = text.find("    - d").unwrap();
+        let text_of_d = text.find("\ttext").unwrap();
+        let e = text.find("- e").unwrap();
+        // A tab is as wide as 8 spaces: b and c are siblings, d is
+        // less indented, and the line after d belongs to d.
+        assert_eq!(
+            vec![
+                (0, 0, e),
+                (b, 8, c),
+                (c, 8, d),
+                (d, 4, e),
+                (e, 0, text.len())
+            ],
+            items(text, 8)
+        );
+        // A tab is as wide as 4 spaces: c is a child of b, d a sibling
+        // of b, and the line after d ends it.
+        assert_eq!(
+            vec![
+                (0, 0, e),
+                (b, 4, d),
+                (c, 8, d),
+                (d, 4, text_of_d),
+                (e, 0, text.len())
+            ],
+            items(text, 4)
+        );
+    }
+
+    #[test]
+    fn parse_lists() {
+        let text = "1. a\n2. [X] b\n   - c\n   - d\n\n3. [@5] e\n\nafter\n";
+        let parser = Parser::new(text, ParseGranularity::Element, ParserOptions::default());
+        let tree = parser.parse_buffer();
+        let nodes: Vec<_> = tree
+            .descendants(tree.root())
+            .map(|id| (SyntaxT::from(&tree[id].data), tree[id].location.start))
+            .collect();
+        let at = |s: &str| text.find(s).unwrap();
+        assert_eq!(
+            vec![
+                (SyntaxT::OrgData, 0),
+                (SyntaxT::Section, 0),
+                (SyntaxT::PlainList, 0),
+                (SyntaxT::Item, 0),
+                (SyntaxT::Paragraph, at("a")),
+                (SyntaxT::Item, at("2.")),
+                (SyntaxT::Paragraph, at("b")),
+                (SyntaxT::PlainList, at("   - c")),
+                (SyntaxT::Item, at("   - c")),
+                (SyntaxT::Paragraph, at("c")),
+                (SyntaxT::Item, at("   - d")),
+                (SyntaxT::Paragraph, at("d")),
+                (SyntaxT::Item, at("3.")),
+                (SyntaxT::Paragraph, at("e")),
+                (SyntaxT::Paragraph, at("after")),
+            ],
+            nodes
+        );
+
+        let lists: Vec<_> = tree
+            .descendants(tree.root())
+            .filter_map(|id| match &tree[id].data {
+                Syntax::PlainList(d) => Some((d.type_s.clone(), tree[id].location.end)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                (ListKind::Ordered, at("after")),
+                (ListKind::Unordered, at("\n\n3.") + 1),
+            ],
+            lists
+        );
+
+        let items: Vec<_> = tree
+            .descendants(tree.root())
+            .filter_map(|id| match &tree[id].data {
+                Syntax::Item(d) => Some((
+                    d.bullet.as_ref(),
+                    d.counter,
+                    d.checkbox.clone(),
+                    tree[id].post_blank,
+                )),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ("1. ", 0, None, 0),
+                ("2. ", 0, Some(CheckBox::On), 1),
+                ("- ", 0, None, 0),
+                ("- ", 0, None, 1),
+                ("3. ", 5, None, 0),
+            ],
+            items
+        );
     }
 }
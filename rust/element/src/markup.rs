@@ -15,9 +15,11 @@
 //
 
 use crate::affiliated::AffiliatedData;
-use crate::data::SyntaxNode;
+use crate::data::{owned, Interval, Syntax, SyntaxNode};
 use crate::parser::Parser;
+use crate::source::TextSource;
 use regex::Regex;
+use std::borrow::Cow;
 
 lazy_static! {
     pub static ref REGEX_HORIZONTAL_RULE: Regex = Regex::new(r"[ \t]*-{5,}[ \t]*$").unwrap();
@@ -34,31 +36,59 @@ lazy_static! {
 
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct CommentData<'a> {
     /// Comments, with pound signs (string).
-    value: &'a str,
+    pub value: Cow<'a, str>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct FixedWidthData<'a> {
     ///Contents, without colons prefix (string).
-    value: &'a str,
+    pub value: Cow<'a, str>,
 }
 
 /// Greater element
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct FootnoteDefinitionData<'a> {
     /// Label used for references (string).
-    label: &'a str,
+    pub label: Cow<'a, str>,
 
     /// Number of newline characters between the
     /// beginning of the footnoote and the beginning
     /// of the contents (0, 1 or 2).
-    pre_blank: u8,
+    pub pre_blank: u8,
 }
 
-impl<'a> Parser<'a> {
+impl<'a> CommentData<'a> {
+    pub fn into_owned(self) -> CommentData<'static> {
+        CommentData {
+            value: owned(self.value),
+        }
+    }
+}
+
+impl<'a> FixedWidthData<'a> {
+    pub fn into_owned(self) -> FixedWidthData<'static> {
+        FixedWidthData {
+            value: owned(self.value),
+        }
+    }
+}
+
+impl<'a> FootnoteDefinitionData<'a> {
+    pub fn into_owned(self) -> FootnoteDefinitionData<'static> {
+        FootnoteDefinitionData {
+            label: owned(self.label),
+            pre_blank: self.pre_blank,
+        }
+    }
+}
+
+impl<'a, S: TextSource<'a>> Parser<'a, S> {
     // TODO implement comment_parser
     pub fn comment_parser(
         &self,
@@ -79,14 +109,108 @@ impl<'a> Parser<'a> {
         unimplemented!()
     }
 
-    // TODO implement footnote_definition_parser
+    /// Parse a footnote definition.
+    ///
+    /// LIMIT bounds the search.  AFFILIATED is a list of which CAR is
+    /// the buffer position at the beginning of the first affiliated
+    /// keyword and CDR is a plist of affiliated keywords along with
+    /// their value.
+    ///
+    /// Return a list whose CAR is `footnote-definition' and CDR is
+    /// a plist containing `:label', `:begin' `:end', `:contents-begin',
+    /// `:contents-end', `:pre-blank',`:post-blank' and
+    /// `:post-affiliated' keywords.
+    ///
+    /// Assume point is at the beginning of the footnote definition.
+    /// (defun org-element-footnote-definition-parser (limit affiliated)
     pub fn footnote_definition_parser(
         &self,
         limit: usize,
         start: usize,
-        affiliated: Option<AffiliatedData>,
+        maybe_aff: Option<AffiliatedData<'a>>,
     ) -> SyntaxNode<'a> {
-        unimplemented!()
+        let post_affiliated = self.cursor.borrow().pos();
+        let end = self.footnote_definition_end(limit);
+        let mut c = self.cursor.borrow_mut();
+        c.set(post_affiliated);
+        let cap = c
+            .capturing_at(&*REGEX_FOOTNOTE_DEFINITION)
+            .expect("footnote definition parser called outside of a definition");
+        let label = cap.get(1).unwrap().text();
+
+        let mut pre_blank = 0;
+        c.set(cap.end());
+        c.skip_chars_forward(" \r\t\n", Some(end));
+        let contents_begin = if c.pos() == end {
+            None
+        } else if c.line_beginning_position(None) == post_affiliated {
+            Some(c.pos())
+        } else {
+            let line = c.line_beginning_position(None);
+            pre_blank = c.count_lines(post_affiliated, line) as u8;
+            Some(line)
+        };
+        c.set(end);
+        c.skip_chars_backward(" \r\t\n", Some(post_affiliated));
+        let contents_end = c.line_beginning_position(Some(2)).min(end);
+        let post_blank = c.count_lines(contents_end, end);
+        c.set(post_affiliated);
+
+        let mut node = SyntaxNode::new(
+            Syntax::FootnoteDefinition(Box::new(FootnoteDefinitionData { label, pre_blank })),
+            Interval { start, end },
+            contents_begin.map(|start| Interval {
+                start,
+                end: contents_end,
+            }),
+            post_blank,
+        );
+        node.affiliated = maybe_aff
+            .filter(|aff| *aff != AffiliatedData::default())
+            .map(Box::new);
+        node
+    }
+
+    /// End of the footnote definition at point: the next headline,
+    /// footnote definition or two consecutive blank lines. Cursor is
+    /// moved.
+    ///
+    /// elisp: `org-element--footnote-separator`
+    fn footnote_definition_end(&self, limit: usize) -> usize {
+        let mut c = self.cursor.borrow_mut();
+        let post_affiliated = c.pos();
+        let next_line = c.line_beginning_position(Some(2));
+        let mut lines = self.lines(next_line, limit).peekable();
+        while let Some((line, line_end)) = lines.next() {
+            let text = self.input.slice(line, line_end);
+            let stars = text.trim_start_matches('*');
+            if stars.len() < text.len() && stars.starts_with(|c| c == ' ' || c == '\t') {
+                return line;
+            }
+            if REGEX_FOOTNOTE_DEFINITION.is_match(&text) {
+                // At a new footnote definition, make sure we end
+                // before any affiliated keyword above.
+                c.set(line);
+                c.goto_prev_line();
+                while c.pos() > post_affiliated && c.looking_at(&self.re.affiliated).is_some() {
+                    c.goto_prev_line();
+                }
+                return c.line_beginning_position(Some(2));
+            }
+            let is_blank = |(line, end): (usize, usize)| {
+                end < limit && self.input.slice(line, end).trim().is_empty()
+            };
+            if is_blank((line, line_end)) && lines.peek().map_or(false, |&l| is_blank(l)) {
+                c.set(line);
+                c.skip_chars_forward(" \r\t\n", Some(limit));
+                return if c.pos() == limit {
+                    limit
+                } else {
+                    c.line_beginning_position(None)
+                };
+            }
+        }
+        limit
     }
 
     // TODO implement fixed_width_parser
@@ -99,3 +223,70 @@ impl<'a> Parser<'a> {
         unimplemented!()
     }
 }
+
+mod test {
+    use crate::data::{Syntax, SyntaxT, SyntaxTree};
+    use crate::options::ParserOptions;
+    use crate::parser::{ParseGranularity, Parser};
+
+    #[test]
+    fn footnote_definitions() {
+        let text = "[fn:1] One\nline.\n[fn:two]\n\nTwo.\n\n\nAfter\n* H\n";
+        let parser = Parser::new(text, ParseGranularity::Element, ParserOptions::default());
+        let tree = parser.parse_buffer();
+        let section = tree.children(tree.root()).next().unwrap();
+        let at = |s: &str| text.find(s).unwrap();
+        let definitions: Vec<_> = tree
+            .children(section)
+            .map(|id| {
+                let node = &tree[id];
+                let (label, pre_blank) = match &node.data {
+                    Syntax::FootnoteDefinition(d) => (d.label.to_string(), d.pre_blank),
+                    _ => (String::new(), 0),
+                };
+                let contents = node.content_location.unwrap();
+                (
+                    SyntaxT::from(&node.data),
+                    label,
+                    pre_blank,
+                    (node.location.start, node.location.end),
+                    (contents.start, contents.end),
+                    node.post_blank,
+                )
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                (
+                    SyntaxT::FootnoteDefinition,
+                    "1".to_string(),
+                    0,
+                    (0, at("[fn:two]")),
+                    (at("One"), at("[fn:two]")),
+                    0
+                ),
+                (
+                    SyntaxT::FootnoteDefinition,
+                    "two".to_string(),
+                    2,
+                    (at("[fn:two]"), at("After")),
+                    (at("Two."), at("Two.") + 5),
+                    2
+                ),
+                (
+                    SyntaxT::Paragraph,
+                    String::new(),
+                    0,
+                    (at("After"), at("* H")),
+                    (at("After"), at("* H")),
+                    0
+                ),
+            ],
+            definitions
+        );
+        // Contents on the label line are a paragraph
+        let first = tree.children(section).next().unwrap();
+        let paragraph = tree.children(first).next().unwrap();
+        assert_eq!(at("One"), tree[paragraph].location.start);
+    }
+}
@@ -0,0 +1,980 @@
+//    This file is part of org-rs.
+//
+//    org-rs is free software: you can redistribute it and/or modify
+//    it under the terms of the GNU General Public License as published by
+//    the Free Software Foundation, either version 3 of the License, or
+//    (at your option) any later version.
+//
+//    org-rs is distributed in the hope that it will be useful,
+//    but WITHOUT ANY WARRANTY; without even the implied warranty of
+//    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//    GNU General Public License for more details.
+//
+//    You should have received a copy of the GNU General Public License
+//    along with org-rs.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Objects
+//! https://orgmode.org/worg/dev/org-syntax.html#Objects
+//!
+//! Objects can only be found in the following locations: affiliated keywords
+//! defined in `org-element-parsed-keywords`, document properties, headline
+//! and inlinetask titles, item tags, paragraphs, table cells, table rows
+//! and verse blocks.
+//!
+//! Every parser below expects the cursor at the beginning of the object
+//! and `limit` at the end of the container, and returns `None` when the
+//! text does not hold an object of its type. Cursor is left untouched.
+//!
+//! LaTeX fragments, subscripts, superscripts,
+//! inline babel calls, inline source blocks and radio targets are not
+//! parsed yet: `object_lex` skips them and their text stays plain text.
+
+use crate::data::{
+    CodeData, EntityData, ExportSnippetData, FootnoteReferenceData, Interval, LinkData, LinkFormat,
+    MacroData, RepeaterType, StatisticsCookieData, Syntax, SyntaxNode, TargetData, TimeUnit,
+    TimestampData, TimestampType, VerbatimData, WarningType,
+};
+use crate::entities;
+use crate::parser::Parser;
+use crate::source::{self, TextSource};
+use regex::Regex;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+lazy_static! {
+    /// Regexps matching text markup, one per marker.
+    ///
+    /// Group `emph` is the object itself, markers included,
+    /// group `body` its contents.
+    ///
+    /// Original implementation uses a back reference to match the closing
+    /// marker, which Rust's regex does not support, hence one regex per marker.
+    /// Components follow default `org-emphasis-regexp-components`:
+    /// pre: `-[:space:]('"{`, post: `-[:space:].,:!?;'")}\[`,
+    /// border: `[:space:]`, body: `.`, newline: 1
+    ///
+    /// elisp: `org-emph-re` and `org-verbatim-re`
+    static ref REGEX_EMPHASIS: HashMap<char, Regex> = "*/_+=~"
+        .chars()
+        .map(|marker| {
+            let m = regex::escape(&marker.to_string());
+            let re = format!(
+                r#"(?m)\A(?:^|[-\s('"{{])(?P<emph>{m}(?P<body>[^\s]|[^\s].*?(?:\n.*?)?[^\s]){m})(?:[-\s.,:!?;'")}}\[]|$)"#,
+                m = m
+            );
+            (marker, Regex::new(&re).unwrap())
+        })
+        .collect();
+
+    /// Link in group 1, description in group 3
+    /// elisp: `org-bracket-link-regexp`
+    static ref REGEX_BRACKET_LINK: Regex =
+        Regex::new(r"\A\[\[([^\]\[]+)\](\[([^\]\[]+)\])?\]").unwrap();
+
+    static ref REGEX_FILE_LINK_PATH: Regex = Regex::new(r"\A///*(.:)?/").unwrap();
+
+    static ref REGEX_LINK_NEWLINE: Regex = Regex::new(r"[ \t]*\n[ \t]*").unwrap();
+
+    /// Label in group 1 or 3, group 2 matches for inline footnotes
+    /// elisp: `org-footnote-re`
+    static ref REGEX_FOOTNOTE_REFERENCE: Regex =
+        Regex::new(r"\A\[fn:(?:([-_\w]+)?(:)|([-_\w]+)\])").unwrap();
+
+    static ref REGEX_STATISTICS_COOKIE: Regex =
+        Regex::new(r"\A\[[0-9]*(?:%|/[0-9]*)\]").unwrap();
+
+    /// elisp: `org-target-regexp`
+    static ref REGEX_TARGET: Regex =
+        Regex::new(r"\A<<([^<>\n\r \t](?:[^<>\n\r]*[^<>\n\r \t])?)>>").unwrap();
+
+    static ref REGEX_EXPORT_SNIPPET: Regex = Regex::new(r"\A@@([-A-Za-z0-9]+):").unwrap();
+
+    /// Name in group 1, arguments in group 3
+    static ref REGEX_MACRO: Regex =
+        Regex::new(r"\A\{\{\{([a-zA-Z][-a-zA-Z0-9_]*)(\((?s:(.*?))\))?\}\}\}").unwrap();
+
+    /// Name in group 1 for spaces, e.g. `\_  `, or group 2,
+    /// then `{}`, a non-alphabetic character or nothing in group 3
+    static ref REGEX_ENTITY: Regex = Regex::new(
+        r"(?m)\A\\(?:(_ +)|(there4|sup[123]|frac[13][24]|[a-zA-Z]+)($|\{\}|\P{Alphabetic}))"
+    )
+    .unwrap();
+
+    /// elisp: `org-element--timestamp-regexp`
+    static ref REGEX_TIMESTAMP: Regex = Regex::new(
+        r"\A(?:[\[<][0-9]{4}-[0-9]{2}-[0-9]{2}(?: .*?)?[\]>]|<%%\([^>\n]+\)>)"
+    )
+    .unwrap();
+
+    /// Start date in group 1, diary sexp marker in group 2,
+    /// end date of a range in group 3
+    static ref REGEX_TIMESTAMP_RAW: Regex =
+        Regex::new(r"\A([\[<](%%)?.*?)[\]>](?:--([\[<].*?[\]>]))?").unwrap();
+
+    /// End hour and minute in groups 2 and 3
+    static ref REGEX_TIME_RANGE: Regex =
+        Regex::new(r"[012]?[0-9]:[0-5][0-9](-([012]?[0-9]):([0-5][0-9]))").unwrap();
+
+    static ref REGEX_REPEATER: Regex = Regex::new(r"([.+]?\+)([0-9]+)([hdwmy])").unwrap();
+
+    static ref REGEX_WARNING: Regex = Regex::new(r"(-)?-([0-9]+)([hdwmy])").unwrap();
+
+    /// Year, month, day, hour and minute in groups 1 to 5
+    /// elisp: `org-ts-regexp0`
+    static ref REGEX_TIMESTAMP_DATE: Regex = Regex::new(
+        r"([0-9]{4})-([0-9]{2})-([0-9]{2})(?: +[^\]+0-9>\r\n -]+)?(?: +([0-9]{1,2}):([0-9]{2}))?"
+    )
+    .unwrap();
+
+    static ref REGEX_LINE_BREAK: Regex = Regex::new(r"(?m)\A\\\\[ \t]*$").unwrap();
+
+    static ref REGEX_TABLE_CELL: Regex = Regex::new(r"(?m)\A[ \t]*(.*?)[ \t]*(?:\||$)").unwrap();
+}
+
+impl<'a, S: TextSource<'a>> Parser<'a, S> {
+    /// Number of spaces and tabs at `pos`, before `limit`
+    fn blanks_at(&self, pos: usize, limit: usize) -> usize {
+        (pos..limit)
+            .take_while(|&p| matches!(self.input.byte_at(p), b' ' | b'\t'))
+            .count()
+    }
+
+    /// Locates text markup at cursor, delimited with `marker`.
+    ///
+    /// Returns contents location, end of the closing marker and
+    /// number of blanks after it.
+    fn emphasis(&self, limit: usize, marker: char) -> Option<(Interval, usize, usize)> {
+        let pos = self.cursor.borrow().pos();
+        // (unless (bolp) (backward-char 1))
+        let (text, from) = self.window(pos, limit);
+        let captures = REGEX_EMPHASIS[&marker].captures(&text)?;
+        let emph = captures.name("emph")?;
+        if from + emph.start() != pos {
+            return None;
+        }
+        let body = captures.name("body").unwrap();
+        let contents = Interval {
+            start: from + body.start(),
+            end: from + body.end(),
+        };
+        let markup_end = from + emph.end();
+        Some((contents, markup_end, self.blanks_at(markup_end, limit)))
+    }
+
+    /// Parse recursive text markup at cursor: bold, italic,
+    /// underline or strike-through depending on `marker`.
+    ///
+    /// (defun org-element-bold-parser ()
+    /// (defun org-element-italic-parser ()
+    /// (defun org-element-underline-parser ()
+    /// (defun org-element-strike-through-parser ()
+    pub(crate) fn text_markup_parser(&self, limit: usize, marker: char) -> Option<SyntaxNode<'a>> {
+        let begin = self.cursor.borrow().pos();
+        let (contents, markup_end, post_blank) = self.emphasis(limit, marker)?;
+        let data = match marker {
+            '*' => Syntax::Bold,
+            '/' => Syntax::Italic,
+            '_' => Syntax::Underline,
+            '+' => Syntax::StrikeThrough,
+            _ => return None,
+        };
+        Some(SyntaxNode::new(
+            data,
+            Interval {
+                start: begin,
+                end: markup_end + post_blank,
+            },
+            Some(contents),
+            post_blank,
+        ))
+    }
+
+    /// Parse code or verbatim object at cursor, depending on `marker`.
+    ///
+    /// (defun org-element-code-parser ()
+    /// (defun org-element-verbatim-parser ()
+    pub(crate) fn verbatim_parser(&self, limit: usize, marker: char) -> Option<SyntaxNode<'a>> {
+        let begin = self.cursor.borrow().pos();
+        let (contents, markup_end, post_blank) = self.emphasis(limit, marker)?;
+        let value = self.input.slice(contents.start, contents.end);
+        let data = match marker {
+            '~' => Syntax::Code(Box::new(CodeData { value })),
+            '=' => Syntax::Verbatim(Box::new(VerbatimData { value })),
+            _ => return None,
+        };
+        Some(SyntaxNode::new(
+            data,
+            Interval {
+                start: begin,
+                end: markup_end + post_blank,
+            },
+            None,
+            post_blank,
+        ))
+    }
+
+    /// Parse link at cursor: bracket, plain or angle link.
+    /// Radio links are not supported yet.
+    ///
+    /// (defun org-element-link-parser ()
+    pub(crate) fn link_parser(&self, limit: usize) -> Option<SyntaxNode<'a>> {
+        let begin = self.cursor.borrow().pos();
+        let text = self.input.slice(begin, limit);
+        let at = |start, end| source::sub(&text, start, end);
+
+        let format;
+        let raw_link: Cow<'a, str>;
+        let mut link_type: Cow<'a, str>;
+        let mut path: Cow<'a, str>;
+        let link_end;
+        let mut contents = None;
+
+        if let Some(cap) = REGEX_BRACKET_LINK.captures(&text) {
+            // Standard link, i.e. [[https://orgmode.org][homepage]]
+            format = LinkFormat::Bracket;
+            if let Some(desc) = cap.get(3) {
+                contents = Some(Interval {
+                    start: begin + desc.start(),
+                    end: begin + desc.end(),
+                });
+            }
+            link_end = begin + cap.get(0).unwrap().end();
+            let raw_match = cap.get(1).unwrap();
+            let (raw, raw_start) = (raw_match.as_str(), raw_match.start());
+            raw_link = at(raw_start, raw_match.end());
+            // Determine type of link and set path accordingly.  According
+            // to RFC 3986, remove whitespaces from URI in external links.
+            // In internal ones, treat indentation as a single space.
+            if raw.starts_with('/') || raw.starts_with("./") || raw.starts_with("../") {
+                link_type = Cow::from("file");
+                path = raw_link.clone();
+            } else if let Some(t) = self.re.link_type.captures(raw) {
+                let kind = t.get(1).unwrap();
+                link_type = at(raw_start + kind.start(), raw_start + kind.end());
+                let path_start = raw_start + t.get(0).unwrap().end();
+                path = without_newlines(at(path_start, raw_match.end()), "");
+            } else if raw.len() > 1 && raw.starts_with('(') && raw.ends_with(')') {
+                link_type = Cow::from("coderef");
+                path = at(raw_start + 1, raw_match.end() - 1);
+            } else if raw.starts_with('#') {
+                link_type = Cow::from("custom-id");
+                path = at(raw_start + 1, raw_match.end());
+            } else {
+                link_type = Cow::from("fuzzy");
+                path = without_newlines(raw_link.clone(), " ");
+            }
+        } else if let Some(cap) = self.re.plain_link.captures(&text) {
+            // Plain link, e.g., https://orgmode.org
+            format = LinkFormat::Plain;
+            let group = |i| {
+                cap.get(i)
+                    .map(|m: regex::Match| at(m.start(), m.end()))
+                    .unwrap()
+            };
+            raw_link = group(0);
+            link_type = group(1);
+            path = group(2);
+            link_end = begin + cap.get(0).unwrap().end();
+        } else if let Some(cap) = self.re.angle_link.captures(&text) {
+            // Angular link, e.g., <https://orgmode.org>.  Unlike to
+            // bracket links, follow RFC 3986 and remove any extra
+            // whitespace in URI.
+            format = LinkFormat::Angle;
+            let (kind, uri) = (cap.get(1).unwrap(), cap.get(2).unwrap());
+            link_type = at(kind.start(), kind.end());
+            raw_link = at(kind.start(), uri.end());
+            path = without_newlines(at(uri.start(), uri.end()), "");
+            link_end = begin + cap.get(0).unwrap().end();
+        } else {
+            return None;
+        }
+
+        // In any case, deduce end point after trailing white space.
+        let post_blank = self.blanks_at(link_end, limit);
+
+        // Special "file" type link processing.  Extract opening
+        // application and search option, if any.  Also normalize URI.
+        let mut application = None;
+        let mut search_option = None;
+        if link_type == "file" || link_type.starts_with("file+") {
+            if let Some(app) = link_type.strip_prefix("file+") {
+                application = Some(Cow::from(app.to_string()));
+            }
+            link_type = Cow::from("file");
+            if let Some(idx) = path.find("::") {
+                search_option = Some(Cow::from(path[idx + 2..].to_string()));
+                path = Cow::from(path[..idx].to_string());
+            }
+            if REGEX_FILE_LINK_PATH.is_match(&path) {
+                path = Cow::from(REGEX_FILE_LINK_PATH.replace(&path, "${1}/").into_owned());
+            }
+        }
+
+        Some(SyntaxNode::new(
+            Syntax::Link(Box::new(LinkData {
+                application,
+                format,
+                path,
+                raw_link,
+                search_option,
+                link_type,
+            })),
+            Interval {
+                start: begin,
+                end: link_end + post_blank,
+            },
+            contents,
+            post_blank,
+        ))
+    }
+
+    /// Parse footnote reference at cursor.
+    ///
+    /// (defun org-element-footnote-reference-parser ()
+    pub(crate) fn footnote_reference_parser(&self, limit: usize) -> Option<SyntaxNode<'a>> {
+        let begin = self.cursor.borrow().pos();
+        let text = self.input.slice(begin, limit);
+        let cap = REGEX_FOOTNOTE_REFERENCE.captures(&text)?;
+
+        // Position after the matching closing bracket.
+        // (scan-lists (point) 1 0) with `org-element--pair-square-table'
+        let mut depth = 0;
+        let mut closing = None;
+        for (i, b) in text.bytes().enumerate() {
+            match b {
+                b'[' => depth += 1,
+                b']' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        closing = Some(begin + i + 1);
+                        break;
+                    }
+                }
+                _ => (),
+            }
+        }
+        let closing = closing?;
+
+        let label = cap
+            .get(1)
+            .or_else(|| cap.get(3))
+            .map(|m| source::sub(&text, m.start(), m.end()));
+        let inline = cap.get(2).is_some();
+        let contents = if inline {
+            Some(Interval {
+                start: begin + cap.get(0).unwrap().end(),
+                end: closing - 1,
+            })
+        } else {
+            None
+        };
+        let post_blank = self.blanks_at(closing, limit);
+        Some(SyntaxNode::new(
+            Syntax::FootnoteReference(Box::new(FootnoteReferenceData {
+                label,
+                type_s: Cow::from(if inline { "inline" } else { "standard" }),
+            })),
+            Interval {
+                start: begin,
+                end: closing + post_blank,
+            },
+            contents,
+            post_blank,
+        ))
+    }
+
+    /// Parse statistics cookie at cursor, e.g. `[1/3]` or `[50%]`.
+    ///
+    /// (defun org-element-statistics-cookie-parser ()
+    pub(crate) fn statistics_cookie_parser(&self, limit: usize) -> Option<SyntaxNode<'a>> {
+        let begin = self.cursor.borrow().pos();
+        let text = self.input.slice(begin, limit);
+        let m = REGEX_STATISTICS_COOKIE.find(&text)?;
+        let cookie_end = begin + m.end();
+        let post_blank = self.blanks_at(cookie_end, limit);
+        Some(SyntaxNode::new(
+            Syntax::StatisticsCookie(Box::new(StatisticsCookieData {
+                value: source::sub(&text, m.start(), m.end()),
+            })),
+            Interval {
+                start: begin,
+                end: cookie_end + post_blank,
+            },
+            None,
+            post_blank,
+        ))
+    }
+
+    /// Parse time stamp at cursor, e.g. `<2024-01-01 Mon 10:00 +1w>`
+    /// or `[2024-01-01]--[2024-01-03]`.
+    ///
+    /// (defun org-element-timestamp-parser ()
+    pub(crate) fn timestamp_parser(&self, limit: usize) -> Option<SyntaxNode<'a>> {
+        let begin = self.cursor.borrow().pos();
+        let text = self.input.slice(begin, limit);
+        if !REGEX_TIMESTAMP.is_match(&text) {
+            return None;
+        }
+        let cap = REGEX_TIMESTAMP_RAW.captures(&text)?;
+        let active = text.starts_with('<');
+        let raw_value = &cap[0];
+        let date_start = &cap[1];
+        let date_end = cap.get(3).map(|m| m.as_str());
+        let diary = cap.get(2).is_some();
+        let timestamp_end = begin + cap.get(0).unwrap().end();
+        let post_blank = self.blanks_at(timestamp_end, limit);
+        let time_range = REGEX_TIME_RANGE
+            .captures(date_start)
+            .filter(|_| !diary)
+            .map(|cap| (cap[2].parse().unwrap(), cap[3].parse().unwrap()));
+        let type_s = match (diary, active, date_end.is_some() || time_range.is_some()) {
+            (true, _, _) => TimestampType::Diary,
+            (_, true, true) => TimestampType::ActiveRange,
+            (_, true, false) => TimestampType::Active,
+            (_, false, true) => TimestampType::InactiveRange,
+            (_, false, false) => TimestampType::Inactive,
+        };
+        let repeater = REGEX_REPEATER.captures(raw_value).filter(|_| !diary);
+        let warning = REGEX_WARNING.captures(raw_value).filter(|_| !diary);
+
+        // Parse date-start. Compute date-end. It can be provided directly
+        // in time-stamp, or extracted from time range. Otherwise, it
+        // defaults to the same values as date-start.
+        let start = if diary { None } else { parse_time(date_start) };
+        let end = date_end.and_then(parse_time);
+        let field = |date: Option<[Option<usize>; 5]>, i: usize| date.and_then(|d| d[i]);
+        let data = TimestampData {
+            day_end: field(end, 2).or_else(|| field(start, 2)).unwrap_or(0),
+            day_start: field(start, 2).unwrap_or(0),
+            hour_end: field(end, 3)
+                .or_else(|| time_range.map(|r| r.0))
+                .or_else(|| field(start, 3)),
+            hour_start: field(start, 3),
+            minute_end: field(end, 4)
+                .or_else(|| time_range.map(|r| r.1))
+                .or_else(|| field(start, 4)),
+            minute_start: field(start, 4),
+            month_end: field(end, 1).or_else(|| field(start, 1)).unwrap_or(0),
+            month_start: field(start, 1).unwrap_or(0),
+            raw_value: source::sub(&text, 0, raw_value.len()),
+            repeater_type: repeater.as_ref().map(|cap| match &cap[1] {
+                "++" => RepeaterType::CatchUp,
+                ".+" => RepeaterType::Restart,
+                _ => RepeaterType::Cumulate,
+            }),
+            repeater_unit: repeater.as_ref().map(|cap| time_unit(&cap[3])),
+            repeater_value: repeater.as_ref().map(|cap| cap[2].parse().unwrap()),
+            type_s,
+            warning_type: warning.as_ref().map(|cap| match cap.get(1) {
+                Some(_) => WarningType::First,
+                None => WarningType::All,
+            }),
+            warning_unit: warning.as_ref().map(|cap| time_unit(&cap[3])),
+            warning_value: warning.as_ref().map(|cap| cap[2].parse().unwrap()),
+            year_end: field(end, 0).or_else(|| field(start, 0)).unwrap_or(0),
+            year_start: field(start, 0).unwrap_or(0),
+        };
+        Some(SyntaxNode::new(
+            Syntax::Timestamp(Box::new(data)),
+            Interval {
+                start: begin,
+                end: timestamp_end + post_blank,
+            },
+            None,
+            post_blank,
+        ))
+    }
+
+    /// Parse target at cursor, e.g. `<<target>>`.
+    ///
+    /// (defun org-element-target-parser ()
+    pub(crate) fn target_parser(&self, limit: usize) -> Option<SyntaxNode<'a>> {
+        let begin = self.cursor.borrow().pos();
+        let text = self.input.slice(begin, limit);
+        let cap = REGEX_TARGET.captures(&text)?;
+        let target_end = begin + cap.get(0).unwrap().end();
+        let post_blank = self.blanks_at(target_end, limit);
+        Some(SyntaxNode::new(
+            Syntax::Target(Box::new(TargetData {
+                value: cap
+                    .get(1)
+                    .map(|m| source::sub(&text, m.start(), m.end()))
+                    .unwrap(),
+            })),
+            Interval {
+                start: begin,
+                end: target_end + post_blank,
+            },
+            None,
+            post_blank,
+        ))
+    }
+
+    /// Parse export snippet at cursor, e.g. `@@html:<br>@@`.
+    ///
+    /// (defun org-element-export-snippet-parser ()
+    pub(crate) fn export_snippet_parser(&self, limit: usize) -> Option<SyntaxNode<'a>> {
+        let begin = self.cursor.borrow().pos();
+        let text = self.input.slice(begin, limit);
+        let cap = REGEX_EXPORT_SNIPPET.captures(&text)?;
+        let value_begin = cap.get(0).unwrap().end();
+        let value_end = value_begin + text[value_begin..].find("@@")?;
+        let snippet_end = begin + value_end + 2;
+        let post_blank = self.blanks_at(snippet_end, limit);
+        Some(SyntaxNode::new(
+            Syntax::ExportSnippet(Box::new(ExportSnippetData {
+                back_end: cap
+                    .get(1)
+                    .map(|m| source::sub(&text, m.start(), m.end()))
+                    .unwrap(),
+                value: source::sub(&text, value_begin, value_end),
+            })),
+            Interval {
+                start: begin,
+                end: snippet_end + post_blank,
+            },
+            None,
+            post_blank,
+        ))
+    }
+
+    /// Parse macro at cursor, e.g. `{{{title}}}` or `{{{name(arg1,arg2)}}}`.
+    ///
+    /// (defun org-element-macro-parser ()
+    pub(crate) fn macro_parser(&self, limit: usize) -> Option<SyntaxNode<'a>> {
+        let begin = self.cursor.borrow().pos();
+        let text = self.input.slice(begin, limit);
+        let cap = REGEX_MACRO.captures(&text)?;
+        let macro_end = begin + cap.get(0).unwrap().end();
+        let post_blank = self.blanks_at(macro_end, limit);
+        let args = cap
+            .get(3)
+            .map(|m| macro_arguments(m.as_str()))
+            .unwrap_or_default();
+        Some(SyntaxNode::new(
+            Syntax::Macro(Box::new(MacroData {
+                args,
+                key: Cow::from(cap.get(1).unwrap().as_str().to_lowercase()),
+                value: cap
+                    .get(0)
+                    .map(|m| source::sub(&text, m.start(), m.end()))
+                    .unwrap(),
+            })),
+            Interval {
+                start: begin,
+                end: macro_end + post_blank,
+            },
+            None,
+            post_blank,
+        ))
+    }
+
+    /// Parse entity at cursor, e.g. `\alpha` or `\alpha{}`.
+    ///
+    /// (defun org-element-entity-parser ()
+    pub(crate) fn entity_parser(&self, limit: usize) -> Option<SyntaxNode<'a>> {
+        let begin = self.cursor.borrow().pos();
+        let text = self.input.slice(begin, limit);
+        let cap = REGEX_ENTITY.captures(&text)?;
+        let name = cap.get(1).or_else(|| cap.get(2)).unwrap();
+        let value = entities::entity(name.as_str())?;
+        let use_brackets_p = cap.get(3).map_or(false, |m| m.as_str() == "{}");
+        let entity_end = begin + name.end() + if use_brackets_p { 2 } else { 0 };
+        let post_blank = self.blanks_at(entity_end, limit);
+        Some(SyntaxNode::new(
+            Syntax::Entity(Box::new(EntityData {
+                ascii: value.ascii.clone(),
+                html: value.html.clone(),
+                latex: value.latex.clone(),
+                latex_math_p: value.latex_math_p,
+                latin1: value.latin1.clone(),
+                name: source::sub(&text, name.start(), name.end()),
+                use_brackets_p,
+                utf_8: value.utf_8.clone(),
+            })),
+            Interval {
+                start: begin,
+                end: entity_end + post_blank,
+            },
+            None,
+            post_blank,
+        ))
+    }
+
+    /// Parse line break at cursor, i.e. `\\` at the end of a line.
+    ///
+    /// (defun org-element-line-break-parser ()
+    pub(crate) fn line_break_parser(&self, limit: usize) -> Option<SyntaxNode<'a>> {
+        let begin = self.cursor.borrow().pos();
+        if !REGEX_LINE_BREAK.is_match(&self.input.slice(begin, limit))
+            || (begin > 0 && self.input.byte_at(begin - 1) == b'\\')
+        {
+            return None;
+        }
+        let end = self
+            .input
+            .find_byte(b'\n', begin, self.input.len())
+            .map_or(self.input.len(), |p| p + 1);
+        Some(SyntaxNode::new(
+            Syntax::LineBreak,
+            Interval { start: begin, end },
+            None,
+            0,
+        ))
+    }
+
+    /// Parse table cell at cursor.
+    ///
+    /// (defun org-element-table-cell-parser ()
+    pub(crate) fn table_cell_parser(&self, limit: usize) -> Option<SyntaxNode<'a>> {
+        let begin = self.cursor.borrow().pos();
+        let text = self.input.slice(begin, limit);
+        let cap = REGEX_TABLE_CELL.captures(&text)?;
+        let contents = cap.get(1).unwrap();
+        Some(SyntaxNode::new(
+            Syntax::TableCell,
+            Interval {
+                start: begin,
+                end: begin + cap.get(0).unwrap().end(),
+            },
+            Some(Interval {
+                start: begin + contents.start(),
+                end: begin + contents.end(),
+            }),
+            0,
+        ))
+    }
+}
+
+/// Replaces new lines in a link `path`, along with the blanks
+/// around them, with `rep`
+fn without_newlines<'a>(path: Cow<'a, str>, rep: &str) -> Cow<'a, str> {
+    if REGEX_LINK_NEWLINE.is_match(&path) {
+        Cow::from(REGEX_LINK_NEWLINE.replace_all(&path, rep).into_owned())
+    } else {
+        path
+    }
+}
+
+/// Splits macro arguments at commas. Commas can be escaped
+/// with a backslash, which can itself be escaped.
+/// elisp: `org-macro-extract-arguments`
+fn macro_arguments(s: &str) -> Vec<Cow<'static, str>> {
+    let mut args = vec![];
+    let mut current = String::new();
+    let mut backslashes = 0;
+    for c in s.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            ',' => {
+                current.extend(std::iter::repeat('\\').take(backslashes / 2));
+                if backslashes % 2 == 0 {
+                    args.push(Cow::from(std::mem::take(&mut current)));
+                } else {
+                    current.push(',');
+                }
+                backslashes = 0;
+            }
+            c => {
+                current.extend(std::iter::repeat('\\').take(backslashes));
+                backslashes = 0;
+                current.push(c);
+            }
+        }
+    }
+    current.extend(std::iter::repeat('\\').take(backslashes));
+    args.push(Cow::from(current));
+    args
+}
+
+/// Year, month, day, hour and minute of the first date in `s`,
+/// without defaults for the time.
+///
+/// (defun org-parse-time-string (s &optional nodefault)
+fn parse_time(s: &str) -> Option<[Option<usize>; 5]> {
+    let cap = REGEX_TIMESTAMP_DATE.captures(s)?;
+    let number = |i| cap.get(i).map(|m| m.as_str().parse().unwrap());
+    Some([number(1), number(2), number(3), number(4), number(5)])
+}
+
+/// Unit of a repeater or a warning delay, e.g. `w` in `+1w`
+fn time_unit(unit: &str) -> TimeUnit {
+    match unit {
+        "h" => TimeUnit::Hour,
+        "d" => TimeUnit::Day,
+        "w" => TimeUnit::Week,
+        "m" => TimeUnit::Month,
+        _ => TimeUnit::Year,
+    }
+}
+
+mod test {
+    use super::macro_arguments;
+    use crate::data::{NodeId, Syntax, SyntaxT, SyntaxTree};
+    use crate::options::ParserOptions;
+    use crate::parser::{ParseGranularity, Parser};
+
+    /// Types, texts and children of objects parsed in `text`
+    fn objects(text: &str) -> Vec<String> {
+        fn describe(tree: &SyntaxTree, id: NodeId, text: &str, out: &mut Vec<String>) {
+            let node = &tree[id];
+            let name = SyntaxT::from(&node.data).org_name();
+            out.push(format!(
+                "{} {:?}",
+                name,
+                &text[node.location.start..node.location.end]
+            ));
+            for child in tree.children(id) {
+                describe(tree, child, text, out);
+            }
+        }
+        let parser = Parser::new(text, ParseGranularity::Object, ParserOptions::default());
+        let ids =
+            parser.parse_object_list(0, text.len(), &|that| SyntaxT::Paragraph.can_contain(that));
+        let tree = parser.tree.borrow();
+        let mut out = vec![];
+        for id in ids {
+            describe(&tree, id, text, &mut out);
+        }
+        out
+    }
+
+    #[test]
+    fn text_markup() {
+        assert_eq!(
+            vec![
+                "plain-text \"Some \"",
+                "bold \"*bold /and italic/* \"",
+                "plain-text \"bold \"",
+                "italic \"/and italic/\"",
+                "plain-text \"and italic\"",
+                "plain-text \"and \"",
+                "code \"~code~\"",
+                "plain-text \", \"",
+                "verbatim \"=verb=\"",
+            ],
+            objects("Some *bold /and italic/* and ~code~, =verb=")
+        );
+        assert_eq!(vec!["plain-text \"a*b* 2*3\""], objects("a*b* 2*3"));
+        assert_eq!(vec!["plain-text \"* not bold *\""], objects("* not bold *"));
+        assert_eq!(
+            vec![
+                "strike-through \"+two\\nlines+\"",
+                "plain-text \"two\\nlines\""
+            ],
+            objects("+two\nlines+")
+        );
+    }
+
+    #[test]
+    fn links() {
+        let text = "See [[https://orgmode.org][the *site*]], <mailto:a@b.c> and \
+                    https://example.com/x. Also [[file:a.org::*Head]] [[#id]] [[Target]]";
+        let parser = Parser::new(text, ParseGranularity::Object, ParserOptions::default());
+        let ids =
+            parser.parse_object_list(0, text.len(), &|that| SyntaxT::Paragraph.can_contain(that));
+        let tree = parser.tree.borrow();
+        let links: Vec<_> = ids
+            .iter()
+            .filter_map(|&id| match &tree[id].data {
+                Syntax::Link(link) => Some((id, link.clone())),
+                _ => None,
+            })
+            .collect();
+        let summary: Vec<_> = links
+            .iter()
+            .map(|(_, l)| {
+                format!(
+                    "{} {} {:?}",
+                    l.link_type,
+                    l.path,
+                    l.search_option.as_ref().map(|s| s.to_string())
+                )
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                "https //orgmode.org None",
+                "mailto a@b.c None",
+                "https //example.com/x None",
+                "file a.org Some(\"*Head\")",
+                "custom-id id None",
+                "fuzzy Target None",
+            ],
+            summary
+        );
+        let description: Vec<_> = tree
+            .children(links[0].0)
+            .map(|id| SyntaxT::from(&tree[id].data).org_name())
+            .collect();
+        assert_eq!(vec!["plain-text", "bold"], description);
+    }
+
+    #[test]
+    fn other_objects() {
+        assert_eq!(
+            vec![
+                "plain-text \"Done \"",
+                "statistics-cookie \"[1/2] \"",
+                "target \"<<here>>\"",
+                "plain-text \", \"",
+                "export-snippet \"@@html:<b>@@ \"",
+                "macro \"{{{kbd(C-x\\\\, b)}}}\"",
+                "footnote-reference \"[fn:1] \"",
+                "footnote-reference \"[fn::inline *fn*] \"",
+                "plain-text \"inline \"",
+                "bold \"*fn*\"",
+                "plain-text \"fn\"",
+                "plain-text \"end\"",
+                "line-break \"\\\\\\\\\\n\"",
+                "plain-text \"next\"",
+            ],
+            objects(
+                "Done [1/2] <<here>>, @@html:<b>@@ {{{kbd(C-x\\, b)}}}[fn:1] \
+                 [fn::inline *fn*] end\\\\\nnext"
+            )
+        );
+    }
+
+    #[test]
+    fn timestamps() {
+        use crate::data::{RepeaterType, TimeUnit, TimestampType, WarningType};
+
+        let text = "<2024-01-01 Mon 10:00-11:30 +1w -2d> \
+                    [2024-01-01 Mon]--[2024-01-03 Wed] <%%(diary-float t 4 2)> [1/2]";
+        assert_eq!(
+            vec![
+                "timestamp \"<2024-01-01 Mon 10:00-11:30 +1w -2d> \"",
+                "timestamp \"[2024-01-01 Mon]--[2024-01-03 Wed] \"",
+                "timestamp \"<%%(diary-float t 4 2)> \"",
+                "statistics-cookie \"[1/2]\"",
+            ],
+            objects(text)
+        );
+        let parser = Parser::new(text, ParseGranularity::Object, ParserOptions::default());
+        let ids =
+            parser.parse_object_list(0, text.len(), &|that| SyntaxT::Paragraph.can_contain(that));
+        let tree = parser.tree.borrow();
+        let timestamps: Vec<_> = ids
+            .iter()
+            .filter_map(|&id| match &tree[id].data {
+                Syntax::Timestamp(t) => Some(t.clone()),
+                _ => None,
+            })
+            .collect();
+
+        let range = &timestamps[0];
+        assert_eq!(TimestampType::ActiveRange, range.type_s);
+        assert_eq!(
+            (2024, 1, 1, Some(10), Some(0)),
+            (
+                range.year_start,
+                range.month_start,
+                range.day_start,
+                range.hour_start,
+                range.minute_start
+            )
+        );
+        assert_eq!(
+            (2024, 1, 1, Some(11), Some(30)),
+            (
+                range.year_end,
+                range.month_end,
+                range.day_end,
+                range.hour_end,
+                range.minute_end
+            )
+        );
+        assert_eq!(
+            (Some(RepeaterType::Cumulate), Some(1), Some(TimeUnit::Week)),
+            (
+                range.repeater_type.clone(),
+                range.repeater_value,
+                range.repeater_unit.clone()
+            )
+        );
+        assert_eq!(
+            (Some(WarningType::All), Some(2), Some(TimeUnit::Day)),
+            (
+                range.warning_type.clone(),
+                range.warning_value,
+                range.warning_unit.clone()
+            )
+        );
+
+        let inactive = &timestamps[1];
+        assert_eq!(TimestampType::InactiveRange, inactive.type_s);
+        assert_eq!((1, 3), (inactive.day_start, inactive.day_end));
+        assert_eq!((None, None), (inactive.hour_start, inactive.hour_end));
+
+        let diary = &timestamps[2];
+        assert_eq!(TimestampType::Diary, diary.type_s);
+        assert_eq!("<%%(diary-float t 4 2)>", diary.raw_value);
+    }
+
+    #[test]
+    fn entities() {
+        let text = r"a \alpha{}b \nbsp\alphax \_  end";
+        assert_eq!(
+            vec![
+                "plain-text \"a \"",
+                "entity \"\\\\alpha{}\"",
+                "plain-text \"b \"",
+                "entity \"\\\\nbsp\"",
+                "plain-text \"\\\\alphax \"",
+                "entity \"\\\\_  \"",
+                "plain-text \"end\"",
+            ],
+            objects(text)
+        );
+        let parser = Parser::new(text, ParseGranularity::Object, ParserOptions::default());
+        let ids =
+            parser.parse_object_list(0, text.len(), &|that| SyntaxT::Paragraph.can_contain(that));
+        let tree = parser.tree.borrow();
+        let entities: Vec<_> = ids
+            .iter()
+            .filter_map(|&id| match &tree[id].data {
+                Syntax::Entity(e) => Some((
+                    e.name.to_string(),
+                    e.use_brackets_p,
+                    e.latex.to_string(),
+                    e.latex_math_p,
+                    e.html.to_string(),
+                    e.utf_8.to_string(),
+                )),
+                _ => None,
+            })
+            .collect();
+        let entity = |name: &str, brackets, latex: &str, math, html: &str, utf_8: &str| {
+            (
+                name.to_string(),
+                brackets,
+                latex.to_string(),
+                math,
+                html.to_string(),
+                utf_8.to_string(),
+            )
+        };
+        assert_eq!(
+            vec![
+                entity("alpha", true, r"\alpha", true, "&alpha;", "α"),
+                entity("nbsp", false, "~", false, "&nbsp;", "\u{a0}"),
+                entity(
+                    "_  ",
+                    false,
+                    r"\hspace*{1.0em}",
+                    false,
+                    "&ensp;&ensp;",
+                    "\u{2002}\u{2002}"
+                ),
+            ],
+            entities
+        );
+    }
+
+    #[test]
+    fn macro_args() {
+        assert_eq!(vec!["a", "b,c", " d\\"], macro_arguments("a,b\\,c, d\\"));
+        assert_eq!(vec!["x\\", "y"], macro_arguments("x\\\\,y"));
+    }
+}
@@ -0,0 +1,504 @@
+//    This file is part of org-rs.
+//
+//    org-rs is free software: you can redistribute it and/or modify
+//    it under the terms of the GNU General Public License as published by
+//    the Free Software Foundation, either version 3 of the License, or
+//    (at your option) any later version.
+//
+//    org-rs is distributed in the hope that it will be useful,
+//    but WITHOUT ANY WARRANTY; without even the implied warranty of
+//    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//    GNU General Public License for more details.
+//
+//    You should have received a copy of the GNU General Public License
+//    along with org-rs.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Parser configuration
+//!
+//! Org mode changes the syntax it recognizes depending on a handful of
+//! customization variables. `ParserOptions` gathers those the parser
+//! depends on. Defaults match the defaults of Org mode.
+//!
+//! Regexes depending on the options are built once per `Parser`,
+//! see `Regexes`.
+
+use regex::Regex;
+
+/// The character that makes a line with leading number an ordered list item.
+/// elisp: `org-plain-list-ordered-item-terminator`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemTerminator {
+    /// Both `1.` and `1)` start an item
+    Both,
+    /// Only `1.` starts an item
+    Dot,
+    /// Only `1)` starts an item
+    Paren,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParserOptions {
+    /// elisp: `org-plain-list-ordered-item-terminator`
+    pub item_terminator: ItemTerminator,
+
+    /// Allow single alphabetical characters as list bullets,
+    /// i.e. `a.`, `B)`.
+    /// elisp: `org-list-allow-alphabetical`
+    pub allow_alphabetical: bool,
+
+    /// Title of the headline holding footnote definitions,
+    /// `None` if footnotes are not gathered in a section.
+    /// elisp: `org-footnote-section`
+    pub footnote_section: Option<String>,
+
+    /// Minimum level a headline must have to be an inlinetask,
+    /// `None` disables inlinetasks.
+    /// elisp: `org-inlinetask-min-level`
+    pub inlinetask_min_level: Option<usize>,
+
+    /// Keywords of unfinished TODO states
+    /// elisp: `org-not-done-keywords`, derived from `org-todo-keywords`
+    pub todo_keywords: Vec<String>,
+
+    /// Keywords of finished TODO states
+    /// elisp: `org-done-keywords`, derived from `org-todo-keywords`
+    pub done_keywords: Vec<String>,
+
+    /// Keywords that can be attached to an element,
+    /// `#+ATTR_BACKEND` keywords are always accepted.
+    /// elisp: `org-element-affiliated-keywords`
+    pub affiliated_keywords: Vec<String>,
+
+    /// Affiliated keywords that can have a secondary value,
+    /// i.e. `#+KEY[SECONDARY]: VALUE`.
+    /// elisp: `org-element-dual-keywords`
+    pub dual_keywords: Vec<String>,
+
+    /// Affiliated keywords whose value can contain objects.
+    /// elisp: `org-element-parsed-keywords`
+    pub parsed_keywords: Vec<String>,
+
+    /// Affiliated keywords that can occur more than once in an element.
+    /// `#+ATTR_BACKEND` keywords always can.
+    /// elisp: `org-element-multiple-keywords`
+    pub multiple_keywords: Vec<String>,
+
+    /// Pairs of (old, new) affiliated keyword names,
+    /// old names are stored under the new one.
+    /// elisp: `org-element-keyword-translation-alist`
+    pub keyword_translations: Vec<(String, String)>,
+
+    /// Link types recognized in plain and angle links, and as
+    /// explicit types of bracket links.
+    /// elisp: `org-link-types`, built from `org-link-parameters`
+    pub link_types: Vec<String>,
+
+    /// Distance between tab stops, used to compute indentation,
+    /// e.g. to tell which list item a line belongs to.
+    /// elisp: `tab-width`
+    pub tab_width: usize,
+}
+
+fn strings(list: &[&str]) -> Vec<String> {
+    list.iter().map(|s| s.to_string()).collect()
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        ParserOptions {
+            item_terminator: ItemTerminator::Both,
+            allow_alphabetical: false,
+            footnote_section: Some("Footnotes".to_string()),
+            inlinetask_min_level: Some(15),
+            todo_keywords: vec!["TODO".to_string()],
+            done_keywords: vec!["DONE".to_string()],
+            affiliated_keywords: strings(&[
+                "CAPTION", "DATA", "HEADER", "HEADERS", "LABEL", "NAME", "PLOT", "RESNAME",
+                "RESULT", "RESULTS", "SOURCE", "SRCNAME", "TBLNAME",
+            ]),
+            dual_keywords: strings(&["CAPTION", "RESULTS"]),
+            parsed_keywords: strings(&["CAPTION"]),
+            multiple_keywords: strings(&["CAPTION", "HEADER"]),
+            keyword_translations: [
+                ("DATA", "NAME"),
+                ("LABEL", "NAME"),
+                ("RESNAME", "NAME"),
+                ("SOURCE", "NAME"),
+                ("SRCNAME", "NAME"),
+                ("TBLNAME", "NAME"),
+                ("RESULT", "RESULTS"),
+                ("HEADERS", "HEADER"),
+            ]
+            .iter()
+            .map(|(old, new)| (old.to_string(), new.to_string()))
+            .collect(),
+            link_types: strings(&[
+                "doi",
+                "elisp",
+                "file",
+                "file+emacs",
+                "file+sys",
+                "ftp",
+                "help",
+                "http",
+                "https",
+                "id",
+                "info",
+                "irc",
+                "mailto",
+                "news",
+                "shell",
+            ]),
+            tab_width: 8,
+        }
+    }
+}
+
+impl ParserOptions {
+    /// Return the correct regular expression for plain lists.
+    ///
+    /// group 1: bullet
+    /// elisp: `org-item-re`
+    pub fn item_re(&self) -> String {
+        format!(
+            r"^([ \t]*(?:[-+]|{})|[ \t]+\*)(?:[ \t]+|$)",
+            self.ordered_bullet_re()
+        )
+    }
+
+    /// Regexp matching the stars of an inlinetask, `None` when
+    /// inlinetasks are disabled.
+    ///
+    /// elisp: `org-inlinetask-outline-regexp`
+    pub fn inlinetask_re(&self) -> Option<String> {
+        self.inlinetask_min_level
+            .map(|level| format!(r"(?m)^\*{{{},}}[ \t]+", level))
+    }
+
+    /// Regexp to separate paragraphs in an Org buffer.
+    /// In the case of lines starting with "#" and ":", this regexp
+    /// is not sufficient to know if point is at a paragraph ending.
+    /// See `paragraph_parser` for more information.
+    ///
+    /// elisp: `org-element-paragraph-separate`
+    pub fn paragraph_separate_re(&self) -> String {
+        [
+            r"(?mi)^(?:",
+            // Headlines, inlinetasks.
+            r"\*+ |",
+            // Footnote definitions.
+            r"\[fn:[-_\w]+\]|",
+            // Diary sexps.
+            r"%%\(|",
+            r"[ \t]*(?:",
+            // Empty lines.
+            r"$|",
+            // Tables (any type).
+            r"\||",
+            r"\+(?:-+\+)+[ \t]*$|",
+            // Comments, keyword-like or block-like constructs.
+            // Blocks and keywords with dual values need to be
+            // double-checked.
+            r"#(?: |$|\+(?:BEGIN_\S+|\S+(?:\[.*\])?:[ \t]*))|",
+            // Drawers (any type) and fixed-width areas. Drawers
+            // need to be double-checked.
+            r":(?: |$|[-_\w]+:[ \t]*$)|",
+            // Horizontal rules.
+            r"-{5,}[ \t]*$|",
+            // LaTeX environments.
+            r"\\begin\{[A-Za-z0-9*]+\}|",
+            // Clock lines.
+            r"CLOCK:|",
+            // Lists.
+            &format!(r"(?:[-+*]|{})(?:[ \t]|$)", self.ordered_bullet_re()),
+            r"))",
+        ]
+        .concat()
+    }
+
+    /// Counter and terminator of ordered list items, e.g. `1.`
+    fn ordered_bullet_re(&self) -> String {
+        let term = match self.item_terminator {
+            ItemTerminator::Both => r"[.)]",
+            ItemTerminator::Dot => r"\.",
+            ItemTerminator::Paren => r"\)",
+        };
+        let alpha = if self.allow_alphabetical {
+            "|[A-Za-z]"
+        } else {
+            ""
+        };
+        format!("(?:[0-9]+{}){}", alpha, term)
+    }
+
+    /// Regexp matching any TODO keyword at the beginning of
+    /// a headline title, keyword is in group 1.
+    /// `None` when there are no keywords.
+    /// elisp: `org-todo-regexp`
+    pub fn todo_re(&self) -> Option<String> {
+        if self.todo_keywords.is_empty() && self.done_keywords.is_empty() {
+            return None;
+        }
+        let keywords: Vec<String> = self
+            .todo_keywords
+            .iter()
+            .chain(self.done_keywords.iter())
+            .map(|k| regex::escape(k))
+            .collect();
+        Some(format!(r"^({})(?: |$)", keywords.join("|")))
+    }
+
+    /// Regexp matching any affiliated keyword.
+    ///
+    /// Keyword name is captured in one of the following groups:
+    /// - `DUAL` for `dual_keywords`, their secondary value is in `SECONDARY`
+    /// - `REGULAR` for other `affiliated_keywords`
+    /// - `ATTR` for `#+ATTR_BACKEND` keywords
+    ///
+    /// Rust's regex can't reuse group numbers as `\\(?1:...\\)` does,
+    /// hence the named groups.
+    /// elisp: `org-element--affiliated-re`
+    pub fn affiliated_re(&self) -> String {
+        let regexp_opt = |keywords: &mut dyn Iterator<Item = &String>| {
+            keywords
+                .map(|k| regex::escape(k))
+                .collect::<Vec<_>>()
+                .join("|")
+        };
+        let duals = regexp_opt(&mut self.dual_keywords.iter());
+        let regulars = regexp_opt(
+            &mut self
+                .affiliated_keywords
+                .iter()
+                .filter(|k| !self.is_dual_keyword(k)),
+        );
+        let mut alternatives = vec![];
+        if !duals.is_empty() {
+            // Dual affiliated keywords.
+            alternatives.push(format!(r"(?P<DUAL>{})(?:\[(?P<SECONDARY>.*)\])?", duals));
+        }
+        if !regulars.is_empty() {
+            // Regular affiliated keywords.
+            alternatives.push(format!("(?P<REGULAR>{})", regulars));
+        }
+        // Export attributes.
+        alternatives.push(r"(?P<ATTR>ATTR_[-_A-Za-z0-9]+)".to_string());
+        format!(r"(?i)^[ \t]*#\+(?:{}):[ \t]*", alternatives.join("|"))
+    }
+
+    /// Alternation of `link_types`, longest first
+    fn link_types_re(&self) -> String {
+        let mut types: Vec<&String> = self.link_types.iter().collect();
+        types.sort_by(|a, b| b.len().cmp(&a.len()));
+        let types: Vec<String> = types.iter().map(|t| regex::escape(t)).collect();
+        format!("(?:{})", types.join("|"))
+    }
+
+    /// Regexp possibly matching the beginning of an object.
+    /// This regexp allows false positives. Dedicated parsers
+    /// will take care of further filtering.
+    /// elisp: `org-element--object-regexp`
+    pub fn object_re(&self) -> String {
+        let types = self.link_types_re();
+        [
+            "(?m)",
+            // Sub/superscript candidate.
+            r"[_^][-{(*+.,\w]|",
+            // Bold, code, italic, strike-through, underline and verbatim.
+            r"[*~=+_/][^\s]|",
+            // Plain links.
+            &format!(r"\b{}:|", types),
+            // Objects starting with "[": regular link, footnote
+            // reference, statistics cookie, timestamp (inactive).
+            r"\[(?:fn:|(?:[0-9]|(?:%|/[0-9]*)\])|\[)|",
+            // Objects starting with "@": export snippets.
+            "@@|",
+            // Objects starting with "{": macro.
+            r"\{\{\{|",
+            // Objects starting with "<" : timestamp (active, diary),
+            // target, radio target and angular links.
+            &format!(r"<(?:%%|<|[0-9]|{})|", types),
+            // Objects starting with "$": latex fragment.
+            r"\$|",
+            // Objects starting with "\": line break, entity, latex fragment.
+            r"\\(?:[a-zA-Z\[(]|\\[ \t]*$|_ +)|",
+            // Objects starting with raw text: inline Babel source
+            // block, inline Babel call.
+            "(?i:call|src)_",
+        ]
+        .concat()
+    }
+
+    /// Matches a plain link, type in group 1 and path in group 2.
+    /// elisp: `org-plain-link-re`
+    pub fn plain_link_re(&self) -> String {
+        format!(
+            r"\A\b({}):([^ \t\n()<>]+(?:\([\w0-9_]+\)|[^[:punct:] \t\n]|/))",
+            self.link_types_re()
+        )
+    }
+
+    /// Matches an angle link, type in group 1 and path in group 2.
+    /// elisp: `org-angle-link-re`
+    pub fn angle_link_re(&self) -> String {
+        format!(
+            r"\A<({}):([^>\n]*(?:\n[ \t]*[^> \t\n][^>\n]*)*)>",
+            self.link_types_re()
+        )
+    }
+
+    /// Matches an explicit link type at the beginning of a link,
+    /// type in group 1.
+    /// elisp: `org-link-types-re`
+    pub fn link_type_re(&self) -> String {
+        format!(r"\A({}):", self.link_types_re())
+    }
+
+    /// Name `keyword` is stored under, upper case.
+    /// elisp: `org-element-keyword-translation-alist`
+    pub fn translate_keyword(&self, keyword: &str) -> String {
+        let keyword = keyword.to_ascii_uppercase();
+        match self
+            .keyword_translations
+            .iter()
+            .find(|(old, _)| old.eq_ignore_ascii_case(&keyword))
+        {
+            Some((_, new)) => new.to_ascii_uppercase(),
+            None => keyword,
+        }
+    }
+
+    /// elisp: `(member kwd org-element-dual-keywords)`
+    pub fn is_dual_keyword(&self, keyword: &str) -> bool {
+        contains_keyword(&self.dual_keywords, keyword)
+    }
+
+    /// elisp: `(member kwd org-element-parsed-keywords)`
+    pub fn is_parsed_keyword(&self, keyword: &str) -> bool {
+        contains_keyword(&self.parsed_keywords, keyword)
+    }
+
+    /// Attributes can always appear on multiple lines.
+    /// elisp: `(or (member kwd org-element-multiple-keywords) (string-match "^ATTR_" kwd))`
+    pub fn is_multiple_keyword(&self, keyword: &str) -> bool {
+        contains_keyword(&self.multiple_keywords, keyword)
+            || keyword
+                .get(..5)
+                .map_or(false, |p| p.eq_ignore_ascii_case("ATTR_"))
+    }
+}
+
+fn contains_keyword(list: &[String], keyword: &str) -> bool {
+    list.iter().any(|k| k.eq_ignore_ascii_case(keyword))
+}
+
+/// Regexes derived from `ParserOptions`
+#[derive(Clone)]
+pub(crate) struct Regexes {
+    pub item: Regex,
+    pub paragraph_separate: Regex,
+    pub inlinetask: Option<Regex>,
+    pub todo: Option<Regex>,
+    pub affiliated: Regex,
+    pub object: Regex,
+    pub plain_link: Regex,
+    pub angle_link: Regex,
+    pub link_type: Regex,
+}
+
+impl Regexes {
+    pub fn new(options: &ParserOptions) -> Regexes {
+        Regexes {
+            item: Regex::new(&options.item_re()).unwrap(),
+            paragraph_separate: Regex::new(&options.paragraph_separate_re()).unwrap(),
+            inlinetask: options.inlinetask_re().map(|re| Regex::new(&re).unwrap()),
+            todo: options.todo_re().map(|re| Regex::new(&re).unwrap()),
+            affiliated: Regex::new(&options.affiliated_re()).unwrap(),
+            object: Regex::new(&options.object_re()).unwrap(),
+            plain_link: Regex::new(&options.plain_link_re()).unwrap(),
+            angle_link: Regex::new(&options.angle_link_re()).unwrap(),
+            link_type: Regex::new(&options.link_type_re()).unwrap(),
+        }
+    }
+}
+
+mod test {
+    use super::{ItemTerminator, ParserOptions, Regexes};
+
+    #[test]
+    fn item_re() {
+        let re = Regexes::new(&ParserOptions::default()).item;
+        assert!(re.is_match("- item"));
+        assert!(re.is_match("  + item"));
+        assert!(re.is_match(" * item"));
+        assert!(re.is_match("1. item"));
+        assert!(re.is_match("2) item"));
+        assert!(!re.is_match("* headline"));
+        assert!(!re.is_match("a. item"));
+        assert!(!re.is_match("text - not an item"));
+
+        let options = ParserOptions {
+            item_terminator: ItemTerminator::Paren,
+            allow_alphabetical: true,
+            ..ParserOptions::default()
+        };
+        let re = Regexes::new(&options).item;
+        assert!(re.is_match("a) item"));
+        assert!(re.is_match("10) item"));
+        assert!(!re.is_match("1. item"));
+    }
+
+    #[test]
+    fn inlinetask_re() {
+        let options = ParserOptions {
+            inlinetask_min_level: Some(3),
+            ..ParserOptions::default()
+        };
+        let re = Regexes::new(&options).inlinetask.unwrap();
+        assert!(re.is_match("*** task"));
+        assert!(re.is_match("**** task"));
+        assert!(!re.is_match("** headline"));
+        let options = ParserOptions {
+            inlinetask_min_level: None,
+            ..ParserOptions::default()
+        };
+        assert!(Regexes::new(&options).inlinetask.is_none());
+    }
+
+    #[test]
+    fn paragraph_separate_re() {
+        let re = Regexes::new(&ParserOptions::default()).paragraph_separate;
+        assert!(re.is_match("text\n1. item"));
+        assert!(!re.is_match("text\na. item"));
+
+        let options = ParserOptions {
+            item_terminator: ItemTerminator::Dot,
+            allow_alphabetical: true,
+            ..ParserOptions::default()
+        };
+        let re = Regexes::new(&options).paragraph_separate;
+        assert!(re.is_match("text\na. item"));
+        assert!(!re.is_match("text\n1) item"));
+    }
+
+    #[test]
+    fn todo_re() {
+        let options = ParserOptions {
+            todo_keywords: vec!["NEXT".to_string(), "WAIT?".to_string()],
+            done_keywords: vec![],
+            ..ParserOptions::default()
+        };
+        let re = Regexes::new(&options).todo.unwrap();
+        assert_eq!("WAIT?", &re.captures("WAIT? for it").unwrap()[1]);
+        assert!(re.is_match("NEXT"));
+        assert!(!re.is_match("TODO task"));
+        assert!(!re.is_match("NEXTSTEP"));
+
+        let options = ParserOptions {
+            todo_keywords: vec![],
+            done_keywords: vec![],
+            ..ParserOptions::default()
+        };
+        assert!(Regexes::new(&options).todo.is_none());
+    }
+}
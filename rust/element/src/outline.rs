@@ -0,0 +1,300 @@
+//    This file is part of org-rs.
+//
+//    org-rs is free software: you can redistribute it and/or modify
+//    it under the terms of the GNU General Public License as published by
+//    the Free Software Foundation, either version 3 of the License, or
+//    (at your option) any later version.
+//
+//    org-rs is distributed in the hope that it will be useful,
+//    but WITHOUT ANY WARRANTY; without even the implied warranty of
+//    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//    GNU General Public License for more details.
+//
+//    You should have received a copy of the GNU General Public License
+//    along with org-rs.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Outline scanner
+//!
+//! Outline views and indexers only need headlines. `Outline` finds
+//! them with `memchr`, skipping the text in between without looking
+//! at it, and yields what an outline shows about each of them. No
+//! `SyntaxNode` is built: a headline is parsed only up to its first
+//! line, and its property drawer when it has one.
+//!
+//! Headline lines are parsed by `Parser::heading_line`, the same way
+//! `Parser::headline_parser` parses them. Inlinetasks are skipped.
+
+use crate::data::Interval;
+use crate::headline::{NodePropertyData, Tag, TodoType, REGEX_NODE_PROPERTY, REGEX_PLANNING_LINE};
+use crate::options::ParserOptions;
+use crate::parser::{ParseGranularity, Parser};
+use memchr::memchr;
+use memchr::memmem::Finder;
+use regex::Regex;
+use std::borrow::Cow;
+
+lazy_static! {
+    static ref REGEX_PROPERTIES: Regex = Regex::new(r"(?i)^[ \t]*:PROPERTIES:[ \t]*$").unwrap();
+    static ref REGEX_END: Regex = Regex::new(r"(?i)^[ \t]*:END:[ \t]*$").unwrap();
+}
+
+/// A headline, as seen by `Outline`
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutlineHeadline<'a> {
+    /// Reduced level of the headline (integer).
+    pub level: usize,
+    /// Its TODO keyword, if any (string or nil).
+    pub todo_keyword: Option<Cow<'a, str>>,
+    /// Type of headline's TODO keyword, if any (symbol done, todo).
+    pub todo_type: Option<TodoType>,
+    /// Headline's priority, as a character (integer).
+    pub priority: usize,
+    /// Non_nil if the headline has a comment keyword (boolean).
+    pub commentedp: bool,
+    /// Raw headline's text, without the stars and the tags (string).
+    pub title: &'a str,
+    /// Headline's tags, if any, without the archive tag. (list of strings).
+    pub tags: Vec<Tag<'a>>,
+    /// From the beginning of the headline to the beginning of the
+    /// next one, whatever its level. The subtree of the headline
+    /// extends to the next headline of the same or upper level.
+    pub location: Interval,
+    /// Properties from the property drawer, in order
+    pub properties: Vec<NodePropertyData<'a>>,
+}
+
+/// Iterator over the headlines of a document, see module documentation
+pub struct Outline<'a> {
+    parser: Parser<'a>,
+    input: &'a str,
+    finder: Finder<'static>,
+    /// Beginning of the next headline to yield
+    next: Option<usize>,
+}
+
+impl<'a> Outline<'a> {
+    pub fn new(input: &'a str, options: ParserOptions) -> Outline<'a> {
+        let mut outline = Outline {
+            parser: Parser::new(input, ParseGranularity::Headline, options),
+            input,
+            finder: Finder::new(b"\n*").into_owned(),
+            next: None,
+        };
+        outline.next = if outline.is_headline_at(0) {
+            Some(0)
+        } else {
+            outline.next_headline(0)
+        };
+        outline
+    }
+
+    /// Returns true if a headline, not an inlinetask, starts at `pos`
+    fn is_headline_at(&self, pos: usize) -> bool {
+        let bytes = &self.input.as_bytes()[pos..];
+        let level = bytes.iter().take_while(|&&b| b == b'*').count();
+        level > 0
+            && matches!(bytes.get(level), Some(b' ') | Some(b'\t'))
+            && !self.parser.is_inlinetask_level(level)
+    }
+
+    /// Beginning of the first headline on a line after `pos`
+    fn next_headline(&self, mut pos: usize) -> Option<usize> {
+        while let Some(found) = self.finder.find(&self.input.as_bytes()[pos..]) {
+            let line = pos + found + 1;
+            if self.is_headline_at(line) {
+                return Some(line);
+            }
+            pos = line;
+        }
+        None
+    }
+
+    /// Returns the end of the line starting at `pos`, and the
+    /// beginning of the next one
+    fn line_at(&self, pos: usize) -> (usize, usize) {
+        match memchr(b'\n', &self.input.as_bytes()[pos..]) {
+            Some(p) => (pos + p, pos + p + 1),
+            None => (self.input.len(), self.input.len()),
+        }
+    }
+
+    /// Properties of the drawer starting at `pos`, if any, before `limit`.
+    /// Like `org-property-drawer-re`, a drawer containing anything but
+    /// properties is not a property drawer.
+    fn properties(&self, mut pos: usize, limit: usize) -> Vec<NodePropertyData<'a>> {
+        let (end, next) = self.line_at(pos);
+        if pos >= limit || !REGEX_PROPERTIES.is_match(&self.input[pos..end]) {
+            return vec![];
+        }
+        pos = next;
+        let mut properties = vec![];
+        while pos < limit {
+            let (end, next) = self.line_at(pos);
+            let line = &self.input[pos..end];
+            if REGEX_END.is_match(line) {
+                return properties;
+            }
+            match REGEX_NODE_PROPERTY.captures(line) {
+                Some(cap) => properties.push(NodePropertyData {
+                    key: Cow::from(cap.get(1).unwrap().as_str()),
+                    value: Cow::from(cap.get(2).map_or("", |m| m.as_str())),
+                }),
+                None => return vec![],
+            }
+            pos = next;
+        }
+        vec![]
+    }
+}
+
+impl<'a> Iterator for Outline<'a> {
+    type Item = OutlineHeadline<'a>;
+
+    fn next(&mut self) -> Option<OutlineHeadline<'a>> {
+        let begin = self.next?;
+        let line = self.parser.heading_line(begin);
+        let (_, mut pos) = self.line_at(line.end);
+        self.next = self.next_headline(line.end);
+        let end = self.next.unwrap_or_else(|| self.input.len());
+
+        // The property drawer follows the headline, or its planning line.
+        if pos < end && REGEX_PLANNING_LINE.is_match(&self.input[pos..self.line_at(pos).0]) {
+            pos = self.line_at(pos).1;
+        }
+
+        Some(OutlineHeadline {
+            level: line.level,
+            todo_keyword: line.todo_keyword,
+            todo_type: line.todo_type,
+            priority: line.priority,
+            commentedp: line.commentedp,
+            title: &self.input[line.title.start..line.title.end],
+            tags: line.tags,
+            location: Interval { start: begin, end },
+            properties: self.properties(pos, end),
+        })
+    }
+}
+
+mod test {
+    use super::Outline;
+    use crate::data::{Interval, Syntax, SyntaxTree};
+    use crate::headline::{Tag, TodoType};
+    use crate::options::ParserOptions;
+    use crate::parser::{ParseGranularity, Parser};
+    use std::borrow::Cow;
+
+    const TEXT: &str = "Text before\n\
+                        * TODO [#A] First :work:urgent:\n\
+                        SCHEDULED: <2019-01-01 Tue>\n\
+                        :PROPERTIES:\n\
+                        :ID:       1234\n\
+                        :EMPTY:\n\
+                        :END:\n\
+                        Body *with bold* text\n\
+                        *** Inline task\n\
+                        ** DONE Second\n\
+                        :PROPERTIES:\n\
+                        not a property\n\
+                        :END:\n\
+                        * COMMENT Third\n\
+                        *not a headline*\n\
+                        *\tLast";
+
+    fn options() -> ParserOptions {
+        ParserOptions {
+            inlinetask_min_level: Some(3),
+            ..ParserOptions::default()
+        }
+    }
+
+    #[test]
+    fn outline() {
+        let headlines: Vec<_> = Outline::new(TEXT, options()).collect();
+        assert_eq!(4, headlines.len());
+
+        let first = &headlines[0];
+        assert_eq!(1, first.level);
+        assert_eq!(Some(Cow::from("TODO")), first.todo_keyword);
+        assert_eq!(Some(TodoType::Todo), first.todo_type);
+        assert_eq!('A' as usize, first.priority);
+        assert_eq!("First", first.title);
+        assert_eq!(
+            vec![Tag(Cow::from("work")), Tag(Cow::from("urgent"))],
+            first.tags
+        );
+        assert_eq!(
+            Interval {
+                start: TEXT.find("* TODO").unwrap(),
+                end: TEXT.find("** DONE").unwrap(),
+            },
+            first.location
+        );
+        let properties: Vec<_> = first
+            .properties
+            .iter()
+            .map(|p| (p.key.as_ref(), p.value.as_ref()))
+            .collect();
+        assert_eq!(vec![("ID", "1234"), ("EMPTY", "")], properties);
+
+        let second = &headlines[1];
+        assert_eq!(2, second.level);
+        assert_eq!(Some(TodoType::Done), second.todo_type);
+        assert!(second.properties.is_empty());
+
+        let third = &headlines[2];
+        assert!(third.commentedp);
+        assert_eq!("Third", third.title);
+
+        assert_eq!("Last", headlines[3].title);
+        assert_eq!(TEXT.len(), headlines[3].location.end);
+    }
+
+    /// Only a space or a tab after the stars makes a headline
+    #[test]
+    fn blank_after_stars() {
+        let text = "*\u{a0}x\n*\u{2003}y\n* Real\n";
+        let titles: Vec<_> = Outline::new(text, options()).map(|h| h.title).collect();
+        assert_eq!(vec!["Real"], titles);
+
+        let parser = Parser::new(text, ParseGranularity::Headline, options());
+        let tree = parser.parse_buffer();
+        let headlines = tree
+            .descendants(tree.root())
+            .filter(|&id| matches!(tree[id].data, Syntax::Headline(_)))
+            .count();
+        assert_eq!(1, headlines);
+    }
+
+    /// Headlines agree with the ones of a parsed buffer
+    #[test]
+    fn same_as_parser() {
+        let parser = Parser::new(TEXT, ParseGranularity::Headline, options());
+        let tree: SyntaxTree = parser.parse_buffer();
+        let parsed: Vec<_> = tree
+            .descendants(tree.root())
+            .filter_map(|id| match &tree[id].data {
+                Syntax::Headline(h) => Some((
+                    tree[id].location.start,
+                    h.level,
+                    h.todo_keyword.clone(),
+                    h.raw_value.clone(),
+                    h.tags.clone(),
+                )),
+                _ => None,
+            })
+            .collect();
+        let scanned: Vec<_> = Outline::new(TEXT, options())
+            .map(|h| {
+                (
+                    h.location.start,
+                    h.level,
+                    h.todo_keyword,
+                    Cow::from(h.title),
+                    h.tags,
+                )
+            })
+            .collect();
+        assert_eq!(parsed, scanned);
+    }
+}
@@ -14,8 +14,20 @@
 //    along with org-rs.  If not, see <https://www.gnu.org/licenses/>.
 
 use crate::affiliated::AffiliatedData;
-use crate::data::SyntaxNode;
+use crate::blocks::block_end_re;
+use crate::data::{Interval, Syntax, SyntaxNode};
+use crate::drawer::REGEX_DRAWER;
+use crate::latex::REGEX_LATEX_BEGIN_ENVIRIONMENT;
 use crate::parser::Parser;
+use crate::source::TextSource;
+use regex::Regex;
+
+lazy_static! {
+    pub(crate) static ref REGEX_DRAWER_END: Regex =
+        Regex::new(r"(?mi)^[ \t]*:END:[ \t]*$").unwrap();
+    static ref REGEX_BLOCK_BEGIN_NAME: Regex = Regex::new(r"(?i)^[ \t]*#\+BEGIN(_\S+)").unwrap();
+    static ref REGEX_DUAL_KEYWORD: Regex = Regex::new(r"^[ \t]*#\+(\S+)\[.*\]:").unwrap();
+}
 
 /// Parse a paragraph.
 ///
@@ -30,19 +42,181 @@ use crate::parser::Parser;
 ///
 /// Assume point is at the beginning of the paragraph."
 /// (defun org-element-paragraph-parser (limit affiliated)
-impl<'a> Parser<'a> {
-    // TODO implement paragraph_parser
+impl<'a, S: TextSource<'a>> Parser<'a, S> {
     pub fn paragraph_parser(
         &self,
         limit: usize,
         start: usize,
-        maybe_aff: Option<AffiliatedData>,
+        maybe_aff: Option<AffiliatedData<'a>>,
     ) -> SyntaxNode<'a> {
-        unimplemented!()
+        let mut c = self.cursor.borrow_mut();
+        let contents_begin = c.pos();
+
+        // A matching paragraph separator is not necessarily
+        // the end of the paragraph. In particular, drawers, blocks or
+        // LaTeX environments opening lines must be closed. Moreover
+        // keywords with a secondary value must belong to "dual keywords".
+        let mut line_end = c.line_end_position(None);
+        let before_blank = loop {
+            let separator = match self.next_separator(line_end, limit) {
+                None => break limit,
+                Some(separator) => separator,
+            };
+            c.set(separator);
+            let closed_by = |end_re: &Regex| {
+                self.lines(separator, limit)
+                    .any(|(line, end)| end_re.is_match(&self.input.slice(line, end)))
+            };
+            let is_end = if let Some(cap) = c.capturing_at(&*REGEX_BLOCK_BEGIN_NAME) {
+                closed_by(&self.regex(block_end_re(cap.get(1).unwrap().as_str())))
+            } else if c.looking_at(&*REGEX_DRAWER).is_some() {
+                closed_by(&*REGEX_DRAWER_END)
+            } else if let Some(cap) = c.capturing_at(&*REGEX_LATEX_BEGIN_ENVIRIONMENT) {
+                let name = regex::escape(cap.get(1).unwrap().as_str());
+                closed_by(&self.regex(format!(r"(?mi)^[ \t]*\\end\{{{}\}}[ \t]*$", name)))
+            } else if let Some(cap) = c.capturing_at(&*REGEX_DUAL_KEYWORD) {
+                let key = cap.get(1).unwrap().as_str();
+                self.options.is_dual_keyword(key)
+            } else {
+                // Everything else is unambiguous.
+                true
+            };
+            if is_end {
+                break separator;
+            }
+            line_end = c.line_end_position(None);
+        };
+
+        c.set(before_blank);
+        c.skip_chars_backward(" \r\t\n", Some(contents_begin));
+        let contents_end = c.line_beginning_position(Some(2));
+
+        c.set(before_blank);
+        c.skip_chars_forward(" \r\t\n", Some(limit));
+        let end = if c.pos() >= self.input.len() {
+            self.input.len()
+        } else {
+            c.line_beginning_position(None)
+        }
+        .min(limit);
+        let post_blank = c.count_lines(before_blank, end);
+        c.set(contents_begin);
+
+        let mut node = SyntaxNode::new(
+            Syntax::Paragraph,
+            Interval { start, end },
+            Some(Interval {
+                start: contents_begin,
+                end: contents_end,
+            }),
+            post_blank,
+        );
+        node.affiliated = maybe_aff
+            .filter(|aff| *aff != AffiliatedData::default())
+            .map(Box::new);
+        node
+    }
+
+    /// Beginning of the first line after `line_end` and before `limit`
+    /// matching `org-element-paragraph-separate`. The regex only
+    /// spans single lines, so lines are matched one at a time.
+    fn next_separator(&self, mut line_end: usize, limit: usize) -> Option<usize> {
+        while line_end + 1 < limit {
+            let line_start = line_end + 1;
+            line_end = self
+                .input
+                .find_byte(b'\n', line_start, limit)
+                .unwrap_or(limit);
+            let line = self.input.slice(line_start, line_end);
+            if self.re.paragraph_separate.is_match(&line) {
+                return Some(line_start);
+            }
+        }
+        None
     }
 
-    // TODO implement section_parser
+    /// Parse a section.
+    ///
+    /// LIMIT bounds the search.
+    ///
+    /// Return a list whose CAR is `section' and CDR is a plist
+    /// containing `:begin', `:end', `:contents-begin', `contents-end',
+    /// `:post-blank' and `:post-affiliated' keywords.
+    /// (defun org-element-section-parser (_)
     pub fn section_parser(&self, limit: usize) -> SyntaxNode<'a> {
-        unimplemented!()
+        let begin = self.cursor.borrow().pos();
+        let end = self.next_headline().unwrap_or(self.input.len()).min(limit);
+        let mut c = self.cursor.borrow_mut();
+        c.set(end);
+        c.skip_chars_backward(" \r\t\n", Some(begin));
+        let pos_before_blank = c.line_beginning_position(Some(2)).min(end);
+        let post_blank = c.count_lines(pos_before_blank, end);
+        c.set(begin);
+
+        SyntaxNode::new(
+            Syntax::Section,
+            Interval { start: begin, end },
+            Some(Interval {
+                start: begin,
+                end: pos_before_blank,
+            }),
+            post_blank,
+        )
+    }
+}
+
+mod test {
+    use crate::data::{SyntaxT, SyntaxTree};
+    use crate::options::ParserOptions;
+    use crate::parser::{ParseGranularity, Parser};
+
+    fn outline(tree: &SyntaxTree) -> Vec<(SyntaxT, usize, usize)> {
+        tree.descendants(tree.root())
+            .skip(1)
+            .map(|id| {
+                let node = &tree[id];
+                let t = SyntaxT::from(&node.data);
+                (t, node.location.start, node.location.end)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn paragraphs() {
+        use SyntaxT::*;
+        let text = "First\nline\n\n\nSecond :not: a drawer\n:DRAWER:\nwithout end\n* H\n";
+        let parser = Parser::new(text, ParseGranularity::Element, ParserOptions::default());
+        let tree = parser.parse_buffer();
+        assert_eq!(
+            vec![
+                (Section, 0, 56),
+                (Paragraph, 0, 13),
+                (Paragraph, 13, 56),
+                (Headline, 56, 60),
+            ],
+            outline(&tree)
+        );
+        let first = tree.children(tree.root()).next().unwrap();
+        let first = tree.children(first).next().unwrap();
+        assert_eq!(2, tree[first].post_blank);
+        assert_eq!(11, tree[first].content_location.unwrap().end);
+    }
+
+    #[test]
+    fn separated_by_block() {
+        use SyntaxT::*;
+        let text = "Text\n#+BEGIN_QUOTE\nunterminated\n\nText\n#+begin_quote\n#+end_quote\n";
+        // Quote block parser is not implemented yet, stop right before it
+        let end = text.find("#+begin").unwrap();
+        let parser = Parser::new(
+            &text[..end],
+            ParseGranularity::GreaterElement,
+            ParserOptions::default(),
+        );
+        let tree = parser.parse_buffer();
+        assert_eq!(
+            vec![(Section, 0, end), (Paragraph, 0, 33), (Paragraph, 33, end)],
+            outline(&tree)
+        );
     }
 }
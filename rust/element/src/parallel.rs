@@ -0,0 +1,136 @@
+//    This file is part of org-rs.
+//
+//    org-rs is free software: you can redistribute it and/or modify
+//    it under the terms of the GNU General Public License as published by
+//    the Free Software Foundation, either version 3 of the License, or
+//    (at your option) any later version.
+//
+//    org-rs is distributed in the hope that it will be useful,
+//    but WITHOUT ANY WARRANTY; without even the implied warranty of
+//    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//    GNU General Public License for more details.
+//
+//    You should have received a copy of the GNU General Public License
+//    along with org-rs.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Parallel parsing, with the `parallel` cargo feature.
+//!
+//! Top-level subtrees are independent: a level-1 headline ends where
+//! the next one starts, and nothing before it changes how it is
+//! parsed. `Parser::parse_buffer_parallel` splits the input at level-1
+//! headlines, parses the parts on rayon's thread pool, each with its
+//! own `Parser`, and grafts them in order under one root. Nodes are
+//! allocated in the same order as in a sequential parse, so the
+//! resulting tree is the same as the one of `parse_buffer`, down
+//! to the `NodeId`s.
+
+use crate::cursor::Cursor;
+use crate::data::{SyntaxTree, TreeText};
+use crate::headline::headline_level;
+use crate::parser::Parser;
+use crate::source::TextSource;
+use rayon::prelude::*;
+use std::borrow::Cow;
+
+impl<'a, S: TextSource<'a> + Send + Sync> Parser<'a, S> {
+    /// Same as `parse_buffer`, parsing top-level subtrees on multiple
+    /// threads.
+    ///
+    /// The function given to `skip_hidden` can't be shared between
+    /// threads, so a parser skipping hidden text parses sequentially.
+    pub fn parse_buffer_parallel(&self) -> SyntaxTree<'a> {
+        if self.hidden.is_some() {
+            return self.parse_buffer();
+        }
+        let bounds = self.top_level_bounds();
+        let input = self.input;
+        let granularity = self.granularity;
+        let options = &self.options;
+        let re = &self.re;
+        let parts: Vec<SyntaxTree<'a>> = bounds
+            .par_windows(2)
+            .map_init(
+                || Parser::with_regexes(input, granularity, options.clone(), re.clone()),
+                |parser, range| {
+                    parser.parse_narrowed(range[0], range[1], TreeText::Flat(Cow::from("")))
+                },
+            )
+            .collect();
+
+        let mut tree = SyntaxTree::from_source(self.input);
+        let root = tree.root();
+        for part in parts {
+            tree.graft(root, part);
+        }
+        tree
+    }
+
+    /// Beginning of the input, beginnings of level-1 headlines and
+    /// end of the input, i.e. bounds of the parts that can be parsed
+    /// independently
+    fn top_level_bounds(&self) -> Vec<usize> {
+        let mut bounds = vec![0];
+        if !self.is_inlinetask_level(1) {
+            let mut c = Cursor::from_source(self.input, 0);
+            if c.on_headline() && headline_level(self.input, 0) == 1 {
+                bounds.push(0);
+            }
+            while let Some(start) = c.next_headline() {
+                if headline_level(self.input, start) == 1 {
+                    bounds.push(start);
+                }
+            }
+        }
+        bounds.push(self.input.len());
+        bounds.dedup();
+        bounds
+    }
+}
+
+mod test {
+    use crate::options::ParserOptions;
+    use crate::parser::{ParseGranularity, Parser};
+
+    const TEXT: &str = "Archive of old notes.\n\
+                        Intro with a [[https://orgmode.org][link]].\n\
+                        ** Orphan subheading\n\
+                        * TODO First *bold* title :tag:\n\
+                        Text with =code=.\n\
+                        ** Sub [1/2]\n\
+                        *** Deeper\n\n\
+                        * Second\n\
+                        *not a headline*\n\
+                        * Third\n";
+
+    fn assert_same(text: &str, granularity: ParseGranularity) {
+        let parser = Parser::new(text, granularity, ParserOptions::default());
+        assert_eq!(parser.parse_buffer(), parser.parse_buffer_parallel());
+    }
+
+    #[test]
+    fn same_as_sequential() {
+        for &granularity in &[
+            ParseGranularity::Headline,
+            ParseGranularity::Element,
+            ParseGranularity::Object,
+        ] {
+            assert_same(TEXT, granularity);
+            assert_same(&TEXT.repeat(50), granularity);
+            assert_same(&TEXT[TEXT.find("* TODO").unwrap()..], granularity);
+            assert_same("No headline at all\n", granularity);
+            assert_same("", granularity);
+        }
+    }
+
+    #[test]
+    fn bounds() {
+        let parser = Parser::new(TEXT, ParseGranularity::Element, ParserOptions::default());
+        let first = TEXT.find("* TODO").unwrap();
+        let second = TEXT.find("* Second").unwrap();
+        let third = TEXT.find("* Third").unwrap();
+        assert_eq!(
+            vec![0, first, second, third, TEXT.len()],
+            parser.top_level_bounds()
+        );
+    }
+}
@@ -13,14 +13,20 @@
 //    You should have received a copy of the GNU General Public License
 //    along with org-rs.  If not, see <https://www.gnu.org/licenses/>.
 
+use std::borrow::Cow;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
+use std::sync::Arc;
 
 use regex::Regex;
 
 use crate::babel::REGEX_BABEL_CALL;
-use crate::cursor::Cursor;
-use crate::data::{Handle, Syntax, SyntaxNode, SyntaxT};
+use crate::cursor::{BaseMetric, Cursor, Metric};
+use crate::data::{
+    Interval, NodeId, StringOrObject, Syntax, SyntaxNode, SyntaxT, SyntaxTree, TreeText,
+};
+use crate::source::{Narrowed, TextSource};
 
 use crate::blocks::{
     REGEX_BLOCK_BEGIN, REGEX_COLON_OR_EOL, REGEX_DYNAMIC_BLOCK, REGEX_STARTS_WITH_HASHTAG,
@@ -36,11 +42,12 @@ use crate::list::*;
 use crate::markup::REGEX_FIXED_WIDTH;
 use crate::markup::REGEX_FOOTNOTE_DEFINITION;
 use crate::markup::REGEX_HORIZONTAL_RULE;
+use crate::options::{ParserOptions, Regexes};
 use crate::planning::REGEX_DIARY_SEXP;
 use crate::table::{REGEX_TABLE_BORDER, REGEX_TABLE_PRE_BORDER, REGEX_TABLE_RULE};
 
 /// determines the depth of the recursion.
-#[derive(PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ParseGranularity {
     /// Only parse headlines.
     Headline,
@@ -67,10 +74,20 @@ pub enum ParserMode {
     PropertyDrawer,
 }
 
-pub struct Parser<'a> {
-    pub cursor: RefCell<Cursor<'a>>,
-    pub input: &'a str,
+/// Org parser over a `TextSource`, a `&str` unless specified otherwise
+pub struct Parser<'a, S: TextSource<'a> = &'a str> {
+    pub cursor: RefCell<Cursor<'a, S>>,
+    pub input: S,
     pub granularity: ParseGranularity,
+    /// Arena that receives parsed nodes
+    pub tree: RefCell<SyntaxTree<'a>>,
+    pub options: ParserOptions,
+    /// Regexes built from `options`
+    pub(crate) re: Regexes,
+    /// See `Parser::skip_hidden`
+    pub(crate) hidden: Option<Rc<dyn Fn(usize) -> Option<usize> + 'a>>,
+    /// Regexes built from the input, see `Parser::regex`
+    built: RefCell<HashMap<String, Regex>>,
 }
 
 macro_rules! looking_at {
@@ -86,14 +103,53 @@ macro_rules! capturing_at {
 }
 
 impl<'a> Parser<'a> {
-    pub fn new(input: &'a str, granularity: ParseGranularity) -> Parser {
+    pub fn new(input: &'a str, granularity: ParseGranularity, options: ParserOptions) -> Parser {
+        Parser::from_source(input, granularity, options)
+    }
+}
+
+impl<'a, S: TextSource<'a>> Parser<'a, S> {
+    /// Parser reading from any `TextSource`, e.g. a rope.
+    ///
+    /// Parse trees keep the text they describe, see `SyntaxTree::text`.
+    /// They borrow the chunks of the source, which are only joined
+    /// the first time the whole text is needed as a single string.
+    /// Use `parse_range` to parse only the part that changed.
+    pub fn from_source(input: S, granularity: ParseGranularity, options: ParserOptions) -> Self {
+        let re = Regexes::new(&options);
+        Parser::with_regexes(input, granularity, options, re)
+    }
+
+    /// Parser with regexes already built from `options`
+    pub(crate) fn with_regexes(
+        input: S,
+        granularity: ParseGranularity,
+        options: ParserOptions,
+        re: Regexes,
+    ) -> Self {
         Parser {
-            cursor: RefCell::new(Cursor::new(input, 0)),
+            cursor: RefCell::new(Cursor::from_source(input, 0)),
             input,
             granularity,
+            tree: RefCell::new(SyntaxTree::new()),
+            re,
+            options,
+            hidden: None,
+            built: RefCell::new(HashMap::new()),
         }
     }
 
+    /// Skip the parts of the input that are not displayed, e.g. folded
+    /// by an editor, like VISIBLE-ONLY argument of `org-element-parse-buffer`.
+    ///
+    /// `hidden` returns the end of the hidden range containing the
+    /// given position, or `None` when it is visible. Elements starting
+    /// in a hidden range are not parsed.
+    pub fn skip_hidden(mut self, hidden: impl Fn(usize) -> Option<usize> + 'a) -> Self {
+        self.hidden = Some(Rc::new(hidden));
+        self
+    }
+
     /// Returns parser mode according to given `element` and `is_parent`
     /// `element` is AllElements variant representing the type of an element
     /// containing next element if `is_parent` is true, or before it
@@ -102,7 +158,7 @@ impl<'a> Parser<'a> {
     /// Original function name: org-element--next-mode
     /// https://code.orgmode.org/bzg/org-mode/src/master/lisp/org-element.el#L4273
     #[rustfmt::skip]
-    fn next_mode(syntax: SyntaxT, is_parent: bool) -> Option<ParserMode> {
+    pub(crate) fn next_mode(syntax: SyntaxT, is_parent: bool) -> Option<ParserMode> {
         use SyntaxT::*;
 
         if is_parent {
@@ -128,14 +184,73 @@ impl<'a> Parser<'a> {
 
     /// org-element-parse-buffer
     /// Parses input from beginning to the end
-    pub fn parse_buffer(&'a self) -> SyntaxNode {
-        self.cursor.borrow_mut().set(0);
-        self.cursor.borrow_mut().skip_whitespace();
+    pub fn parse_buffer(&self) -> SyntaxTree<'a> {
+        self.parse_from(0, TreeText::from_source(self.input))
+    }
+
+    /// Parses input between `beg` and `end` as if the buffer was
+    /// narrowed to them, e.g. with `org-narrow-to-subtree`. Nothing
+    /// before `beg` or after `end` affects the result.
+    ///
+    /// `beg` is moved back to the beginning of its line, `end` to the
+    /// beginning of the character it falls in. Locations in
+    /// the returned tree are offsets in the whole input, and its root
+    /// spans the narrowed range.
+    pub fn parse_range(&self, beg: usize, end: usize) -> SyntaxTree<'a> {
+        self.parse_narrowed(beg, end, TreeText::from_source(self.input))
+    }
 
+    /// `parse_range` returning a tree holding `text`
+    pub(crate) fn parse_narrowed(
+        &self,
+        beg: usize,
+        end: usize,
+        text: TreeText<'a>,
+    ) -> SyntaxTree<'a> {
+        let end = self.input.floor_char_boundary(end.min(self.input.len()));
+        let beg = self
+            .input
+            .rfind_byte(b'\n', 0, beg.min(end))
+            .map_or(0, |p| p + 1);
+        let input = Narrowed {
+            source: self.input,
+            end,
+        };
+        let narrowed = Parser {
+            cursor: RefCell::new(Cursor::from_source(input, beg)),
+            input,
+            granularity: self.granularity,
+            tree: RefCell::new(SyntaxTree::new()),
+            options: self.options.clone(),
+            re: self.re.clone(),
+            hidden: self.hidden.clone(),
+            built: self.built.clone(),
+        };
+        narrowed.parse_from(beg, text)
+    }
+
+    /// Regex for `pattern`, compiled the first time it is needed.
+    /// Used for regexes depending on the input, like the end line
+    /// of a block, which are looked for again and again.
+    pub(crate) fn regex(&self, pattern: String) -> Regex {
+        self.built
+            .borrow_mut()
+            .entry(pattern)
+            .or_insert_with_key(|pattern| Regex::new(pattern).unwrap())
+            .clone()
+    }
+
+    /// Parses input from `beg` to its end into a new tree holding `text`
+    fn parse_from(&self, beg: usize, text: TreeText<'a>) -> SyntaxTree<'a> {
         let end = self.input.len();
-        let mut root = SyntaxNode::create_root();
-        root.children = RefCell::new(self.parse_elements(0, end, ParserMode::FirstSection, None));
-        root
+        let mut tree = SyntaxTree::with_tree_text(text);
+        let root = tree.root();
+        let location = Interval { start: beg, end };
+        tree[root].location = location;
+        tree[root].content_location = Some(location);
+        self.tree.replace(tree);
+        self.parse_elements(beg, end, ParserMode::FirstSection, None, root);
+        self.tree.replace(SyntaxTree::new())
     }
 
     /// Parse elements between BEG and END positions.
@@ -151,30 +266,44 @@ impl<'a> Parser<'a> {
     /// Elements are accumulated into ACC."
     /// (defun org-element--parse-elements
     ///     (beg end mode structure granularity visible-only acc)
-    /// TODO do not forget to fix child-parent and parent-child links on tree updates
+    ///
+    /// Parsed elements are appended to the children of `acc` node.
     fn parse_elements(
-        &'a self,
+        &self,
         beg: usize,
         end: usize,
         mut mode: ParserMode,
-        structure: Option<Rc<ListStruct>>,
-    ) -> Vec<Handle> {
+        structure: Option<Arc<ListStruct>>,
+        acc: NodeId,
+    ) {
         let pos = self.cursor.borrow_mut().pos();
         self.cursor.borrow_mut().set(beg);
 
         // When parsing only headlines, skip any text before first one.
-        if self.granularity == ParseGranularity::Headline && !self.cursor.borrow_mut().on_headline()
+        // Like `outline-next-heading`, go to the end when there is none.
+        if self.granularity == ParseGranularity::Headline
+            && !self.on_headline()
+            && self.next_headline().is_none()
         {
-            self.cursor.borrow_mut().next_headline();
+            self.cursor.borrow_mut().set(end);
         }
 
-        let mut elements: Vec<Handle> = vec![];
         loop {
             let current_pos = self.cursor.borrow().pos();
             if current_pos >= end {
                 break;
             }
 
+            // Visible only: skip invisible parts due to folding.
+            if let Some(hidden_end) = self.hidden_end(current_pos) {
+                let mut pos = hidden_end.min(end);
+                if pos < end && self.input.byte_at(pos) == b'\n' {
+                    pos += 1;
+                }
+                self.cursor.borrow_mut().set(pos);
+                continue;
+            }
+
             // Find current element's type and parse it accordingly to its category.
             // (org-element--current-element end granularity mode structure))
             let list_struct = match &structure {
@@ -186,10 +315,19 @@ impl<'a> Parser<'a> {
             // (goto-char (org-element-property :end element))
             self.cursor.borrow_mut().set(element.location.end);
 
-            // Recurse into element's children if it has contents
-            if element.content_location.is_some() {
-                let content_location = element.content_location.unwrap();
+            let syntax = SyntaxT::from(&element.data);
+            let content_location = element.content_location;
+            // (and (memq type '(item plain-list))
+            // (org-element-property :structure element))
+            let list_sturct = match &element.data {
+                Syntax::PlainList(d) => Some(d.structure.clone()),
+                Syntax::Item(d) => Some(d.structure.clone()),
+                _ => None,
+            };
+            let id = self.tree.borrow_mut().append(acc, element);
 
+            // Recurse into element's children if it has contents
+            if let Some(content_location) = content_location {
                 // If this is a Greater element:
                 // parse it between `contents_begin' and `contents_end'
                 // if one the following conditions holds:
@@ -197,31 +335,28 @@ impl<'a> Parser<'a> {
                 //    in order to get sub-level headings.
                 // 2. Granularity is Element or Object
                 // 3. This is Section and Granularity is GreaterElement
-                if SyntaxT::from(&element.data).is_greater_element() {
-                    if (SyntaxT::Headline == SyntaxT::from(&element.data))
+                if syntax.is_greater_element() {
+                    if (SyntaxT::Headline == syntax)
                         || (self.granularity == ParseGranularity::Element
                             || self.granularity == ParseGranularity::Object)
-                        || ((SyntaxT::Section == SyntaxT::from(&element.data))
+                        || ((SyntaxT::Section == syntax)
                             && (self.granularity == ParseGranularity::GreaterElement))
                     {
-                        // (and (memq type '(item plain-list))
-                        // (org-element-property :structure element))
-                        let list_sturct = match &element.data {
-                            Syntax::PlainList(d) => Some(d.structure.clone()),
-                            _ => None,
-                        };
-
                         //  Possibly switch to a special mode.
                         // (org-element--next-mode type t)
+                        // Elements with no special mode of their own are
+                        // parsed in planning mode, which only differs from
+                        // no mode at all right after a headline.
                         let new_mode =
-                            Parser::next_mode(SyntaxT::from(&element.data), true).unwrap_or(mode);
+                            Self::next_mode(syntax, true).unwrap_or(ParserMode::Planning);
 
-                        element.children.replace(self.parse_elements(
+                        self.parse_elements(
                             content_location.start,
                             content_location.end,
                             new_mode,
                             list_sturct,
-                        ));
+                            id,
+                        );
                     }
                 }
                 // Any other element with contents, if granularity allows it
@@ -230,21 +365,29 @@ impl<'a> Parser<'a> {
                     //    cbeg (org-element-property :contents-end element)
                     //    element (org-element-restriction type))))
                     if let ParseGranularity::Object = &self.granularity {
-                        element.children.replace(self.parse_objects(
+                        self.parse_objects(
                             content_location.start,
                             content_location.end,
-                            |that| SyntaxT::from(&element.data).can_contain(that),
-                        ));
+                            id,
+                            |that| syntax.can_contain(that),
+                        );
                     }
                 }
             }
-            if let Some(m) = Parser::next_mode(SyntaxT::from(&element.data), false) {
+            if let Some(m) = Self::next_mode(syntax, false) {
                 mode = m
             }
-            elements.push(Rc::new(element));
         }
         self.cursor.borrow_mut().set(pos);
-        elements
+    }
+
+    /// End of the hidden range containing `pos`, see `Parser::skip_hidden`.
+    /// Ranges that don't end after `pos` are ignored.
+    fn hidden_end(&self, pos: usize) -> Option<usize> {
+        self.hidden
+            .as_ref()
+            .and_then(|hidden| hidden(pos))
+            .filter(|&end| end > pos)
     }
 
     /// Parse the element starting at cursor position (point).
@@ -263,15 +406,15 @@ impl<'a> Parser<'a> {
     ///
     /// This function assumes cursor is always at the beginning of the
     /// element it has to parse."
-    fn current_element(
+    pub(crate) fn current_element(
         &self,
         limit: usize,
         mode: ParserMode,
-        structure: Option<Rc<ListStruct>>,
+        structure: Option<Arc<ListStruct>>,
     ) -> SyntaxNode<'a> {
         let pos = self.cursor.borrow().pos();
 
-        let raw_secondary_p = self.granularity == ParseGranularity::Object;
+        let raw_secondary_p = self.granularity != ParseGranularity::Object;
 
         let get_current_element = || -> SyntaxNode<'a> {
             use crate::parser::ParserMode::*;
@@ -292,8 +435,8 @@ impl<'a> Parser<'a> {
             }
 
             // Headline.
-            if self.cursor.borrow_mut().on_headline() {
-                return self.headline_parser();
+            if self.on_headline() {
+                return self.headline_parser(limit, raw_secondary_p);
             }
 
             // Sections (must be checked after headline).
@@ -303,7 +446,7 @@ impl<'a> Parser<'a> {
 
             if mode == FirstSection {
                 let pos = self.cursor.borrow().pos();
-                let lim = self.cursor.borrow_mut().next_headline().unwrap_or(limit);
+                let lim = self.next_headline().unwrap_or(limit);
                 self.cursor.borrow_mut().set(pos);
                 return self.section_parser(lim);
             }
@@ -330,7 +473,9 @@ impl<'a> Parser<'a> {
                 let maybe_star = c.char_after(maybe_headline_offset);
                 let is_prev_line_headline = Some('*') == maybe_star;
 
-                let is_match_property_drawer = c.looking_at(&*REGEX_PROPERTY_DRAWER).is_some();
+                let is_match_property_drawer = c
+                    .looking_at_within(&*REGEX_PROPERTY_DRAWER, limit)
+                    .is_some();
                 drop(c);
 
                 if (mode == Planning || mode == PropertyDrawer)
@@ -344,7 +489,8 @@ impl<'a> Parser<'a> {
             // When not at bol, point is at the beginning of an item or
             // a footnote definition: next item is always a paragraph.
             if !self.cursor.borrow().is_bol() {
-                return self.paragraph_parser(limit, self.cursor.borrow().pos(), None);
+                let pos = self.cursor.borrow().pos();
+                return self.paragraph_parser(limit, pos, None);
             }
 
             // Clock.
@@ -384,14 +530,16 @@ impl<'a> Parser<'a> {
             }
 
             // Inline Comments, Blocks, Babel Calls, Dynamic Blocks and Keywords.
-            if let Some(m) = looking_at!(REGEX_STARTS_WITH_HASHTAG, self) {
-                self.cursor.borrow_mut().set(m.end());
+            let hashtag_end = looking_at!(REGEX_STARTS_WITH_HASHTAG, self);
+            if let Some(end) = hashtag_end {
+                self.cursor.borrow_mut().set(end);
                 if looking_at!(REGEX_COLON_OR_EOL, self).is_some() {
                     self.cursor.borrow_mut().goto_line_begin();
                     return self.comment_parser(limit, aff_start, maybe_aff);
                 }
 
-                if let Some(cap) = capturing_at!(REGEX_BLOCK_BEGIN, self) {
+                let block_begin = capturing_at!(REGEX_BLOCK_BEGIN, self);
+                if let Some(cap) = block_begin {
                     self.cursor.borrow_mut().goto_line_begin();
                     let name = cap.get(1).unwrap().as_str().to_owned().to_ascii_uppercase();
                     match name.as_ref() {
@@ -452,9 +600,9 @@ impl<'a> Parser<'a> {
             //   (org-element-plain-list-parser
             //    limit affiliated
             //    (or structure (org-element--list-struct limit))))
-            if looking_at!(REGEX_ITEM, self).is_some() {
-                let s = structure.unwrap_or(self.list_struct(limit));
-                return self.plain_list_parser(limit, aff_start, maybe_aff, s.clone());
+            if self.cursor.borrow().looking_at(&self.re.item).is_some() {
+                let s = structure.unwrap_or_else(|| self.list_struct(limit));
+                return self.plain_list_parser(limit, aff_start, maybe_aff, s);
             }
 
             // Default element: Paragraph.
@@ -478,16 +626,377 @@ impl<'a> Parser<'a> {
     /// Eventually, if both ACC and PARENT are nil, the common parent is
     /// the list of objects itself."
     /// (defun org-element--parse-objects (beg end acc restriction &optional parent)
+    ///
+    /// Parsed objects are appended to the children of `acc` node.
+    /// Secondary strings are parsed with `parse_object_list` instead.
     pub fn parse_objects(
         &self,
         beg: usize,
         end: usize,
+        acc: NodeId,
         restriction: impl Fn(SyntaxT) -> bool,
-    ) -> Vec<Handle> //acc
-    {
+    ) {
+        for id in self.parse_object_list(beg, end, &restriction) {
+            self.tree.borrow_mut().link_last(acc, id);
+        }
+    }
+
+    /// Parse objects between `beg` and `end` into the tree without
+    /// attaching them anywhere, and return top level ones in order.
+    /// Contents of recursive objects are parsed as their children.
+    ///
+    /// This is `org-element--parse-objects` with a nil ACC, used for
+    /// secondary strings: nodes holding the returned objects become
+    /// their parent when they are added to the tree.
+    pub(crate) fn parse_object_list(
+        &self,
+        beg: usize,
+        end: usize,
+        restriction: &dyn Fn(SyntaxT) -> bool,
+    ) -> Vec<NodeId> {
         let pos = self.cursor.borrow().pos();
-        // TODO write parse_objects function #8
+        self.cursor.borrow_mut().set(beg);
+
+        let plain_text = |start: usize, end: usize| {
+            SyntaxNode::new(
+                Syntax::PlainText(self.input.slice(start, end)),
+                Interval { start, end },
+                None,
+                0,
+            )
+        };
+
+        let mut contents = vec![];
+        let mut text_begin = beg;
+        while text_begin < end {
+            let next_object = match self.object_lex(end, restriction) {
+                Some(object) => object,
+                None => break,
+            };
+            // Text before any object.
+            let obj_beg = next_object.location.start;
+            if text_begin != obj_beg {
+                let text = plain_text(text_begin, obj_beg);
+                contents.push(self.tree.borrow_mut().alloc(text));
+            }
+            // Object...
+            let obj_end = next_object.location.end;
+            let syntax = SyntaxT::from(&next_object.data);
+            let object_contents = next_object.content_location;
+            let id = self.tree.borrow_mut().alloc(next_object);
+            // Fill contents of next object if possible.
+            if let Some(c) = object_contents {
+                for child in
+                    self.parse_object_list(c.start, c.end, &|that| syntax.can_contain(that))
+                {
+                    self.tree.borrow_mut().link_last(id, child);
+                }
+            }
+            contents.push(id);
+            text_begin = obj_end;
+            self.cursor.borrow_mut().set(obj_end);
+        }
+        // Text after last object.
+        if text_begin < end {
+            let text = plain_text(text_begin, end);
+            contents.push(self.tree.borrow_mut().alloc(text));
+        }
+
         self.cursor.borrow_mut().set(pos);
-        unimplemented!();
+        contents
+    }
+
+    /// Secondary string of `container` element, e.g. a headline
+    /// title, spanning `range`. Parsed into objects allowed in
+    /// `container`, unless `raw_secondary_p` is set.
+    pub(crate) fn parse_secondary_string(
+        &self,
+        range: Interval,
+        container: SyntaxT,
+        raw_secondary_p: bool,
+    ) -> StringOrObject<'a> {
+        if raw_secondary_p {
+            StringOrObject::Raw(self.input.slice(range.start, range.end))
+        } else {
+            StringOrObject::Parsed(
+                self.parse_object_list(range.start, range.end, &|that| container.can_contain(that)),
+            )
+        }
+    }
+
+    /// Text between `start` and `end` to match regexes against, and
+    /// its offset. Unless `start` is at the beginning of a line, it
+    /// begins with the character before `start`, so that regexes
+    /// looking behind, e.g. with `\b`, match as they would on the
+    /// whole input when searching from `start`.
+    pub(crate) fn window(&self, start: usize, end: usize) -> (Cow<'a, str>, usize) {
+        let from = match BaseMetric::prev(self.input, start) {
+            Some(prev) if self.input.byte_at(prev) != b'\n' => prev,
+            _ => start,
+        };
+        (self.input.slice(from, end), from)
+    }
+
+    /// Lines starting between `from`, at the beginning of a line,
+    /// and `limit`, as their beginning and the end of their text,
+    /// before the newline or `limit`.
+    pub(crate) fn lines(&self, from: usize, limit: usize) -> impl Iterator<Item = (usize, usize)> {
+        let input = self.input;
+        let mut start = from;
+        std::iter::from_fn(move || {
+            if start >= limit {
+                return None;
+            }
+            let end = input.find_byte(b'\n', start, limit).unwrap_or(limit);
+            let line = (start, end);
+            start = end + 1;
+            Some(line)
+        })
+    }
+
+    /// Return next object in current buffer or nil.
+    /// https://code.orgmode.org/bzg/org-mode/src/master/lisp/org-element.el#L4427
+    ///
+    /// RESTRICTION is a list of object types, as symbols, that should be
+    /// looked after.  This function assumes that the buffer is narrowed
+    /// to an appropriate container (e.g., a paragraph).
+    /// (defun org-element--object-lex (restriction)
+    ///
+    /// Objects are searched between cursor position and `limit`.
+    /// Cursor is left at the beginning of the object found,
+    /// or at `limit` when there is none.
+    pub fn object_lex(
+        &self,
+        limit: usize,
+        restriction: impl Fn(SyntaxT) -> bool,
+    ) -> Option<SyntaxNode<'a>> {
+        use SyntaxT::*;
+
+        if restriction(TableCell) {
+            return self.table_cell_parser(limit);
+        }
+
+        let (input, offset) = self.window(self.cursor.borrow().pos(), limit);
+        let mut pos = self.cursor.borrow().pos() - offset;
+        while let Some(m) = self.re.object.find_at(&input, pos) {
+            self.cursor.borrow_mut().set(offset + m.start());
+            let result = m.as_str();
+            let mut chars = result.chars();
+            let first = chars.next().unwrap();
+            let second = chars.next();
+            let allowed = |syntax| restriction(syntax);
+            let is_prefix = |prefix: &str| {
+                result
+                    .get(..prefix.len())
+                    .map_or(false, |r| r.eq_ignore_ascii_case(prefix))
+            };
+
+            let found = if is_prefix("call_") || is_prefix("src_") {
+                // TODO inline babel calls and inline source blocks
+                None
+            } else {
+                match first {
+                    // TODO superscripts
+                    '^' => None,
+                    // TODO subscripts
+                    '_' => when(allowed(Underline), || self.text_markup_parser(limit, first)),
+                    '*' => when(allowed(Bold), || self.text_markup_parser(limit, first)),
+                    '/' => when(allowed(Italic), || self.text_markup_parser(limit, first)),
+                    '~' => when(allowed(Code), || self.verbatim_parser(limit, first)),
+                    '=' => when(allowed(Verbatim), || self.verbatim_parser(limit, first)),
+                    '+' => when(allowed(StrikeThrough), || {
+                        self.text_markup_parser(limit, first)
+                    }),
+                    '@' => when(allowed(ExportSnippet), || self.export_snippet_parser(limit)),
+                    '{' => when(allowed(Macro), || self.macro_parser(limit)),
+                    // TODO LaTeX fragments
+                    '$' => None,
+                    // TODO radio targets
+                    '<' if second == Some('<') => {
+                        when(allowed(Target), || self.target_parser(limit))
+                    }
+                    '<' => when(allowed(Timestamp), || self.timestamp_parser(limit))
+                        .or_else(|| when(allowed(Link), || self.link_parser(limit))),
+                    '\\' if second == Some('\\') => {
+                        when(allowed(LineBreak), || self.line_break_parser(limit))
+                    }
+                    // TODO LaTeX fragments
+                    '\\' => when(allowed(Entity), || self.entity_parser(limit)),
+                    '[' if second == Some('[') => when(allowed(Link), || self.link_parser(limit)),
+                    '[' => when(allowed(FootnoteReference), || {
+                        self.footnote_reference_parser(limit)
+                    })
+                    .or_else(|| when(allowed(Timestamp), || self.timestamp_parser(limit)))
+                    .or_else(|| {
+                        when(allowed(StatisticsCookie), || {
+                            self.statistics_cookie_parser(limit)
+                        })
+                    }),
+                    // This is probably a plain link.
+                    _ => when(allowed(Link), || self.link_parser(limit)),
+                }
+            };
+            if found.is_some() {
+                return found;
+            }
+            pos = m.start() + first.len_utf8();
+        }
+        self.cursor.borrow_mut().set(limit);
+        None
+    }
+}
+
+/// Runs `parse` only when `allowed`, e.g. when the restriction
+/// of the container accepts the object
+fn when<T>(allowed: bool, parse: impl FnOnce() -> Option<T>) -> Option<T> {
+    if allowed {
+        parse()
+    } else {
+        None
+    }
+}
+
+mod test {
+    use super::{ParseGranularity, Parser};
+    use crate::data::{Interval, SyntaxT, SyntaxTree, TreeText};
+    use crate::options::ParserOptions;
+
+    const TEXT: &str = "* One\nFirst text.\n** Sub\nSub text.\n* Two\nSecond text.\n";
+
+    /// Type and location of every node in `tree`, in document order
+    fn nodes(tree: &SyntaxTree) -> Vec<(SyntaxT, usize, usize)> {
+        tree.descendants(tree.root())
+            .map(|id| {
+                let node = &tree[id];
+                (
+                    SyntaxT::from(&node.data),
+                    node.location.start,
+                    node.location.end,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parse_range() {
+        let parser = Parser::new(TEXT, ParseGranularity::Element, ParserOptions::default());
+        let sub = TEXT.find("** Sub").unwrap();
+        let two = TEXT.find("* Two").unwrap();
+
+        // Narrowed to "** Sub" subtree, starting in the middle of its line
+        let tree = parser.parse_range(sub + 3, two);
+        let root = tree.root();
+        assert_eq!(
+            Interval {
+                start: sub,
+                end: two
+            },
+            tree[root].location
+        );
+        assert_eq!(TEXT, tree.text());
+        assert_eq!(
+            vec![
+                (SyntaxT::OrgData, sub, two),
+                (SyntaxT::Headline, sub, two),
+                (SyntaxT::Section, sub + 7, two),
+                (SyntaxT::Paragraph, sub + 7, two),
+            ],
+            nodes(&tree)
+        );
+
+        // End in the middle of a multibyte character
+        let text = "* Één\nTexte\n";
+        let parser = Parser::new(text, ParseGranularity::Element, ParserOptions::default());
+        let tree = parser.parse_range(1, 5);
+        assert_eq!(Interval { start: 0, end: 4 }, tree[tree.root()].location);
+
+        // The whole range is the same as parse_buffer
+        assert_eq!(
+            nodes(&parser.parse_buffer()),
+            nodes(&parser.parse_range(0, TEXT.len()))
+        );
+    }
+
+    #[test]
+    fn skip_hidden() {
+        // Contents of "* One" are folded
+        let one_end = TEXT.find('\n').unwrap();
+        let two = TEXT.find("* Two").unwrap();
+        let parser = Parser::new(TEXT, ParseGranularity::Element, ParserOptions::default())
+            .skip_hidden(move |pos| {
+                if one_end <= pos && pos < two - 1 {
+                    Some(two - 1)
+                } else {
+                    None
+                }
+            });
+        let tree = parser.parse_buffer();
+        let types: Vec<_> = nodes(&tree).into_iter().map(|n| n.0).collect();
+        assert_eq!(
+            vec![
+                SyntaxT::OrgData,
+                SyntaxT::Headline,
+                SyntaxT::Headline,
+                SyntaxT::Section,
+                SyntaxT::Paragraph,
+            ],
+            types
+        );
+    }
+
+    #[test]
+    fn headline_granularity() {
+        let parser = Parser::new(TEXT, ParseGranularity::Headline, ParserOptions::default());
+        let types: Vec<_> = nodes(&parser.parse_buffer())
+            .into_iter()
+            .map(|n| n.0)
+            .collect();
+        // No section, even in the last headline
+        assert_eq!(
+            vec![
+                SyntaxT::OrgData,
+                SyntaxT::Headline,
+                SyntaxT::Headline,
+                SyntaxT::Headline,
+            ],
+            types
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "rope")]
+    fn parse_rope() {
+        let text = "* TODO Heading with [[https://orgmode.org][a *bold* link]] :tag:\n\
+                    Some /text/ with a footnote[fn:1] and =code=.\n\n\
+                    ** Sub [1/2]\n{{{macro(arg)}}} <<target>>\n"
+            .repeat(100);
+        let rope = ropey::Rope::from_str(&text);
+        let from_str = Parser::new(&text, ParseGranularity::Object, ParserOptions::default());
+        let from_rope = Parser::from_source(
+            rope.slice(..),
+            ParseGranularity::Object,
+            ParserOptions::default(),
+        );
+        let expected = from_str.parse_buffer();
+        let tree = from_rope.parse_buffer();
+        assert_eq!(nodes(&expected), nodes(&tree));
+        let data = |tree: &SyntaxTree| -> Vec<String> {
+            tree.descendants(tree.root())
+                .map(|id| format!("{:?}", tree[id].data))
+                .collect()
+        };
+        assert_eq!(data(&expected), data(&tree));
+
+        // The tree borrows the chunks of the rope, they are not copied
+        match &tree.text {
+            TreeText::Chunks(chunks, joined) => {
+                assert!(joined.get().is_none());
+                let pointers =
+                    |chunks: Vec<&str>| -> Vec<_> { chunks.iter().map(|c| c.as_ptr()).collect() };
+                assert_eq!(pointers(rope.chunks().collect()), pointers(chunks.clone()));
+            }
+            TreeText::Flat(_) => panic!("the text of the rope was copied"),
+        }
+        assert_eq!(text, tree.text());
     }
 }
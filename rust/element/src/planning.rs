@@ -14,19 +14,112 @@
 //    along with org-rs.  If not, see <https://www.gnu.org/licenses/>.
 
 use crate::affiliated::AffiliatedData;
-use crate::data::SyntaxNode;
+use crate::data::{Interval, PlanningData, Syntax, SyntaxNode};
+use crate::headline::REGEX_PLANNING_LINE;
 use crate::parser::Parser;
+use crate::source::TextSource;
 use regex::Regex;
 
 lazy_static! {
     pub static ref REGEX_DIARY_SEXP: Regex = Regex::new(r"%%\(").unwrap();
+
+    /// Matches a planning keyword, keyword is in group 1
+    /// elisp: `org-keyword-time-not-clock-regexp`
+    static ref REGEX_PLANNING_KEYWORD: Regex =
+        Regex::new(r"\b(CLOSED|DEADLINE|SCHEDULED):").unwrap();
 }
 
-impl<'a> Parser<'a> {
-    // TODO implement planning_parser
+impl<'a, S: TextSource<'a>> Parser<'a, S> {
+    /// Parse a planning.
+    ///
+    /// LIMIT bounds the search.
+    ///
+    /// Return a list whose CAR is `planning' and CDR is a plist
+    /// containing `:closed', `:deadline', `:scheduled', `:begin',
+    /// `:end', `:post-blank' and `:post-affiliated' keywords.
+    /// (defun org-element-planning-parser (limit)
     pub fn planning_parser(&self, limit: usize) -> SyntaxNode<'a> {
-        unimplemented!()
+        let mut c = self.cursor.borrow_mut();
+        let begin = c.pos();
+        let line_end = c.line_end_position(None);
+        let before_blank = c.goto_next_line();
+        c.skip_chars_forward(" \r\t\n", Some(limit));
+        let end = if c.pos() >= self.input.len() {
+            self.input.len()
+        } else {
+            c.line_beginning_position(None)
+        }
+        .min(limit);
+        let post_blank = c.count_lines(before_blank, end);
+        drop(c);
+
+        let data = self.planning_times(begin, line_end);
+        self.cursor.borrow_mut().set(begin);
+        SyntaxNode::new(
+            Syntax::Planning(Box::new(data)),
+            Interval { start: begin, end },
+            None,
+            post_blank,
+        )
+    }
+
+    /// Time properties of the headline or inlinetask whose first line
+    /// ends at `line_end`: the timestamps of the planning line following
+    /// it, if any. Leaves the cursor where it was.
+    ///
+    /// (defun org-element--get-time-properties ()
+    pub(crate) fn time_properties(&self, line_end: usize) -> PlanningData<'a> {
+        let saved = self.cursor.borrow().pos();
+        let mut c = self.cursor.borrow_mut();
+        c.set(line_end);
+        let next = c.goto_next_line();
+        let is_planning = next > line_end && c.looking_at(&*REGEX_PLANNING_LINE).is_some();
+        let next_end = c.line_end_position(None);
+        drop(c);
+        let data = if is_planning {
+            self.planning_times(next, next_end)
+        } else {
+            PlanningData {
+                closed: None,
+                deadline: None,
+                scheduled: None,
+            }
+        };
+        self.cursor.borrow_mut().set(saved);
+        data
+    }
+
+    /// Timestamps following planning keywords on the line between
+    /// `begin` and `line_end`. Leaves the cursor at `line_end`.
+    fn planning_times(&self, begin: usize, line_end: usize) -> PlanningData<'a> {
+        let mut data = PlanningData {
+            closed: None,
+            deadline: None,
+            scheduled: None,
+        };
+        let line = self.input.slice(begin, line_end);
+        for cap in REGEX_PLANNING_KEYWORD.captures_iter(&line) {
+            let keyword = cap.get(1).unwrap();
+            let time = {
+                let mut c = self.cursor.borrow_mut();
+                c.set(begin + keyword.end() + 1);
+                c.skip_chars_forward(" \t", Some(line_end));
+                drop(c);
+                match self.timestamp_parser(line_end).map(|node| node.data) {
+                    Some(Syntax::Timestamp(t)) => Some(*t),
+                    _ => None,
+                }
+            };
+            match keyword.as_str() {
+                "CLOSED" => data.closed = time,
+                "DEADLINE" => data.deadline = time,
+                _ => data.scheduled = time,
+            }
+        }
+        self.cursor.borrow_mut().set(line_end);
+        data
     }
+
     // TODO implement clock_line_parser
     pub fn clock_line_parser(&self, limit: usize) -> SyntaxNode<'a> {
         unimplemented!()
@@ -42,3 +135,63 @@ impl<'a> Parser<'a> {
         unimplemented!()
     }
 }
+
+mod test {
+    use crate::data::{Syntax, SyntaxT, TimestampData};
+    use crate::options::ParserOptions;
+    use crate::parser::{ParseGranularity, Parser};
+
+    #[test]
+    fn planning() {
+        let text = "* TODO Task\n\
+                    DEADLINE: <2024-03-08 Fri> SCHEDULED: <2024-03-01 Fri 10:00>\n\
+                    \n\
+                    Text\n\
+                    * Not planning\n\
+                    Text DEADLINE: <2024-03-08 Fri>\n";
+        let parser = Parser::new(text, ParseGranularity::Element, ParserOptions::default());
+        let tree = parser.parse_buffer();
+        let headlines: Vec<_> = tree.children(tree.root()).collect();
+
+        let section = tree.children(headlines[0]).next().unwrap();
+        let planning = tree.children(section).next().unwrap();
+        let planning_end = text.find("Text").unwrap();
+        assert_eq!(
+            (SyntaxT::Planning, 12, planning_end, 1),
+            (
+                SyntaxT::from(&tree[planning].data),
+                tree[planning].location.start,
+                tree[planning].location.end,
+                tree[planning].post_blank
+            )
+        );
+        let data = match &tree[planning].data {
+            Syntax::Planning(data) => data,
+            data => panic!("not a planning: {:?}", data),
+        };
+        let raw = |t: &Option<TimestampData>| t.as_ref().map(|t| t.raw_value.to_string());
+        assert_eq!(Some("<2024-03-08 Fri>".to_string()), raw(&data.deadline));
+        assert_eq!(
+            Some("<2024-03-01 Fri 10:00>".to_string()),
+            raw(&data.scheduled)
+        );
+        assert_eq!(None, raw(&data.closed));
+        assert_eq!(Some(10), data.scheduled.as_ref().and_then(|t| t.hour_start));
+
+        // Headlines get the timestamps of their planning line
+        match &tree[headlines[0]].data {
+            Syntax::Headline(h) => {
+                assert_eq!(data.deadline, h.deadline);
+                assert_eq!(data.scheduled, h.scheduled);
+            }
+            data => panic!("not a headline: {:?}", data),
+        }
+        match &tree[headlines[1]].data {
+            Syntax::Headline(h) => assert_eq!(None, h.deadline),
+            data => panic!("not a headline: {:?}", data),
+        }
+        let section = tree.children(headlines[1]).next().unwrap();
+        let paragraph = tree.children(section).next().unwrap();
+        assert_eq!(SyntaxT::Paragraph, SyntaxT::from(&tree[paragraph].data));
+    }
+}
@@ -0,0 +1,443 @@
+//    This file is part of org-rs.
+//
+//    org-rs is free software: you can redistribute it and/or modify
+//    it under the terms of the GNU General Public License as published by
+//    the Free Software Foundation, either version 3 of the License, or
+//    (at your option) any later version.
+//
+//    org-rs is distributed in the hope that it will be useful,
+//    but WITHOUT ANY WARRANTY; without even the implied warranty of
+//    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//    GNU General Public License for more details.
+//
+//    You should have received a copy of the GNU General Public License
+//    along with org-rs.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Serde serialization of the parse tree.
+//! Available with the `serde` cargo feature.
+//!
+//! `SyntaxTree` serializes to the following document. JSON is used
+//! below, but any serde data format works the same way.
+//!
+//! ```json
+//! {
+//!   "version": 4,
+//!   "root": NODE
+//! }
+//! ```
+//!
+//! `version` is `SCHEMA_VERSION`. It is bumped on every change that can
+//! break a consumer: renamed or removed fields, changed value types.
+//! Adding a new property to a node type does not bump the version.
+//!
+//! Every NODE is an object with the following fields, always present:
+//!
+//! - `type`       - org-element name of the node type, e.g. `"src-block"`,
+//!                  see `SyntaxT::org_name`
+//! - `begin`      - byte offset of the node's beginning, 0-based
+//! - `end`        - byte offset of the node's end, exclusive
+//! - `contents`   - `{"begin": N, "end": N}` or `null` for nodes without contents
+//! - `post_blank` - blank lines (elements) or white spaces (objects) at the end
+//! - `affiliated` - `null`, or an object mapping upper case keyword names
+//!                  (`NAME`, `CAPTION`, `ATTR_HTML`...) to lists of
+//!                  `{"value", "secondary"}`, `secondary` being `null` unless
+//!                  the keyword is dual. Keywords that can't occur more than
+//!                  once have a single element list. Values are strings, or
+//!                  lists of NODEs for parsed secondary strings (e.g. `CAPTION`
+//!                  parsed with `Object` granularity).
+//! - `properties` - object with type specific properties, named after
+//!                  org-element ones in kebab-case (`raw-value`, `use-brackets-p`...).
+//!                  `type` property of some nodes (`:type` in org-element)
+//!                  is stored there as well. Symbols are serialized as
+//!                  kebab-case strings (`"active-range"`), optional values as `null`.
+//!                  `plain-text` nodes store their text in `value`.
+//!                  `title` of headlines and inlinetasks and `tag` of
+//!                  items are secondary strings, like affiliated values.
+//!                  List structures are internal and are not serialized.
+//! - `children`   - list of NODEs
+//!
+//! Keys of `affiliated` are sorted, so serializing the same tree twice
+//! produces the same output.
+
+use crate::affiliated::{AffiliatedData, DualVal};
+use crate::data::{NodeId, StringOrObject, Syntax, SyntaxT, SyntaxTree};
+use serde::ser::{Error, SerializeMap, SerializeSeq, SerializeStruct};
+use serde::{Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap};
+
+/// Version of the serialized tree schema
+pub const SCHEMA_VERSION: u32 = 4;
+
+impl<'a> Serialize for SyntaxTree<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut document = serializer.serialize_struct("SyntaxTree", 2)?;
+        document.serialize_field("version", &SCHEMA_VERSION)?;
+        document.serialize_field(
+            "root",
+            &Node {
+                tree: self,
+                id: self.root(),
+            },
+        )?;
+        document.end()
+    }
+}
+
+struct Node<'t, 'a> {
+    tree: &'t SyntaxTree<'a>,
+    id: NodeId,
+}
+
+#[derive(Serialize)]
+struct Range {
+    begin: usize,
+    end: usize,
+}
+
+struct Children<'t, 'a>(Node<'t, 'a>);
+
+struct Properties<'t, 'a> {
+    tree: &'t SyntaxTree<'a>,
+    data: &'t Syntax<'a>,
+}
+
+/// Properties of a headline or an inlinetask, with their title
+#[derive(Serialize)]
+struct Titled<'t, 'a, T> {
+    #[serde(flatten)]
+    properties: &'t T,
+    title: Secondary<'t, 'a>,
+}
+
+/// Properties of an item, with its tag
+#[derive(Serialize)]
+struct Tagged<'t, 'a, T> {
+    #[serde(flatten)]
+    properties: &'t T,
+    tag: Option<Secondary<'t, 'a>>,
+}
+
+struct Affiliated<'t, 'a> {
+    tree: &'t SyntaxTree<'a>,
+    data: &'t AffiliatedData<'a>,
+}
+
+/// Secondary string, serialized with the tree holding its objects
+struct Secondary<'t, 'a> {
+    tree: &'t SyntaxTree<'a>,
+    value: &'t StringOrObject<'a>,
+}
+
+impl<'t, 'a> Serialize for Node<'t, 'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let node = &self.tree[self.id];
+        let mut map = serializer.serialize_struct("Node", 8)?;
+        map.serialize_field("type", SyntaxT::from(&node.data).org_name())?;
+        map.serialize_field("begin", &node.location.start)?;
+        map.serialize_field("end", &node.location.end)?;
+        map.serialize_field(
+            "contents",
+            &node.content_location.map(|c| Range {
+                begin: c.start,
+                end: c.end,
+            }),
+        )?;
+        map.serialize_field("post_blank", &node.post_blank)?;
+        map.serialize_field(
+            "affiliated",
+            &node.affiliated.as_ref().map(|data| Affiliated {
+                tree: self.tree,
+                data,
+            }),
+        )?;
+        map.serialize_field(
+            "properties",
+            &Properties {
+                tree: self.tree,
+                data: &node.data,
+            },
+        )?;
+        map.serialize_field(
+            "children",
+            &Children(Node {
+                tree: self.tree,
+                id: self.id,
+            }),
+        )?;
+        map.end()
+    }
+}
+
+impl<'t, 'a> Serialize for Children<'t, 'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let tree = self.0.tree;
+        let mut seq = serializer.serialize_seq(None)?;
+        for id in tree.children(self.0.id) {
+            seq.serialize_element(&Node { tree, id })?;
+        }
+        seq.end()
+    }
+}
+
+impl<'t, 'a> Serialize for Properties<'t, 'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use Syntax::*;
+        let tree = self.tree;
+        match self.data {
+            BabelCall(d) => d.serialize(serializer),
+            Clock(d) => d.serialize(serializer),
+            Comment(d) => d.serialize(serializer),
+            CommentBlock(d) => d.serialize(serializer),
+            DiarySexp(d) => d.serialize(serializer),
+            Drawer(d) => d.serialize(serializer),
+            DynamicBlock(d) => d.serialize(serializer),
+            ExampleBlock(d) => d.serialize(serializer),
+            ExportBlock(d) => d.serialize(serializer),
+            FixedWidth(d) => d.serialize(serializer),
+            FootnoteDefinition(d) => d.serialize(serializer),
+            Headline(d) => Titled {
+                properties: d,
+                title: Secondary {
+                    tree,
+                    value: &d.title,
+                },
+            }
+            .serialize(serializer),
+            InlineTask(d) => Titled {
+                properties: d,
+                title: Secondary {
+                    tree,
+                    value: &d.title,
+                },
+            }
+            .serialize(serializer),
+            Item(d) => Tagged {
+                properties: d,
+                tag: d.tag.as_ref().map(|value| Secondary { tree, value }),
+            }
+            .serialize(serializer),
+            Keyword(d) => d.serialize(serializer),
+            LatexEnvironment(d) => d.serialize(serializer),
+            NodeProperty(d) => d.serialize(serializer),
+            PlainList(d) => d.serialize(serializer),
+            Planning(d) => d.serialize(serializer),
+            SpecialBlock(d) => d.serialize(serializer),
+            SrcBlock(d) => d.serialize(serializer),
+            Table(d) => d.serialize(serializer),
+            TableRow(d) => d.serialize(serializer),
+            Code(d) => d.serialize(serializer),
+            Entity(d) => d.serialize(serializer),
+            ExportSnippet(d) => d.serialize(serializer),
+            FootnoteReference(d) => d.serialize(serializer),
+            InlineBabelCall(d) => d.serialize(serializer),
+            InlineSrcBlock(d) => d.serialize(serializer),
+            LatexFragment(d) => d.serialize(serializer),
+            Link(d) => d.serialize(serializer),
+            Macro(d) => d.serialize(serializer),
+            RadioTarget(d) => d.serialize(serializer),
+            StatisticsCookie(d) => d.serialize(serializer),
+            Subscript(d) => d.serialize(serializer),
+            Superscript(d) => d.serialize(serializer),
+            Target(d) => d.serialize(serializer),
+            Timestamp(d) => d.serialize(serializer),
+            Verbatim(d) => d.serialize(serializer),
+            PlainText(text) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("value", text)?;
+                map.end()
+            }
+            OrgData | CenterBlock | HorizontalRule | Paragraph | PropertyDrawer | QuoteBlock
+            | Section | VerseBlock | Bold | Italic | LineBreak | StrikeThrough | TableCell
+            | Underline => serializer.serialize_map(Some(0))?.end(),
+        }
+    }
+}
+
+impl<'t, 'a> Serialize for Affiliated<'t, 'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let keywords: BTreeMap<_, _> = self.data.keywords.iter().collect();
+        let mut map = serializer.serialize_map(Some(keywords.len()))?;
+        for (keyword, values) in keywords {
+            let values: Vec<_> = values
+                .iter()
+                .map(|v| DualVal {
+                    value: Secondary {
+                        tree: self.tree,
+                        value: &v.value,
+                    },
+                    secondary: v.secondary.as_ref().map(|value| Secondary {
+                        tree: self.tree,
+                        value,
+                    }),
+                })
+                .collect();
+            map.serialize_entry(keyword, &values)?;
+        }
+        map.end()
+    }
+}
+
+impl<'t, 'a> Serialize for Secondary<'t, 'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.value {
+            StringOrObject::Raw(raw) => serializer.serialize_str(raw),
+            StringOrObject::Parsed(objects) => {
+                let mut seq = serializer.serialize_seq(Some(objects.len()))?;
+                for &id in objects {
+                    seq.serialize_element(&Node {
+                        tree: self.tree,
+                        id,
+                    })?;
+                }
+                seq.end()
+            }
+        }
+    }
+}
+
+/// Parsed secondary strings refer to nodes of their tree, so they
+/// can only be serialized as a part of it.
+impl<'a> Serialize for StringOrObject<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            StringOrObject::Raw(raw) => serializer.serialize_str(raw),
+            StringOrObject::Parsed(_) => Err(S::Error::custom(
+                "parsed secondary strings can only be serialized with their SyntaxTree",
+            )),
+        }
+    }
+}
+
+/// Serializes a `HashMap` with sorted keys, to get stable output
+pub fn ordered_map<S, K, V>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    K: Ord + Serialize,
+    V: Serialize,
+{
+    map.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::affiliated::{AffiliatedData, DualVal};
+    use crate::data::{Interval, NodeId, StringOrObject, Syntax, SyntaxNode, SyntaxTree};
+    use crate::headline::{HeadlineData, Tag, TodoType};
+    use std::borrow::Cow;
+    use std::env;
+    use std::fs;
+
+    /// Compares `tree` serialized to JSON against a fixture in
+    /// `tests/fixtures/serialize`. Run tests with `UPDATE_SNAPSHOTS`
+    /// environment variable set to rewrite fixtures instead.
+    fn assert_snapshot(name: &str, tree: &SyntaxTree) {
+        let path = format!(
+            "{}/tests/fixtures/serialize/{}.json",
+            env!("CARGO_MANIFEST_DIR"),
+            name
+        );
+        let actual = serde_json::to_string_pretty(tree).unwrap() + "\n";
+        if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            fs::write(&path, &actual).unwrap();
+        }
+        let expected = fs::read_to_string(&path).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    fn node(
+        data: Syntax,
+        start: usize,
+        end: usize,
+        contents: Option<(usize, usize)>,
+    ) -> SyntaxNode {
+        SyntaxNode::new(
+            data,
+            Interval { start, end },
+            contents.map(|(start, end)| Interval { start, end }),
+            0,
+        )
+    }
+
+    #[test]
+    fn headline_with_paragraph() {
+        let text =
+            "* TODO Greeting :tag:\n#+NAME: greeting\n#+ATTR_HTML: :class hi\nHello *world*\n";
+        let mut tree = SyntaxTree::with_text(text);
+        let root = tree.root();
+
+        let headline = tree.append(
+            root,
+            node(
+                Syntax::Headline(Box::new(HeadlineData {
+                    archivedp: false,
+                    closed: None,
+                    commentedp: false,
+                    deadline: None,
+                    footnote_section_p: false,
+                    level: 1,
+                    pre_blank: 0,
+                    priority: 0,
+                    quotedp: false,
+                    raw_value: Cow::from("Greeting"),
+                    scheduled: None,
+                    tags: vec![Tag(Cow::from("tag"))],
+                    title: StringOrObject::Raw(Cow::from("Greeting")),
+                    todo_keyword: Some(Cow::from("TODO")),
+                    todo_type: Some(TodoType::Todo),
+                })),
+                0,
+                76,
+                Some((22, 76)),
+            ),
+        );
+        let section = tree.append(headline, node(Syntax::Section, 22, 76, Some((22, 76))));
+
+        let mut affiliated = AffiliatedData::default();
+        for (keyword, value) in &[("NAME", "greeting"), ("ATTR_HTML", ":class hi")] {
+            let value = DualVal {
+                value: StringOrObject::Raw(Cow::from(*value)),
+                secondary: None,
+            };
+            affiliated.insert(keyword.to_string(), value, false);
+        }
+        let mut paragraph = node(Syntax::Paragraph, 22, 76, Some((62, 76)));
+        paragraph.affiliated = Some(Box::new(affiliated));
+        let paragraph = tree.append(section, paragraph);
+
+        tree.append(
+            paragraph,
+            node(Syntax::PlainText(Cow::from("Hello ")), 62, 68, None),
+        );
+        let bold = tree.append(paragraph, node(Syntax::Bold, 68, 75, Some((69, 74))));
+        tree.append(
+            bold,
+            node(Syntax::PlainText(Cow::from("world")), 69, 74, None),
+        );
+        tree.append(
+            paragraph,
+            node(Syntax::PlainText(Cow::from("\n")), 75, 76, None),
+        );
+
+        assert_snapshot("headline_with_paragraph", &tree);
+    }
+
+    #[test]
+    fn parsed_caption() {
+        use crate::options::ParserOptions;
+        use crate::parser::{ParseGranularity, Parser};
+
+        let text = "#+CAPTION: A [[https://orgmode.org][link]]\nText\n";
+        let parser = Parser::new(text, ParseGranularity::Object, ParserOptions::default());
+        assert_snapshot("parsed_caption", &parser.parse_buffer());
+    }
+
+    #[test]
+    fn parsed_title() {
+        use crate::options::ParserOptions;
+        use crate::parser::{ParseGranularity, Parser};
+
+        let text = "* DONE Read [[https://orgmode.org][the manual]] [2/2]\n";
+        let parser = Parser::new(text, ParseGranularity::Object, ParserOptions::default());
+        assert_snapshot("parsed_title", &parser.parse_buffer());
+    }
+}
@@ -0,0 +1,855 @@
+//    This file is part of org-rs.
+//
+//    org-rs is free software: you can redistribute it and/or modify
+//    it under the terms of the GNU General Public License as published by
+//    the Free Software Foundation, either version 3 of the License, or
+//    (at your option) any later version.
+//
+//    org-rs is distributed in the hope that it will be useful,
+//    but WITHOUT ANY WARRANTY; without even the implied warranty of
+//    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//    GNU General Public License for more details.
+//
+//    You should have received a copy of the GNU General Public License
+//    along with org-rs.  If not, see <https://www.gnu.org/licenses/>.
+
+//! S-expression dump of the parse tree, in the shape of the value of
+//! `(org-element-parse-buffer)`, to compare the parser with org-element.
+//! Available with the `sexp` cargo feature, which enables `serde`: the
+//! dump is built from the serialization of the tree.
+//!
+//! Every node is printed as `(TYPE PROPERTIES CHILDREN...)`, plain text
+//! as a string. PROPERTIES is a property list starting with the
+//! properties org-element gives to all nodes:
+//!
+//! - `:begin`, `:end`, `:contents-begin`, `:contents-end` are buffer
+//!   positions, i.e. 1-based character offsets, not byte offsets.
+//!   Contents properties are left out for nodes without contents.
+//! - `:post-blank`
+//! - `:post-affiliated`, for elements only
+//!
+//! Affiliated keywords follow, as properties named after the lower
+//! case keyword (`:name`, `:attr_html`...). Values of dual keywords are
+//! `(VALUE . SECONDARY)` cons cells, keywords that can occur more than
+//! once hold a list of values. Then come the properties specific to the
+//! type of the node, with the names, and the values, documented in
+//! `serialize`: symbols are printed as symbols, booleans as `t` or
+//! `nil`, nested timestamps as `(timestamp PROPERTIES)`.
+//!
+//! Text properties and `:parent` are left out. Strings are printed as
+//! with `print-escape-newlines`, children are indented on their own
+//! line, so that dumps can be compared line by line. A reference dump
+//! is produced in Emacs with `(pp (org-element-parse-buffer))`, after
+//! removing `:parent` properties.
+
+use crate::data::SyntaxTree;
+use crate::options::ParserOptions;
+use regex::Regex;
+use serde::ser::{self, Serialize};
+use std::fmt;
+
+/// Types of the nodes that are elements, along with `org-data`. Only
+/// elements have `:post-affiliated`.
+///
+/// elisp: `org-element-all-elements`
+const ALL_ELEMENTS: [&str; 31] = [
+    "org-data",
+    "babel-call",
+    "center-block",
+    "clock",
+    "comment",
+    "comment-block",
+    "diary-sexp",
+    "drawer",
+    "dynamic-block",
+    "example-block",
+    "export-block",
+    "fixed-width",
+    "footnote-definition",
+    "headline",
+    "horizontal-rule",
+    "inlinetask",
+    "item",
+    "keyword",
+    "latex-environment",
+    "node-property",
+    "paragraph",
+    "plain-list",
+    "planning",
+    "property-drawer",
+    "quote-block",
+    "section",
+    "special-block",
+    "src-block",
+    "table",
+    "table-row",
+    "verse-block",
+];
+
+/// Dumps `tree` as an s-expression, see module documentation.
+/// `options` must be the ones `tree` was parsed with: they tell which
+/// affiliated keywords are dual, or can occur more than once.
+pub fn to_sexp(tree: &SyntaxTree, options: &ParserOptions) -> String {
+    let mut out = String::new();
+    document(tree, options).write(&mut out, Some(0));
+    out.push('\n');
+    out
+}
+
+/// Root node of `tree` as a `Sexp`
+fn document(tree: &SyntaxTree, options: &ParserOptions) -> Sexp {
+    let text = tree.text();
+    let context = Context {
+        text,
+        chars: text.char_indices().map(|(i, _)| i).collect(),
+        affiliated: Regex::new(&options.affiliated_re()).unwrap(),
+        options,
+    };
+    let document = tree
+        .serialize(Serializer { context: &context })
+        .expect("syntax trees always serialize");
+    let root = match document {
+        Sexp::Plist(mut fields) => fields.pop().map(|(_, root)| root),
+        _ => None,
+    };
+    root.unwrap_or(Sexp::Nil)
+}
+
+/// Lisp value
+#[derive(Debug, Clone, PartialEq)]
+enum Sexp {
+    Nil,
+    T,
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Symbol(String),
+    /// `(CAR . CDR)`
+    Cons(Box<Sexp>, Box<Sexp>),
+    List(Vec<Sexp>),
+    /// Property list, keys without their colon
+    Plist(Vec<(String, Sexp)>),
+    /// `(TYPE PROPERTIES CHILDREN...)`
+    Node(String, Vec<(String, Sexp)>, Vec<Sexp>),
+}
+
+impl Sexp {
+    /// Prints the value to `out`. Children of nodes go on their own
+    /// line, indented from `indent`, or on the same line with `None`.
+    fn write(&self, out: &mut String, indent: Option<usize>) {
+        match self {
+            Sexp::Nil => out.push_str("nil"),
+            Sexp::T => out.push('t'),
+            Sexp::Int(n) => out.push_str(&n.to_string()),
+            Sexp::Float(f) => out.push_str(&format!("{:?}", f)),
+            Sexp::Str(s) => {
+                out.push('"');
+                for c in s.chars() {
+                    match c {
+                        '"' => out.push_str("\\\""),
+                        '\\' => out.push_str("\\\\"),
+                        '\n' => out.push_str("\\n"),
+                        '\x0c' => out.push_str("\\f"),
+                        c => out.push(c),
+                    }
+                }
+                out.push('"');
+            }
+            Sexp::Symbol(s) => out.push_str(s),
+            Sexp::Cons(car, cdr) => {
+                out.push('(');
+                car.write(out, None);
+                match &**cdr {
+                    Sexp::Nil => {}
+                    // `(A . (B C))` reads back as `(A B C)`
+                    Sexp::List(items) if !items.is_empty() => {
+                        for item in items {
+                            out.push(' ');
+                            item.write(out, None);
+                        }
+                    }
+                    cdr => {
+                        out.push_str(" . ");
+                        cdr.write(out, None);
+                    }
+                }
+                out.push(')');
+            }
+            Sexp::List(items) if items.is_empty() => out.push_str("nil"),
+            Sexp::List(items) => {
+                out.push('(');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push(' ');
+                    }
+                    item.write(out, None);
+                }
+                out.push(')');
+            }
+            Sexp::Plist(properties) => write_plist(out, properties),
+            Sexp::Node(kind, properties, children) => {
+                out.push('(');
+                out.push_str(kind);
+                out.push(' ');
+                write_plist(out, properties);
+                for child in children {
+                    match indent {
+                        Some(indent) => {
+                            out.push('\n');
+                            out.push_str(&" ".repeat(indent + 2));
+                            child.write(out, Some(indent + 2));
+                        }
+                        None => {
+                            out.push(' ');
+                            child.write(out, None);
+                        }
+                    }
+                }
+                out.push(')');
+            }
+        }
+    }
+}
+
+fn write_plist(out: &mut String, properties: &[(String, Sexp)]) {
+    if properties.is_empty() {
+        out.push_str("nil");
+        return;
+    }
+    out.push('(');
+    for (i, (key, value)) in properties.iter().enumerate() {
+        if i > 0 {
+            out.push(' ');
+        }
+        out.push(':');
+        out.push_str(key);
+        out.push(' ');
+        value.write(out, None);
+    }
+    out.push(')');
+}
+
+/// What is needed to turn the serialized tree into org-element's shape
+struct Context<'t> {
+    text: &'t str,
+    /// Byte offset of every character of `text`
+    chars: Vec<usize>,
+    affiliated: Regex,
+    options: &'t ParserOptions,
+}
+
+impl<'t> Context<'t> {
+    /// Buffer position at byte `offset`
+    fn position(&self, offset: usize) -> i64 {
+        let chars = if offset > self.text.len() {
+            self.chars.len() + offset - self.text.len()
+        } else {
+            self.chars.partition_point(|&c| c < offset)
+        };
+        chars as i64 + 1
+    }
+
+    /// Byte offset of the first line after the affiliated keywords of
+    /// an element spanning from `begin` to `end`
+    fn post_affiliated(&self, begin: usize, end: usize) -> usize {
+        let end = end.min(self.text.len());
+        let mut pos = begin;
+        while pos < end {
+            let line = match self.text.get(pos..end) {
+                Some(rest) => rest.split_inclusive('\n').next().unwrap_or(rest),
+                None => break,
+            };
+            if !self.affiliated.is_match(line) {
+                break;
+            }
+            pos += line.len();
+        }
+        pos
+    }
+
+    /// Value of affiliated `keyword` from its serialized values
+    fn affiliated_value(&self, keyword: &str, values: Sexp) -> Sexp {
+        let dual = self.options.is_dual_keyword(keyword);
+        let mut values: Vec<Sexp> = match values {
+            Sexp::List(values) => values,
+            _ => vec![],
+        }
+        .into_iter()
+        .map(|value| {
+            let mut fields = match value {
+                Sexp::Plist(fields) => fields,
+                _ => vec![],
+            };
+            let secondary = take(&mut fields, "secondary");
+            let value = take(&mut fields, "value");
+            if dual {
+                Sexp::Cons(Box::new(value), Box::new(secondary))
+            } else {
+                value
+            }
+        })
+        .collect();
+        if self.options.is_multiple_keyword(keyword) {
+            Sexp::List(values)
+        } else {
+            values.pop().unwrap_or(Sexp::Nil)
+        }
+    }
+
+    /// Node from the fields of a serialized `Node`, see `serialize`
+    fn node(&self, mut fields: Vec<(String, Sexp)>) -> Sexp {
+        let kind = match take(&mut fields, "type") {
+            Sexp::Str(kind) => kind,
+            _ => String::new(),
+        };
+        let mut properties = match take(&mut fields, "properties") {
+            Sexp::Plist(properties) => properties,
+            // Fields of the `*Data` struct of the node
+            Sexp::Node(_, properties, _) => properties,
+            _ => vec![],
+        };
+        if kind == "plain-text" {
+            return take(&mut properties, "value");
+        }
+        let offset = |field: Sexp| match field {
+            Sexp::Int(n) => n as usize,
+            _ => 0,
+        };
+        let begin = offset(take(&mut fields, "begin"));
+        let end = offset(take(&mut fields, "end"));
+        let mut plist = vec![
+            ("begin".to_string(), Sexp::Int(self.position(begin))),
+            ("end".to_string(), Sexp::Int(self.position(end))),
+        ];
+        if let Sexp::Plist(mut contents) = take(&mut fields, "contents") {
+            let contents_begin = offset(take(&mut contents, "begin"));
+            let contents_end = offset(take(&mut contents, "end"));
+            plist.push((
+                "contents-begin".to_string(),
+                Sexp::Int(self.position(contents_begin)),
+            ));
+            plist.push((
+                "contents-end".to_string(),
+                Sexp::Int(self.position(contents_end)),
+            ));
+        }
+        plist.push(("post-blank".to_string(), take(&mut fields, "post_blank")));
+        let affiliated = take(&mut fields, "affiliated");
+        if ALL_ELEMENTS.contains(&kind.as_str()) {
+            let post_affiliated = match affiliated {
+                Sexp::Nil => begin,
+                _ => self.post_affiliated(begin, end),
+            };
+            plist.push((
+                "post-affiliated".to_string(),
+                Sexp::Int(self.position(post_affiliated)),
+            ));
+        }
+        if let Sexp::Plist(keywords) = affiliated {
+            for (keyword, values) in keywords {
+                let value = self.affiliated_value(&keyword, values);
+                plist.push((keyword.to_lowercase(), value));
+            }
+        }
+        plist.extend(properties);
+        let children = match take(&mut fields, "children") {
+            Sexp::List(children) => children,
+            _ => vec![],
+        };
+        Sexp::Node(kind, plist, children)
+    }
+}
+
+/// Removes `key` from `fields` and returns its value
+fn take(fields: &mut Vec<(String, Sexp)>, key: &str) -> Sexp {
+    match fields.iter().position(|(k, _)| k == key) {
+        Some(i) => fields.remove(i).1,
+        None => Sexp::Nil,
+    }
+}
+
+/// `TimestampData` → `timestamp`
+fn type_name(struct_name: &str) -> String {
+    let name = struct_name.strip_suffix("Data").unwrap_or(struct_name);
+    let mut kebab = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            kebab.push('-');
+        }
+        kebab.extend(c.to_lowercase());
+    }
+    kebab
+}
+
+#[derive(Debug)]
+struct Error(String);
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error(msg.to_string())
+    }
+}
+
+/// Serializes to `Sexp`. Structs named `Node` are nodes of the tree
+/// and are turned into org-element nodes on the way.
+#[derive(Clone, Copy)]
+struct Serializer<'c> {
+    context: &'c Context<'c>,
+}
+
+struct Seq<'c> {
+    context: &'c Context<'c>,
+    items: Vec<Sexp>,
+}
+
+struct Map<'c> {
+    context: &'c Context<'c>,
+    entries: Vec<(String, Sexp)>,
+    key: Option<String>,
+}
+
+struct Struct<'c> {
+    context: &'c Context<'c>,
+    name: &'static str,
+    fields: Vec<(String, Sexp)>,
+}
+
+impl<'c> ser::Serializer for Serializer<'c> {
+    type Ok = Sexp;
+    type Error = Error;
+    type SerializeSeq = Seq<'c>;
+    type SerializeTuple = Seq<'c>;
+    type SerializeTupleStruct = Seq<'c>;
+    type SerializeTupleVariant = Seq<'c>;
+    type SerializeMap = Map<'c>;
+    type SerializeStruct = Struct<'c>;
+    type SerializeStructVariant = Struct<'c>;
+
+    fn serialize_bool(self, v: bool) -> Result<Sexp, Error> {
+        Ok(if v { Sexp::T } else { Sexp::Nil })
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Sexp, Error> {
+        Ok(Sexp::Int(v.into()))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Sexp, Error> {
+        Ok(Sexp::Int(v.into()))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Sexp, Error> {
+        Ok(Sexp::Int(v.into()))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Sexp, Error> {
+        Ok(Sexp::Int(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Sexp, Error> {
+        Ok(Sexp::Int(v.into()))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Sexp, Error> {
+        Ok(Sexp::Int(v.into()))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Sexp, Error> {
+        Ok(Sexp::Int(v.into()))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Sexp, Error> {
+        Ok(Sexp::Int(v as i64))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Sexp, Error> {
+        Ok(Sexp::Float(v.into()))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Sexp, Error> {
+        Ok(Sexp::Float(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Sexp, Error> {
+        Ok(Sexp::Str(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Sexp, Error> {
+        Ok(Sexp::Str(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Sexp, Error> {
+        Ok(Sexp::List(v.iter().map(|&b| Sexp::Int(b.into())).collect()))
+    }
+
+    fn serialize_none(self) -> Result<Sexp, Error> {
+        Ok(Sexp::Nil)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Sexp, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Sexp, Error> {
+        Ok(Sexp::Nil)
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Sexp, Error> {
+        Ok(Sexp::Nil)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+    ) -> Result<Sexp, Error> {
+        Ok(Sexp::Symbol(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Sexp, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Sexp, Error> {
+        Ok(Sexp::List(vec![
+            Sexp::Symbol(variant.to_string()),
+            value.serialize(self)?,
+        ]))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Seq<'c>, Error> {
+        Ok(Seq {
+            context: self.context,
+            items: vec![],
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Seq<'c>, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, name: &'static str, len: usize) -> Result<Seq<'c>, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Seq<'c>, Error> {
+        Ok(Seq {
+            context: self.context,
+            items: vec![Sexp::Symbol(variant.to_string())],
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Map<'c>, Error> {
+        Ok(Map {
+            context: self.context,
+            entries: vec![],
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<Struct<'c>, Error> {
+        Ok(Struct {
+            context: self.context,
+            name,
+            fields: vec![],
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Struct<'c>, Error> {
+        self.serialize_struct(variant, len)
+    }
+}
+
+impl<'c> Seq<'c> {
+    fn push<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        let value = value.serialize(Serializer {
+            context: self.context,
+        })?;
+        self.items.push(value);
+        Ok(())
+    }
+}
+
+impl<'c> ser::SerializeSeq for Seq<'c> {
+    type Ok = Sexp;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Sexp, Error> {
+        Ok(Sexp::List(self.items))
+    }
+}
+
+impl<'c> ser::SerializeTuple for Seq<'c> {
+    type Ok = Sexp;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Sexp, Error> {
+        Ok(Sexp::List(self.items))
+    }
+}
+
+impl<'c> ser::SerializeTupleStruct for Seq<'c> {
+    type Ok = Sexp;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Sexp, Error> {
+        Ok(Sexp::List(self.items))
+    }
+}
+
+impl<'c> ser::SerializeTupleVariant for Seq<'c> {
+    type Ok = Sexp;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Sexp, Error> {
+        Ok(Sexp::List(self.items))
+    }
+}
+
+impl<'c> ser::SerializeMap for Map<'c> {
+    type Ok = Sexp;
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Error> {
+        let key = match key.serialize(Serializer {
+            context: self.context,
+        })? {
+            Sexp::Str(key) | Sexp::Symbol(key) => key,
+            key => return Err(ser::Error::custom(format!("invalid key: {:?}", key))),
+        };
+        self.key = Some(key);
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self.key.take().unwrap_or_default();
+        let value = value.serialize(Serializer {
+            context: self.context,
+        })?;
+        self.entries.push((key, value));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Sexp, Error> {
+        Ok(Sexp::Plist(self.entries))
+    }
+}
+
+impl<'c> Struct<'c> {
+    fn push<T: ?Sized + Serialize>(&mut self, key: &str, value: &T) -> Result<(), Error> {
+        let value = value.serialize(Serializer {
+            context: self.context,
+        })?;
+        self.fields.push((key.to_string(), value));
+        Ok(())
+    }
+
+    /// The `*Data` structs holding the properties of nodes become
+    /// property lists in `Context::node`, and stay nodes when nested in
+    /// other properties, e.g. timestamps of a planning line.
+    fn finish(self) -> Sexp {
+        match self.name {
+            "Node" => self.context.node(self.fields),
+            name if name.ends_with("Data") => Sexp::Node(type_name(name), self.fields, vec![]),
+            _ => Sexp::Plist(self.fields),
+        }
+    }
+}
+
+impl<'c> ser::SerializeStruct for Struct<'c> {
+    type Ok = Sexp;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.push(key, value)
+    }
+
+    fn end(self) -> Result<Sexp, Error> {
+        Ok(self.finish())
+    }
+}
+
+impl<'c> ser::SerializeStructVariant for Struct<'c> {
+    type Ok = Sexp;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.push(key, value)
+    }
+
+    fn end(self) -> Result<Sexp, Error> {
+        Ok(self.finish())
+    }
+}
+
+mod test {
+    use super::{document, to_sexp, Sexp};
+    use crate::options::ParserOptions;
+    use crate::parser::{ParseGranularity, Parser};
+    use std::env;
+    use std::fs;
+
+    /// Compares the dump of `text` against a fixture in
+    /// `tests/fixtures/sexp`. Run tests with `UPDATE_SNAPSHOTS`
+    /// environment variable set to rewrite fixtures instead.
+    fn assert_dump(name: &str, text: &str) {
+        let path = format!(
+            "{}/tests/fixtures/sexp/{}.el",
+            env!("CARGO_MANIFEST_DIR"),
+            name
+        );
+        let options = ParserOptions::default();
+        let tree = Parser::new(text, ParseGranularity::Object, options.clone()).parse_buffer();
+        let actual = to_sexp(&tree, &options);
+        if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            fs::write(&path, &actual).unwrap();
+        }
+        let expected = fs::read_to_string(&path).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn headlines() {
+        assert_dump(
+            "headlines",
+            "* TODO Café :tag:\nSome *bold* \"text\"\n\n** Child\n",
+        );
+    }
+
+    #[test]
+    fn affiliated_keywords() {
+        assert_dump(
+            "affiliated_keywords",
+            "#+NAME: greeting\n#+CAPTION[Short]: A /caption/\n#+ATTR_HTML: :class hi\nHello\n",
+        );
+    }
+
+    #[test]
+    fn links() {
+        assert_dump(
+            "links",
+            "See [[https://orgmode.org][Org]] and [[file:notes.org::*Intro]].\n",
+        );
+    }
+
+    #[test]
+    fn print() {
+        let mut out = String::new();
+        Sexp::List(vec![
+            Sexp::Cons(
+                Box::new(Sexp::Str("a\\b\n".to_string())),
+                Box::new(Sexp::Nil),
+            ),
+            Sexp::Cons(Box::new(Sexp::Int(1)), Box::new(Sexp::T)),
+            Sexp::Cons(
+                Box::new(Sexp::Int(1)),
+                Box::new(Sexp::List(vec![Sexp::Int(2)])),
+            ),
+            Sexp::List(vec![]),
+            Sexp::Symbol("active-range".to_string()),
+        ])
+        .write(&mut out, Some(0));
+        assert_eq!(r#"(("a\\b\n") (1 . t) (1 2) nil active-range)"#, out);
+    }
+
+    /// Printed value of property `key` of the first node of type `kind`
+    fn property(sexp: &Sexp, kind: &str, key: &str) -> Option<String> {
+        match sexp {
+            Sexp::Node(k, properties, _) if k == kind => {
+                let (_, value) = properties.iter().find(|(k, _)| k == key)?;
+                let mut out = String::new();
+                value.write(&mut out, None);
+                Some(out)
+            }
+            Sexp::Node(_, _, children) => {
+                children.iter().find_map(|child| property(child, kind, key))
+            }
+            _ => None,
+        }
+    }
+
+    /// Type specific properties, as given by `org-element-parse-buffer`
+    /// on the same text with Org 9.6, text properties left out
+    #[test]
+    fn type_properties() {
+        let text = "* TODO Head\nSee [[https://orgmode.org][Org]].\n";
+        let options = ParserOptions::default();
+        let tree = Parser::new(text, ParseGranularity::Object, options.clone()).parse_buffer();
+        let document = document(&tree, &options);
+        let expected = [
+            ("headline", "level", "1"),
+            ("headline", "todo-keyword", "\"TODO\""),
+            ("headline", "todo-type", "todo"),
+            ("headline", "raw-value", "\"Head\""),
+            ("link", "type", "\"https\""),
+            ("link", "path", "\"//orgmode.org\""),
+            ("link", "raw-link", "\"https://orgmode.org\""),
+            ("link", "format", "bracket"),
+            ("link", "application", "nil"),
+            ("link", "search-option", "nil"),
+        ];
+        for &(kind, key, value) in expected.iter() {
+            assert_eq!(
+                Some(value.to_string()),
+                property(&document, kind, key),
+                ":{} of {}",
+                key,
+                kind
+            );
+        }
+    }
+}
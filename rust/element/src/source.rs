@@ -0,0 +1,291 @@
+//    This file is part of org-rs.
+//
+//    org-rs is free software: you can redistribute it and/or modify
+//    it under the terms of the GNU General Public License as published by
+//    the Free Software Foundation, either version 3 of the License, or
+//    (at your option) any later version.
+//
+//    org-rs is distributed in the hope that it will be useful,
+//    but WITHOUT ANY WARRANTY; without even the implied warranty of
+//    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//    GNU General Public License for more details.
+//
+//    You should have received a copy of the GNU General Public License
+//    along with org-rs.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Text the parser reads from.
+//!
+//! `Cursor` and `Parser` don't need the whole input in one string.
+//! They work with any `TextSource`: a sequence of contiguous chunks,
+//! like the leaves of a rope. `&str` is a source made of a single chunk.
+//! With the `rope` cargo feature, `ropey::RopeSlice` is a source as well,
+//! so editors holding their buffers in a rope can parse them directly.
+//!
+//! Cursor movements walk the chunks, and strings stored in the parse
+//! tree borrow from a chunk when they fit in one. Text is only copied
+//! when a regex has to run over a window spanning several chunks, or
+//! a string to store spans them. The tree keeps the text it describes
+//! as the list of chunks of the source, joined only when
+//! `SyntaxTree::text` is called or the tree is edited.
+//!
+//! Offsets are always byte offsets from the beginning of the source.
+
+use memchr::{memchr, memrchr};
+use std::borrow::Cow;
+
+pub trait TextSource<'a>: Copy {
+    /// Length of the text in bytes
+    fn len(self) -> usize;
+
+    /// Returns the chunk containing the byte at `offset`, and the
+    /// offset of the first byte of the chunk. With `offset` equal to
+    /// `len()`, returns the last chunk.
+    fn chunk_at(self, offset: usize) -> (&'a str, usize);
+
+    fn is_empty(self) -> bool {
+        self.len() == 0
+    }
+
+    /// Text between `start` and `end`, borrowed when it lies
+    /// within one chunk.
+    fn slice(self, start: usize, end: usize) -> Cow<'a, str> {
+        let (chunk, chunk_start) = self.chunk_at(start);
+        if end <= chunk_start + chunk.len() {
+            return Cow::from(&chunk[start - chunk_start..end - chunk_start]);
+        }
+        let mut text = String::with_capacity(end - start);
+        let mut pos = start;
+        while pos < end {
+            let (chunk, chunk_start) = self.chunk_at(pos);
+            let chunk_end = (chunk_start + chunk.len()).min(end);
+            text.push_str(&chunk[pos - chunk_start..chunk_end - chunk_start]);
+            pos = chunk_end;
+        }
+        Cow::from(text)
+    }
+
+    /// Byte at `offset`, which must be less than `len()`
+    fn byte_at(self, offset: usize) -> u8 {
+        let (chunk, chunk_start) = self.chunk_at(offset);
+        chunk.as_bytes()[offset - chunk_start]
+    }
+
+    /// `offset` moved back to the beginning of the character it falls
+    /// in, if any
+    fn floor_char_boundary(self, mut offset: usize) -> usize {
+        while offset > 0 && offset < self.len() && self.byte_at(offset) & 0xC0 == 0x80 {
+            offset -= 1;
+        }
+        offset
+    }
+
+    /// Offset of the first `byte` between `start` and `end`
+    fn find_byte(self, byte: u8, start: usize, end: usize) -> Option<usize> {
+        self.chunks(start, end)
+            .find_map(|(chunk, offset)| memchr(byte, chunk.as_bytes()).map(|p| offset + p))
+    }
+
+    /// Offset of the last `byte` between `start` and `end`
+    fn rfind_byte(self, byte: u8, start: usize, mut end: usize) -> Option<usize> {
+        while end > start {
+            let (chunk, chunk_start) = self.chunk_at(end - 1);
+            let from = chunk_start.max(start);
+            let bytes = &chunk.as_bytes()[from - chunk_start..end - chunk_start];
+            if let Some(p) = memrchr(byte, bytes) {
+                return Some(from + p);
+            }
+            end = from;
+        }
+        None
+    }
+
+    /// Chunks covering the text between `start` and `end`, cut to
+    /// that range, with their offsets.
+    fn chunks(self, start: usize, end: usize) -> Chunks<'a, Self> {
+        Chunks {
+            source: self,
+            pos: start,
+            end,
+            _text: std::marker::PhantomData,
+        }
+    }
+}
+
+/// Iterator returned by `TextSource::chunks`
+pub struct Chunks<'a, S> {
+    source: S,
+    pos: usize,
+    end: usize,
+    _text: std::marker::PhantomData<&'a str>,
+}
+
+impl<'a, S: TextSource<'a>> Iterator for Chunks<'a, S> {
+    type Item = (&'a str, usize);
+
+    fn next(&mut self) -> Option<(&'a str, usize)> {
+        if self.pos >= self.end {
+            return None;
+        }
+        let (chunk, chunk_start) = self.source.chunk_at(self.pos);
+        let chunk_end = (chunk_start + chunk.len()).min(self.end);
+        let start = self.pos;
+        self.pos = chunk_end;
+        Some((&chunk[start - chunk_start..chunk_end - chunk_start], start))
+    }
+}
+
+impl<'a> TextSource<'a> for &'a str {
+    fn len(self) -> usize {
+        str::len(self)
+    }
+
+    fn chunk_at(self, _offset: usize) -> (&'a str, usize) {
+        (self, 0)
+    }
+
+    fn slice(self, start: usize, end: usize) -> Cow<'a, str> {
+        Cow::from(&self[start..end])
+    }
+
+    fn byte_at(self, offset: usize) -> u8 {
+        self.as_bytes()[offset]
+    }
+
+    fn find_byte(self, byte: u8, start: usize, end: usize) -> Option<usize> {
+        memchr(byte, &self.as_bytes()[start..end]).map(|p| start + p)
+    }
+
+    fn rfind_byte(self, byte: u8, start: usize, end: usize) -> Option<usize> {
+        memrchr(byte, &self.as_bytes()[start..end]).map(|p| start + p)
+    }
+}
+
+/// Source cut at `end`, see `Parser::parse_range`
+#[derive(Clone, Copy)]
+pub(crate) struct Narrowed<S> {
+    pub source: S,
+    pub end: usize,
+}
+
+impl<'a, S: TextSource<'a>> TextSource<'a> for Narrowed<S> {
+    fn len(self) -> usize {
+        self.end
+    }
+
+    fn chunk_at(self, offset: usize) -> (&'a str, usize) {
+        let offset = if offset >= self.end {
+            self.end.saturating_sub(1)
+        } else {
+            offset
+        };
+        let (chunk, start) = self.source.chunk_at(offset);
+        (&chunk[..chunk.len().min(self.end - start)], start)
+    }
+}
+
+/// Part of `text` between `start` and `end`, borrowed from the
+/// source when `text` is.
+pub(crate) fn sub<'a>(text: &Cow<'a, str>, start: usize, end: usize) -> Cow<'a, str> {
+    match text {
+        Cow::Borrowed(text) => Cow::from(&text[start..end]),
+        Cow::Owned(text) => Cow::from(text[start..end].to_string()),
+    }
+}
+
+#[cfg(feature = "rope")]
+impl<'a> TextSource<'a> for ropey::RopeSlice<'a> {
+    fn len(self) -> usize {
+        self.len_bytes()
+    }
+
+    fn chunk_at(self, offset: usize) -> (&'a str, usize) {
+        let (chunk, start, _, _) = self.chunk_at_byte(offset);
+        (chunk, start)
+    }
+}
+
+mod test {
+    use super::TextSource;
+    use crate::data::TreeText;
+    use crate::options::ParserOptions;
+    use crate::parser::{ParseGranularity, Parser};
+
+    /// Source cut in chunks of `size` bytes at most
+    #[derive(Clone, Copy)]
+    struct Split<'a> {
+        text: &'a str,
+        size: usize,
+    }
+
+    impl<'a> TextSource<'a> for Split<'a> {
+        fn len(self) -> usize {
+            self.text.len()
+        }
+
+        fn chunk_at(self, offset: usize) -> (&'a str, usize) {
+            let start = offset.min(self.text.len().saturating_sub(1)) / self.size * self.size;
+            let end = (start + self.size).min(self.text.len());
+            (&self.text[start..end], start)
+        }
+    }
+
+    #[test]
+    fn chunked() {
+        let text = "first line\nsecond line\n";
+        let source = Split { text, size: 4 };
+        assert_eq!(text.len(), source.len());
+        assert_eq!(("firs", 0), source.chunk_at(3));
+        assert_eq!(("ne\n", 20), source.chunk_at(text.len()));
+        assert_eq!("irs", source.slice(1, 4));
+        assert!(matches!(source.slice(1, 4), std::borrow::Cow::Borrowed(_)));
+        assert_eq!("line\nsecond", source.slice(6, 17));
+        assert_eq!(b'\n', source.byte_at(10));
+        let chunks: Vec<_> = source.chunks(2, 9).collect();
+        assert_eq!(vec![("rs", 2), ("t li", 4), ("n", 8)], chunks);
+        assert_eq!(Some(10), source.find_byte(b'\n', 3, text.len()));
+        assert_eq!(Some(22), source.find_byte(b'\n', 11, text.len()));
+        assert_eq!(None, source.find_byte(b'\n', 11, 22));
+        assert_eq!(Some(10), source.rfind_byte(b'\n', 0, 22));
+        assert_eq!(None, source.rfind_byte(b'\n', 0, 10));
+
+        let text = "aé€";
+        let source = Split { text, size: 3 };
+        assert_eq!(1, source.floor_char_boundary(2));
+        assert_eq!(3, source.floor_char_boundary(5));
+        assert_eq!(text.len(), source.floor_char_boundary(text.len()));
+    }
+
+    #[test]
+    fn parse_chunks() {
+        let text = "* Heading\nSome text\n".repeat(100);
+        let source = Split {
+            text: &text,
+            size: 64,
+        };
+        let parser =
+            Parser::from_source(source, ParseGranularity::Element, ParserOptions::default());
+        let tree = parser.parse_buffer();
+        match &tree.text {
+            TreeText::Chunks(chunks, joined) => {
+                assert!(joined.get().is_none());
+                assert_eq!((text.len() + 63) / 64, chunks.len());
+                let range = text.as_bytes().as_ptr_range();
+                assert!(chunks.iter().all(|c| range.contains(&c.as_ptr())));
+            }
+            TreeText::Flat(_) => panic!("the text of the source was copied"),
+        }
+        assert_eq!(text, tree.text());
+    }
+
+    #[test]
+    #[cfg(feature = "rope")]
+    fn rope() {
+        let text = "* Headline\n".repeat(1000);
+        let rope = ropey::Rope::from_str(&text);
+        let source = rope.slice(..);
+        assert_eq!(text.len(), source.len());
+        let (chunk, start) = source.chunk_at(5000);
+        assert!(start <= 5000 && 5000 < start + chunk.len());
+        assert_eq!(&text[4990..5010], source.slice(4990, 5010));
+    }
+}
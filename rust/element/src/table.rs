@@ -16,20 +16,25 @@
 // TODO add table related docs
 
 use crate::affiliated::AffiliatedData;
-use crate::data::SyntaxNode;
+use crate::data::{owned, Interval, Syntax, SyntaxNode};
 use crate::parser::Parser;
+use crate::source::{self, TextSource};
 use regex::Regex;
+use std::borrow::Cow;
 
 lazy_static! {
     pub static ref REGEX_TABLE_BORDER: Regex = Regex::new(r"[ \t]*\|").unwrap();
     pub static ref REGEX_TABLE_RULE: Regex = Regex::new(r"[ \t]*\+(-+\+)+[ \t]*$").unwrap();
     pub static ref REGEX_TABLE_PRE_BORDER: Regex = Regex::new(r"^[ \t]*($|[^|])").unwrap();
+    /// Formulas in group 1
+    static ref REGEX_TBLFM: Regex = Regex::new(r"(?i)^[ \t]*#\+TBLFM: +(.*)[ \t]*$").unwrap();
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct TableData<'a> {
     /// Formulas associated to the table, if any (string or nil).
-    tblfm: Option<&'a str>,
+    pub tblfm: Option<Cow<'a, str>>,
     //Table's origin (symbol table.el, org).
     // type_s
 
@@ -37,32 +42,191 @@ pub struct TableData<'a> {
     // value
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub struct TableRowData {
-    table_row_type: TableRowType,
+    pub table_row_type: TableRowType,
 }
 
 /// Row's type (symbol standard, rule).
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "kebab-case"))]
 pub enum TableRowType {
     Standard,
     Rule,
 }
 
-impl<'a> Parser<'a> {
-    // TODO implement table_row_parser
-    // https://code.orgmode.org/bzg/org-mode/src/master/lisp/org-element.el#L2637
+impl<'a> TableData<'a> {
+    pub fn into_owned(self) -> TableData<'static> {
+        TableData {
+            tblfm: self.tblfm.map(owned),
+        }
+    }
+}
+
+impl<'a, S: TextSource<'a>> Parser<'a, S> {
+    /// Parse table row at point.
+    ///
+    /// Return a list whose CAR is `table-row' and CDR is a plist
+    /// containing `:begin', `:end', `:contents-begin', `:contents-end',
+    /// `:type', `:post-blank' and `:post-affiliated' keywords.
+    /// (defun org-element-table-row-parser (_)
     pub fn table_row_parser(&self) -> SyntaxNode<'a> {
-        unimplemented!()
+        let mut c = self.cursor.borrow_mut();
+        let begin = c.pos();
+        let end = c.line_beginning_position(Some(2));
+        let line_end = c.line_end_position(None);
+        let line = self.input.slice(begin, line_end);
+        let trimmed = line.trim_start_matches(|c| c == ' ' || c == '\t');
+        // A table rule has no contents.
+        let (table_row_type, contents) = if trimmed.starts_with("|-") {
+            (TableRowType::Rule, None)
+        } else {
+            let contents_begin = begin + (line.len() - trimmed.len()) + 1;
+            let contents_end = begin + line.trim_end_matches(|c| c == ' ' || c == '\t').len();
+            (
+                TableRowType::Standard,
+                Some(Interval {
+                    start: contents_begin,
+                    end: contents_end.max(contents_begin),
+                }),
+            )
+        };
+        SyntaxNode::new(
+            Syntax::TableRow(Box::new(TableRowData { table_row_type })),
+            Interval { start: begin, end },
+            contents,
+            0,
+        )
     }
 
-    // TODO implement table_parser
+    /// Parse a table at point.
+    ///
+    /// LIMIT bounds the search.  AFFILIATED is a list of which CAR is
+    /// the buffer position at the beginning of the first affiliated
+    /// keyword and CDR is a plist of affiliated keywords along with
+    /// their value.
+    ///
+    /// Return a list whose CAR is `table' and CDR is a plist containing
+    /// `:begin', `:end', `:tblfm', `:type', `:contents-begin',
+    /// `:contents-end', `:value', `:post-blank' and `:post-affiliated'
+    /// keywords.
+    ///
+    /// Assume point is at the beginning of the table.
+    /// (defun org-element-table-parser (limit affiliated)
+    ///
+    /// Only Org tables are supported, table.el ones are not.
     pub fn table_parser(
         &self,
         limit: usize,
         start: usize,
-        maybe_aff: Option<AffiliatedData>,
+        maybe_aff: Option<AffiliatedData<'a>>,
     ) -> SyntaxNode<'a> {
-        unimplemented!()
+        let mut c = self.cursor.borrow_mut();
+        let table_begin = c.pos();
+        let table_end = self
+            .lines(table_begin, limit)
+            .find(|&(line, end)| {
+                !self
+                    .input
+                    .slice(line, end)
+                    .trim_start_matches(|c| c == ' ' || c == '\t')
+                    .starts_with('|')
+            })
+            .map_or(limit, |(line, _)| line);
+
+        // Only the first formulas line is used by Org.
+        let mut tblfm = None;
+        let mut pos_before_blank = table_end;
+        for (line, end) in self.lines(table_end, limit) {
+            let text = self.input.slice(line, end);
+            match REGEX_TBLFM.captures(&text) {
+                Some(cap) => {
+                    if tblfm.is_none() {
+                        let formulas = cap.get(1).unwrap();
+                        tblfm = Some(source::sub(&text, formulas.start(), formulas.end()));
+                    }
+                    pos_before_blank = (end + 1).min(limit);
+                }
+                None => break,
+            }
+        }
+
+        c.set(pos_before_blank);
+        c.skip_chars_forward(" \r\t\n", Some(limit));
+        let end = if c.pos() >= self.input.len() {
+            self.input.len()
+        } else {
+            c.line_beginning_position(None)
+        }
+        .min(limit);
+        let post_blank = c.count_lines(pos_before_blank, end);
+        c.set(table_begin);
+
+        let mut node = SyntaxNode::new(
+            Syntax::Table(Box::new(TableData { tblfm })),
+            Interval { start, end },
+            Some(Interval {
+                start: table_begin,
+                end: table_end,
+            }),
+            post_blank,
+        );
+        node.affiliated = maybe_aff
+            .filter(|aff| *aff != AffiliatedData::default())
+            .map(Box::new);
+        node
+    }
+}
+
+mod test {
+    use crate::data::{Syntax, SyntaxT, TableRowType};
+    use crate::options::ParserOptions;
+    use crate::parser::{ParseGranularity, Parser};
+
+    #[test]
+    fn tables() {
+        let text = "| a | b |\n  |---+---|\n| 1 |\n#+TBLFM: $2=$1\n#+tblfm: $3=1\n\nAfter\n";
+        let parser = Parser::new(text, ParseGranularity::Object, ParserOptions::default());
+        let tree = parser.parse_buffer();
+        let section = tree.children(tree.root()).next().unwrap();
+        let table = tree.children(section).next().unwrap();
+        match &tree[table].data {
+            Syntax::Table(data) => assert_eq!(Some("$2=$1"), data.tblfm.as_deref()),
+            data => panic!("not a table: {:?}", data),
+        }
+        let at = |s: &str| text.find(s).unwrap();
+        assert_eq!(
+            (0, at("After")),
+            (tree[table].location.start, tree[table].location.end)
+        );
+        assert_eq!(1, tree[table].post_blank);
+
+        let rows: Vec<_> = tree
+            .children(table)
+            .map(|row| {
+                let row_type = match &tree[row].data {
+                    Syntax::TableRow(data) => data.table_row_type,
+                    data => panic!("not a table row: {:?}", data),
+                };
+                let cells: Vec<_> = tree
+                    .children(row)
+                    .map(|cell| {
+                        assert_eq!(SyntaxT::TableCell, SyntaxT::from(&tree[cell].data));
+                        let contents = tree[cell].content_location.unwrap();
+                        &text[contents.start..contents.end]
+                    })
+                    .collect();
+                (row_type, cells)
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                (TableRowType::Standard, vec!["a", "b"]),
+                (TableRowType::Rule, vec![]),
+                (TableRowType::Standard, vec!["1"]),
+            ],
+            rows
+        );
     }
 }
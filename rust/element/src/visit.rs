@@ -0,0 +1,405 @@
+//    This file is part of org-rs.
+//
+//    org-rs is free software: you can redistribute it and/or modify
+//    it under the terms of the GNU General Public License as published by
+//    the Free Software Foundation, either version 3 of the License, or
+//    (at your option) any later version.
+//
+//    org-rs is distributed in the hope that it will be useful,
+//    but WITHOUT ANY WARRANTY; without even the implied warranty of
+//    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//    GNU General Public License for more details.
+//
+//    You should have received a copy of the GNU General Public License
+//    along with org-rs.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Tree traversal
+//!
+//! `Visitor` and `VisitorMut` walk a `SyntaxTree` depth-first, in
+//! document order. For every node the walk calls:
+//!
+//! 1. `enter`
+//! 2. the method of the node's `Syntax` variant, e.g. `visit_headline`,
+//!    only if `enter` returned `Walk::Continue`
+//! 3. the same steps for every child, unless `enter` or the variant
+//!    method returned `Walk::Skip`
+//! 4. `leave`
+//!
+//! All methods do nothing by default, so implementors only override
+//! those they are interested in.
+//!
+//! `VisitorMut` can change node data in place but not the shape of the
+//! tree, use the mutation API in `edit` for that.
+
+use crate::data::*;
+use std::borrow::Cow;
+
+/// Tells the walk whether to descend into the children of a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Walk {
+    /// Visit children
+    Continue,
+    /// Skip the subtree of the node. `leave` is still called for it.
+    Skip,
+}
+
+macro_rules! visitors {
+    (
+        data { $($data_variant:ident => $data_method:ident($data_type:ty),)* }
+        unit { $($unit_variant:ident => $unit_method:ident,)* }
+    ) => {
+        /// Read-only traversal of a `SyntaxTree`, see module documentation
+        pub trait Visitor<'a> {
+            /// Called for every node before anything else
+            fn enter(&mut self, tree: &SyntaxTree<'a>, id: NodeId) -> Walk {
+                Walk::Continue
+            }
+
+            /// Called for every entered node after its subtree was walked
+            fn leave(&mut self, tree: &SyntaxTree<'a>, id: NodeId) {}
+
+            $(
+                #[doc = concat!("Called for `Syntax::", stringify!($data_variant), "`")]
+                fn $data_method(
+                    &mut self,
+                    tree: &SyntaxTree<'a>,
+                    id: NodeId,
+                    data: &$data_type,
+                ) -> Walk {
+                    Walk::Continue
+                }
+            )*
+
+            $(
+                #[doc = concat!("Called for `Syntax::", stringify!($unit_variant), "`")]
+                fn $unit_method(&mut self, tree: &SyntaxTree<'a>, id: NodeId) -> Walk {
+                    Walk::Continue
+                }
+            )*
+        }
+
+        /// Traversal of a `SyntaxTree` with mutable access to node data,
+        /// see module documentation
+        pub trait VisitorMut<'a> {
+            /// Called for every node before anything else
+            fn enter(&mut self, id: NodeId, node: &mut SyntaxNode<'a>) -> Walk {
+                Walk::Continue
+            }
+
+            /// Called for every entered node after its subtree was walked
+            fn leave(&mut self, id: NodeId, node: &mut SyntaxNode<'a>) {}
+
+            $(
+                #[doc = concat!("Called for `Syntax::", stringify!($data_variant), "`")]
+                fn $data_method(&mut self, id: NodeId, data: &mut $data_type) -> Walk {
+                    Walk::Continue
+                }
+            )*
+
+            $(
+                #[doc = concat!("Called for `Syntax::", stringify!($unit_variant), "`")]
+                fn $unit_method(&mut self, id: NodeId) -> Walk {
+                    Walk::Continue
+                }
+            )*
+        }
+
+        fn dispatch<'a, V: Visitor<'a> + ?Sized>(
+            visitor: &mut V,
+            tree: &SyntaxTree<'a>,
+            id: NodeId,
+        ) -> Walk {
+            match &tree[id].data {
+                $(Syntax::$data_variant(data) => visitor.$data_method(tree, id, data),)*
+                $(Syntax::$unit_variant => visitor.$unit_method(tree, id),)*
+            }
+        }
+
+        fn dispatch_mut<'a, V: VisitorMut<'a> + ?Sized>(
+            visitor: &mut V,
+            id: NodeId,
+            node: &mut SyntaxNode<'a>,
+        ) -> Walk {
+            match &mut node.data {
+                $(Syntax::$data_variant(data) => visitor.$data_method(id, data),)*
+                $(Syntax::$unit_variant => visitor.$unit_method(id),)*
+            }
+        }
+    };
+}
+
+visitors! {
+    data {
+        BabelCall => visit_babel_call(BabelCallData<'a>),
+        Clock => visit_clock(ClockData<'a>),
+        Comment => visit_comment(CommentData<'a>),
+        CommentBlock => visit_comment_block(CommentBlockData<'a>),
+        DiarySexp => visit_diary_sexp(DiarySexpData<'a>),
+        Drawer => visit_drawer(DrawerData<'a>),
+        DynamicBlock => visit_dynamic_block(DynamicBlockData<'a>),
+        ExampleBlock => visit_example_block(ExampleBlockData<'a>),
+        ExportBlock => visit_export_block(ExportBlockData<'a>),
+        FixedWidth => visit_fixed_width(FixedWidthData<'a>),
+        FootnoteDefinition => visit_footnote_definition(FootnoteDefinitionData<'a>),
+        Headline => visit_headline(HeadlineData<'a>),
+        InlineTask => visit_inline_task(InlineTaskData<'a>),
+        Item => visit_item(ItemData<'a>),
+        Keyword => visit_keyword(KeywordData<'a>),
+        LatexEnvironment => visit_latex_environment(LatexEnvironmentData<'a>),
+        NodeProperty => visit_node_property(NodePropertyData<'a>),
+        PlainList => visit_plain_list(PlainListData),
+        Planning => visit_planning(PlanningData<'a>),
+        SpecialBlock => visit_special_block(SpecialBlockData<'a>),
+        SrcBlock => visit_src_block(SrcBlockData<'a>),
+        Table => visit_table(TableData<'a>),
+        TableRow => visit_table_row(TableRowData),
+        Code => visit_code(CodeData<'a>),
+        Entity => visit_entity(EntityData<'a>),
+        ExportSnippet => visit_export_snippet(ExportSnippetData<'a>),
+        FootnoteReference => visit_footnote_reference(FootnoteReferenceData<'a>),
+        InlineBabelCall => visit_inline_babel_call(InlineBabelCallData<'a>),
+        InlineSrcBlock => visit_inline_src_block(InlineSrcBlockData<'a>),
+        LatexFragment => visit_latex_fragment(LatexFragmentData<'a>),
+        Link => visit_link(LinkData<'a>),
+        Macro => visit_macro(MacroData<'a>),
+        RadioTarget => visit_radio_target(RadioTargetData<'a>),
+        StatisticsCookie => visit_statistics_cookie(StatisticsCookieData<'a>),
+        Subscript => visit_subscript(SubscriptData),
+        Superscript => visit_superscript(SuperscriptData),
+        Target => visit_target(TargetData<'a>),
+        Timestamp => visit_timestamp(TimestampData<'a>),
+        Verbatim => visit_verbatim(VerbatimData<'a>),
+        PlainText => visit_plain_text(Cow<'a, str>),
+    }
+    unit {
+        OrgData => visit_org_data,
+        CenterBlock => visit_center_block,
+        HorizontalRule => visit_horizontal_rule,
+        Paragraph => visit_paragraph,
+        PropertyDrawer => visit_property_drawer,
+        QuoteBlock => visit_quote_block,
+        Section => visit_section,
+        VerseBlock => visit_verse_block,
+        Bold => visit_bold,
+        Italic => visit_italic,
+        LineBreak => visit_line_break,
+        StrikeThrough => visit_strike_through,
+        TableCell => visit_table_cell,
+        Underline => visit_underline,
+    }
+}
+
+/// Walks the subtree of `id` with `visitor`
+pub fn walk<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, tree: &SyntaxTree<'a>, id: NodeId) {
+    let mut walk = visitor.enter(tree, id);
+    if walk == Walk::Continue {
+        walk = dispatch(visitor, tree, id);
+    }
+    if walk == Walk::Continue {
+        for child in tree.children(id) {
+            self::walk(visitor, tree, child);
+        }
+    }
+    visitor.leave(tree, id);
+}
+
+/// Walks the subtree of `id` with `visitor`, allowing it to modify nodes
+pub fn walk_mut<'a, V: VisitorMut<'a> + ?Sized>(
+    visitor: &mut V,
+    tree: &mut SyntaxTree<'a>,
+    id: NodeId,
+) {
+    let mut walk = visitor.enter(id, &mut tree[id]);
+    if walk == Walk::Continue {
+        walk = dispatch_mut(visitor, id, &mut tree[id]);
+    }
+    if walk == Walk::Continue {
+        let mut child = tree[id].first_child;
+        while let Some(id) = child {
+            walk_mut(visitor, tree, id);
+            child = tree[id].next_sibling;
+        }
+    }
+    visitor.leave(id, &mut tree[id]);
+}
+
+impl<'a> SyntaxTree<'a> {
+    /// Walks the whole tree with `visitor`
+    pub fn visit<V: Visitor<'a> + ?Sized>(&self, visitor: &mut V) {
+        walk(visitor, self, self.root())
+    }
+
+    /// Walks the whole tree with `visitor`, allowing it to modify nodes
+    pub fn visit_mut<V: VisitorMut<'a> + ?Sized>(&mut self, visitor: &mut V) {
+        let root = self.root();
+        walk_mut(visitor, self, root)
+    }
+}
+
+mod test {
+    use super::{Visitor, VisitorMut, Walk};
+    use crate::data::{Interval, NodeId, StringOrObject, Syntax, SyntaxNode, SyntaxT, SyntaxTree};
+    use crate::headline::{HeadlineData, TodoType};
+    use std::borrow::Cow;
+
+    fn headline(level: usize) -> Syntax<'static> {
+        Syntax::Headline(Box::new(HeadlineData {
+            archivedp: false,
+            closed: None,
+            commentedp: false,
+            deadline: None,
+            footnote_section_p: false,
+            level,
+            pre_blank: 0,
+            priority: 0,
+            quotedp: false,
+            raw_value: Cow::from(""),
+            scheduled: None,
+            tags: vec![],
+            title: StringOrObject::Raw(Cow::from("")),
+            todo_keyword: Some(Cow::from("TODO")),
+            todo_type: Some(TodoType::Todo),
+        }))
+    }
+
+    fn node(data: Syntax) -> SyntaxNode {
+        SyntaxNode::new(data, Interval { start: 0, end: 0 }, None, 0)
+    }
+
+    /// org-data
+    ///   headline
+    ///     section
+    ///       paragraph
+    ///         plain-text "a"
+    ///     headline
+    ///       section
+    ///         paragraph
+    ///           plain-text "b"
+    fn sample() -> SyntaxTree<'static> {
+        let mut tree = SyntaxTree::new();
+        let root = tree.root();
+        let h1 = tree.append(root, node(headline(1)));
+        let s1 = tree.append(h1, node(Syntax::Section));
+        let p1 = tree.append(s1, node(Syntax::Paragraph));
+        tree.append(p1, node(Syntax::PlainText(Cow::from("a"))));
+        let h2 = tree.append(h1, node(headline(2)));
+        let s2 = tree.append(h2, node(Syntax::Section));
+        let p2 = tree.append(s2, node(Syntax::Paragraph));
+        tree.append(p2, node(Syntax::PlainText(Cow::from("b"))));
+        tree
+    }
+
+    #[derive(Default)]
+    struct Trace {
+        events: Vec<String>,
+        skip_level: Option<usize>,
+    }
+
+    impl<'a> Visitor<'a> for Trace {
+        fn enter(&mut self, tree: &SyntaxTree<'a>, id: NodeId) -> Walk {
+            let t = SyntaxT::from(&tree[id].data);
+            self.events.push(format!("+{}", t.org_name()));
+            Walk::Continue
+        }
+
+        fn leave(&mut self, tree: &SyntaxTree<'a>, id: NodeId) {
+            let t = SyntaxT::from(&tree[id].data);
+            self.events.push(format!("-{}", t.org_name()));
+        }
+
+        fn visit_headline(
+            &mut self,
+            tree: &SyntaxTree<'a>,
+            id: NodeId,
+            data: &HeadlineData<'a>,
+        ) -> Walk {
+            if Some(data.level) == self.skip_level {
+                Walk::Skip
+            } else {
+                Walk::Continue
+            }
+        }
+
+        fn visit_plain_text(
+            &mut self,
+            tree: &SyntaxTree<'a>,
+            id: NodeId,
+            data: &Cow<'a, str>,
+        ) -> Walk {
+            self.events.push(data.to_string());
+            Walk::Continue
+        }
+    }
+
+    #[test]
+    fn order() {
+        let mut trace = Trace::default();
+        sample().visit(&mut trace);
+        assert_eq!(
+            trace.events.join(" "),
+            "+org-data +headline +section +paragraph +plain-text a -plain-text \
+             -paragraph -section +headline +section +paragraph +plain-text b \
+             -plain-text -paragraph -section -headline -headline -org-data"
+        );
+    }
+
+    #[test]
+    fn skip() {
+        let mut trace = Trace {
+            skip_level: Some(2),
+            ..Trace::default()
+        };
+        sample().visit(&mut trace);
+        assert_eq!(
+            trace.events.join(" "),
+            "+org-data +headline +section +paragraph +plain-text a -plain-text \
+             -paragraph -section +headline -headline -headline -org-data"
+        );
+
+        struct SkipSections;
+        impl<'a> Visitor<'a> for SkipSections {
+            fn enter(&mut self, tree: &SyntaxTree<'a>, id: NodeId) -> Walk {
+                match tree[id].data {
+                    Syntax::Section => Walk::Skip,
+                    _ => Walk::Continue,
+                }
+            }
+
+            fn visit_paragraph(&mut self, tree: &SyntaxTree<'a>, id: NodeId) -> Walk {
+                panic!("paragraphs are inside of skipped sections")
+            }
+        }
+        sample().visit(&mut SkipSections);
+    }
+
+    #[test]
+    fn mutate() {
+        struct Demote;
+        impl<'a> VisitorMut<'a> for Demote {
+            fn visit_headline(&mut self, id: NodeId, data: &mut HeadlineData<'a>) -> Walk {
+                data.level += 1;
+                Walk::Continue
+            }
+
+            fn visit_plain_text(&mut self, id: NodeId, data: &mut Cow<'a, str>) -> Walk {
+                *data = Cow::from(data.to_uppercase());
+                Walk::Continue
+            }
+        }
+
+        let mut tree = sample();
+        tree.visit_mut(&mut Demote);
+
+        let mut levels = vec![];
+        let mut texts = vec![];
+        for id in tree.descendants(tree.root()) {
+            match &tree[id].data {
+                Syntax::Headline(h) => levels.push(h.level),
+                Syntax::PlainText(t) => texts.push(t.to_string()),
+                _ => {}
+            }
+        }
+        assert_eq!(levels, vec![2, 3]);
+        assert_eq!(texts, vec!["A", "B"]);
+    }
+}
@@ -0,0 +1,4 @@
+(org-data (:begin 1 :end 77 :contents-begin 1 :contents-end 77 :post-blank 0 :post-affiliated 1)
+  (section (:begin 1 :end 77 :contents-begin 1 :contents-end 77 :post-blank 0 :post-affiliated 1)
+    (paragraph (:begin 1 :end 77 :contents-begin 71 :contents-end 77 :post-blank 0 :post-affiliated 71 :attr_html (":class hi") :caption ((("A " (italic (:begin 38 :end 47 :contents-begin 39 :contents-end 46 :post-blank 0) "caption")) "Short")) :name "greeting")
+      "Hello\n")))
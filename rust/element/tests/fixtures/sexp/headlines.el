@@ -0,0 +1,9 @@
+(org-data (:begin 1 :end 48 :contents-begin 1 :contents-end 48 :post-blank 0 :post-affiliated 1)
+  (headline (:begin 1 :end 48 :contents-begin 19 :contents-end 48 :post-blank 0 :post-affiliated 1 :archivedp nil :closed nil :commentedp nil :deadline nil :footnote-section-p nil :level 1 :pre-blank 0 :priority 0 :quotedp nil :raw-value "Café" :scheduled nil :tags ("tag") :todo-keyword "TODO" :todo-type todo :title ("Café"))
+    (section (:begin 19 :end 39 :contents-begin 19 :contents-end 38 :post-blank 1 :post-affiliated 19)
+      (paragraph (:begin 19 :end 38 :contents-begin 19 :contents-end 38 :post-blank 0 :post-affiliated 19)
+        "Some "
+        (bold (:begin 24 :end 31 :contents-begin 25 :contents-end 29 :post-blank 1)
+          "bold")
+        "\"text\"\n"))
+    (headline (:begin 39 :end 48 :post-blank 0 :post-affiliated 39 :archivedp nil :closed nil :commentedp nil :deadline nil :footnote-section-p nil :level 2 :pre-blank 0 :priority 0 :quotedp nil :raw-value "Child" :scheduled nil :tags nil :todo-keyword nil :todo-type nil :title ("Child")))))
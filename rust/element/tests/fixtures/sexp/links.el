@@ -0,0 +1,9 @@
+(org-data (:begin 1 :end 66 :contents-begin 1 :contents-end 66 :post-blank 0 :post-affiliated 1)
+  (section (:begin 1 :end 66 :contents-begin 1 :contents-end 66 :post-blank 0 :post-affiliated 1)
+    (paragraph (:begin 1 :end 66 :contents-begin 1 :contents-end 66 :post-blank 0 :post-affiliated 1)
+      "See "
+      (link (:begin 5 :end 34 :contents-begin 28 :contents-end 31 :post-blank 1 :application nil :format bracket :path "//orgmode.org" :raw-link "https://orgmode.org" :search-option nil :type "https")
+        "Org")
+      "and "
+      (link (:begin 38 :end 64 :post-blank 0 :application nil :format bracket :path "notes.org" :raw-link "file:notes.org::*Intro" :search-option "*Intro" :type "file"))
+      ".\n")))